@@ -2,12 +2,32 @@ mod middleware;
 mod response;
 mod routes;
 
-use axum::{routing::{get, post, put, delete}, Router};
-use loafy_integrations::supabase::SupabaseAuth;
-use middleware::AppState;
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{header, HeaderValue, Method},
+    middleware::{from_fn, from_fn_with_state},
+    routing::{get, post, put, delete},
+    Router,
+};
+use loafy_core::shutdown::{close_pool, shutdown_signal, shutdown_timeout};
+use loafy_integrations::{
+    fx::FxRates,
+    supabase::{SupabaseAuth, DEFAULT_JWKS_CACHE_DURATION},
+};
+use middleware::{AppState, ImpersonationSigner};
+use routes::metrics::MetricsState;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower_http::cors::{CorsLayer, Any};
+use tower_http::{
+    compression::{
+        predicate::{DefaultPredicate, NotForContentType, Predicate},
+        CompressionLayer,
+    },
+    cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
@@ -19,20 +39,34 @@ async fn main() -> anyhow::Result<()> {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "loafy_api=debug,tower_http=debug".into()),
+                .unwrap_or_else(|_| "loafy_api=debug,tower_http=debug,sqlx=warn".into()),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
 
     tracing::info!("Starting Loafy Club API server...");
 
+    let metrics_handle = middleware::install_metrics_recorder()?;
+
     // Get configuration from environment
     let port = std::env::var("API_PORT")
         .unwrap_or_else(|_| "3000".to_string())
         .parse::<u16>()?;
 
-    let frontend_url = std::env::var("FRONTEND_URL")
+    // Serve /metrics on METRICS_PORT when set, keeping it off the public
+    // listener; otherwise it's served alongside the API
+    let metrics_port = std::env::var("METRICS_PORT")
+        .ok()
+        .map(|v| v.trim().parse::<u16>())
+        .transpose()?;
+
+    // Allowed CORS origins: CORS_ALLOWED_ORIGINS (comma-separated), else FRONTEND_URL
+    let cors_origins = std::env::var("CORS_ALLOWED_ORIGINS")
+        .or_else(|_| std::env::var("FRONTEND_URL"))
         .unwrap_or_else(|_| "http://localhost:5173".to_string());
+    let allowed_origins = parse_cors_origins(&cors_origins)?;
+
+    tracing::info!("✓ CORS allowed origins: {}", cors_origins);
 
     // Initialize database pool
     let database_url = std::env::var("DATABASE_URL")
@@ -41,6 +75,10 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("✓ Database connection established");
 
+    let default_signup_role = default_signup_role(&pool).await?;
+
+    tracing::info!("✓ New signups get the '{}' role", default_signup_role);
+
     // Initialize Supabase client
     let supabase_url = std::env::var("SUPABASE_URL")
         .expect("SUPABASE_URL must be set");
@@ -49,79 +87,279 @@ async fn main() -> anyhow::Result<()> {
     let supabase_service_key = std::env::var("SUPABASE_SERVICE_KEY")
         .expect("SUPABASE_SERVICE_KEY must be set");
 
+    // How long JWKS signing keys are cached (keys are also refreshed on an unknown kid)
+    let jwks_cache_duration = std::env::var("SUPABASE_JWKS_CACHE_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(DEFAULT_JWKS_CACHE_DURATION);
+
     let supabase = SupabaseAuth::new(
         supabase_url,
         supabase_anon_key,
         supabase_service_key,
-    );
+    )
+    .with_jwks_cache_duration(jwks_cache_duration);
 
     tracing::info!("✓ Supabase client initialized");
 
+    let impersonation = ImpersonationSigner::from_env()?;
+    if impersonation.is_some() {
+        tracing::info!("✓ Support impersonation enabled");
+    } else {
+        tracing::info!("Support impersonation disabled (IMPERSONATION_SECRET not set)");
+    }
+
     // Create app state
     let state = AppState {
         supabase,
-        db: pool,
+        db: pool.clone(),
+        fx: FxRates::from_env(),
+        default_signup_role,
+        impersonation,
     };
 
     // Build application router
     let app = Router::new()
-        .route("/health", get(health_check))
+        .route("/health", get(routes::health::health_check))
+        .route("/health/ready", get(routes::health::health_ready))
         // Auth routes
         .route("/api/auth/callback", post(routes::auth::handle_callback))
         .route("/api/auth/me", get(routes::auth::get_current_user))
         .route("/api/auth/logout", post(routes::auth::logout))
         // User routes
+        .route("/api/users/me/booking-quota", get(routes::users::get_booking_quota_route))
+        .route("/api/users/me/stats", get(routes::users::get_user_stats_route))
+        .route("/api/users/me/sessions", get(routes::users::list_auth_sessions))
+        .route("/api/users/me/sessions/revoke-others", post(routes::users::revoke_other_auth_sessions))
+        .route(
+            "/api/users/me/notifications",
+            get(routes::users::get_notification_preferences).put(routes::users::update_notification_preferences),
+        )
+        .route(
+            "/api/users/me/calendar-token",
+            post(routes::users::create_calendar_feed_token).delete(routes::users::revoke_calendar_feed_token),
+        )
+        .route("/api/users/me/calendar.ics", get(routes::users::get_calendar_feed))
         .route("/api/users/me", put(routes::users::update_profile).delete(routes::users::delete_account))
         // Session routes
         .route("/api/sessions", get(routes::sessions::list_sessions))
         .route("/api/sessions/locations", get(routes::sessions::list_locations))
+        .route("/api/sessions/by-location", get(routes::sessions::list_sessions_by_location))
         .route("/api/sessions/:id", get(routes::sessions::get_session))
         .route("/api/sessions/:id/participants", get(routes::sessions::get_session_participants))
-        .route("/api/sessions", post(routes::sessions::create_session))
+        .route("/api/sessions/:id/participant-count", get(routes::sessions::get_session_participant_count))
+        .route(
+            "/api/sessions/:id/waitlist",
+            post(routes::sessions::join_waitlist)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard))
+                .delete(routes::sessions::leave_waitlist),
+        )
+        .route("/api/sessions/:id/waitlist/me", get(routes::sessions::get_my_waitlist_position))
+        .route(
+            "/api/sessions",
+            post(routes::sessions::create_session)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
+        .route(
+            "/api/sessions/:id/duplicate",
+            post(routes::sessions::duplicate_session)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
         .route("/api/sessions/:id", put(routes::sessions::update_session))
         .route("/api/sessions/:id", delete(routes::sessions::delete_session))
+        .route(
+            "/api/sessions/:id/image",
+            post(routes::sessions::upload_session_image)
+                .delete(routes::sessions::delete_session_image)
+                // Room for the largest image plus multipart framing
+                .layer(DefaultBodyLimit::max(routes::sessions::MAX_SESSION_IMAGE_BYTES + 64 * 1024)),
+        )
+        .route("/api/sessions/:id/expenses/:expense_id", get(routes::sessions::get_session_expense))
+        .route("/api/sessions/:id/expenses/:expense_id", put(routes::sessions::update_session_expense))
+        .route("/api/sessions/:id/expenses/:expense_id", delete(routes::sessions::delete_session_expense))
+        .route("/api/sessions/:id/announce", post(routes::sessions::announce_session))
+        .route("/api/sessions/:id/verify-code", get(routes::sessions::verify_booking_code))
+        .route("/api/sessions/:id/check-in", post(routes::sessions::check_in_booking))
         // Booking routes
         .route("/api/bookings", get(routes::bookings::list_my_bookings))
+        .route("/api/bookings/pending", delete(routes::bookings::cancel_pending_bookings_route))
         .route("/api/bookings/:id", get(routes::bookings::get_booking))
-        .route("/api/bookings", post(routes::bookings::create_booking))
+        .route("/api/bookings/by-code/:code", get(routes::bookings::get_booking_by_code))
+        .route(
+            "/api/bookings",
+            post(routes::bookings::create_booking)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
         .route("/api/bookings/:id", delete(routes::bookings::cancel_booking_route))
+        .route("/api/bookings/:id/cancel-preview", get(routes::bookings::get_cancel_preview))
+        .route("/api/bookings/:id/payment-instructions", get(routes::bookings::get_payment_instructions))
+        .route("/api/bookings/:id/calendar.ics", get(routes::bookings::get_booking_calendar))
+        .route("/api/bookings/:id/payment-proof", post(routes::bookings::upload_payment_proof))
+        .route(
+            "/api/bookings/:id/reschedule",
+            post(routes::bookings::reschedule_booking_route)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
+        .route("/api/bookings/:id/invite", post(routes::bookings::create_booking_invite_route))
+        .route(
+            "/api/invites/:token",
+            post(routes::bookings::redeem_invite_route)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
         // Payment routes
         .route("/api/payments/stripe/intent", post(routes::payments::create_payment_intent))
+        .route("/api/payments/stripe/intent/:booking_id", get(routes::payments::get_payment_intent_status))
         .route("/api/webhooks/stripe", post(routes::payments::stripe_webhook))
         // Subscription/ticket routes
         .route("/api/subscriptions/tickets", get(routes::subscriptions::get_ticket_balance))
         .route("/api/subscriptions/tickets/history", get(routes::subscriptions::get_ticket_history))
-        .route("/api/subscriptions/purchase", post(routes::subscriptions::create_checkout_session))
+        .route("/api/subscriptions/tickets/bonuses", get(routes::subscriptions::get_bonus_tickets))
+        .route(
+            "/api/subscriptions/purchase",
+            post(routes::subscriptions::create_checkout_session)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
+        .route(
+            "/api/subscriptions/gift",
+            post(routes::subscriptions::create_gift_checkout_session)
+                .route_layer(from_fn_with_state(state.clone(), middleware::maintenance_guard)),
+        )
         .route("/api/subscriptions/current", get(routes::subscriptions::get_current_subscription))
         .route("/api/subscriptions/cancel", post(routes::subscriptions::cancel_subscription))
         .route("/api/subscriptions/resume", post(routes::subscriptions::resume_subscription))
+        // Organizer routes
+        .route("/api/organizer/payouts", get(routes::organizer::get_payouts))
+        .route("/api/organizer/sessions/editable", get(routes::organizer::list_editable_sessions))
+        .route(
+            "/api/organizer/expense-templates",
+            get(routes::organizer::list_expense_templates).post(routes::organizer::create_expense_template),
+        )
+        .route(
+            "/api/organizer/expense-templates/:id",
+            get(routes::organizer::get_expense_template)
+                .put(routes::organizer::update_expense_template)
+                .delete(routes::organizer::delete_expense_template),
+        )
         // Admin routes
         .route("/api/admin/stats", get(routes::admin::get_stats))
+        .route("/api/admin/config", get(routes::admin::get_config).post(routes::admin::update_config))
+        .route("/api/admin/maintenance", post(routes::admin::set_maintenance))
         .route("/api/admin/users", get(routes::admin::list_users))
+        .route("/api/admin/users/merge", post(routes::admin::merge_users))
+        .route("/api/admin/users/orphaned-roles", get(routes::admin::list_orphaned_role_users))
         .route("/api/admin/users/:id", put(routes::admin::update_user).delete(routes::admin::delete_user))
         .route("/api/admin/users/:id/role", put(routes::admin::update_user_role))
         .route("/api/admin/users/:id/suspend", post(routes::admin::suspend_user))
+        .route("/api/admin/users/:id/suspension-impact", get(routes::admin::get_suspension_impact))
+        .route("/api/admin/users/:id/impersonation-token", post(routes::admin::create_impersonation_token))
         .route("/api/admin/users/:id/unsuspend", post(routes::admin::unsuspend_user))
+        .route("/api/admin/users/:id/notes", get(routes::admin::list_user_notes).post(routes::admin::add_user_note))
         .route("/api/admin/users/:id/tickets", get(routes::admin::get_user_tickets))
+        .route("/api/admin/users/:id/ticket-summary", get(routes::admin::get_user_ticket_summary))
         .route("/api/admin/users/:id/tickets/grant", post(routes::admin::grant_tickets))
         .route("/api/admin/users/:id/tickets/revoke", post(routes::admin::revoke_tickets))
+        .route(
+            "/api/admin/subscriptions/:stripe_subscription_id/reconcile",
+            post(routes::admin::reconcile_subscription),
+        )
+        .route("/api/admin/tickets/transactions", get(routes::admin::list_ticket_transactions))
         .route("/api/admin/bookings", get(routes::admin::list_bookings))
+        .route("/api/admin/bookings/comp", post(routes::admin::create_comp_booking))
         .route("/api/admin/bookings/:id", get(routes::admin::get_booking).put(routes::admin::update_booking))
+        .route("/api/admin/bookings/:id/verify", post(routes::admin::verify_payment_proof))
+        .route("/api/admin/bookings/:id/reject", post(routes::admin::reject_payment_proof))
+        .route("/api/admin/payments/discrepancies", get(routes::admin::get_payment_discrepancies))
+        .route("/api/admin/locations", get(routes::admin::list_locations).post(routes::admin::create_location))
+        .route("/api/admin/locations/:id", put(routes::admin::update_location).delete(routes::admin::delete_location))
         .route("/api/admin/sessions", get(routes::admin::list_sessions))
+        .route("/api/admin/sessions/import", post(routes::sessions::import_sessions))
         .route("/api/admin/roles", get(routes::admin::list_roles))
+        .route("/api/admin/roles/permissions", get(routes::admin::get_permission_matrix))
         // Admin profit routes
         .route("/api/admin/stats/profit", get(routes::admin::get_profit_stats))
+        .route("/api/admin/stats/booking-funnel", get(routes::admin::get_booking_funnel))
         .route("/api/admin/sessions/profit", get(routes::admin::get_sessions_profit))
+        .route("/api/admin/sessions/:id/profit-report.pdf", get(routes::admin::get_session_profit_report))
+        .route("/api/admin/sessions/:id/expenses", get(routes::admin::get_session_expenses))
+        .route("/api/admin/sessions/fill-analytics", get(routes::admin::get_fill_analytics))
         .route("/api/admin/expenses/by-category", get(routes::admin::get_expenses_by_category))
         .route("/api/admin/profit/daily", get(routes::admin::get_daily_profit_data))
+        .route(
+            "/api/admin/fixed-expenses",
+            get(routes::admin::list_fixed_expenses).post(routes::admin::create_fixed_expense),
+        )
+        .route(
+            "/api/admin/fixed-expenses/:id",
+            put(routes::admin::update_fixed_expense).delete(routes::admin::delete_fixed_expense),
+        )
+        // Admin discount code routes
+        .route(
+            "/api/admin/discount-codes",
+            get(routes::admin::list_discount_codes).post(routes::admin::create_discount_code),
+        )
+        .route(
+            "/api/admin/discount-codes/:id",
+            put(routes::admin::update_discount_code).delete(routes::admin::delete_discount_code),
+        )
+        .layer(from_fn(middleware::track_metrics))
         .layer(
             CorsLayer::new()
-                .allow_origin(frontend_url.parse::<axum::http::HeaderValue>()?)
-                .allow_methods(Any)
-                .allow_headers(Any)
+                .allow_origin(allowed_origins)
+                .allow_methods([
+                    Method::GET,
+                    Method::POST,
+                    Method::PUT,
+                    Method::DELETE,
+                    Method::OPTIONS,
+                ])
+                .allow_headers([
+                    header::AUTHORIZATION,
+                    header::CONTENT_TYPE,
+                    header::ACCEPT,
+                    middleware::REQUEST_ID_HEADER,
+                ])
+                .expose_headers([middleware::REQUEST_ID_HEADER])
         )
+        // gzip/br per Accept-Encoding; the default predicate already skips tiny
+        // bodies, images and event streams, so also skip other compressed formats
+        .layer(
+            CompressionLayer::new().compress_when(
+                DefaultPredicate::new()
+                    .and(NotForContentType::const_new("application/zip"))
+                    .and(NotForContentType::const_new("application/gzip"))
+                    .and(NotForContentType::const_new("application/pdf")),
+            ),
+        )
+        // Layers run bottom-up: assign the request id first, then trace inside
+        // its span, then echo the id on the response
+        .layer(PropagateRequestIdLayer::new(middleware::REQUEST_ID_HEADER))
+        .layer(TraceLayer::new_for_http().make_span_with(middleware::request_span))
+        .layer(SetRequestIdLayer::new(middleware::REQUEST_ID_HEADER, MakeRequestUuid))
         .with_state(state);
 
+    let metrics_app = Router::new()
+        .route("/metrics", get(routes::metrics::metrics))
+        .with_state(MetricsState {
+            handle: metrics_handle,
+            db: pool.clone(),
+        });
+
+    let app = match metrics_port {
+        Some(metrics_port) => {
+            let metrics_addr = SocketAddr::from(([0, 0, 0, 0], metrics_port));
+            let metrics_listener = TcpListener::bind(metrics_addr).await?;
+            tracing::info!("✓ Metrics listening on {}", metrics_addr);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(metrics_listener, metrics_app).await {
+                    tracing::error!("Metrics server stopped: {}", e);
+                }
+            });
+            app
+        }
+        None => app.merge(metrics_app),
+    };
+
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     let listener = TcpListener::bind(addr).await?;
@@ -129,11 +367,102 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("✓ Server listening on {}", addr);
     tracing::info!("📡 API ready at http://{}:{}/api", addr.ip(), port);
 
-    axum::serve(listener, app).await?;
+    // On SIGTERM/SIGINT stop accepting connections and let in-flight
+    // requests finish, so booking transactions aren't cut off mid-commit
+    let shutdown_started = Arc::new(tokio::sync::Notify::new());
+    let server = axum::serve(listener, app).with_graceful_shutdown({
+        let shutdown_started = shutdown_started.clone();
+        async move {
+            shutdown_signal().await;
+            tracing::info!("Shutdown signal received, draining in-flight requests...");
+            shutdown_started.notify_one();
+        }
+    });
+
+    let drain_timeout = shutdown_timeout();
+    tokio::select! {
+        result = server => result?,
+        _ = async {
+            shutdown_started.notified().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            tracing::warn!(
+                "In-flight requests still running after {}s, shutting down anyway",
+                drain_timeout.as_secs()
+            );
+        }
+    }
+
+    close_pool(&pool).await;
+    tracing::info!("✓ Server stopped");
+
+    Ok(())
+}
+
+/// Role given to new signups (`DEFAULT_SIGNUP_ROLE`, default "user")
+///
+/// Fails startup when the configured role doesn't exist or isn't allowed for
+/// signups, rather than failing (or over-granting) every signup later. Admin is
+/// never allowed; any role other than "user" must also be listed in
+/// `SIGNUP_ROLE_ALLOWLIST` (comma-separated).
+async fn default_signup_role(pool: &sqlx::PgPool) -> anyhow::Result<String> {
+    let role = std::env::var("DEFAULT_SIGNUP_ROLE")
+        .ok()
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "user".to_string());
+    let allowlist = std::env::var("SIGNUP_ROLE_ALLOWLIST").unwrap_or_default();
+
+    check_signup_role(&role, &allowlist)?;
+
+    if loafy_db::queries::users::get_role_by_name(pool, &role).await?.is_none() {
+        anyhow::bail!("DEFAULT_SIGNUP_ROLE '{}' is not an existing role", role);
+    }
+
+    Ok(role)
+}
+
+/// Reject signup roles that would grant more than a regular user without an
+/// explicit opt-in
+fn check_signup_role(role: &str, allowlist: &str) -> anyhow::Result<()> {
+    if role == "user" {
+        return Ok(());
+    }
+    if role == "admin" {
+        anyhow::bail!("DEFAULT_SIGNUP_ROLE cannot be 'admin'");
+    }
+
+    let allowed = allowlist
+        .split(',')
+        .map(|v| v.trim().to_lowercase())
+        .any(|v| v == role);
+    if !allowed {
+        anyhow::bail!(
+            "DEFAULT_SIGNUP_ROLE '{}' must also be listed in SIGNUP_ROLE_ALLOWLIST",
+            role
+        );
+    }
 
     Ok(())
 }
 
-async fn health_check() -> &'static str {
-    "OK"
+/// Parse a comma-separated list of origins into CORS header values
+fn parse_cors_origins(origins: &str) -> anyhow::Result<Vec<HeaderValue>> {
+    let parsed = origins
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            origin
+                .trim_end_matches('/')
+                .parse::<HeaderValue>()
+                .map_err(|e| anyhow::anyhow!("Invalid CORS origin '{}': {}", origin, e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if parsed.is_empty() {
+        anyhow::bail!("No CORS origins configured");
+    }
+
+    Ok(parsed)
 }
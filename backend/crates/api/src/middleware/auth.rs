@@ -9,24 +9,30 @@ use axum_extra::{
     TypedHeader,
 };
 use loafy_db::{queries::users, models::UserWithRole, PgPool};
-use loafy_integrations::supabase::SupabaseAuth;
+use loafy_integrations::{fx::FxRates, supabase::{SupabaseAuth, UserLookupError}};
 use loafy_types::api::admin::SuspendedUserError;
 use loafy_types::AppError;
 
+use super::impersonation::{impersonated_user, is_impersonation_token, ImpersonationSigner};
+
 /// Extractor for authenticated user (required)
 /// Usage: async fn handler(AuthUser(user): AuthUser)
 pub struct AuthUser(pub UserWithRole);
 
 /// Extractor for optional authenticated user
 /// Usage: async fn handler(OptionalAuthUser(user): OptionalAuthUser)
-#[allow(dead_code)]
 pub struct OptionalAuthUser(pub Option<UserWithRole>);
 
-/// Application state containing Supabase client and database pool
+/// Application state containing Supabase client, database pool and exchange rates
 #[derive(Clone)]
 pub struct AppState {
     pub supabase: SupabaseAuth,
     pub db: PgPool,
+    pub fx: FxRates,
+    /// Role given to new signups, checked against the roles table at startup
+    pub default_signup_role: String,
+    /// Signs support impersonation tokens; None when `IMPERSONATION_SECRET` is unset
+    pub impersonation: Option<ImpersonationSigner>,
 }
 
 /// Auth error that can be returned from extractors
@@ -35,17 +41,27 @@ pub enum AuthError {
     Unauthorized(String),
     /// User account is suspended
     Suspended(SuspendedUserError),
+    /// Authenticated, but the token may not be used for this request
+    Forbidden(String),
+    /// The token couldn't be checked because Supabase is down; retrying may succeed
+    Unavailable(String),
 }
 
 impl axum::response::IntoResponse for AuthError {
     fn into_response(self) -> axum::response::Response {
         match self {
             AuthError::Unauthorized(msg) => {
-                (StatusCode::UNAUTHORIZED, msg).into_response()
+                crate::response::unauthorized(msg).into_response()
             }
             AuthError::Suspended(error) => {
                 (StatusCode::FORBIDDEN, Json(error)).into_response()
             }
+            AuthError::Forbidden(msg) => {
+                crate::response::forbidden(msg).into_response()
+            }
+            AuthError::Unavailable(msg) => {
+                crate::response::service_unavailable(msg).into_response()
+            }
         }
     }
 }
@@ -72,27 +88,12 @@ where
         // Get app state using FromRef
         let app_state: AppState = AppState::from_ref(state);
 
-        // Verify JWT token (async)
-        let claims = app_state
-            .supabase
-            .verify_token(token)
-            .await
-            .map_err(|e| {
-                AuthError::Unauthorized(format!("Invalid token: {}", e))
-            })?;
-
-        // Get Supabase user ID from claims (stored as auth_provider_id in our DB)
-        let supabase_user_id = &claims.sub;
-
-        // Fetch user from database by auth_provider_id
-        let user = users::find_with_role_by_auth_provider_id(&app_state.db, supabase_user_id)
-            .await
-            .map_err(|e| {
-                AuthError::Unauthorized(format!("Database error: {}", e))
-            })?
-            .ok_or_else(|| {
-                AuthError::Unauthorized("User not found".to_string())
-            })?;
+        // Admin acting as a user for support
+        let user = if is_impersonation_token(token) {
+            impersonated_user(&app_state, parts, token).await?
+        } else {
+            supabase_user(&app_state, token).await?
+        };
 
         // Check if user is suspended
         if user.is_suspended() {
@@ -127,22 +128,25 @@ where
         // Get app state using FromRef
         let app_state: AppState = AppState::from_ref(state);
 
-        // Verify JWT token (async)
-        let claims = match app_state.supabase.verify_token(&token).await {
-            Ok(claims) => claims,
-            Err(_) => return Ok(OptionalAuthUser(None)), // Invalid token, return None
+        // An impersonation token is never silently treated as anonymous, so
+        // its read-only and expiry checks can't be sidestepped
+        let user = if is_impersonation_token(&token) {
+            Some(impersonated_user(&app_state, parts, &token).await?)
+        } else {
+            // Verify JWT token (async)
+            let claims = match app_state.supabase.verify_token(&token).await {
+                Ok(claims) => claims,
+                Err(_) => return Ok(OptionalAuthUser(None)), // Invalid token, return None
+            };
+
+            // Fetch user from database by auth_provider_id (Supabase user ID)
+            users::find_with_role_by_auth_provider_id(&app_state.db, &claims.sub)
+                .await
+                .map_err(|e| {
+                    AuthError::Unauthorized(format!("Database error: {}", e))
+                })?
         };
 
-        // Get Supabase user ID from claims
-        let supabase_user_id = &claims.sub;
-
-        // Fetch user from database by auth_provider_id
-        let user = users::find_with_role_by_auth_provider_id(&app_state.db, supabase_user_id)
-            .await
-            .map_err(|e| {
-                AuthError::Unauthorized(format!("Database error: {}", e))
-            })?;
-
         // Check if user is suspended
         if let Some(ref u) = user {
             if u.is_suspended() {
@@ -158,6 +162,37 @@ where
     }
 }
 
+/// Resolve a Supabase access token to our user
+async fn supabase_user(app_state: &AppState, token: &str) -> Result<UserWithRole, AuthError> {
+    // Verify JWT token (async)
+    let claims = app_state
+        .supabase
+        .verify_token(token)
+        .await
+        .map_err(|e| match e.downcast_ref::<UserLookupError>() {
+            Some(UserLookupError::Unavailable(_)) => {
+                tracing::error!("Token check failed, Supabase unavailable: {}", e);
+                AuthError::Unavailable("Authentication is temporarily unavailable, please try again".to_string())
+            }
+            _ => AuthError::Unauthorized(format!("Invalid token: {}", e)),
+        })?;
+
+    // Get Supabase user ID from claims (stored as auth_provider_id in our DB)
+    let supabase_user_id = &claims.sub;
+
+    // Fetch user from database by auth_provider_id
+    let user = users::find_with_role_by_auth_provider_id(&app_state.db, supabase_user_id)
+        .await
+        .map_err(|e| {
+            AuthError::Unauthorized(format!("Database error: {}", e))
+        })?
+        .ok_or_else(|| {
+            AuthError::Unauthorized("User not found".to_string())
+        })?;
+
+    Ok(user)
+}
+
 /// Helper to check if user has required role
 pub fn require_role(user: &UserWithRole, required_role: &str) -> Result<(), AppError> {
     match required_role {
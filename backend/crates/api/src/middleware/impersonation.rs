@@ -0,0 +1,201 @@
+use anyhow::{anyhow, Result};
+use axum::http::{request::Parts, Method};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use loafy_db::{
+    models::UserWithRole,
+    queries::{impersonation_grants, users},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::{AppState, AuthError};
+
+/// Prefix telling impersonation tokens apart from Supabase access tokens
+pub const IMPERSONATION_TOKEN_PREFIX: &str = "imp_";
+
+/// How long an impersonation token works for
+pub const IMPERSONATION_TOKEN_TTL: Duration = Duration::minutes(15);
+
+/// Audience of impersonation tokens, so no other token signed with the same
+/// secret is accepted
+const IMPERSONATION_AUDIENCE: &str = "loafy-impersonation";
+
+/// Shortest accepted `IMPERSONATION_SECRET`, in bytes
+const MIN_SECRET_LENGTH: usize = 32;
+
+/// Claims of an impersonation token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationClaims {
+    /// Impersonated user
+    pub sub: Uuid,
+    /// Admin the token was issued to
+    pub admin_id: Uuid,
+    /// Impersonation grant backing the token
+    pub jti: Uuid,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Signs and verifies impersonation tokens (HS256, `IMPERSONATION_SECRET`)
+#[derive(Clone)]
+pub struct ImpersonationSigner {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl ImpersonationSigner {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// Signer from `IMPERSONATION_SECRET`; None (impersonation disabled) when unset
+    pub fn from_env() -> Result<Option<Self>> {
+        let secret = match std::env::var("IMPERSONATION_SECRET") {
+            Ok(secret) if !secret.trim().is_empty() => secret,
+            _ => return Ok(None),
+        };
+        if secret.len() < MIN_SECRET_LENGTH {
+            anyhow::bail!("IMPERSONATION_SECRET must be at least {} bytes", MIN_SECRET_LENGTH);
+        }
+
+        Ok(Some(Self::new(secret.as_bytes())))
+    }
+
+    /// Issue a token letting `admin_id` act as `user_id` until `expires_at`
+    pub fn sign(&self, grant_id: Uuid, admin_id: Uuid, user_id: Uuid, expires_at: DateTime<Utc>) -> Result<String> {
+        let claims = ImpersonationClaims {
+            sub: user_id,
+            admin_id,
+            jti: grant_id,
+            aud: IMPERSONATION_AUDIENCE.to_string(),
+            iat: Utc::now().timestamp(),
+            exp: expires_at.timestamp(),
+        };
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| anyhow!("Failed to sign impersonation token: {}", e))?;
+
+        Ok(format!("{}{}", IMPERSONATION_TOKEN_PREFIX, token))
+    }
+
+    /// Check a token's signature, audience and expiry
+    pub fn verify(&self, token: &str) -> Result<ImpersonationClaims> {
+        let token = token
+            .strip_prefix(IMPERSONATION_TOKEN_PREFIX)
+            .ok_or_else(|| anyhow!("Not an impersonation token"))?;
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        validation.set_audience(&[IMPERSONATION_AUDIENCE]);
+
+        let data = decode::<ImpersonationClaims>(token, &self.decoding_key, &validation)
+            .map_err(|e| anyhow!("Impersonation token verification failed: {}", e))?;
+
+        Ok(data.claims)
+    }
+}
+
+/// Whether a bearer token is an impersonation token rather than a Supabase one
+pub fn is_impersonation_token(token: &str) -> bool {
+    token.starts_with(IMPERSONATION_TOKEN_PREFIX)
+}
+
+/// Resolve an impersonation token to the user it acts as
+///
+/// Only read-only (GET/HEAD) requests are allowed. The grant must still be
+/// live, its admin must still be an admin, and the user must not have become
+/// one. Each accepted request is counted on the grant and logged.
+pub(crate) async fn impersonated_user(
+    state: &AppState,
+    parts: &Parts,
+    token: &str,
+) -> Result<UserWithRole, AuthError> {
+    let signer = state
+        .impersonation
+        .as_ref()
+        .ok_or_else(|| AuthError::Unauthorized("Impersonation is not enabled".to_string()))?;
+    let claims = signer
+        .verify(token)
+        .map_err(|e| AuthError::Unauthorized(format!("Invalid token: {}", e)))?;
+
+    if !matches!(parts.method, Method::GET | Method::HEAD) {
+        return Err(AuthError::Forbidden("Impersonation tokens are read-only".to_string()));
+    }
+
+    let grant = impersonation_grants::record_use(&state.db, claims.jti)
+        .await
+        .map_err(|e| AuthError::Unauthorized(format!("Database error: {}", e)))?
+        .filter(|grant| grant.admin_id == claims.admin_id && grant.user_id == claims.sub)
+        .ok_or_else(|| AuthError::Unauthorized("Impersonation token has expired".to_string()))?;
+
+    let admin = users::find_with_role_by_id(&state.db, grant.admin_id)
+        .await
+        .map_err(|e| AuthError::Unauthorized(format!("Database error: {}", e)))?
+        .filter(|admin| admin.is_admin() && !admin.is_deleted() && !admin.is_suspended())
+        .ok_or_else(|| AuthError::Unauthorized("Impersonation token is no longer valid".to_string()))?;
+
+    let user = users::find_with_role_by_id(&state.db, grant.user_id)
+        .await
+        .map_err(|e| AuthError::Unauthorized(format!("Database error: {}", e)))?
+        .filter(|user| !user.is_deleted())
+        .ok_or_else(|| AuthError::Unauthorized("User not found".to_string()))?;
+    if user.is_admin() {
+        return Err(AuthError::Forbidden("Admin users can't be impersonated".to_string()));
+    }
+
+    tracing::info!(
+        grant_id = %grant.id,
+        admin_id = %admin.id,
+        user_id = %user.id,
+        method = %parts.method,
+        uri = %parts.uri,
+        "Impersonated request"
+    );
+
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-impersonation-secret-0123456789";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = ImpersonationSigner::new(SECRET);
+        let (grant_id, admin_id, user_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let token = signer
+            .sign(grant_id, admin_id, user_id, Utc::now() + IMPERSONATION_TOKEN_TTL)
+            .unwrap();
+
+        assert!(is_impersonation_token(&token));
+        let claims = signer.verify(&token).unwrap();
+        assert_eq!(claims.sub, user_id);
+        assert_eq!(claims.admin_id, admin_id);
+        assert_eq!(claims.jti, grant_id);
+    }
+
+    #[test]
+    fn test_rejects_other_secret_and_expired_tokens() {
+        let signer = ImpersonationSigner::new(SECRET);
+        let (grant_id, admin_id, user_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let token = signer
+            .sign(grant_id, admin_id, user_id, Utc::now() + IMPERSONATION_TOKEN_TTL)
+            .unwrap();
+        assert!(ImpersonationSigner::new(b"another-secret-another-secret-0123").verify(&token).is_err());
+
+        let expired = signer
+            .sign(grant_id, admin_id, user_id, Utc::now() - Duration::minutes(1))
+            .unwrap();
+        assert!(signer.verify(&expired).is_err());
+
+        // The bare JWT, without the prefix, isn't accepted
+        assert!(signer.verify(token.trim_start_matches(IMPERSONATION_TOKEN_PREFIX)).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use loafy_db::queries::config;
+
+use super::AppState;
+use crate::response;
+
+/// Shown when maintenance mode is on without a custom message
+const DEFAULT_MAINTENANCE_MESSAGE: &str =
+    "Bookings are temporarily paused for maintenance. Please try again later.";
+
+/// Reject the request with 503 while maintenance mode is enabled
+///
+/// Layered onto the write routes that create bookings, sessions and
+/// subscriptions; reads and admin routes are left alone. If the config can't
+/// be loaded the request goes through rather than blocking the club.
+pub async fn maintenance_guard(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match config::load(&state.db).await {
+        Ok(config) if config.maintenance_mode => response::maintenance(
+            config
+                .maintenance_message
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.to_string()),
+        )
+        .into_response(),
+        Ok(_) => next.run(request).await,
+        Err(e) => {
+            tracing::warn!("Failed to load config for maintenance check: {}", e);
+            next.run(request).await
+        }
+    }
+}
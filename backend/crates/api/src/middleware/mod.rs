@@ -1,6 +1,11 @@
 pub mod auth;
+pub mod impersonation;
+pub mod maintenance;
+pub mod metrics;
+pub mod request_id;
 
-pub use auth::{AuthUser, AppState, require_role};
-
-// OptionalAuthUser is defined but not currently exported/used
-// Re-export when needed: pub use auth::OptionalAuthUser;
+pub use auth::{AuthUser, AppState, OptionalAuthUser, require_role};
+pub use impersonation::{is_impersonation_token, ImpersonationSigner, IMPERSONATION_TOKEN_TTL};
+pub use maintenance::maintenance_guard;
+pub use metrics::{install_metrics_recorder, track_metrics};
+pub use request_id::{request_span, REQUEST_ID_HEADER};
@@ -0,0 +1,23 @@
+use axum::{extract::Request, http::HeaderName};
+use tracing::Span;
+
+/// Header carrying the request id, read from the client or generated, and
+/// echoed on every response
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Tracing span for a request, tagged with its request id so every log line
+/// (including slow query warnings) can be matched to a user's bug report
+pub fn request_span(request: &Request) -> Span {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("-");
+
+    tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+    )
+}
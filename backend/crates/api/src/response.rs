@@ -1,54 +1,113 @@
 //! Common API response helpers to reduce error handling boilerplate
 
-use axum::http::StatusCode;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use loafy_types::{AppError, ErrorCode, ErrorResponse};
 
-/// Type alias for the common error response tuple
-pub type ApiError = (StatusCode, String);
+/// Error returned by route handlers, serialized as `{ code, message }`
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse {
+            code: self.code,
+            message: self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(err: AppError) -> Self {
+        Self::new(
+            StatusCode::from_u16(err.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            err.code(),
+            err.to_string(),
+        )
+    }
+}
 
 /// Create a NOT_FOUND error response
 pub fn not_found(resource: &str) -> ApiError {
-    (StatusCode::NOT_FOUND, format!("{} not found", resource))
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        ErrorCode::NotFound,
+        format!("{} not found", resource),
+    )
 }
 
 /// Create an INTERNAL_SERVER_ERROR response from a database error
 pub fn db_error<E: std::fmt::Display>(err: E) -> ApiError {
-    (
+    ApiError::new(
         StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::InternalError,
         format!("Database error: {}", err),
     )
 }
 
 /// Create an INTERNAL_SERVER_ERROR response with a custom message
 pub fn internal_error<E: std::fmt::Display>(err: E) -> ApiError {
-    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::InternalError, err.to_string())
 }
 
 /// Create an INTERNAL_SERVER_ERROR response with a formatted message
 pub fn internal_error_msg<E: std::fmt::Display>(prefix: &str, err: E) -> ApiError {
-    (
+    ApiError::new(
         StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::InternalError,
         format!("{}: {}", prefix, err),
     )
 }
 
 /// Create a BAD_REQUEST error response
 pub fn bad_request(message: impl Into<String>) -> ApiError {
-    (StatusCode::BAD_REQUEST, message.into())
+    ApiError::new(StatusCode::BAD_REQUEST, ErrorCode::BadRequest, message)
+}
+
+/// Create an UNPROCESSABLE_ENTITY validation error response
+pub fn unprocessable(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::ValidationError, message)
 }
 
 /// Create an UNAUTHORIZED error response
 pub fn unauthorized(message: impl Into<String>) -> ApiError {
-    (StatusCode::UNAUTHORIZED, message.into())
+    ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, message)
 }
 
 /// Create a FORBIDDEN error response
 pub fn forbidden(message: impl Into<String>) -> ApiError {
-    (StatusCode::FORBIDDEN, message.into())
+    ApiError::new(StatusCode::FORBIDDEN, ErrorCode::Forbidden, message)
+}
+
+/// Create a SERVICE_UNAVAILABLE response for write endpoints paused by maintenance mode
+pub fn maintenance(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::SERVICE_UNAVAILABLE, ErrorCode::MaintenanceMode, message)
+}
+
+/// Create a SERVICE_UNAVAILABLE response for a dependency that is down; the client should retry
+pub fn service_unavailable(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::SERVICE_UNAVAILABLE, ErrorCode::ExternalServiceError, message)
 }
 
 /// Create a CONFLICT error response
-#[allow(dead_code)]
 pub fn conflict(message: impl Into<String>) -> ApiError {
-    (StatusCode::CONFLICT, message.into())
+    ApiError::new(StatusCode::CONFLICT, ErrorCode::Conflict, message)
 }
-
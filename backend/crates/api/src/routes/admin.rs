@@ -1,34 +1,55 @@
 use axum::{
     extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
     Json,
 };
-use chrono::Duration;
-use loafy_types::{parse_period, validate_payment_method, validate_payment_status, validate_role};
+use chrono::{NaiveDate, Utc};
+use loafy_core::booking::{cancel_booking, club_now, club_today, create_comp_booking as comp_booking, CancelledBy};
+use loafy_core::session::{render_profit_report, ProfitReport};
+use loafy_types::{parse_period, validate_payment_method, validate_payment_status, validate_role, Pagination};
 use loafy_db::{
-    models::{bonus_types, transaction_types},
-    queries::{admin, bookings, sessions as sessions_queries, subscriptions, ticket_transactions, users},
+    models::{bonus_types, permissions, transaction_types, NotificationKind, UserWithRole},
+    queries::{
+        admin::{self, UserMergeError}, admin_notes, bookings, config, discount_codes, fixed_expenses, impersonation_grants, locations,
+        notification_preferences, session_expenses, sessions as sessions_queries,
+        subscriptions, ticket_transactions, users,
+        sessions::TimeOfDay,
+    },
 };
 use loafy_types::api::admin::{
-    AdminBookingResponse, AdminSessionResponse, AdminUserResponse,
-    PageInfo, PaginatedBookingsResponse, PaginatedSessionsResponse, PaginatedUsersResponse,
-    SuspendUserRequest, UpdateBookingRequest, UpdateUserRequest,
+    AdminBookingResponse, AdminNoteResponse, AdminSessionExpense, AdminSessionExpensesResponse,
+    AdminSessionResponse, AdminUserResponse, BookingFailuresResponse, BookingFunnelResponse,
+    ConfigResponse, CreateAdminNoteRequest, DiscountCodeRequest, DiscountCodeResponse, FillAnalyticsResponse, FixedExpenseRequest, FixedExpenseResponse,
+    MaintenanceRequest, MaintenanceResponse,
+    MergeUsersRequest, MergeUsersResponse,
+    PermissionMatrixResponse, PermissionResponse, RolePermissionsResponse, SlotFillResponse,
+    PaginatedBookingsResponse, PaginatedSessionsResponse, PaginatedUsersResponse,
+    PaymentDiscrepanciesResponse, PaymentDiscrepancyResponse, VerifyPaymentRequest,
+    CompBookingRequest, ImpersonationTokenRequest, ImpersonationTokenResponse, SuspendUserRequest, SuspensionImpactResponse, UpdateBookingRequest, UpdateConfigRequest, UpdateUserRequest,
 };
 use loafy_types::api::subscriptions::{
-    AdminGrantTicketsRequest, AdminUserTicketsResponse, TicketBalanceResponse,
-    TicketTransactionResponse,
+    AdminGrantTicketsRequest, AdminTicketTransactionResponse, AdminTicketTransactionsResponse,
+    AdminTicketSummaryResponse, AdminUserTicketsResponse, ReconcileSubscriptionRequest, ReconcileSubscriptionResponse,
+    SubscriptionResponse, TicketBalanceResponse, TicketTransactionResponse,
 };
-use loafy_types::api::sessions::ParticipantInfo;
+use loafy_integrations::email::{escape_html, EmailClient};
+use loafy_integrations::stripe::{self, refund_cancelled_booking, RefundOutcome, StripePayments};
+use loafy_types::api::sessions::{LocationRequest, LocationResponse, ParticipantInfo};
+use loafy_types::enums::{PaymentStatus, VerificationStatus};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::middleware::{AppState, AuthUser, require_role};
+use crate::middleware::{AppState, AuthUser, require_role, IMPERSONATION_TOKEN_TTL};
 use crate::response::{self, ApiError};
+use crate::routes::sessions::{location_response, EXPENSE_CATEGORIES};
+use crate::routes::subscriptions::{get_stripe_subscriptions, ticket_balance_response};
 
 /// Query parameters for stats endpoint
 #[derive(Deserialize)]
 pub struct StatsQuery {
-    /// Period filter: "7d", "30d", "90d", "365d", or "all"
+    /// Period filter: "7d", "30d", "90d", "365d", or "all"; anything else is a 400
     #[serde(default = "default_period")]
     pub period: String,
 }
@@ -47,6 +68,10 @@ pub struct DailyDataPoint {
 /// Previous period stats for comparison
 #[derive(Serialize)]
 pub struct PreviousPeriodResponse {
+    /// Start of the comparison window (RFC 3339, inclusive)
+    pub previous_period_start: String,
+    /// End of the comparison window (RFC 3339, exclusive; the current period's start)
+    pub previous_period_end: String,
     pub new_users: i64,
     pub total_bookings: i64,
     pub total_revenue_vnd: i64,
@@ -88,17 +113,19 @@ pub async fn get_stats(
     require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
 
     // Parse period to get since date and duration
-    let period = parse_period(&query.period);
-    let (since, days) = (period.since, period.days);
+    let period = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?;
 
-    let stats = admin::get_admin_stats(&state.db, since)
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+    let stats = admin::get_admin_stats(&state.db, period.since, club_today(offset))
         .await
         .map_err(response::db_error)?;
 
     // Get previous period stats and daily data if a specific period is selected
-    let (previous_period, daily_data) = if let (Some(current_start), Some(period_days)) = (since, days) {
-        let previous_start = current_start - Duration::days(period_days);
-
+    let (previous_period, daily_data) = if let Some((previous_start, current_start)) = period.previous_window() {
         // Fetch previous period stats
         let prev_stats = admin::get_previous_period_stats(&state.db, current_start, previous_start)
             .await
@@ -114,6 +141,8 @@ pub async fn get_stats(
 
         (
             Some(PreviousPeriodResponse {
+                previous_period_start: previous_start.to_rfc3339(),
+                previous_period_end: current_start.to_rfc3339(),
                 new_users: prev_stats.new_users,
                 total_bookings: prev_stats.total_bookings,
                 total_revenue_vnd: prev_stats.total_revenue_vnd,
@@ -168,6 +197,9 @@ fn default_per_page() -> i32 {
     10
 }
 
+/// Largest page size accepted by admin list endpoints
+const MAX_PER_PAGE: i32 = 100;
+
 /// List users with pagination (admin only)
 pub async fn list_users(
     AuthUser(user): AuthUser,
@@ -176,14 +208,12 @@ pub async fn list_users(
 ) -> Result<Json<PaginatedUsersResponse>, ApiError> {
     require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
 
-    let page = query.page.max(1);
-    let per_page = query.per_page.clamp(1, 100);
+    let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
 
     let (db_users, total) = admin::list_users_paginated(
         &state.db,
         admin::UsersQueryParams {
-            page,
-            per_page,
+            pagination,
             search: query.search,
             role: query.role,
             status: query.status,
@@ -194,21 +224,104 @@ pub async fn list_users(
     .await
     .map_err(response::db_error)?;
 
-    let total_pages = ((total as f64) / (per_page as f64)).ceil() as i32;
+    let user_ids: Vec<Uuid> = db_users.iter().map(|u| u.id).collect();
+    let notes_counts = admin_notes::count_for_users(&state.db, &user_ids)
+        .await
+        .map_err(response::db_error)?;
 
     let data: Vec<AdminUserResponse> = db_users
         .into_iter()
-        .map(AdminUserResponse::from)
+        .map(|u| {
+            let notes_count = notes_counts.get(&u.id).copied().unwrap_or(0);
+            let mut response = AdminUserResponse::from(u);
+            response.notes_count = Some(notes_count);
+            response
+        })
         .collect();
 
     Ok(Json(PaginatedUsersResponse {
         data,
-        page_info: PageInfo {
-            page,
-            per_page,
-            total,
-            total_pages,
-        },
+        page_info: pagination.page_info(total),
+    }))
+}
+
+/// List users whose role no longer exists (admin only)
+///
+/// These users read as plain users everywhere else; this surfaces them so
+/// an admin can assign a real role.
+pub async fn list_orphaned_role_users(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AdminUserResponse>>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let db_users = users::find_orphaned_role_users(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(db_users.into_iter().map(AdminUserResponse::from).collect()))
+}
+
+/// List internal staff notes for a user (admin only)
+pub async fn list_user_notes(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<AdminNoteResponse>>, ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    users::find_by_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    let notes = admin_notes::list_for_user(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?;
+
+    let response = notes
+        .into_iter()
+        .map(|n| AdminNoteResponse {
+            id: n.id,
+            note: n.note,
+            author_id: n.author_id,
+            author_name: n.author_name,
+            created_at: n.created_at,
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// Add an internal staff note to a user (admin only)
+pub async fn add_user_note(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<CreateAdminNoteRequest>,
+) -> Result<Json<AdminNoteResponse>, ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    request.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    if request.note.trim().is_empty() {
+        return Err(response::bad_request("Note cannot be empty"));
+    }
+
+    users::find_by_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    let note = admin_notes::create_note(&state.db, user_id, admin.id, request.note.trim())
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(AdminNoteResponse {
+        id: note.id,
+        note: note.note,
+        author_id: note.author_id,
+        author_name: admin.name,
+        created_at: note.created_at,
     }))
 }
 
@@ -225,7 +338,9 @@ pub async fn update_user_role(
     Path(user_id): Path<Uuid>,
     Json(request): Json<UpdateRoleRequest>,
 ) -> Result<Json<AdminUserResponse>, ApiError> {
-    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !user.has_permission(permissions::ROLES_MANAGE) {
+        return Err(response::forbidden("Permission to change roles required"));
+    }
 
     // Validate role
     validate_role(&request.role).map_err(response::bad_request)?;
@@ -293,6 +408,10 @@ pub async fn suspend_user(
     .await
     .map_err(response::db_error)?;
 
+    if request.cancel_bookings {
+        cancel_upcoming_bookings(&state, &target, admin.id).await?;
+    }
+
     // Fetch updated user with role for response
     let user_with_role = users::find_with_role_by_id(&state.db, user_id)
         .await
@@ -302,6 +421,196 @@ pub async fn suspend_user(
     Ok(Json(AdminUserResponse::from(user_with_role)))
 }
 
+/// Cancel a suspended user's upcoming bookings and email them what was cancelled
+///
+/// Tickets are returned by `cancel_booking`; paid card bookings are refunded
+/// here, or marked `refund_failed` for a manual refund. A booking that fails to
+/// cancel is logged and left for the admin. The email follows the user's
+/// cancellation email preference and is best-effort.
+async fn cancel_upcoming_bookings(
+    state: &AppState,
+    target: &UserWithRole,
+    admin_id: Uuid,
+) -> Result<(), ApiError> {
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+    let upcoming = bookings::list_upcoming_active(&state.db, target.id, club_now(offset))
+        .await
+        .map_err(response::db_error)?;
+
+    let stripe = std::env::var("STRIPE_SECRET_KEY").ok().map(StripePayments::new);
+    let mut cancelled = Vec::new();
+    let mut refunds_failed = false;
+    for booking in &upcoming {
+        match cancel_booking(&state.db, booking.id, CancelledBy::Admin(admin_id)).await {
+            Ok(_) => {
+                let outcome = refund_cancelled_booking(&state.db, stripe.as_ref(), booking).await;
+                refunds_failed |= matches!(outcome, RefundOutcome::Failed(_));
+                cancelled.push(booking);
+            }
+            Err(e) => tracing::error!(
+                "Failed to cancel booking {} of suspended user {}: {}",
+                booking.booking_code,
+                target.id,
+                e
+            ),
+        }
+    }
+
+    tracing::info!(
+        "Admin {} cancelled {}/{} upcoming bookings of suspended user {}",
+        admin_id,
+        cancelled.len(),
+        upcoming.len(),
+        target.id
+    );
+
+    if cancelled.is_empty()
+        || !notification_preferences::should_email(&state.db, target.id, NotificationKind::SessionCancelled).await
+    {
+        return Ok(());
+    }
+    let Some(email) = EmailClient::from_env() else {
+        return Ok(());
+    };
+
+    let session_ids: Vec<Uuid> = cancelled.iter().map(|b| b.session_id).collect();
+    let sessions = sessions_queries::find_by_ids(&state.db, &session_ids)
+        .await
+        .map_err(response::db_error)?;
+    let items: String = cancelled
+        .iter()
+        .map(|booking| match sessions.iter().find(|s| s.id == booking.session_id) {
+            Some(session) => format!(
+                "<li>{} on {} at {} ({})</li>",
+                escape_html(&session.title),
+                session.date.format("%Y-%m-%d"),
+                session.time.format("%H:%M"),
+                escape_html(&booking.booking_code),
+            ),
+            None => format!("<li>{}</li>", escape_html(&booking.booking_code)),
+        })
+        .collect();
+
+    let refund_note = if refunds_failed {
+        "card payments will be refunded by our team shortly"
+    } else {
+        "card payments have been refunded"
+    };
+    let html = format!(
+        "<p>Hi {},</p>\
+         <p>Your account has been suspended, so the following bookings have been cancelled:</p>\
+         <ul>{}</ul>\
+         <p>Any ticket used has been returned to your balance and {}.</p>\
+         <p>— Loafy Club</p>",
+        escape_html(target.name.as_deref().unwrap_or("there")),
+        items,
+        refund_note,
+    );
+
+    if let Err(e) = email.send(&target.email, "Your bookings have been cancelled", &html).await {
+        tracing::error!("Failed to notify suspended user {} about cancelled bookings: {}", target.id, e);
+    }
+
+    Ok(())
+}
+
+/// POST /api/admin/users/:id/impersonation-token
+/// Issue a short-lived token to view the app as a user, for support (admin only)
+///
+/// The token only works for GET requests and can't be issued for admins.
+/// Issuing it, and every request made with it, is recorded on an
+/// impersonation grant.
+pub async fn create_impersonation_token(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<ImpersonationTokenRequest>,
+) -> Result<(StatusCode, Json<ImpersonationTokenResponse>), ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    request
+        .validate()
+        .map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    if request.reason.trim().is_empty() {
+        return Err(response::bad_request("Impersonation reason is required"));
+    }
+
+    let signer = state
+        .impersonation
+        .as_ref()
+        .ok_or_else(|| response::service_unavailable("Impersonation is not configured"))?;
+
+    let target = users::find_with_role_by_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?
+        .filter(|target| !target.is_deleted())
+        .ok_or_else(|| response::not_found("User"))?;
+    if target.is_admin() {
+        return Err(response::forbidden("Cannot impersonate admin users"));
+    }
+
+    let expires_at = Utc::now() + IMPERSONATION_TOKEN_TTL;
+    let grant = impersonation_grants::create(&state.db, admin.id, target.id, request.reason.trim(), expires_at)
+        .await
+        .map_err(response::db_error)?;
+    let token = signer
+        .sign(grant.id, admin.id, target.id, grant.expires_at)
+        .map_err(|e| response::internal_error_msg("Failed to issue impersonation token", e))?;
+
+    tracing::info!(
+        grant_id = %grant.id,
+        admin_id = %admin.id,
+        user_id = %target.id,
+        "Impersonation token issued"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImpersonationTokenResponse {
+            token,
+            user_id: target.id,
+            expires_at: grant.expires_at,
+        }),
+    ))
+}
+
+/// Preview what suspending a user would affect (admin only)
+pub async fn get_suspension_impact(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<SuspensionImpactResponse>, ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let _target = users::find_with_role_by_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+    let impact = admin::get_suspension_impact(&state.db, user_id, club_today(offset))
+        .await
+        .map_err(response::db_error)?;
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(SuspensionImpactResponse {
+        user_id,
+        upcoming_confirmed_bookings: impact.upcoming_confirmed_bookings,
+        pending_bookings: impact.pending_bookings,
+        organized_sessions: impact.organized_sessions,
+        has_active_subscription: subscription.as_ref().is_some_and(|s| s.is_active()),
+        subscription_status: subscription.as_ref().map(|s| s.status.clone()),
+        tickets_remaining: subscription.map(|s| s.tickets_remaining),
+    }))
+}
+
 /// Unsuspend a user (admin only)
 pub async fn unsuspend_user(
     AuthUser(admin): AuthUser,
@@ -420,6 +729,85 @@ pub async fn delete_user(
     })))
 }
 
+/// POST /api/admin/users/merge
+/// Merge a duplicate account into the primary one (admin only)
+pub async fn merge_users(
+    AuthUser(admin_user): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<MergeUsersRequest>,
+) -> Result<Json<MergeUsersResponse>, ApiError> {
+    require_role(&admin_user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    if request.primary_id == request.duplicate_id {
+        return Err(response::bad_request("Cannot merge an account into itself"));
+    }
+
+    let primary = users::find_with_role_by_id(&state.db, request.primary_id)
+        .await
+        .map_err(response::db_error)?
+        .filter(|u| !u.is_deleted())
+        .ok_or_else(|| response::not_found("Primary user"))?;
+
+    let duplicate = users::find_with_role_by_id(&state.db, request.duplicate_id)
+        .await
+        .map_err(response::db_error)?
+        .filter(|u| !u.is_deleted())
+        .ok_or_else(|| response::not_found("Duplicate user"))?;
+
+    // The duplicate is soft-deleted, so the same rule as deleting applies
+    if duplicate.is_admin() {
+        return Err(response::bad_request("Cannot merge away an admin account"));
+    }
+
+    // Both live subscriptions would keep billing; one has to be cancelled in
+    // Stripe by hand before the accounts can be merged
+    let primary_sub = subscriptions::find_active_or_latest_by_user_id(&state.db, primary.id)
+        .await
+        .map_err(response::db_error)?;
+    let duplicate_sub = subscriptions::find_active_or_latest_by_user_id(&state.db, duplicate.id)
+        .await
+        .map_err(response::db_error)?;
+    let is_stripe_active = |sub: &Option<loafy_db::models::Subscription>| {
+        sub.as_ref().is_some_and(|s| s.is_active() && s.stripe_subscription_id.is_some())
+    };
+    if is_stripe_active(&primary_sub) && is_stripe_active(&duplicate_sub) {
+        return Err(response::conflict(
+            "Both accounts have active Stripe subscriptions; cancel one in Stripe before merging",
+        ));
+    }
+
+    let summary = admin::merge_users(&state.db, primary.id, duplicate.id, admin_user.id)
+        .await
+        .map_err(|e| match e.downcast_ref::<UserMergeError>() {
+            Some(UserMergeError::UserNotFound) => response::not_found("User"),
+            Some(conflict) => response::conflict(conflict.to_string()),
+            None => response::db_error(e),
+        })?;
+
+    tracing::info!(
+        "Admin {} merged user {} into {}: {:?}",
+        admin_user.id,
+        duplicate.id,
+        primary.id,
+        summary
+    );
+
+    let user = users::find_with_role_by_id(&state.db, primary.id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    Ok(Json(MergeUsersResponse {
+        user: user.into(),
+        bookings_moved: summary.bookings_moved as i64,
+        ticket_transactions_moved: summary.ticket_transactions_moved as i64,
+        bonus_tickets_moved: summary.bonus_tickets_moved as i64,
+        waitlist_entries_moved: summary.waitlist_entries_moved as i64,
+        tickets_moved: summary.tickets_moved,
+        subscription_moved: summary.subscription_moved,
+    }))
+}
+
 /// Query parameters for bookings list endpoint
 #[derive(Deserialize)]
 pub struct BookingsQuery {
@@ -440,16 +828,16 @@ pub async fn list_bookings(
     State(state): State<AppState>,
     Query(query): Query<BookingsQuery>,
 ) -> Result<Json<PaginatedBookingsResponse>, ApiError> {
-    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !user.has_permission(permissions::BOOKINGS_MANAGE) {
+        return Err(response::forbidden("Permission to manage bookings required"));
+    }
 
-    let page = query.page.max(1);
-    let per_page = query.per_page.clamp(1, 100);
+    let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
 
     let (bookings, total) = admin::list_bookings_paginated(
         &state.db,
         admin::BookingsQueryParams {
-            page,
-            per_page,
+            pagination,
             search: query.search,
             payment_status: query.payment_status,
             session_id: query.session_id,
@@ -460,38 +848,14 @@ pub async fn list_bookings(
     .await
     .map_err(response::db_error)?;
 
-    let total_pages = ((total as f64) / (per_page as f64)).ceil() as i32;
-
     let data: Vec<AdminBookingResponse> = bookings
         .into_iter()
-        .map(|b| AdminBookingResponse {
-            id: b.id,
-            user_id: b.user_id,
-            session_id: b.session_id,
-            booking_code: b.booking_code,
-            guest_count: b.guest_count,
-            total_price_vnd: b.price_paid_vnd + b.guest_price_paid_vnd,
-            payment_method: b.payment_method,
-            payment_status: b.payment_status,
-            payment_deadline: b.payment_deadline,
-            cancelled_at: b.cancelled_at,
-            created_at: b.created_at,
-            user_email: b.user_email,
-            user_name: b.user_name,
-            session_title: b.session_title,
-            session_date: b.session_date,
-            session_time: b.session_time,
-        })
+        .map(AdminBookingResponse::from)
         .collect();
 
     Ok(Json(PaginatedBookingsResponse {
         data,
-        page_info: PageInfo {
-            page,
-            per_page,
-            total,
-            total_pages,
-        },
+        page_info: pagination.page_info(total),
     }))
 }
 
@@ -501,31 +865,16 @@ pub async fn get_booking(
     State(state): State<AppState>,
     Path(booking_id): Path<Uuid>,
 ) -> Result<Json<AdminBookingResponse>, ApiError> {
-    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !user.has_permission(permissions::BOOKINGS_MANAGE) {
+        return Err(response::forbidden("Permission to manage bookings required"));
+    }
 
     let booking = admin::get_booking_by_id(&state.db, booking_id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::not_found("Booking"))?;
 
-    Ok(Json(AdminBookingResponse {
-        id: booking.id,
-        user_id: booking.user_id,
-        session_id: booking.session_id,
-        booking_code: booking.booking_code,
-        guest_count: booking.guest_count,
-        total_price_vnd: booking.price_paid_vnd + booking.guest_price_paid_vnd,
-        payment_method: booking.payment_method,
-        payment_status: booking.payment_status,
-        payment_deadline: booking.payment_deadline,
-        cancelled_at: booking.cancelled_at,
-        created_at: booking.created_at,
-        user_email: booking.user_email,
-        user_name: booking.user_name,
-        session_title: booking.session_title,
-        session_date: booking.session_date,
-        session_time: booking.session_time,
-    }))
+    Ok(Json(AdminBookingResponse::from(booking)))
 }
 
 /// Update a booking (admin only)
@@ -535,7 +884,9 @@ pub async fn update_booking(
     Path(booking_id): Path<Uuid>,
     Json(request): Json<UpdateBookingRequest>,
 ) -> Result<Json<AdminBookingResponse>, ApiError> {
-    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !user.has_permission(permissions::BOOKINGS_MANAGE) {
+        return Err(response::forbidden("Permission to manage bookings required"));
+    }
 
     // Validate payment_status if provided
     if let Some(ref status) = request.payment_status {
@@ -554,8 +905,12 @@ pub async fn update_booking(
         }
     }
 
+    // Cancellation goes through the shared core path (slots, ticket restore)
+    let cancel_requested = request.payment_status.as_deref() == Some("cancelled");
+    let payment_status = if cancel_requested { None } else { request.payment_status };
+
     // Update the booking
-    let updated = admin::update_booking(
+    let mut updated = admin::update_booking(
         &state.db,
         booking_id,
         admin::UpdateBookingParams {
@@ -563,7 +918,7 @@ pub async fn update_booking(
             price_paid_vnd: request.price_paid_vnd,
             guest_price_paid_vnd: request.guest_price_paid_vnd,
             payment_method: request.payment_method,
-            payment_status: request.payment_status,
+            payment_status,
         },
     )
     .await
@@ -572,96 +927,245 @@ pub async fn update_booking(
         if msg.contains("not found") {
             response::not_found("Booking")
         } else if msg.contains("Not enough available slots") {
-            response::bad_request(msg)
+            response::conflict(msg)
         } else {
             response::db_error(e)
         }
     })?;
 
+    if cancel_requested {
+        cancel_booking(&state.db, booking_id, CancelledBy::Admin(user.id))
+            .await
+            .map_err(ApiError::from)?;
+
+        updated = admin::get_booking_by_id(&state.db, booking_id)
+            .await
+            .map_err(response::db_error)?
+            .ok_or_else(|| response::not_found("Booking"))?;
+    }
+
+    // Admins may exceed the session guest limit, but leave a trace when they do
+    if let Some(count) = request.guest_count {
+        if let Ok(Some(session)) = sessions_queries::find_by_id(&state.db, updated.session_id).await {
+            let max_guests = match session.max_guests_per_booking {
+                Some(max) => max,
+                None => config::load(&state.db)
+                    .await
+                    .map_err(response::db_error)?
+                    .max_guests_per_booking,
+            };
+            if count > max_guests {
+                tracing::warn!(
+                    "Admin {} set guest_count {} on booking {} (session limit {})",
+                    user.id, count, booking_id, max_guests
+                );
+            }
+        }
+    }
+
     // TODO: If admin_notes was provided, store it in an audit log
 
-    Ok(Json(AdminBookingResponse {
-        id: updated.id,
-        user_id: updated.user_id,
-        session_id: updated.session_id,
-        booking_code: updated.booking_code,
-        guest_count: updated.guest_count,
-        total_price_vnd: updated.price_paid_vnd + updated.guest_price_paid_vnd,
-        payment_method: updated.payment_method,
-        payment_status: updated.payment_status,
-        payment_deadline: updated.payment_deadline,
-        cancelled_at: updated.cancelled_at,
-        created_at: updated.created_at,
-        user_email: updated.user_email,
-        user_name: updated.user_name,
-        session_title: updated.session_title,
-        session_date: updated.session_date,
-        session_time: updated.session_time,
-    }))
+    Ok(Json(AdminBookingResponse::from(updated)))
 }
 
-/// Query parameters for sessions list endpoint
-#[derive(Deserialize)]
-pub struct SessionsQuery {
-    #[serde(default = "default_page")]
-    pub page: i32,
-    #[serde(default = "default_per_page")]
-    pub per_page: i32,
-    pub search: Option<String>,
-    pub status: Option<String>,
-    pub organizer_id: Option<Uuid>,
-    pub sort_by: Option<String>,
-    pub sort_order: Option<String>,
+/// Comp a user into a session: confirmed, free, and outside their ticket balance
+pub async fn create_comp_booking(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<CompBookingRequest>,
+) -> Result<Json<AdminBookingResponse>, ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    request.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(response::bad_request("A reason is required to comp a booking"));
+    }
+
+    users::find_by_id(&state.db, request.user_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    let booking = comp_booking(
+        &state.db,
+        request.user_id,
+        request.session_id,
+        request.guest_count,
+        admin.id,
+        reason,
+    )
+    .await
+    .map_err(ApiError::from)?;
+
+    tracing::info!(
+        "Admin {} comped booking {} for user {} ({})",
+        admin.id, booking.booking_code, request.user_id, reason
+    );
+
+    let booking = admin::get_booking_by_id(&state.db, booking.id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    Ok(Json(AdminBookingResponse::from(booking)))
 }
 
-/// List sessions with pagination (admin only)
-pub async fn list_sessions(
+/// Approve a booking's payment proof after manual review
+///
+/// `received_amount_vnd` records what actually arrived; send `{}` to approve
+/// without it. The booking is confirmed either way, and mismatches show up in
+/// the payment discrepancy report. A malformed body is rejected rather than
+/// approving without the amount.
+pub async fn verify_payment_proof(
     AuthUser(user): AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<SessionsQuery>,
-) -> Result<Json<PaginatedSessionsResponse>, ApiError> {
-    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    Path(booking_id): Path<Uuid>,
+    Json(payload): Json<VerifyPaymentRequest>,
+) -> Result<Json<AdminBookingResponse>, ApiError> {
+    payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
 
-    let page = query.page.max(1);
-    let per_page = query.per_page.clamp(1, 100);
+    review_payment_proof(&state, &user, booking_id, true, payload.received_amount_vnd).await
+}
 
-    let (sessions, total) = admin::list_sessions_paginated(
+/// Reject a booking's payment proof after manual review
+pub async fn reject_payment_proof(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(booking_id): Path<Uuid>,
+) -> Result<Json<AdminBookingResponse>, ApiError> {
+    review_payment_proof(&state, &user, booking_id, false, None).await
+}
+
+/// Shared review path; the conditional update makes concurrent reviews of the
+/// same booking resolve to exactly one winner
+async fn review_payment_proof(
+    state: &AppState,
+    user: &UserWithRole,
+    booking_id: Uuid,
+    approve: bool,
+    received_amount_vnd: Option<i32>,
+) -> Result<Json<AdminBookingResponse>, ApiError> {
+    if !user.has_permission(permissions::BOOKINGS_MANAGE) {
+        return Err(response::forbidden("Permission to manage bookings required"));
+    }
+
+    let (verification_status, payment_status) = if approve {
+        (VerificationStatus::Confirmed, Some(PaymentStatus::Confirmed.as_str()))
+    } else {
+        (VerificationStatus::Rejected, None)
+    };
+
+    let reviewed = bookings::review_payment_proof(
         &state.db,
-        admin::SessionsQueryParams {
-            page,
-            per_page,
-            search: query.search,
-            status: query.status,
-            organizer_id: query.organizer_id,
-            sort_by: query.sort_by,
-            sort_order: query.sort_order,
-        },
+        booking_id,
+        user.id,
+        verification_status.as_str(),
+        payment_status,
+        received_amount_vnd,
     )
     .await
     .map_err(response::db_error)?;
 
-    let total_pages = ((total as f64) / (per_page as f64)).ceil() as i32;
-
-    // Build response with participants for each session
-    let mut data = Vec::with_capacity(sessions.len());
-    for s in sessions {
-        // Fetch participants preview (max 5) and count for each session
-        let participants = sessions_queries::get_session_participants(&state.db, s.id, Some(5))
+    let Some(reviewed) = reviewed else {
+        let exists = bookings::find_by_id(&state.db, booking_id)
+            .await
+            .map_err(response::db_error)?
+            .is_some();
+        return Err(if exists {
+            response::conflict("Payment proof is not awaiting review, or the booking was cancelled or released")
+        } else {
+            response::not_found("Booking")
+        });
+    };
+
+    if approve {
+        loafy_core::metrics::payment_confirmed("bank_transfer");
+    }
+
+    tracing::info!(
+        "Admin {} {} payment proof for booking {}",
+        user.id,
+        verification_status.as_str(),
+        booking_id
+    );
+
+    if let Some(received) = reviewed.received_amount_vnd {
+        let expected = reviewed.price_paid_vnd + reviewed.guest_price_paid_vnd;
+        if received != expected {
+            tracing::warn!(
+                "Booking {} confirmed with {} VND received against {} VND expected",
+                booking_id,
+                received,
+                expected
+            );
+        }
+    }
+
+    let booking = admin::get_booking_by_id(&state.db, booking_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    Ok(Json(AdminBookingResponse::from(booking)))
+}
+
+/// Query parameters for sessions list endpoint
+#[derive(Deserialize)]
+pub struct SessionsQuery {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+    pub search: Option<String>,
+    pub status: Option<String>,
+    pub organizer_id: Option<Uuid>,
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,
+}
+
+/// List sessions with pagination (admin only)
+pub async fn list_sessions(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<SessionsQuery>,
+) -> Result<Json<PaginatedSessionsResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+
+    let (sessions, total) = admin::list_sessions_paginated(
+        &state.db,
+        admin::SessionsQueryParams {
+            pagination,
+            search: query.search,
+            status: query.status,
+            organizer_id: query.organizer_id,
+            sort_by: query.sort_by,
+            sort_order: query.sort_order,
+            today: club_today(offset),
+        },
+    )
+    .await
+    .map_err(response::db_error)?;
+
+    // Build response with participants for each session
+    let mut data = Vec::with_capacity(sessions.len());
+    for s in sessions {
+        // Fetch participants preview (max 5) and count for each session
+        let participants = sessions_queries::get_session_participants(&state.db, s.id, Some(5))
             .await
             .unwrap_or_default();
         let count = sessions_queries::count_session_participants(&state.db, s.id)
             .await
             .unwrap_or(0) as i32;
 
-        let participant_infos: Vec<ParticipantInfo> = participants
-            .into_iter()
-            .map(|p| ParticipantInfo {
-                id: p.user_id,
-                name: p.name,
-                avatar_url: p.avatar_url,
-                guest_count: p.guest_count,
-            })
-            .collect();
+        // Admins see the full roster regardless of visibility settings
+        let participant_infos: Vec<ParticipantInfo> = participants.into_iter().map(Into::into).collect();
 
         data.push(AdminSessionResponse {
             id: s.id,
@@ -685,12 +1189,7 @@ pub async fn list_sessions(
 
     Ok(Json(PaginatedSessionsResponse {
         data,
-        page_info: PageInfo {
-            page,
-            per_page,
-            total,
-            total_pages,
-        },
+        page_info: pagination.page_info(total),
     }))
 }
 
@@ -706,7 +1205,9 @@ pub async fn list_roles(
     AuthUser(user): AuthUser,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<RoleResponse>>, ApiError> {
-    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !user.has_permission(permissions::ROLES_MANAGE) {
+        return Err(response::forbidden("Permission to manage roles required"));
+    }
 
     let roles = admin::list_roles(&state.db)
         .await
@@ -720,6 +1221,36 @@ pub async fn list_roles(
     Ok(Json(response))
 }
 
+/// GET /api/admin/roles/permissions
+/// Role → permission matrix
+pub async fn get_permission_matrix(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<PermissionMatrixResponse>, ApiError> {
+    if !user.has_permission(permissions::ROLES_MANAGE) {
+        return Err(response::forbidden("Permission to manage roles required"));
+    }
+
+    let all_permissions = admin::list_permissions(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    let roles = admin::list_roles_with_permissions(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(PermissionMatrixResponse {
+        permissions: all_permissions
+            .into_iter()
+            .map(|p| PermissionResponse { name: p.name, description: p.description })
+            .collect(),
+        roles: roles
+            .into_iter()
+            .map(|(id, name, permissions)| RolePermissionsResponse { id, name, permissions })
+            .collect(),
+    }))
+}
+
 // =============================================================================
 // Profit & Expense Endpoints
 // =============================================================================
@@ -727,6 +1258,10 @@ pub async fn list_roles(
 /// Previous period profit stats
 #[derive(Serialize)]
 pub struct PreviousProfitStats {
+    /// Start of the comparison window (RFC 3339, inclusive)
+    pub previous_period_start: String,
+    /// End of the comparison window (RFC 3339, exclusive; the current period's start)
+    pub previous_period_end: String,
     pub total_expenses_vnd: i64,
     pub net_profit_vnd: i64,
     pub profit_margin_percent: f64,
@@ -736,9 +1271,21 @@ pub struct PreviousProfitStats {
 #[derive(Serialize)]
 pub struct ProfitStatsResponse {
     pub total_revenue_vnd: i64,
+    /// Variable plus fixed expenses
     pub total_expenses_vnd: i64,
+    /// Per-session expenses
+    pub variable_expenses_vnd: i64,
+    /// Prorated share of recurring fixed expenses
+    pub fixed_expenses_vnd: i64,
     pub net_profit_vnd: i64,
     pub profit_margin_percent: f64,
+    /// USD equivalents (omitted if no exchange rate is available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_revenue_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_expenses_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_profit_usd: Option<f64>,
     pub previous_period: Option<PreviousProfitStats>,
 }
 
@@ -750,24 +1297,40 @@ pub async fn get_profit_stats(
 ) -> Result<Json<ProfitStatsResponse>, ApiError> {
     require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
 
-    let period = parse_period(&query.period);
-    let (since, days) = (period.since, period.days);
+    let period = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?;
 
-    let stats = admin::get_profit_stats(&state.db, since)
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+    let today = club_today(offset);
+    let stats = admin::get_profit_stats(&state.db, period.since, today)
         .await
         .map_err(response::db_error)?;
 
     // Get previous period stats for comparison
-    let previous_period = if let (Some(current_start), Some(period_days)) = (since, days) {
-        let previous_start = current_start - Duration::days(period_days);
-        let prev_stats = admin::get_profit_stats(&state.db, Some(previous_start))
+    let previous_period = if let Some((previous_start, current_start)) = period.previous_window() {
+        // Profit stats only take a start date, so the previous window is the
+        // total since its start minus the current period
+        let since_previous = admin::get_profit_stats(&state.db, Some(previous_start), today)
             .await
             .map_err(response::db_error)?;
+        let revenue_vnd = since_previous.total_revenue_vnd - stats.total_revenue_vnd;
+        let total_expenses_vnd = since_previous.total_expenses_vnd - stats.total_expenses_vnd;
+        let net_profit_vnd = revenue_vnd - total_expenses_vnd;
+        let profit_margin_percent = if revenue_vnd > 0 {
+            (net_profit_vnd as f64 / revenue_vnd as f64) * 100.0
+        } else {
+            0.0
+        };
 
         Some(PreviousProfitStats {
-            total_expenses_vnd: prev_stats.total_expenses_vnd,
-            net_profit_vnd: prev_stats.net_profit_vnd,
-            profit_margin_percent: prev_stats.profit_margin_percent,
+            previous_period_start: previous_start.to_rfc3339(),
+            previous_period_end: current_start.to_rfc3339(),
+            total_expenses_vnd,
+            net_profit_vnd,
+            profit_margin_percent,
         })
     } else {
         None
@@ -776,8 +1339,13 @@ pub async fn get_profit_stats(
     Ok(Json(ProfitStatsResponse {
         total_revenue_vnd: stats.total_revenue_vnd,
         total_expenses_vnd: stats.total_expenses_vnd,
+        variable_expenses_vnd: stats.variable_expenses_vnd,
+        fixed_expenses_vnd: stats.fixed_expenses_vnd,
         net_profit_vnd: stats.net_profit_vnd,
         profit_margin_percent: stats.profit_margin_percent,
+        total_revenue_usd: state.fx.vnd_to_usd(stats.total_revenue_vnd).await,
+        total_expenses_usd: state.fx.vnd_to_usd(stats.total_expenses_vnd).await,
+        net_profit_usd: state.fx.vnd_to_usd(stats.net_profit_vnd).await,
         previous_period,
     }))
 }
@@ -792,6 +1360,9 @@ pub struct SessionProfitResponse {
     pub expenses_vnd: i64,
     pub profit_vnd: i64,
     pub profit_margin_percent: f64,
+    /// USD equivalent of profit_vnd (omitted if no exchange rate is available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profit_usd: Option<f64>,
 }
 
 /// Query params for sessions profit endpoint
@@ -815,12 +1386,16 @@ pub async fn get_sessions_profit(
 ) -> Result<Json<Vec<SessionProfitResponse>>, ApiError> {
     require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
 
-    let since = parse_period(&query.period).since;
+    let since = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?
+        .since;
 
-    let summaries = admin::get_sessions_profit(&state.db, since, query.limit)
+    let summaries = admin::get_sessions_profit(&state.db, since, None, Some(query.limit))
         .await
         .map_err(response::db_error)?;
 
+    let vnd_per_usd = state.fx.vnd_per_usd().await;
+
     let response: Vec<SessionProfitResponse> = summaries
         .into_iter()
         .map(|s| SessionProfitResponse {
@@ -831,12 +1406,102 @@ pub async fn get_sessions_profit(
             expenses_vnd: s.expenses_vnd,
             profit_vnd: s.profit_vnd,
             profit_margin_percent: s.profit_margin_percent,
+            profit_usd: vnd_per_usd.map(|rate| ((s.profit_vnd as f64 / rate) * 100.0).round() / 100.0),
         })
         .collect();
 
     Ok(Json(response))
 }
 
+/// Download one session's profit breakdown as a PDF (admin only)
+pub async fn get_session_profit_report(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let session = sessions_queries::find_by_id(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    let summary = admin::get_session_profit(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    let expenses = session_expenses::list_expenses_for_session(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?;
+
+    let participant_count = sessions_queries::count_session_participants(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?;
+
+    let pdf = render_profit_report(&ProfitReport {
+        session: &session,
+        summary: &summary,
+        expenses: &expenses,
+        participant_count,
+        generated_at: Utc::now(),
+    })
+    .map_err(|e| response::internal_error_msg("Failed to render profit report", e))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"profit-{}-{}.pdf\"", session.date.format("%Y-%m-%d"), session.id),
+            ),
+        ],
+        pdf,
+    ))
+}
+
+/// Verified bookings whose received amount didn't match the price (admin only)
+pub async fn get_payment_discrepancies(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<PaymentDiscrepanciesResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let since = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?
+        .since;
+
+    let rows = admin::get_payment_discrepancies(&state.db, since)
+        .await
+        .map_err(response::db_error)?;
+
+    let discrepancies: Vec<PaymentDiscrepancyResponse> = rows
+        .into_iter()
+        .map(|d| PaymentDiscrepancyResponse {
+            difference_vnd: d.received_vnd as i64 - d.expected_vnd as i64,
+            booking_id: d.booking_id,
+            booking_code: d.booking_code,
+            user_id: d.user_id,
+            user_email: d.user_email,
+            user_name: d.user_name,
+            session_id: d.session_id,
+            session_title: d.session_title,
+            session_date: d.session_date,
+            expected_vnd: d.expected_vnd,
+            received_vnd: d.received_vnd,
+            verified_by: d.verified_by,
+            verified_at: d.verified_at,
+        })
+        .collect();
+
+    Ok(Json(PaymentDiscrepanciesResponse {
+        period: query.period,
+        total_difference_vnd: discrepancies.iter().map(|d| d.difference_vnd).sum(),
+        discrepancies,
+    }))
+}
+
 /// Expense category response
 #[derive(Serialize)]
 pub struct ExpenseCategoryResponse {
@@ -853,7 +1518,9 @@ pub async fn get_expenses_by_category(
 ) -> Result<Json<Vec<ExpenseCategoryResponse>>, ApiError> {
     require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
 
-    let since = parse_period(&query.period).since;
+    let since = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?
+        .since;
 
     let categories = admin::get_expenses_by_category(&state.db, since)
         .await
@@ -871,26 +1538,154 @@ pub async fn get_expenses_by_category(
     Ok(Json(response))
 }
 
+/// Query params for session fill analytics
+#[derive(Debug, Deserialize)]
+pub struct FillAnalyticsQuery {
+    pub location: Option<String>,
+    /// "morning,afternoon,evening" (comma-separated)
+    pub time_of_day: Option<String>,
+    #[serde(default = "default_fill_weeks")]
+    pub weeks: i32,
+}
+
+fn default_fill_weeks() -> i32 {
+    12
+}
+
+/// Longest history the fill analytics look back over
+const MAX_FILL_ANALYTICS_WEEKS: i32 = 104;
+
+/// Get average fill, revenue and cancellation rate per weekday + start hour
+///
+/// Admins see every session; organizers only their own.
+pub async fn get_fill_analytics(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<FillAnalyticsQuery>,
+) -> Result<Json<FillAnalyticsResponse>, ApiError> {
+    if !user.has_permission(permissions::ANALYTICS_VIEW) {
+        return Err(response::forbidden("Only organizers and admins can view fill analytics"));
+    }
+
+    let weeks = query.weeks.clamp(1, MAX_FILL_ANALYTICS_WEEKS);
+    let organizer_id = if user.is_admin() { None } else { Some(user.id) };
+    let location = query.location.as_deref().filter(|loc| !loc.is_empty());
+    let time_of_day = query
+        .time_of_day
+        .as_deref()
+        .map(TimeOfDay::parse_list)
+        .unwrap_or_default();
+
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+    let today = club_today(offset);
+    let stats = admin::get_slot_fill_analytics(&state.db, today, weeks, organizer_id, location, &time_of_day)
+        .await
+        .map_err(response::db_error)?;
+
+    let slots = stats
+        .into_iter()
+        .map(|s| SlotFillResponse {
+            weekday: s.weekday,
+            hour: s.hour,
+            session_count: s.session_count,
+            avg_fill_percent: s.avg_fill_percent.unwrap_or(0.0),
+            avg_revenue_vnd: s.avg_revenue_vnd.unwrap_or(0.0).round() as i64,
+            cancellation_rate_percent: if s.session_count > 0 {
+                s.cancelled_count as f64 * 100.0 / s.session_count as f64
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    Ok(Json(FillAnalyticsResponse { weeks, slots }))
+}
+
+/// Get a session's expenses with per-court amounts multiplied out
+pub async fn get_session_expenses(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AdminSessionExpensesResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let session = sessions_queries::find_by_id(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    let expenses = session_expenses::list_expenses_for_session(&state.db, id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to fetch expenses", e))?;
+
+    let expenses: Vec<AdminSessionExpense> = expenses
+        .into_iter()
+        .map(|e| AdminSessionExpense {
+            computed_amount_vnd: e.amount_for_courts(session.courts),
+            id: e.id,
+            category: e.category,
+            description: e.description,
+            cost_type: e.cost_type,
+            amount_vnd: e.amount_vnd,
+        })
+        .collect();
+    let total_expenses_vnd = expenses.iter().map(|e| e.computed_amount_vnd).sum();
+
+    Ok(Json(AdminSessionExpensesResponse {
+        session_id: session.id,
+        courts: session.courts,
+        expenses,
+        total_expenses_vnd,
+    }))
+}
+
 /// Daily profit data point response
 #[derive(Serialize)]
 pub struct DailyProfitDataPointResponse {
     pub date: String,
     pub revenue_vnd: i64,
+    /// Variable plus fixed expenses
     pub expenses_vnd: i64,
+    /// Prorated share of recurring fixed expenses included in `expenses_vnd`
+    pub fixed_expenses_vnd: i64,
     pub profit_vnd: i64,
 }
 
-/// Get daily profit data for charts (admin only)
+/// Query parameters for the profit trend endpoint
+#[derive(Deserialize)]
+pub struct DailyProfitQuery {
+    /// Period filter: "7d", "30d", "90d", "365d", or "all"
+    #[serde(default = "default_period")]
+    pub period: String,
+    /// Bucket granularity: "day", "week" or "month".
+    /// Defaults to day, or month for ranges over ~180 days and "all".
+    pub bucket: Option<String>,
+}
+
+/// Get profit data for charts, bucketed by day/week/month (admin only)
 pub async fn get_daily_profit_data(
     AuthUser(user): AuthUser,
     State(state): State<AppState>,
-    Query(query): Query<StatsQuery>,
+    Query(query): Query<DailyProfitQuery>,
 ) -> Result<Json<Vec<DailyProfitDataPointResponse>>, ApiError> {
     require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
 
-    let since = parse_period(&query.period).since_or_default();
+    let period = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?;
+    let bucket = match query.bucket.as_deref().filter(|b| !b.is_empty()) {
+        Some(value) => admin::ProfitBucket::parse(value)
+            .ok_or_else(|| response::bad_request("bucket must be one of: day, week, month"))?,
+        None => admin::ProfitBucket::for_range(period.days),
+    };
 
-    let data = admin::get_daily_profit_data(&state.db, since)
+    let offset = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .session_utc_offset;
+    let data = admin::get_daily_profit_data(&state.db, period.since, club_today(offset), bucket)
         .await
         .map_err(response::db_error)?;
 
@@ -900,6 +1695,7 @@ pub async fn get_daily_profit_data(
             date: d.date,
             revenue_vnd: d.revenue,
             expenses_vnd: d.expenses,
+            fixed_expenses_vnd: d.fixed_expenses,
             profit_vnd: d.profit,
         })
         .collect();
@@ -907,6 +1703,279 @@ pub async fn get_daily_profit_data(
     Ok(Json(response))
 }
 
+/// Validate a fixed expense's category, recurrence, description and dates
+fn validate_fixed_expense(request: &FixedExpenseRequest) -> Result<(), ApiError> {
+    request
+        .validate()
+        .map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    if !EXPENSE_CATEGORIES.contains(&request.category.as_str()) {
+        return Err(response::bad_request(format!("Invalid expense category: {}", request.category)));
+    }
+    if !["weekly", "monthly"].contains(&request.recurrence.as_str()) {
+        return Err(response::bad_request("recurrence must be one of: weekly, monthly"));
+    }
+    if request.category == "custom" && request.description.as_deref().is_none_or(|d| d.trim().is_empty()) {
+        return Err(response::bad_request("Custom expenses require a description"));
+    }
+    if request.ends_on.is_some_and(|ends_on| ends_on < request.starts_on) {
+        return Err(response::bad_request("ends_on must not be before starts_on"));
+    }
+    Ok(())
+}
+
+fn fixed_expense_params(request: &FixedExpenseRequest) -> fixed_expenses::FixedExpenseParams<'_> {
+    fixed_expenses::FixedExpenseParams {
+        category: &request.category,
+        description: request.description.as_deref().map(str::trim).filter(|d| !d.is_empty()),
+        amount_vnd: request.amount_vnd,
+        recurrence: &request.recurrence,
+        starts_on: request.starts_on,
+        ends_on: request.ends_on,
+    }
+}
+
+/// GET /api/admin/fixed-expenses
+/// List recurring fixed expenses (admin only)
+pub async fn list_fixed_expenses(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<FixedExpenseResponse>>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let expenses = fixed_expenses::list_all(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(expenses.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/admin/fixed-expenses
+/// Create a recurring fixed expense (admin only)
+pub async fn create_fixed_expense(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<FixedExpenseRequest>,
+) -> Result<(StatusCode, Json<FixedExpenseResponse>), ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    validate_fixed_expense(&request)?;
+
+    let expense = fixed_expenses::create(&state.db, &fixed_expense_params(&request), user.id)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok((StatusCode::CREATED, Json(expense.into())))
+}
+
+/// PUT /api/admin/fixed-expenses/:id
+/// Replace a recurring fixed expense (admin only)
+pub async fn update_fixed_expense(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<FixedExpenseRequest>,
+) -> Result<Json<FixedExpenseResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    validate_fixed_expense(&request)?;
+
+    let expense = fixed_expenses::update(&state.db, id, &fixed_expense_params(&request))
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Fixed expense"))?;
+
+    Ok(Json(expense.into()))
+}
+
+/// DELETE /api/admin/fixed-expenses/:id
+/// Delete a recurring fixed expense (admin only)
+pub async fn delete_fixed_expense(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let deleted = fixed_expenses::delete(&state.db, id)
+        .await
+        .map_err(response::db_error)?;
+    if !deleted {
+        return Err(response::not_found("Fixed expense"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// Discount Code Endpoints
+// =============================================================================
+
+/// Validate a discount code's code, amount and validity window
+fn validate_discount_code(request: &DiscountCodeRequest) -> Result<(), ApiError> {
+    request
+        .validate()
+        .map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    if !request.code.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(response::bad_request("code may only contain letters, digits, '-' and '_'"));
+    }
+    if request.percent_off.is_some() == request.amount_off_vnd.is_some() {
+        return Err(response::bad_request("Set exactly one of percent_off and amount_off_vnd"));
+    }
+    if let (Some(from), Some(until)) = (request.valid_from, request.valid_until) {
+        if until <= from {
+            return Err(response::bad_request("valid_until must be after valid_from"));
+        }
+    }
+    Ok(())
+}
+
+fn discount_code_params<'a>(request: &DiscountCodeRequest, code: &'a str) -> discount_codes::DiscountCodeParams<'a> {
+    discount_codes::DiscountCodeParams {
+        code,
+        percent_off: request.percent_off,
+        amount_off_vnd: request.amount_off_vnd,
+        applies_to_guests: request.applies_to_guests,
+        max_uses: request.max_uses,
+        max_uses_per_user: request.max_uses_per_user,
+        valid_from: request.valid_from,
+        valid_until: request.valid_until,
+        active: request.active.unwrap_or(true),
+    }
+}
+
+/// Map a duplicate code to 409
+fn discount_code_write_error(e: anyhow::Error) -> ApiError {
+    match e.downcast_ref::<discount_codes::DuplicateDiscountCode>() {
+        Some(duplicate) => response::conflict(duplicate.to_string()),
+        None => response::db_error(e),
+    }
+}
+
+/// Fetch a discount code with its usage for a response
+async fn discount_code_response(state: &AppState, id: Uuid) -> Result<DiscountCodeResponse, ApiError> {
+    discount_codes::find_with_usage(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .map(Into::into)
+        .ok_or_else(|| response::not_found("Discount code"))
+}
+
+/// GET /api/admin/discount-codes
+/// List discount codes with their usage (admin only)
+pub async fn list_discount_codes(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DiscountCodeResponse>>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let codes = discount_codes::list_with_usage(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(codes.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/admin/discount-codes
+/// Create a discount code (admin only)
+pub async fn create_discount_code(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<DiscountCodeRequest>,
+) -> Result<(StatusCode, Json<DiscountCodeResponse>), ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    validate_discount_code(&request)?;
+
+    let code = request.code.trim().to_uppercase();
+    let created = discount_codes::create(&state.db, &discount_code_params(&request, &code), user.id)
+        .await
+        .map_err(discount_code_write_error)?;
+
+    Ok((StatusCode::CREATED, Json(discount_code_response(&state, created.id).await?)))
+}
+
+/// PUT /api/admin/discount-codes/:id
+/// Replace a discount code (admin only)
+///
+/// Lowering a limit below the current usage doesn't affect existing bookings;
+/// it only stops further uses.
+pub async fn update_discount_code(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<DiscountCodeRequest>,
+) -> Result<Json<DiscountCodeResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    validate_discount_code(&request)?;
+
+    let code = request.code.trim().to_uppercase();
+    discount_codes::update(&state.db, id, &discount_code_params(&request, &code))
+        .await
+        .map_err(discount_code_write_error)?
+        .ok_or_else(|| response::not_found("Discount code"))?;
+
+    Ok(Json(discount_code_response(&state, id).await?))
+}
+
+/// DELETE /api/admin/discount-codes/:id
+/// Delete a discount code; bookings that used it keep the code text (admin only)
+pub async fn delete_discount_code(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let deleted = discount_codes::delete(&state.db, id)
+        .await
+        .map_err(response::db_error)?;
+    if !deleted {
+        return Err(response::not_found("Discount code"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// Booking Funnel Endpoint
+// =============================================================================
+
+/// GET /api/admin/stats/booking-funnel
+/// Booking attempts, outcomes and conversion rate for a period (admin only)
+pub async fn get_booking_funnel(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<BookingFunnelResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let since = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?
+        .since;
+
+    let stats = admin::get_booking_funnel(&state.db, since)
+        .await
+        .map_err(response::db_error)?;
+
+    let attempts = stats.created + stats.failed_full + stats.failed_already_booked + stats.failed_limit_reached;
+    let conversion_rate_percent = if attempts > 0 {
+        (stats.confirmed as f64 / attempts as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(Json(BookingFunnelResponse {
+        attempts,
+        failed: BookingFailuresResponse {
+            full: stats.failed_full,
+            already_booked: stats.failed_already_booked,
+            limit_reached: stats.failed_limit_reached,
+        },
+        created: stats.created,
+        confirmed: stats.confirmed,
+        expired_unpaid: stats.expired_unpaid,
+        cancelled_by_user: stats.cancelled_by_user,
+        conversion_rate_percent,
+    }))
+}
+
 // =============================================================================
 // Ticket Management Endpoints
 // =============================================================================
@@ -927,12 +1996,12 @@ pub async fn get_user_tickets(
         .ok_or_else(|| response::not_found("User"))?;
 
     // Get subscription and ticket balance
-    let subscription = subscriptions::find_by_user_id(&state.db, user_id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user_id)
         .await
         .map_err(response::db_error)?;
 
     // Get recent transactions (last 10)
-    let (transactions, _) = ticket_transactions::list_user_transactions(&state.db, user_id, 1, 10)
+    let (transactions, _) = ticket_transactions::list_user_transactions(&state.db, user_id, Pagination::from_query(1, 10, 10))
         .await
         .map_err(response::db_error)?;
 
@@ -969,6 +2038,46 @@ pub async fn get_user_tickets(
     }))
 }
 
+/// GET /api/admin/users/:id/ticket-summary
+/// Lifetime ticket totals from the ledger, checked against the stored balance (admin only)
+pub async fn get_user_ticket_summary(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<AdminTicketSummaryResponse>, ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    users::find_by_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    let summary = ticket_transactions::summarize_for_user(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?;
+
+    let current_balance = subscriptions::find_active_or_latest_by_user_id(&state.db, user_id)
+        .await
+        .map_err(response::db_error)?
+        .map(|s| s.tickets_remaining)
+        .unwrap_or(0);
+
+    Ok(Json(AdminTicketSummaryResponse {
+        user_id,
+        subscription_granted: summary.subscription_granted,
+        bonus_granted: summary.bonus_granted,
+        total_granted: summary.subscription_granted + summary.bonus_granted,
+        used: summary.used,
+        restored: summary.restored,
+        expired: summary.expired,
+        revoked: summary.revoked,
+        merged: summary.merged,
+        ledger_balance: summary.ledger_balance,
+        current_balance,
+        reconciles: summary.ledger_balance == current_balance as i64,
+    }))
+}
+
 /// POST /api/admin/users/:id/tickets/grant
 /// Grant bonus tickets to a user (admin only)
 pub async fn grant_tickets(
@@ -977,10 +2086,12 @@ pub async fn grant_tickets(
     Path(user_id): Path<Uuid>,
     Json(request): Json<AdminGrantTicketsRequest>,
 ) -> Result<Json<TicketBalanceResponse>, ApiError> {
-    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !admin.has_permission(permissions::TICKETS_MANAGE) {
+        return Err(response::forbidden("Permission to manage tickets required"));
+    }
 
     // Validate request
-    request.validate().map_err(|e| response::bad_request(&e.to_string()))?;
+    request.validate().map_err(|e| response::bad_request(e.to_string()))?;
 
     // Check user exists
     let _user = users::find_by_id(&state.db, user_id)
@@ -989,7 +2100,7 @@ pub async fn grant_tickets(
         .ok_or_else(|| response::not_found("User"))?;
 
     // Get user's subscription
-    let subscription = subscriptions::find_by_user_id(&state.db, user_id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user_id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::bad_request("User does not have a subscription"))?;
@@ -1027,6 +2138,7 @@ pub async fn grant_tickets(
     )
     .await
     .map_err(response::db_error)?;
+    loafy_core::metrics::tickets_granted("admin", request.amount);
 
     tracing::info!(
         "Admin {} granted {} tickets to user {}",
@@ -1035,11 +2147,9 @@ pub async fn grant_tickets(
         user_id
     );
 
-    Ok(Json(TicketBalanceResponse {
-        tickets_remaining: new_balance,
-        has_active_subscription: subscription.is_active(),
-        current_period_end: subscription.current_period_end.map(|dt| dt.naive_utc()),
-    }))
+    let config = config::load(&state.db).await.map_err(response::db_error)?;
+
+    Ok(Json(ticket_balance_response(&config, Some(&subscription), new_balance)))
 }
 
 /// POST /api/admin/users/:id/tickets/revoke
@@ -1050,10 +2160,12 @@ pub async fn revoke_tickets(
     Path(user_id): Path<Uuid>,
     Json(request): Json<AdminGrantTicketsRequest>,
 ) -> Result<Json<TicketBalanceResponse>, ApiError> {
-    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    if !admin.has_permission(permissions::TICKETS_MANAGE) {
+        return Err(response::forbidden("Permission to manage tickets required"));
+    }
 
     // Validate request
-    request.validate().map_err(|e| response::bad_request(&e.to_string()))?;
+    request.validate().map_err(|e| response::bad_request(e.to_string()))?;
 
     // Check user exists
     let _user = users::find_by_id(&state.db, user_id)
@@ -1062,7 +2174,7 @@ pub async fn revoke_tickets(
         .ok_or_else(|| response::not_found("User"))?;
 
     // Get user's subscription
-    let subscription = subscriptions::find_by_user_id(&state.db, user_id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user_id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::bad_request("User does not have a subscription"))?;
@@ -1094,9 +2206,341 @@ pub async fn revoke_tickets(
         user_id
     );
 
-    Ok(Json(TicketBalanceResponse {
-        tickets_remaining: new_balance,
-        has_active_subscription: subscription.is_active(),
-        current_period_end: subscription.current_period_end.map(|dt| dt.naive_utc()),
+    let config = config::load(&state.db).await.map_err(response::db_error)?;
+
+    Ok(Json(ticket_balance_response(&config, Some(&subscription), new_balance)))
+}
+
+/// Re-apply a subscription's latest paid invoice after a failed webhook
+///
+/// Safe to repeat: periods that were already granted are left alone. The
+/// optional `user_id` attributes a new subscription whose Stripe metadata
+/// names no user.
+pub async fn reconcile_subscription(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Path(stripe_subscription_id): Path<String>,
+    Json(payload): Json<ReconcileSubscriptionRequest>,
+) -> Result<Json<ReconcileSubscriptionResponse>, ApiError> {
+    if !admin.has_permission(permissions::TICKETS_MANAGE) {
+        return Err(response::forbidden("Permission to manage tickets required"));
+    }
+
+    if let Some(user_id) = payload.user_id {
+        users::find_by_id(&state.db, user_id)
+            .await
+            .map_err(response::db_error)?
+            .ok_or_else(|| response::not_found("User"))?;
+    }
+
+    let stripe = get_stripe_subscriptions()?;
+    let (invoice_id, outcome) =
+        stripe::reconcile_subscription(&state.db, &stripe, &stripe_subscription_id, payload.user_id)
+            .await
+            .map_err(|e| {
+                let msg = e.to_string();
+                if msg.contains("not paid") || msg.contains("has no invoice") {
+                    response::conflict(msg)
+                } else {
+                    response::internal_error_msg("Failed to reconcile subscription", e)
+                }
+            })?;
+
+    tracing::info!(
+        "Admin {} reconciled subscription {}: {}",
+        admin.id,
+        stripe_subscription_id,
+        outcome.as_str()
+    );
+
+    let subscription = subscriptions::find_by_stripe_subscription_id(&state.db, &stripe_subscription_id)
+        .await
+        .map_err(response::db_error)?
+        .map(|sub| SubscriptionResponse {
+            id: sub.id,
+            user_id: sub.user_id,
+            tickets_remaining: sub.tickets_remaining,
+            status: sub.status.parse().unwrap_or_default(),
+            current_period_start: sub.current_period_start.map(|dt| dt.naive_utc()),
+            current_period_end: sub.current_period_end.map(|dt| dt.naive_utc()),
+            auto_renew: sub.auto_renew,
+            created_at: sub.created_at.naive_utc(),
+        });
+
+    Ok(Json(ReconcileSubscriptionResponse {
+        stripe_subscription_id,
+        invoice_id,
+        outcome: outcome.as_str().to_string(),
+        subscription,
     }))
 }
+
+/// Query parameters for the ticket ledger endpoint
+#[derive(Deserialize)]
+pub struct TicketTransactionsQuery {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+    pub transaction_type: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+
+/// GET /api/admin/tickets/transactions
+/// List ticket transactions across all users (admin only)
+pub async fn list_ticket_transactions(
+    AuthUser(admin): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<TicketTransactionsQuery>,
+) -> Result<Json<AdminTicketTransactionsResponse>, ApiError> {
+    require_role(&admin, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
+
+    let filters = ticket_transactions::TicketTransactionFilters {
+        transaction_type: query.transaction_type.filter(|t| !t.is_empty()),
+        user_id: query.user_id,
+        from_date: query.from_date,
+        to_date: query.to_date,
+    };
+
+    let (transactions, total) = ticket_transactions::list_transactions_filtered(
+        &state.db,
+        &filters,
+        pagination,
+    )
+    .await
+    .map_err(response::db_error)?;
+
+    let data = transactions
+        .into_iter()
+        .map(|tx| AdminTicketTransactionResponse {
+            transaction: TicketTransactionResponse {
+                id: tx.id,
+                transaction_type: tx.transaction_type,
+                amount: tx.amount,
+                balance_after: tx.balance_after,
+                notes: tx.notes,
+                booking_code: tx.booking_code,
+                created_at: tx.created_at.naive_utc(),
+            },
+            user_id: tx.user_id,
+            user_email: tx.user_email,
+            admin_id: tx.admin_id,
+        })
+        .collect();
+
+    Ok(Json(AdminTicketTransactionsResponse {
+        data,
+        page_info: pagination.page_info(total),
+    }))
+}
+
+/// List all locations with session counts (admin only)
+pub async fn list_locations(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LocationResponse>>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let locations = locations::list_with_counts(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(locations.into_iter().map(location_response).collect()))
+}
+
+fn location_write_error(e: anyhow::Error) -> ApiError {
+    match e.downcast_ref::<locations::LocationWriteError>() {
+        Some(locations::LocationWriteError::NotFound) => response::not_found("Location"),
+        Some(exists @ locations::LocationWriteError::AlreadyExists) => response::conflict(exists.to_string()),
+        None => response::db_error(e),
+    }
+}
+
+/// Create a location (admin only)
+pub async fn create_location(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<LocationRequest>,
+) -> Result<Json<LocationResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    request.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+
+    let location = locations::create(&state.db, &request.name)
+        .await
+        .map_err(location_write_error)?;
+
+    Ok(Json(LocationResponse {
+        id: location.id,
+        name: location.name,
+        session_count: 0,
+    }))
+}
+
+/// Rename a location; linked sessions pick up the new name (admin only)
+pub async fn update_location(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(location_id): Path<Uuid>,
+    Json(request): Json<LocationRequest>,
+) -> Result<Json<LocationResponse>, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+    request.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+
+    let location = locations::rename(&state.db, location_id, &request.name)
+        .await
+        .map_err(location_write_error)?;
+
+    let session_count = locations::count_sessions(&state.db, location.id)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(LocationResponse {
+        id: location.id,
+        name: location.name,
+        session_count,
+    }))
+}
+
+/// Delete a location that no session uses (admin only)
+pub async fn delete_location(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(location_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_role(&user, "admin").map_err(|_| response::forbidden("Admin access required"))?;
+
+    let in_use = locations::count_sessions(&state.db, location_id)
+        .await
+        .map_err(response::db_error)?;
+    if in_use > 0 {
+        return Err(response::conflict(format!(
+            "Location is used by {} session(s)",
+            in_use
+        )));
+    }
+
+    let deleted = locations::delete(&state.db, location_id)
+        .await
+        .map_err(response::db_error)?;
+    if !deleted {
+        return Err(response::not_found("Location"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn config_response(config: &config::Config) -> ConfigResponse {
+    ConfigResponse {
+        out_of_ticket_discount_percent: config.out_of_ticket_discount_percent,
+        subscription_ticket_count: config.subscription_ticket_count,
+        default_payment_hold_minutes: config.default_payment_hold_minutes,
+        birthday_ticket_count: config.birthday_ticket_count,
+        guest_tickets_enabled: config.guest_tickets_enabled,
+        platform_fee_percent: config.platform_fee_percent,
+        ticket_rollover_cap: config.ticket_rollover_cap,
+        default_subscription_period_months: config.default_subscription_period_months,
+        organizer_self_booking: config.organizer_self_booking,
+    }
+}
+
+/// Get pricing and ticket settings (admin only)
+pub async fn get_config(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<ConfigResponse>, ApiError> {
+    if !user.has_permission(permissions::CONFIG_MANAGE) {
+        return Err(response::forbidden("Permission to manage config required"));
+    }
+
+    let config = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(config_response(&config)))
+}
+
+/// Turn maintenance mode on or off (admin only)
+///
+/// While enabled, creating bookings, sessions and subscriptions returns 503
+/// with the message; everything else keeps working.
+pub async fn set_maintenance(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<MaintenanceRequest>,
+) -> Result<Json<MaintenanceResponse>, ApiError> {
+    if !user.has_permission(permissions::CONFIG_MANAGE) {
+        return Err(response::forbidden("Permission to manage config required"));
+    }
+
+    request.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+
+    // An empty message clears any previous one
+    let message = request.message.as_deref().map(str::trim).unwrap_or_default();
+
+    config::set_value(&state.db, config::keys::MAINTENANCE_MESSAGE, message)
+        .await
+        .map_err(response::db_error)?;
+    config::set_value(&state.db, config::keys::MAINTENANCE_MODE, &request.enabled.to_string())
+        .await
+        .map_err(response::db_error)?;
+
+    tracing::info!("Admin {} set maintenance mode to {}", user.id, request.enabled);
+
+    let config = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(MaintenanceResponse {
+        enabled: config.maintenance_mode,
+        message: config.maintenance_message.clone(),
+    }))
+}
+
+/// Update pricing and ticket settings (admin only)
+pub async fn update_config(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(request): Json<UpdateConfigRequest>,
+) -> Result<Json<ConfigResponse>, ApiError> {
+    if !user.has_permission(permissions::CONFIG_MANAGE) {
+        return Err(response::forbidden("Permission to manage config required"));
+    }
+
+    request.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+
+    let admin_digest_cron = request.admin_digest_cron.as_deref().map(str::trim);
+    if let Some(cron) = admin_digest_cron {
+        config::validate_cron(cron).map_err(|e| response::bad_request(e.to_string()))?;
+    }
+
+    let updates = [
+        (config::keys::OUT_OF_TICKET_DISCOUNT_PERCENT, request.out_of_ticket_discount_percent.map(|v| v.to_string())),
+        (config::keys::SUBSCRIPTION_TICKET_COUNT, request.subscription_ticket_count.map(|v| v.to_string())),
+        (config::keys::DEFAULT_PAYMENT_HOLD_MINUTES, request.default_payment_hold_minutes.map(|v| v.to_string())),
+        (config::keys::BIRTHDAY_TICKET_COUNT, request.birthday_ticket_count.map(|v| v.to_string())),
+        (config::keys::GUEST_TICKETS_ENABLED, request.guest_tickets_enabled.map(|v| v.to_string())),
+        (config::keys::PLATFORM_FEE_PERCENT, request.platform_fee_percent.map(|v| v.to_string())),
+        (config::keys::TICKET_ROLLOVER_CAP, request.ticket_rollover_cap.map(|v| v.to_string())),
+        (config::keys::DEFAULT_SUBSCRIPTION_PERIOD_MONTHS, request.default_subscription_period_months.map(|v| v.to_string())),
+        (config::keys::ORGANIZER_SELF_BOOKING, request.organizer_self_booking.map(|v| v.as_str().to_string())),
+        (config::keys::ADMIN_DIGEST_CRON, admin_digest_cron.map(str::to_string)),
+    ];
+
+    for (key, value) in updates {
+        if let Some(value) = value {
+            config::set_value(&state.db, key, &value)
+                .await
+                .map_err(response::db_error)?;
+        }
+    }
+
+    let config = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(config_response(&config)))
+}
@@ -3,7 +3,8 @@ use axum_extra::{
     headers::{authorization::Bearer, Authorization},
     TypedHeader,
 };
-use loafy_db::queries::users;
+use loafy_db::queries::{subscription_gifts, users};
+use loafy_integrations::supabase::UserLookupError;
 use loafy_types::api::AuthUser;
 use serde::{Deserialize, Serialize};
 
@@ -32,7 +33,13 @@ pub async fn handle_callback(
         .supabase
         .get_user_from_token(&payload.token)
         .await
-        .map_err(|e| response::unauthorized(format!("Failed to verify token: {}", e)))?;
+        .map_err(|e| match e {
+            UserLookupError::InvalidToken(_) => response::unauthorized(format!("Failed to verify token: {}", e)),
+            UserLookupError::Unavailable(_) => {
+                tracing::error!("Login failed, Supabase unavailable: {}", e);
+                response::service_unavailable("Sign-in is temporarily unavailable, please try again")
+            }
+        })?;
 
     // Check if user exists in database (including soft-deleted users)
     let db_user = users::find_with_role_by_email(&state.db, &supabase_user.email)
@@ -74,7 +81,7 @@ pub async fn handle_callback(
             }
         }
         None => {
-            // Create new user with 'user' role by default
+            // Create new user with the configured signup role
             // Use providers array (all linked providers) joined by comma
             let auth_provider = supabase_user.app_metadata.providers.join(", ");
             let new_user = users::create_user(
@@ -84,7 +91,7 @@ pub async fn handle_callback(
                 supabase_user.user_metadata.avatar_url.as_deref(),
                 &auth_provider,
                 &supabase_user.id.to_string(),
-                "user", // Default role
+                &state.default_signup_role,
             )
             .await
             .map_err(|e| response::internal_error_msg("Failed to create user", e))?;
@@ -97,6 +104,18 @@ pub async fn handle_callback(
         }
     };
 
+    // Hand over any subscription gifted to this email before they signed up;
+    // a failure here shouldn't block login, the gift stays held for next time
+    match subscription_gifts::claim_for_user(&state.db, user.id, &supabase_user.email).await {
+        Ok(Some(subscription)) => {
+            tracing::info!("User {} claimed gift subscription {}", user.id, subscription.id);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::warn!("Failed to claim gift subscription for user {}: {}", user.id, e);
+        }
+    }
+
     Ok(Json(AuthResponse {
         user: user.into(),
         token: payload.token,
@@ -1,13 +1,24 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{Multipart, Path, Query, State},
+    http::header,
+    response::IntoResponse,
     Json,
 };
-use loafy_core::booking::{cancel_booking, create_booking_with_lock};
-use loafy_db::queries::bookings;
-use loafy_integrations::stripe::StripePayments;
-use loafy_types::api::admin::PageInfo;
-use loafy_types::api::bookings::{BookingResponse, CreateBookingRequest, UserBookingsResponse};
+use loafy_core::booking::{
+    booking_ics, cancel_booking, cancel_pending_bookings, club_today, create_booking_invite, create_booking_with_lock,
+    preview_cancellation, record_failed_attempt, redeem_booking_invite, reschedule_booking, CancelledBy,
+};
+use loafy_db::queries::{bookings, config, sessions, users};
+use loafy_integrations::{
+    google_vision::GoogleVision, ocr, stripe::StripePayments, supabase::{SupabaseStorage, PAYMENT_PROOF_BUCKET},
+};
+use loafy_types::enums::PaymentStatus;
+use loafy_types::{trim_keyset_page, Cursor, Pagination};
+use loafy_types::api::bookings::{
+    BookingInviteResponse, BookingResponse, BulkCancelResponse, CancelBookingResponse, CancelPreviewResponse,
+    CreateBookingRequest, InviteRedemptionResponse, PaymentInstructionsResponse, RescheduleBookingRequest,
+    SkippedCancellation, UserBookingsResponse,
+};
 use serde::Deserialize;
 use stripe::PaymentIntentId;
 use uuid::Uuid;
@@ -23,6 +34,13 @@ pub struct BookingsQuery {
     pub page: i32,
     #[serde(default = "default_per_page")]
     pub per_page: i32,
+    /// Only bookings with this payment status
+    pub status: Option<PaymentStatus>,
+    /// Only active bookings for sessions today or later
+    #[serde(default)]
+    pub upcoming: bool,
+    /// Keyset cursor from a previous `next_cursor`; takes precedence over `page`
+    pub after: Option<String>,
 }
 
 fn default_page() -> i32 {
@@ -33,31 +51,57 @@ fn default_per_page() -> i32 {
     10
 }
 
+/// Largest page size accepted by the bookings list
+const MAX_PER_PAGE: i32 = 50;
+
 /// List my bookings with pagination
 pub async fn list_my_bookings(
     AuthUser(user): AuthUser,
     State(state): State<AppState>,
     Query(query): Query<BookingsQuery>,
 ) -> Result<Json<UserBookingsResponse>, ApiError> {
-    let page = query.page.max(1);
-    let per_page = query.per_page.clamp(1, 50);
+    let upcoming_from = if query.upcoming {
+        let offset = config::load(&state.db)
+            .await
+            .map_err(response::db_error)?
+            .session_utc_offset;
+        Some(club_today(offset))
+    } else {
+        None
+    };
+    let filter = bookings::UserBookingsFilter {
+        status: query.status.map(|s| s.as_str()),
+        upcoming_from,
+    };
+    let key = |b: &loafy_db::models::BookingWithSession| Cursor { created_at: b.created_at, id: b.id };
 
-    let (db_bookings, total) = bookings::list_user_bookings_paginated(&state.db, user.id, page, per_page)
-        .await
-        .map_err(|e| response::internal_error_msg("Failed to fetch bookings", e))?;
+    let (db_bookings, page_info, next_cursor) = if let Some(after) = query.after.as_deref() {
+        let after = Cursor::decode(after).ok_or_else(|| response::bad_request("Invalid cursor"))?;
+        let limit = Pagination::from_query(1, query.per_page, MAX_PER_PAGE).per_page;
 
-    let total_pages = ((total as f64) / (per_page as f64)).ceil() as i32;
+        let mut db_bookings = bookings::list_user_bookings_after(&state.db, user.id, filter, Some(after), limit + 1)
+            .await
+            .map_err(|e| response::internal_error_msg("Failed to fetch bookings", e))?;
+        let next_cursor = trim_keyset_page(&mut db_bookings, limit, key);
+        (db_bookings, None, next_cursor)
+    } else {
+        let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
+
+        let (db_bookings, total) = bookings::list_user_bookings_paginated(&state.db, user.id, filter, pagination)
+            .await
+            .map_err(|e| response::internal_error_msg("Failed to fetch bookings", e))?;
+        let next_cursor = (pagination.offset + (db_bookings.len() as i64) < total)
+            .then(|| db_bookings.last().map(|b| key(b).encode()))
+            .flatten();
+        (db_bookings, Some(pagination.page_info(total)), next_cursor)
+    };
 
     let data: Vec<BookingResponse> = db_bookings.into_iter().map(Into::into).collect();
 
     Ok(Json(UserBookingsResponse {
         data,
-        page_info: PageInfo {
-            page,
-            per_page,
-            total,
-            total_pages,
-        },
+        page_info,
+        next_cursor,
     }))
 }
 
@@ -80,6 +124,201 @@ pub async fn get_booking(
     Ok(Json(booking.into()))
 }
 
+/// Get booking by its code (e.g. from a transfer memo or email)
+///
+/// Codes belonging to someone else are reported as not found, so the endpoint
+/// can't be used to probe which codes exist.
+pub async fn get_booking_by_code(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> Result<Json<BookingResponse>, ApiError> {
+    let booking = bookings::find_by_code(&state.db, &code.trim().to_uppercase())
+        .await
+        .map_err(response::db_error)?
+        .filter(|b| b.user_id == user.id)
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    let booking = bookings::find_by_id_with_session(&state.db, booking.id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    Ok(Json(booking.into()))
+}
+
+/// Export a booking as an iCalendar (.ics) event
+pub async fn get_booking_calendar(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let booking = bookings::find_by_id(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    // Check ownership
+    if booking.user_id != user.id {
+        return Err(response::forbidden("You can only view your own bookings"));
+    }
+
+    let session = sessions::find_by_id(&state.db, booking.session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    let settings = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    let ics = booking_ics(
+        &booking,
+        &session,
+        settings.session_utc_offset,
+        settings.default_session_duration_minutes,
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"loafy-{}.ics\"", booking.booking_code),
+            ),
+        ],
+        ics,
+    ))
+}
+
+/// Get bank transfer instructions for a pending QR-transfer booking
+pub async fn get_payment_instructions(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PaymentInstructionsResponse>, ApiError> {
+    let booking = bookings::find_by_id(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    // Check ownership
+    if booking.user_id != user.id {
+        return Err(response::forbidden("You can only view your own bookings"));
+    }
+
+    if booking.payment_method != "qr_transfer" {
+        return Err(response::bad_request("Payment instructions are only available for QR transfer bookings"));
+    }
+    if booking.payment_status != "pending" {
+        return Err(response::bad_request("Booking is not awaiting payment"));
+    }
+
+    let session = sessions::find_by_id(&state.db, booking.session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    // Bank account details
+    let bank_name = std::env::var("BANK_NAME")
+        .map_err(|_| response::internal_error("Bank transfer not configured"))?;
+    let account_number = std::env::var("BANK_ACCOUNT_NUMBER")
+        .map_err(|_| response::internal_error("Bank transfer not configured"))?;
+    let account_holder = std::env::var("BANK_ACCOUNT_HOLDER")
+        .map_err(|_| response::internal_error("Bank transfer not configured"))?;
+
+    Ok(Json(PaymentInstructionsResponse {
+        booking_id: booking.id,
+        amount_vnd: booking.price_paid_vnd + booking.guest_price_paid_vnd,
+        transfer_reference: booking.booking_code,
+        bank_name,
+        account_number,
+        account_holder,
+        qr_code_url: session.qr_code_url,
+        payment_deadline: booking.payment_deadline,
+    }))
+}
+
+/// Upload a payment screenshot for a pending QR-transfer booking.
+///
+/// The screenshot is stored in Supabase Storage and verified via OCR. Matching
+/// screenshots auto-confirm the booking; everything else goes to manual review.
+pub async fn upload_payment_proof(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<BookingResponse>, ApiError> {
+    let booking = bookings::find_by_id(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    // Check ownership
+    if booking.user_id != user.id {
+        return Err(response::forbidden("You can only update your own bookings"));
+    }
+
+    if booking.payment_method != "qr_transfer" || booking.payment_status != "pending" {
+        return Err(response::bad_request("Payment proof can only be uploaded for pending QR transfer bookings"));
+    }
+
+    // Read the screenshot from the multipart body
+    let mut image: Option<(Vec<u8>, String)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| response::bad_request(format!("Invalid upload: {}", e)))?
+    {
+        if field.name() == Some("file") {
+            let content_type = field.content_type().unwrap_or("image/jpeg").to_string();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| response::bad_request(format!("Invalid upload: {}", e)))?;
+            image = Some((bytes.to_vec(), content_type));
+        }
+    }
+
+    let (image_bytes, content_type) = image.ok_or_else(|| response::bad_request("Missing file field"))?;
+    let extension = match content_type.as_str() {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/webp" => "webp",
+        _ => return Err(response::bad_request("Screenshot must be a PNG, JPEG or WebP image")),
+    };
+
+    // Upload to Supabase Storage
+    let supabase_url = std::env::var("SUPABASE_URL")
+        .map_err(|_| response::internal_error("Storage not configured"))?;
+    let supabase_service_key = std::env::var("SUPABASE_SERVICE_KEY")
+        .map_err(|_| response::internal_error("Storage not configured"))?;
+    let storage = SupabaseStorage::new(supabase_url, supabase_service_key);
+
+    let path = format!("{}/{}.{}", booking.id, chrono::Utc::now().timestamp(), extension);
+    storage
+        .upload_file(PAYMENT_PROOF_BUCKET, &path, image_bytes.clone(), &content_type)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to upload screenshot", e))?;
+
+    let booking = bookings::set_payment_screenshot(&state.db, booking.id, &path)
+        .await
+        .map_err(response::db_error)?;
+
+    // OCR verification (falls back to manual review when not configured)
+    let vision = std::env::var("GOOGLE_VISION_API_KEY").ok().map(GoogleVision::new);
+    ocr::verify_payment_screenshot(&state.db, vision.as_ref(), &booking, &image_bytes)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to verify payment screenshot", e))?;
+
+    let booking = bookings::find_by_id_with_session(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    Ok(Json(booking.into()))
+}
+
 /// Create booking
 pub async fn create_booking(
     AuthUser(user): AuthUser,
@@ -90,21 +329,30 @@ pub async fn create_booking(
     payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
 
     // Create booking with race condition protection
-    let booking = create_booking_with_lock(
+    let booking = match create_booking_with_lock(
         &state.db,
         user.id,
         payload.session_id,
         payload.guest_count,
         payload.payment_method.as_str(),
+        user.is_admin(),
+        payload.use_tickets_for_guests.unwrap_or(false),
+        payload.discount_code.as_deref().map(str::trim).filter(|c| !c.is_empty()),
     )
     .await
-    .map_err(|e| {
-        let status = e.status_code();
-        (
-            StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            e.to_string(),
-        )
-    })?;
+    {
+        Ok(booking) => booking,
+        Err(e) => {
+            record_failed_attempt(&state.db, user.id, payload.session_id, &e).await;
+            return Err(e.into());
+        }
+    };
+
+    // Refetch with session details so the response matches get/list
+    let booking = bookings::find_by_id_with_session(&state.db, booking.id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
 
     Ok(Json(booking.into()))
 }
@@ -114,7 +362,7 @@ pub async fn cancel_booking_route(
     AuthUser(user): AuthUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<BookingResponse>, ApiError> {
+) -> Result<Json<CancelBookingResponse>, ApiError> {
     // Get original booking to check payment status before cancellation
     let original_booking = bookings::find_by_id(&state.db, id)
         .await
@@ -126,18 +374,16 @@ pub async fn cancel_booking_route(
         && original_booking.payment_status == "confirmed"
         && original_booking.stripe_payment_id.is_some();
 
-    let cancelled_booking = cancel_booking(&state.db, id, user.id)
+    let outcome = cancel_booking(&state.db, id, CancelledBy::User(user.id))
         .await
-        .map_err(|e| {
-            let status = e.status_code();
-            (
-                StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-                e.to_string(),
-            )
-        })?;
+        .map_err(ApiError::from)?;
+    let cancelled_booking = outcome.booking;
+
+    // Late cancellations keep the slot released but get no money back
+    let refund_issued = needs_refund && outcome.refund_eligible;
 
     // Process Stripe refund if payment was confirmed
-    if needs_refund {
+    if refund_issued {
         if let Some(ref payment_intent_id) = original_booking.stripe_payment_id {
             // Parse the payment intent ID
             let intent_id: PaymentIntentId = payment_intent_id.parse().map_err(|_| {
@@ -163,5 +409,121 @@ pub async fn cancel_booking_route(
         }
     }
 
-    Ok(Json(cancelled_booking.into()))
+    let booking = bookings::find_by_id_with_session(&state.db, cancelled_booking.id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    Ok(Json(CancelBookingResponse {
+        refund_eligible: outcome.refund_eligible,
+        refund_amount_vnd: if refund_issued {
+            original_booking.price_paid_vnd + original_booking.guest_price_paid_vnd
+        } else {
+            0
+        },
+        ticket_restored: outcome.ticket_restored,
+        booking: booking.into(),
+    }))
+}
+
+/// Preview the refund and ticket outcome of cancelling a booking
+pub async fn get_cancel_preview(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CancelPreviewResponse>, ApiError> {
+    let preview = preview_cancellation(&state.db, id, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(CancelPreviewResponse {
+        can_cancel: preview.can_cancel,
+        refund_eligible: preview.refund_eligible,
+        refund_amount_vnd: preview.refund_amount_vnd,
+        ticket_restored: preview.ticket_restored,
+        deadline: preview.deadline,
+    }))
+}
+
+/// Move booking to another session (keeps ticket and payment)
+pub async fn reschedule_booking_route(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RescheduleBookingRequest>,
+) -> Result<Json<BookingResponse>, ApiError> {
+    reschedule_booking(&state.db, id, user.id, payload.target_session_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    let booking = bookings::find_by_id_with_session(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    Ok(Json(booking.into()))
+}
+
+/// Cancel all of my pending (unpaid) bookings
+pub async fn cancel_pending_bookings_route(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<BulkCancelResponse>, ApiError> {
+    let result = cancel_pending_bookings(&state.db, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(BulkCancelResponse {
+        cancelled: result.cancelled,
+        skipped: result
+            .skipped
+            .into_iter()
+            .map(|(booking_code, reason)| SkippedCancellation { booking_code, reason })
+            .collect(),
+    }))
+}
+
+/// Create a single-use invite for one of the booking's guest slots
+///
+/// The friend redeems it at `redeem_path` once signed in, joining the booking
+/// as a named guest. Expires when the session starts.
+pub async fn create_booking_invite_route(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BookingInviteResponse>, ApiError> {
+    let new_invite = create_booking_invite(&state.db, id, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(BookingInviteResponse {
+        redeem_path: format!("/api/invites/{}", new_invite.token),
+        token: new_invite.token,
+        expires_at: new_invite.invite.expires_at,
+    }))
+}
+
+/// Redeem a booking invite, joining the booking as a named guest
+pub async fn redeem_invite_route(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<InviteRedemptionResponse>, ApiError> {
+    let redeemed = redeem_booking_invite(&state.db, &token, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    let host = users::find_by_id(&state.db, redeemed.booking.user_id)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(InviteRedemptionResponse {
+        booking_id: redeemed.booking.id,
+        session_id: redeemed.session.id,
+        session_title: redeemed.session.title,
+        session_date: redeemed.session.date,
+        session_time: redeemed.session.time,
+        session_location: redeemed.session.location,
+        host_name: host.and_then(|h| h.name),
+    }))
 }
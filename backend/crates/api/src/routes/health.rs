@@ -0,0 +1,65 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::middleware::AppState;
+
+/// Maximum time the readiness probe waits for the database
+const DB_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Connection pool statistics
+#[derive(Serialize)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub active: usize,
+}
+
+/// Readiness probe response
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub database: &'static str,
+    pub pool: PoolStats,
+}
+
+/// Liveness probe (no dependencies touched)
+pub async fn health_check() -> &'static str {
+    "OK"
+}
+
+/// Readiness probe: verifies the database is reachable
+/// Returns 503 if `SELECT 1` fails or times out
+pub async fn health_ready(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let db_ok = matches!(
+        tokio::time::timeout(
+            DB_CHECK_TIMEOUT,
+            sqlx::query("SELECT 1").execute(&state.db),
+        )
+        .await,
+        Ok(Ok(_))
+    );
+
+    let size = state.db.size();
+    let idle = state.db.num_idle();
+    let pool = PoolStats {
+        size,
+        idle,
+        active: (size as usize).saturating_sub(idle),
+    };
+
+    if db_ok {
+        (
+            StatusCode::OK,
+            Json(ReadinessResponse { status: "ok", database: "ok", pool }),
+        )
+    } else {
+        tracing::warn!("Readiness check failed: database unreachable");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse { status: "unavailable", database: "unreachable", pool }),
+        )
+    }
+}
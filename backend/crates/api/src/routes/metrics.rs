@@ -0,0 +1,24 @@
+use axum::extract::State;
+use metrics_exporter_prometheus::PrometheusHandle;
+use sqlx::PgPool;
+
+/// State for the metrics endpoint, which can be served on its own port
+#[derive(Clone)]
+pub struct MetricsState {
+    pub handle: PrometheusHandle,
+    pub db: PgPool,
+}
+
+/// Prometheus scrape endpoint (unauthenticated)
+///
+/// Pool gauges are sampled here rather than on a timer, so they're exactly
+/// as fresh as the scrape.
+pub async fn metrics(State(state): State<MetricsState>) -> String {
+    let size = state.db.size();
+    let idle = state.db.num_idle() as u32;
+    metrics::gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+    metrics::gauge!("db_pool_connections", "state" => "active").set(size.saturating_sub(idle) as f64);
+    metrics::gauge!("db_pool_max_connections").set(state.db.options().get_max_connections() as f64);
+
+    state.handle.render()
+}
@@ -1,6 +1,9 @@
 pub mod admin;
 pub mod auth;
 pub mod bookings;
+pub mod health;
+pub mod metrics;
+pub mod organizer;
 pub mod payments;
 pub mod sessions;
 pub mod subscriptions;
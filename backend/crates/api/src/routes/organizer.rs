@@ -0,0 +1,302 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use loafy_core::booking::{club_today, session_starts_at};
+use loafy_core::session::{organizer_payout_vnd, platform_fee_vnd};
+use loafy_db::conversions::{SessionResponseExt, DEFAULT_MAX_PLAYERS_PER_COURT};
+use loafy_db::models::{ExpenseTemplate, ExpenseTemplateItem, Session, UserWithRole};
+use loafy_db::queries::{admin, config, expense_templates, sessions};
+use loafy_types::api::sessions::{
+    EditableSessionResponse, ExpenseTemplateRequest, ExpenseTemplateResponse, OrganizerPayoutsResponse,
+    SessionPayout, SessionResponse,
+};
+use loafy_types::parse_period;
+use serde::Deserialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::middleware::{AppState, AuthUser};
+use crate::response::{self, ApiError};
+use crate::routes::sessions::{validate_expense, with_price_usd};
+
+/// Query params for the payout report
+#[derive(Debug, Deserialize)]
+pub struct PayoutsQuery {
+    #[serde(default = "default_period")]
+    pub period: String,
+    /// Organizer to report on (admins only; defaults to the caller)
+    pub organizer_id: Option<Uuid>,
+}
+
+fn default_period() -> String {
+    "30d".to_string()
+}
+
+/// Get revenue, expenses and payout per session for an organizer
+pub async fn get_payouts(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<PayoutsQuery>,
+) -> Result<Json<OrganizerPayoutsResponse>, ApiError> {
+    if !user.is_organizer() {
+        return Err(response::forbidden("Only organizers can view payouts"));
+    }
+
+    let organizer_id = match query.organizer_id {
+        Some(id) if id != user.id && !user.is_admin() => {
+            return Err(response::forbidden("You can only view your own payouts"));
+        }
+        Some(id) => id,
+        None => user.id,
+    };
+
+    let since = parse_period(&query.period)
+        .map_err(|e| response::bad_request(e.to_string()))?
+        .since;
+
+    let summaries = admin::get_sessions_profit(&state.db, since, Some(organizer_id), None)
+        .await
+        .map_err(response::db_error)?;
+
+    let fee_percent = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .platform_fee_percent;
+
+    let sessions: Vec<SessionPayout> = summaries
+        .into_iter()
+        .map(|s| SessionPayout {
+            session_id: s.session_id,
+            title: s.title,
+            date: s.date,
+            gross_revenue_vnd: s.revenue_vnd,
+            expenses_vnd: s.expenses_vnd,
+            net_vnd: s.profit_vnd,
+            platform_fee_vnd: platform_fee_vnd(s.profit_vnd, fee_percent),
+            payout_vnd: organizer_payout_vnd(s.profit_vnd, fee_percent),
+        })
+        .collect();
+
+    // Totals sum the per-session figures so they match the rows exactly
+    Ok(Json(OrganizerPayoutsResponse {
+        organizer_id,
+        period: query.period,
+        platform_fee_percent: fee_percent,
+        total_gross_revenue_vnd: sessions.iter().map(|s| s.gross_revenue_vnd).sum(),
+        total_expenses_vnd: sessions.iter().map(|s| s.expenses_vnd).sum(),
+        total_net_vnd: sessions.iter().map(|s| s.net_vnd).sum(),
+        total_platform_fee_vnd: sessions.iter().map(|s| s.platform_fee_vnd).sum(),
+        total_payout_vnd: sessions.iter().map(|s| s.payout_vnd).sum(),
+        sessions,
+    }))
+}
+
+/// List the caller's sessions that haven't started yet, with what their bookings still allow
+pub async fn list_editable_sessions(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<EditableSessionResponse>>, ApiError> {
+    if !user.is_organizer() {
+        return Err(response::forbidden("Only organizers can edit sessions"));
+    }
+
+    let settings = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+    let offset = settings.session_utc_offset;
+    let now = Utc::now();
+
+    let upcoming = sessions::list_upcoming_for_organizer(&state.db, user.id, club_today(offset))
+        .await
+        .map_err(response::db_error)?
+        .into_iter()
+        .filter(|session| session_starts_at(session.date, session.time, offset) > now);
+
+    let mut responses = Vec::new();
+    for session in upcoming {
+        let session = with_price_usd(&state, session).await;
+        let booked_slots = session.total_slots - session.available_slots;
+        responses.push(EditableSessionResponse {
+            can_reduce_courts: can_reduce_courts(&session),
+            session: SessionResponse::from_session(&session, settings.max_guests_per_booking)
+                .with_organizer_name(user.name.clone()),
+            booked_slots,
+            safe_to_edit: booked_slots == 0,
+        });
+    }
+
+    Ok(Json(responses))
+}
+
+/// Whether dropping one court would pass the same capacity check as a session update
+fn can_reduce_courts(session: &Session) -> bool {
+    let max_players = session.max_players_per_court.unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT);
+    session.courts > 1
+        && sessions::recalculate_available_slots(
+            session.total_slots,
+            session.available_slots,
+            (session.courts - 1) * max_players,
+        )
+        .is_ok()
+}
+
+/// List the caller's expense templates
+pub async fn list_expense_templates(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ExpenseTemplateResponse>>, ApiError> {
+    if !user.is_organizer() {
+        return Err(response::forbidden("Only organizers can manage expense templates"));
+    }
+
+    let templates = expense_templates::list_templates_for_organizer(&state.db, user.id)
+        .await
+        .map_err(response::db_error)?;
+
+    let ids: Vec<Uuid> = templates.iter().map(|t| t.id).collect();
+    let mut items_by_template: HashMap<Uuid, Vec<ExpenseTemplateItem>> = HashMap::new();
+    for item in expense_templates::list_items_for_templates(&state.db, &ids)
+        .await
+        .map_err(response::db_error)?
+    {
+        items_by_template.entry(item.template_id).or_default().push(item);
+    }
+
+    let responses = templates
+        .into_iter()
+        .map(|template| {
+            let items = items_by_template.remove(&template.id).unwrap_or_default();
+            template_response(template, items)
+        })
+        .collect();
+
+    Ok(Json(responses))
+}
+
+/// Save a new expense template for the caller
+pub async fn create_expense_template(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<ExpenseTemplateRequest>,
+) -> Result<Json<ExpenseTemplateResponse>, ApiError> {
+    if !user.is_organizer() {
+        return Err(response::forbidden("Only organizers can manage expense templates"));
+    }
+    let items = validate_template(&payload)?;
+
+    let (template, items) = expense_templates::create_template(&state.db, user.id, payload.name.trim(), &items)
+        .await
+        .map_err(template_write_error)?;
+
+    Ok(Json(template_response(template, items)))
+}
+
+/// Get one expense template (owner or admin)
+pub async fn get_expense_template(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(template_id): Path<Uuid>,
+) -> Result<Json<ExpenseTemplateResponse>, ApiError> {
+    let template = find_owned_template(&state, &user, template_id).await?;
+
+    let items = expense_templates::list_items(&state.db, template.id)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(template_response(template, items)))
+}
+
+/// Rename a template and replace its expenses (owner or admin)
+pub async fn update_expense_template(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(template_id): Path<Uuid>,
+    Json(payload): Json<ExpenseTemplateRequest>,
+) -> Result<Json<ExpenseTemplateResponse>, ApiError> {
+    let template = find_owned_template(&state, &user, template_id).await?;
+    let items = validate_template(&payload)?;
+
+    let (template, items) = expense_templates::update_template(&state.db, template.id, payload.name.trim(), &items)
+        .await
+        .map_err(template_write_error)?;
+
+    Ok(Json(template_response(template, items)))
+}
+
+/// Delete an expense template (owner or admin); sessions created from it keep their expenses
+pub async fn delete_expense_template(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(template_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let template = find_owned_template(&state, &user, template_id).await?;
+
+    expense_templates::delete_template(&state.db, template.id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to delete expense template", e))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Fetch a template the user may use: organizers their own, admins any
+pub(crate) async fn find_owned_template(
+    state: &AppState,
+    user: &UserWithRole,
+    template_id: Uuid,
+) -> Result<ExpenseTemplate, ApiError> {
+    let template = expense_templates::find_template(&state.db, template_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Expense template"))?;
+
+    let is_owner = user.is_organizer() && template.organizer_id == user.id;
+    if !user.is_admin() && !is_owner {
+        return Err(response::forbidden("You can only use your own expense templates"));
+    }
+
+    Ok(template)
+}
+
+/// Validate a template request and turn its expenses into rows to insert
+fn validate_template(payload: &ExpenseTemplateRequest) -> Result<Vec<expense_templates::TemplateItemRow>, ApiError> {
+    payload.validate().map_err(|e| response::unprocessable(format!("Validation error: {}", e)))?;
+    if payload.name.trim().is_empty() {
+        return Err(response::bad_request("Template name is required"));
+    }
+
+    payload
+        .expenses
+        .iter()
+        .map(|expense| {
+            validate_expense(expense)?;
+            Ok((
+                expense.category.clone(),
+                expense.description.clone(),
+                expense.cost_type.clone(),
+                expense.amount_vnd,
+            ))
+        })
+        .collect()
+}
+
+fn template_write_error(e: anyhow::Error) -> ApiError {
+    match e.downcast_ref::<expense_templates::DuplicateTemplateName>() {
+        Some(duplicate) => response::conflict(duplicate.to_string()),
+        None => response::internal_error_msg("Failed to save expense template", e),
+    }
+}
+
+fn template_response(template: ExpenseTemplate, items: Vec<ExpenseTemplateItem>) -> ExpenseTemplateResponse {
+    ExpenseTemplateResponse {
+        id: template.id,
+        organizer_id: template.organizer_id,
+        name: template.name,
+        expenses: items.into_iter().map(Into::into).collect(),
+        created_at: template.created_at,
+        updated_at: template.updated_at,
+    }
+}
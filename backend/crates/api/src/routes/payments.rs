@@ -1,11 +1,11 @@
 use axum::{
     body::Bytes,
-    extract::State,
+    extract::{Path, State},
     http::{HeaderMap, StatusCode},
     Json,
 };
 use loafy_db::queries::bookings;
-use loafy_integrations::stripe::{handle_stripe_webhook, StripePayments};
+use loafy_integrations::stripe::{handle_stripe_webhook, PaymentIntentId, PaymentIntentStatus, StripePayments, WebhookRejected};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -23,6 +23,17 @@ pub struct PaymentIntentResponse {
     pub payment_intent_id: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PaymentIntentStatusResponse {
+    pub payment_intent_id: String,
+    /// Stripe PaymentIntent status, e.g. `requires_action` or `succeeded`
+    pub status: String,
+    /// True when the user must complete 3D Secure before the payment succeeds
+    pub requires_action: bool,
+    /// Present while action is required, for Stripe.js `handleNextAction`
+    pub client_secret: Option<String>,
+}
+
 /// Create Stripe payment intent
 pub async fn create_payment_intent(
     AuthUser(user): AuthUser,
@@ -81,6 +92,49 @@ pub async fn create_payment_intent(
     Ok(Json(response))
 }
 
+/// Get the Stripe payment status of a booking
+///
+/// Lets the frontend resume a payment stuck in 3D Secure authentication.
+pub async fn get_payment_intent_status(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(booking_id): Path<Uuid>,
+) -> Result<Json<PaymentIntentStatusResponse>, ApiError> {
+    let booking = bookings::find_by_id(&state.db, booking_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    if booking.user_id != user.id {
+        return Err(response::forbidden("You can only view your own payments"));
+    }
+
+    let intent_id: PaymentIntentId = booking
+        .stripe_payment_id
+        .as_deref()
+        .ok_or_else(|| response::not_found("Payment"))?
+        .parse()
+        .map_err(|_| response::internal_error("Invalid payment intent ID stored in booking"))?;
+
+    let stripe_key = std::env::var("STRIPE_SECRET_KEY")
+        .map_err(|_| response::internal_error("Stripe not configured"))?;
+    let stripe = StripePayments::new(stripe_key);
+
+    let payment_intent = stripe
+        .get_payment_intent(&intent_id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to retrieve payment intent", e))?;
+
+    let requires_action = payment_intent.status == PaymentIntentStatus::RequiresAction;
+
+    Ok(Json(PaymentIntentStatusResponse {
+        payment_intent_id: payment_intent.id.to_string(),
+        status: payment_intent.status.as_str().to_string(),
+        requires_action,
+        client_secret: payment_intent.client_secret.filter(|_| requires_action),
+    }))
+}
+
 /// Stripe webhook handler
 pub async fn stripe_webhook(
     State(state): State<AppState>,
@@ -104,7 +158,10 @@ pub async fn stripe_webhook(
     // Handle webhook
     handle_stripe_webhook(&payload, signature, &webhook_secret, &state.db)
         .await
-        .map_err(|e| response::internal_error_msg("Webhook processing failed", e))?;
+        .map_err(|e| match e.downcast_ref::<WebhookRejected>() {
+            Some(rejected) => response::bad_request(rejected.to_string()),
+            None => response::internal_error_msg("Webhook processing failed", e),
+        })?;
 
     Ok(StatusCode::OK)
 }
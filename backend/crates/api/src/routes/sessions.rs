@@ -1,17 +1,38 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
     Json,
 };
-use chrono::{NaiveDate, NaiveDateTime};
-use loafy_db::{conversions::SessionResponseExt, queries::{sessions, session_expenses}};
-use loafy_types::api::sessions::{CreateSessionRequest, ParticipantInfo, SessionParticipantsResponse, SessionResponse};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use loafy_db::{
+    conversions::SessionResponseExt,
+    models::{permissions, ExpenseTemplateItem, Location, LocationWithCount, Session, UserWithRole},
+    queries::{
+        booking_invites, bookings, config, expense_templates, locations, session_announcements, sessions, session_expenses, waitlist,
+    },
+};
+use loafy_types::api::sessions::{
+    AnnounceSessionRequest, CheckInRequest, CheckInResponse, CreateSessionRequest, DuplicateSessionRequest, ExpenseInput,
+    ExpenseResponse, JoinWaitlistRequest, LocationResponse, SessionAnnouncementResponse,
+    SessionExpensesResponse, SessionImportError, SessionImportResponse, SessionParticipantCountResponse, SessionParticipantsResponse,
+    SessionResponse, VerifyBookingCodeResponse, WaitlistPositionResponse, MAX_SESSION_IMPORT_ROWS,
+    MAX_SESSION_SLOTS,
+};
+use loafy_core::booking::{club_today, session_starts_at};
+use loafy_core::session::{visible_participants, RosterViewer};
+use loafy_core::waitlist::get_waitlist_position;
+use loafy_integrations::email::{escape_html, EmailClient};
+use loafy_integrations::supabase::{SupabaseStorage, SESSION_IMAGE_BUCKET};
+use loafy_types::enums::{PaymentStatus, RosterVisibility};
+use loafy_types::ErrorCode;
 use serde::Deserialize;
+use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::middleware::{AppState, AuthUser, require_role};
+use crate::middleware::{AppState, AuthUser, OptionalAuthUser};
 use crate::response::{self, ApiError};
+use crate::routes::organizer::find_owned_template;
 
 #[derive(Debug, Deserialize)]
 pub struct SessionFilters {
@@ -21,15 +42,20 @@ pub struct SessionFilters {
     pub location: Option<String>,
     pub organizer_id: Option<Uuid>,
     pub available_only: Option<bool>,
-    pub page: Option<i32>,
-    pub per_page: Option<i32>,
+    /// Only full sessions that still take waitlist entries
+    pub waitlist_available: Option<bool>,
 }
 
 /// List upcoming sessions
 pub async fn list_sessions(
+    OptionalAuthUser(user): OptionalAuthUser,
     State(state): State<AppState>,
     Query(filters): Query<SessionFilters>,
 ) -> Result<Json<Vec<SessionResponse>>, ApiError> {
+    let settings = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
     let db_sessions = sessions::list_sessions(
         &state.db,
         sessions::SessionQueryFilters {
@@ -39,44 +65,122 @@ pub async fn list_sessions(
             location: filters.location.clone(),
             organizer_id: filters.organizer_id,
             available_only: filters.available_only.unwrap_or(false),
+            waitlist_available: filters.waitlist_available.unwrap_or(false),
+            // Sessions of deleted or suspended organizers are hidden from the public feed
+            active_organizers_only: true,
+            ..Default::default()
         },
+        club_today(settings.session_utc_offset),
     )
     .await
     .map_err(|e| response::internal_error_msg("Failed to fetch sessions", e))?;
 
+    // Counts for every card in one query; they include participants the
+    // viewer is not shown
+    let session_ids: Vec<Uuid> = db_sessions.iter().map(|s| s.id).collect();
+    let counts = sessions::counts_for_sessions(&state.db, &session_ids)
+        .await
+        .unwrap_or_default();
+    let waitlist_counts = waitlist::counts_for_sessions(&state.db, &session_ids)
+        .await
+        .unwrap_or_default();
+    let mut my_statuses = match &user {
+        Some(user) => bookings::active_statuses_for_sessions(&state.db, user.id, &session_ids)
+            .await
+            .unwrap_or_default(),
+        None => HashMap::new(),
+    };
+
     // Build response with participants preview for each session
     let mut response = Vec::with_capacity(db_sessions.len());
     for session in db_sessions {
         let session_id = session.id;
-        let mut session_response: SessionResponse = session.into();
+        let viewer = roster_viewer(&state, user.as_ref(), &session).await;
+        let visibility = session.roster_visibility.parse().unwrap_or_default();
+        let mut session_response = SessionResponse::from_session(&session, settings.max_guests_per_booking);
 
-        // Fetch participants preview (max 5) and count
+        // Fetch participants preview (max 5)
         let participants = sessions::get_session_participants(&state.db, session_id, Some(5))
             .await
             .unwrap_or_default();
-        let count = sessions::count_session_participants(&state.db, session_id)
-            .await
-            .unwrap_or(0) as i32;
-
-        let participant_infos: Vec<ParticipantInfo> = participants
-            .into_iter()
-            .map(|p| ParticipantInfo {
-                id: p.user_id,
-                name: p.name,
-                avatar_url: p.avatar_url,
-                guest_count: p.guest_count,
-            })
-            .collect();
-
-        session_response = session_response.with_participants(participant_infos, count);
+        let count = counts.get(&session_id).copied().unwrap_or(0) as i32;
+
+        let participant_infos = visible_participants(participants, visibility, viewer);
+
+        let waitlist_count = waitlist_counts.get(&session_id).copied().unwrap_or(0) as i32;
+
+        session_response = session_response
+            .with_participants(participant_infos, count)
+            .with_waitlist_count(waitlist_count)
+            .with_my_booking_status(my_statuses.remove(&session_id));
         response.push(session_response);
     }
 
     Ok(Json(response))
 }
 
+/// Query parameters for the sessions-at-a-location view
+#[derive(Debug, Deserialize)]
+pub struct SessionsByLocationQuery {
+    pub location: String,
+    pub date: NaiveDate,
+}
+
+/// List every session at a location on one date (organizer or admin)
+///
+/// Unlike the public feed this includes cancelled sessions and ones that have
+/// already started, for venue coordination.
+pub async fn list_sessions_by_location(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<SessionsByLocationQuery>,
+) -> Result<Json<Vec<SessionResponse>>, ApiError> {
+    if !user.is_organizer() {
+        return Err(response::forbidden("Only organizers and admins can view sessions by location"));
+    }
+
+    let location = query.location.trim();
+    if location.is_empty() {
+        return Err(response::bad_request("location is required"));
+    }
+
+    let db_sessions = sessions::list_sessions(
+        &state.db,
+        sessions::SessionQueryFilters {
+            location: Some(location.to_string()),
+            on_date: Some(query.date),
+            include_cancelled: true,
+            ..Default::default()
+        },
+        query.date,
+    )
+    .await
+    .map_err(|e| response::internal_error_msg("Failed to fetch sessions", e))?;
+
+    let session_ids: Vec<Uuid> = db_sessions.iter().map(|s| s.id).collect();
+    let counts = sessions::counts_for_sessions(&state.db, &session_ids)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to count participants", e))?;
+
+    let max_guests = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .max_guests_per_booking;
+
+    let mut response = Vec::with_capacity(db_sessions.len());
+    for session in db_sessions {
+        let count = counts.get(&session.id).copied().unwrap_or(0) as i32;
+
+        let session_response = SessionResponse::from_session(&session, max_guests);
+        response.push(session_response.with_participants(Vec::new(), count));
+    }
+
+    Ok(Json(response))
+}
+
 /// Get session by ID
 pub async fn get_session(
+    OptionalAuthUser(user): OptionalAuthUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<SessionResponse>, ApiError> {
@@ -91,13 +195,7 @@ pub async fn get_session(
         .map_err(|e| response::internal_error_msg("Failed to fetch expenses", e))?;
 
     // Calculate total expenses
-    let total_expenses: i64 = expenses.iter().map(|e| {
-        if e.cost_type == "per_court" {
-            e.amount_vnd as i64 * session.courts as i64
-        } else {
-            e.amount_vnd as i64
-        }
-    }).sum();
+    let total_expenses: i64 = expenses.iter().map(|e| e.amount_for_courts(session.courts)).sum();
 
     let expense_responses: Vec<_> = expenses.into_iter().map(Into::into).collect();
 
@@ -108,21 +206,27 @@ pub async fn get_session(
     let count = sessions::count_session_participants(&state.db, id)
         .await
         .unwrap_or(0) as i32;
+    let waitlist_count = waitlist::count_waiting(&state.db, id)
+        .await
+        .unwrap_or(0) as i32;
+    let my_status = match &user {
+        Some(user) => bookings::active_statuses_for_sessions(&state.db, user.id, &[id])
+            .await
+            .unwrap_or_default()
+            .remove(&id),
+        None => None,
+    };
 
-    let participant_infos: Vec<ParticipantInfo> = participants
-        .into_iter()
-        .map(|p| ParticipantInfo {
-            id: p.user_id,
-            name: p.name,
-            avatar_url: p.avatar_url,
-            guest_count: p.guest_count,
-        })
-        .collect();
+    let viewer = roster_viewer(&state, user.as_ref(), &session).await;
+    let visibility = session.roster_visibility.parse().unwrap_or_default();
+    let participant_infos = visible_participants(participants, visibility, viewer);
 
-    let response: SessionResponse = session.into();
+    let response = session_response(&state, &session).await?;
     let response = response
         .with_expenses(expense_responses, total_expenses)
-        .with_participants(participant_infos, count);
+        .with_participants(participant_infos, count)
+        .with_waitlist_count(waitlist_count)
+        .with_my_booking_status(my_status);
 
     Ok(Json(response))
 }
@@ -134,12 +238,181 @@ pub async fn create_session(
     Json(payload): Json<CreateSessionRequest>,
 ) -> Result<Json<SessionResponse>, ApiError> {
     // Check if user is organizer or admin
-    if !user.is_organizer() {
+    if !user.has_permission(permissions::SESSIONS_CREATE) {
         return Err(response::forbidden("Only organizers and admins can create sessions"));
     }
 
+    let new_session = prepare_new_session(&payload)?;
+
+    // Resolved before creating anything so a bad template id leaves no
+    // session behind
+    let expenses = resolve_expenses(&state, &user, &payload).await?;
+
+    let location = resolve_location(&state, &payload).await?;
+
+    // Create session
+    let session = sessions::create_session(
+        &state.db,
+        user.id,
+        &payload.title,
+        new_session.date,
+        new_session.time,
+        new_session.end_time,
+        &location.name,
+        Some(location.id),
+        new_session.courts,
+        Some(new_session.max_players_per_court),
+        payload.price_vnd,
+        payload.max_guests_per_booking,
+        payload.min_players,
+        payload.min_players_deadline_hours,
+        payload.booking_opens_at,
+    )
+    .await
+    .map_err(|e| response::internal_error_msg("Failed to create session", e))?;
+
+    let session = apply_roster_visibility(&state, session, payload.roster_visibility).await?;
+    let session = apply_waitlist_enabled(&state, session, payload.waitlist_enabled).await?;
+    let session = with_price_usd(&state, session).await;
+
+    // Create the expenses, explicit and from the template
+    let mut expense_responses = Vec::new();
+    let mut total_expenses: i64 = 0;
+
+    for expense in expenses {
+        let created = session_expenses::create_expense(
+            &state.db,
+            session.id,
+            &expense.category,
+            expense.description.as_deref(),
+            &expense.cost_type,
+            expense.amount_vnd,
+        )
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to create expense", e))?;
+
+        // Calculate actual expense (per_court * courts or total)
+        let actual_amount = if expense.cost_type == "per_court" {
+            expense.amount_vnd as i64 * session.courts as i64
+        } else {
+            expense.amount_vnd as i64
+        };
+        total_expenses += actual_amount;
+
+        expense_responses.push(created.into());
+    }
+
+    let response = session_response(&state, &session).await?;
+    let response = response
+        .with_organizer_name(user.name.clone())
+        .with_expenses(expense_responses, total_expenses);
+
+    Ok(Json(response))
+}
+
+/// Copy a session into a new one owned by the caller
+///
+/// Title, location, court layout, pricing, booking rules, roster visibility and
+/// expenses are copied; the copy starts with no bookings and every slot free.
+/// The cover image stays with the source session.
+pub async fn duplicate_session(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<DuplicateSessionRequest>>,
+) -> Result<Json<SessionResponse>, ApiError> {
+    if !user.has_permission(permissions::SESSIONS_CREATE) {
+        return Err(response::forbidden("Only organizers and admins can create sessions"));
+    }
+    let source = find_editable_session(&state, &user, id).await?;
+
+    let Json(overrides) = payload.unwrap_or_default();
+    let date = overrides.date.unwrap_or(source.date + Duration::weeks(1));
+    let time = overrides.time.unwrap_or(source.time);
+    // Keep the source's duration when the start time moves
+    let end_time = source.end_time.map(|end| time + end.signed_duration_since(source.time));
+
+    let settings = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+    if session_starts_at(date, time, settings.session_utc_offset) <= Utc::now() {
+        return Err(response::bad_request("The duplicated session must start in the future"));
+    }
+
+    let expenses = session_expenses::list_expenses_for_session(&state.db, source.id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to fetch expenses", e))?;
+
+    let mut tx = state.db.begin().await.map_err(response::db_error)?;
+
+    let session = sessions::create_session_in_tx(
+        &mut tx,
+        user.id,
+        &source.title,
+        date,
+        time,
+        end_time,
+        &source.location,
+        source.location_id,
+        source.courts,
+        source.max_players_per_court,
+        source.price_vnd,
+        source.max_guests_per_booking,
+        source.min_players,
+        source.min_players_deadline_hours,
+        None,
+    )
+    .await
+    .map_err(|e| response::internal_error_msg("Failed to create session", e))?;
+
+    let expense_rows: Vec<_> = expenses
+        .iter()
+        .map(|e| (e.category.clone(), e.description.clone(), e.cost_type.clone(), e.amount_vnd))
+        .collect();
+    let created_expenses = session_expenses::create_expenses_batch(&mut tx, session.id, &expense_rows)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to create expense", e))?;
+
+    tx.commit().await.map_err(response::db_error)?;
+
+    let session = apply_roster_visibility(
+        &state,
+        session,
+        source.roster_visibility.parse().ok(),
+    )
+    .await?;
+    let session = apply_waitlist_enabled(&state, session, Some(source.waitlist_enabled)).await?;
+    let session = with_price_usd(&state, session).await;
+
+    tracing::info!("User {} duplicated session {} as {}", user.id, source.id, session.id);
+
+    let total_expenses: i64 = created_expenses.iter().map(|e| e.amount_for_courts(session.courts)).sum();
+    let expense_responses: Vec<ExpenseResponse> = created_expenses.into_iter().map(Into::into).collect();
+
+    let response = session_response(&state, &session).await?;
+    let response = response
+        .with_organizer_name(user.name.clone())
+        .with_expenses(expense_responses, total_expenses);
+
+    Ok(Json(response))
+}
+
+/// Session fields resolved from a validated `CreateSessionRequest`
+struct NewSession {
+    date: NaiveDate,
+    time: NaiveTime,
+    end_time: Option<NaiveTime>,
+    courts: i32,
+    max_players_per_court: i32,
+}
+
+/// Validate a new session request and resolve its times and court layout
+///
+/// The location is resolved separately, after all other checks, so rejected
+/// requests don't leave behind a new location.
+fn prepare_new_session(payload: &CreateSessionRequest) -> Result<NewSession, ApiError> {
     // Validate input
-    payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    payload.validate().map_err(|e| response::unprocessable(format!("Validation error: {}", e)))?;
 
     // Parse start_time to extract date and time
     let start_datetime = NaiveDateTime::parse_from_str(&payload.start_time, "%Y-%m-%dT%H:%M")
@@ -156,80 +429,151 @@ pub async fn create_session(
     // Validate expenses if provided
     if let Some(ref expenses) = payload.expenses {
         for expense in expenses {
-            // Validate category
-            if !["court_rental", "equipment", "instructor", "custom"].contains(&expense.category.as_str()) {
-                return Err(response::bad_request(format!("Invalid expense category: {}", expense.category)));
-            }
-            // Validate cost_type
-            if !["per_court", "total"].contains(&expense.cost_type.as_str()) {
-                return Err(response::bad_request(format!("Invalid cost type: {}", expense.cost_type)));
-            }
-            // Custom category requires description
-            if expense.category == "custom" && expense.description.is_none() {
-                return Err(response::bad_request("Custom expenses require a description"));
-            }
-            // Amount must be positive
-            if expense.amount_vnd <= 0 {
-                return Err(response::bad_request("Expense amount must be positive"));
-            }
+            validate_expense(expense)?;
         }
     }
 
-    // For simplicity, treat max_slots as total available (1 court with max_slots players)
-    let courts = 1;
-    let max_players_per_court = Some(payload.max_slots);
+    let (courts, max_players_per_court) = resolve_court_layout(payload, 1)?;
 
-    // Create session
-    let session = sessions::create_session(
-        &state.db,
-        user.id,
-        &payload.title,
+    Ok(NewSession {
         date,
         time,
         end_time,
-        &payload.location,
         courts,
         max_players_per_court,
-        payload.price_vnd,
-    )
-    .await
-    .map_err(|e| response::internal_error_msg("Failed to create session", e))?;
+    })
+}
 
-    // Create expenses if provided
-    let mut expense_responses = Vec::new();
-    let mut total_expenses: i64 = 0;
+/// Import sessions from a JSON array (admin only)
+///
+/// Each row goes through the same validation, expense template expansion and
+/// session settings as `create_session`. Valid rows are created, along with
+/// any new locations, in a single transaction; invalid rows are reported by
+/// index.
+pub async fn import_sessions(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(rows): Json<Vec<CreateSessionRequest>>,
+) -> Result<Json<SessionImportResponse>, ApiError> {
+    if !user.has_permission(permissions::SESSIONS_IMPORT) {
+        return Err(response::forbidden("Only admins can import sessions"));
+    }
 
-    if let Some(expenses) = payload.expenses {
-        for expense in expenses {
-            let created = session_expenses::create_expense(
-                &state.db,
-                session.id,
-                &expense.category,
-                expense.description.as_deref(),
-                &expense.cost_type,
-                expense.amount_vnd,
-            )
-            .await
-            .map_err(|e| response::internal_error_msg("Failed to create expense", e))?;
+    if rows.is_empty() {
+        return Err(response::bad_request("No sessions to import"));
+    }
+    if rows.len() > MAX_SESSION_IMPORT_ROWS {
+        return Err(response::unprocessable(format!(
+            "Too many sessions: {} (maximum {} per import)",
+            rows.len(),
+            MAX_SESSION_IMPORT_ROWS
+        )));
+    }
 
-            // Calculate actual expense (per_court * courts or total)
-            let actual_amount = if expense.cost_type == "per_court" {
-                expense.amount_vnd as i64 * session.courts as i64
-            } else {
-                expense.amount_vnd as i64
-            };
-            total_expenses += actual_amount;
+    let mut prepared = Vec::new();
+    let mut errors = Vec::new();
+    for (row, payload) in rows.into_iter().enumerate() {
+        let new_session = match prepare_new_session(&payload) {
+            Ok(new_session) => new_session,
+            Err(e) => {
+                errors.push(SessionImportError { row, message: e.message });
+                continue;
+            }
+        };
+        match resolve_expenses(&state, &user, &payload).await {
+            Ok(expenses) => prepared.push((row, payload, new_session, expenses)),
+            Err(e) if e.status.is_server_error() => return Err(e),
+            Err(e) => errors.push(SessionImportError { row, message: e.message }),
+        }
+    }
+
+    let mut tx = state.db.begin().await.map_err(response::db_error)?;
+    let mut created = Vec::with_capacity(prepared.len());
+
+    for (row, payload, new_session, expenses) in &prepared {
+        let location = match resolve_location_in_tx(&mut tx, payload).await {
+            Ok(location) => location,
+            // A database failure aborts the transaction, and with it the import
+            Err(e) if e.status.is_server_error() => return Err(e),
+            Err(e) => {
+                errors.push(SessionImportError { row: *row, message: e.message });
+                continue;
+            }
+        };
+
+        let session = sessions::create_session_in_tx(
+            &mut tx,
+            user.id,
+            &payload.title,
+            new_session.date,
+            new_session.time,
+            new_session.end_time,
+            &location.name,
+            Some(location.id),
+            new_session.courts,
+            Some(new_session.max_players_per_court),
+            payload.price_vnd,
+            payload.max_guests_per_booking,
+            payload.min_players,
+            payload.min_players_deadline_hours,
+            payload.booking_opens_at,
+        )
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to create session", e))?;
 
-            expense_responses.push(created.into());
+        // Same defaults as `create_session`: settings left out keep the column defaults
+        let session = match payload.roster_visibility {
+            Some(visibility) if visibility.as_str() != session.roster_visibility => {
+                sessions::set_roster_visibility_in_tx(&mut tx, session.id, visibility.as_str())
+                    .await
+                    .map_err(|e| response::internal_error_msg("Failed to update roster visibility", e))?
+            }
+            _ => session,
+        };
+        let session = match payload.waitlist_enabled {
+            Some(enabled) if enabled != session.waitlist_enabled => {
+                sessions::set_waitlist_enabled_in_tx(&mut tx, session.id, enabled)
+                    .await
+                    .map_err(|e| response::internal_error_msg("Failed to update waitlist setting", e))?
+            }
+            _ => session,
+        };
+
+        if !expenses.is_empty() {
+            let expenses: Vec<_> = expenses
+                .iter()
+                .map(|e| (e.category.clone(), e.description.clone(), e.cost_type.clone(), e.amount_vnd))
+                .collect();
+            session_expenses::create_expenses_batch(&mut tx, session.id, &expenses)
+                .await
+                .map_err(|e| response::internal_error_msg("Failed to create expense", e))?;
         }
+
+        created.push(session);
     }
 
-    let response: SessionResponse = session.into();
-    let response = response
-        .with_organizer_name(user.name.clone())
-        .with_expenses(expense_responses, total_expenses);
+    tx.commit().await.map_err(response::db_error)?;
+    errors.sort_by_key(|e| e.row);
 
-    Ok(Json(response))
+    let mut session_ids = Vec::with_capacity(created.len());
+    for session in created {
+        session_ids.push(session.id);
+        with_price_usd(&state, session).await;
+    }
+
+    tracing::info!(
+        "Admin {} imported {} sessions ({} rows rejected)",
+        user.id,
+        session_ids.len(),
+        errors.len()
+    );
+
+    Ok(Json(SessionImportResponse {
+        created: session_ids.len(),
+        failed: errors.len(),
+        session_ids,
+        errors,
+    }))
 }
 
 /// Update session (admin can update any, organizer can update own)
@@ -259,7 +603,7 @@ pub async fn update_session(
     }
 
     // Validate input
-    payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    payload.validate().map_err(|e| response::unprocessable(format!("Validation error: {}", e)))?;
 
     // Parse start_time to extract date and time
     let start_datetime = NaiveDateTime::parse_from_str(&payload.start_time, "%Y-%m-%dT%H:%M")
@@ -273,9 +617,10 @@ pub async fn update_session(
         .map(|dt| dt.time())
         .ok();
 
-    // For simplicity, treat max_slots as total available (1 court with max_slots players)
-    let courts = 1;
-    let max_players_per_court = Some(payload.max_slots);
+    let location = resolve_location(&state, &payload).await?;
+
+    // Keep the existing court count unless the request changes it
+    let (courts, max_players_per_court) = resolve_court_layout(&payload, existing_session.courts)?;
 
     // Update session
     let session = sessions::update_session(
@@ -285,15 +630,139 @@ pub async fn update_session(
         Some(date),
         Some(time),
         end_time,
-        Some(&payload.location),
+        Some(&location.name),
+        Some(location.id),
         Some(courts),
-        max_players_per_court,
+        Some(max_players_per_court),
         payload.price_vnd,
+        payload.max_guests_per_booking,
+        payload.min_players,
+        payload.min_players_deadline_hours,
+        payload.booking_opens_at,
     )
     .await
-    .map_err(|e| response::internal_error_msg("Failed to update session", e))?;
+    .map_err(|e| match e.downcast_ref::<sessions::CapacityTooLow>() {
+        Some(too_low) => ApiError::new(StatusCode::CONFLICT, ErrorCode::CapacityBelowBookings, too_low.to_string()),
+        None => response::internal_error_msg("Failed to update session", e),
+    })?;
+
+    let session = apply_roster_visibility(&state, session, payload.roster_visibility).await?;
+    let session = apply_waitlist_enabled(&state, session, payload.waitlist_enabled).await?;
+    let session = with_price_usd(&state, session).await;
+
+    Ok(Json(session_response(&state, &session).await?))
+}
+
+/// Expenses for a new session: the ones given explicitly, then the items of
+/// the requested expense template
+async fn resolve_expenses(
+    state: &AppState,
+    user: &UserWithRole,
+    payload: &CreateSessionRequest,
+) -> Result<Vec<ExpenseInput>, ApiError> {
+    let items = match payload.expense_template_id {
+        Some(template_id) => {
+            let template = find_owned_template(state, user, template_id).await?;
+            expense_templates::list_items(&state.db, template.id)
+                .await
+                .map_err(response::db_error)?
+        }
+        None => Vec::new(),
+    };
 
-    Ok(Json(session.into()))
+    Ok(with_template_items(payload.expenses.as_deref(), items))
+}
+
+/// Append template items after the explicitly given expenses
+fn with_template_items(expenses: Option<&[ExpenseInput]>, items: Vec<ExpenseTemplateItem>) -> Vec<ExpenseInput> {
+    let mut expenses = expenses.map(<[ExpenseInput]>::to_vec).unwrap_or_default();
+    expenses.extend(items.into_iter().map(ExpenseInput::from));
+    expenses
+}
+
+/// Store the requested roster visibility, if the request set one
+async fn apply_roster_visibility(
+    state: &AppState,
+    session: Session,
+    visibility: Option<RosterVisibility>,
+) -> Result<Session, ApiError> {
+    match visibility {
+        Some(visibility) if visibility.as_str() != session.roster_visibility => {
+            sessions::set_roster_visibility(&state.db, session.id, visibility.as_str())
+                .await
+                .map_err(|e| response::internal_error_msg("Failed to update roster visibility", e))
+        }
+        _ => Ok(session),
+    }
+}
+
+/// Store the requested waitlist setting, if the request set one
+async fn apply_waitlist_enabled(
+    state: &AppState,
+    session: Session,
+    enabled: Option<bool>,
+) -> Result<Session, ApiError> {
+    match enabled {
+        Some(enabled) if enabled != session.waitlist_enabled => {
+            sessions::set_waitlist_enabled(&state.db, session.id, enabled)
+                .await
+                .map_err(|e| response::internal_error_msg("Failed to update waitlist setting", e))
+        }
+        _ => Ok(session),
+    }
+}
+
+/// Work out how much of a session's roster `user` may see.
+/// Anonymous visitors get the public view.
+async fn roster_viewer(state: &AppState, user: Option<&UserWithRole>, session: &Session) -> RosterViewer {
+    let Some(user) = user else {
+        return RosterViewer::default();
+    };
+
+    let privileged = user.is_admin() || session.organizer_id == user.id;
+    // Only participants-only rosters need the booking lookup; a redeemed
+    // invite makes the guest a participant too
+    let is_participant = !privileged
+        && session.roster_visibility == RosterVisibility::Participants.as_str()
+        && (bookings::has_active_booking_for_session(&state.db, user.id, session.id)
+            .await
+            .unwrap_or(false)
+            || booking_invites::is_guest_in_session(&state.db, user.id, session.id)
+                .await
+                .unwrap_or(false));
+
+    RosterViewer {
+        user_id: Some(user.id),
+        privileged,
+        is_participant,
+    }
+}
+
+/// Build a session response, falling back to the configured guest limit
+/// for sessions without their own
+async fn session_response(state: &AppState, session: &Session) -> Result<SessionResponse, ApiError> {
+    let max_guests = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?
+        .max_guests_per_booking;
+    Ok(SessionResponse::from_session(session, max_guests))
+}
+
+/// Fill `price_usd` from `price_vnd` using the current exchange rate.
+/// Leaves the session unchanged if no rate is available.
+pub(crate) async fn with_price_usd(state: &AppState, session: Session) -> Session {
+    let Some(rate) = state.fx.vnd_per_usd().await else {
+        return session;
+    };
+
+    let price_usd = sessions::compute_price_usd(session.price_vnd.unwrap_or(100_000), rate);
+    match sessions::update_price_usd(&state.db, session.id, price_usd).await {
+        Ok(updated) => updated,
+        Err(e) => {
+            tracing::warn!("Failed to store price_usd for session {}: {}", session.id, e);
+            session
+        }
+    }
 }
 
 /// Delete session (admin only)
@@ -303,33 +772,657 @@ pub async fn delete_session(
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, ApiError> {
     // Only admins can delete sessions
-    require_role(&user, "admin").map_err(|_| response::forbidden("Only admins can delete sessions"))?;
+    if !user.has_permission(permissions::SESSIONS_DELETE) {
+        return Err(response::forbidden("Only admins can delete sessions"));
+    }
+
+    let image_url = sessions::find_by_id(&state.db, id)
+        .await
+        .map_err(response::db_error)?
+        .and_then(|session| session.image_url);
 
     sessions::delete_session(&state.db, id)
         .await
         .map_err(|e| response::internal_error_msg("Failed to delete session", e))?;
 
+    if let Some(url) = image_url {
+        if let Ok(storage) = session_image_storage() {
+            remove_session_image(&storage, &url).await;
+        }
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Get all distinct session locations
+/// Largest accepted session cover image
+pub const MAX_SESSION_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Upload or replace a session's cover image (owner or admin)
+///
+/// Expects a multipart `file` field with a PNG, JPEG or WebP image. The
+/// previous image, if any, is removed from storage.
+pub async fn upload_session_image(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<SessionResponse>, ApiError> {
+    let session = find_editable_session(&state, &user, id).await?;
+
+    let mut image: Option<(Vec<u8>, String)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| response::bad_request(format!("Invalid upload: {}", e)))?
+    {
+        if field.name() == Some("file") {
+            let content_type = field.content_type().unwrap_or_default().to_string();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| response::bad_request(format!("Invalid upload: {}", e)))?;
+            image = Some((bytes.to_vec(), content_type));
+        }
+    }
+
+    let (image_bytes, content_type) = image.ok_or_else(|| response::bad_request("Missing file field"))?;
+    let extension = match content_type.as_str() {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/webp" => "webp",
+        _ => return Err(response::bad_request("Image must be a PNG, JPEG or WebP image")),
+    };
+    if image_bytes.is_empty() {
+        return Err(response::bad_request("Image is empty"));
+    }
+    if image_bytes.len() > MAX_SESSION_IMAGE_BYTES {
+        return Err(response::bad_request(format!(
+            "Image must be at most {} MB",
+            MAX_SESSION_IMAGE_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let storage = session_image_storage()?;
+
+    // A new path per upload, so cached copies of the old image never linger
+    let path = format!("{}/{}.{}", session.id, Utc::now().timestamp_millis(), extension);
+    let image_url = storage
+        .upload_file(SESSION_IMAGE_BUCKET, &path, image_bytes, &content_type)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to upload image", e))?;
+
+    let updated = sessions::set_image_url(&state.db, session.id, Some(&image_url))
+        .await
+        .map_err(response::db_error)?;
+
+    if let Some(old_url) = session.image_url {
+        remove_session_image(&storage, &old_url).await;
+    }
+
+    Ok(Json(session_response(&state, &updated).await?))
+}
+
+/// Remove a session's cover image (owner or admin)
+pub async fn delete_session_image(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SessionResponse>, ApiError> {
+    let session = find_editable_session(&state, &user, id).await?;
+
+    let Some(old_url) = session.image_url.clone() else {
+        return Ok(Json(session_response(&state, &session).await?));
+    };
+
+    let updated = sessions::set_image_url(&state.db, session.id, None)
+        .await
+        .map_err(response::db_error)?;
+
+    if let Ok(storage) = session_image_storage() {
+        remove_session_image(&storage, &old_url).await;
+    }
+
+    Ok(Json(session_response(&state, &updated).await?))
+}
+
+/// Storage client for session images
+fn session_image_storage() -> Result<SupabaseStorage, ApiError> {
+    let supabase_url = std::env::var("SUPABASE_URL")
+        .map_err(|_| response::internal_error("Storage not configured"))?;
+    let supabase_service_key = std::env::var("SUPABASE_SERVICE_KEY")
+        .map_err(|_| response::internal_error("Storage not configured"))?;
+
+    Ok(SupabaseStorage::new(supabase_url, supabase_service_key))
+}
+
+/// Delete a replaced or removed image; failures only leave an orphaned object
+async fn remove_session_image(storage: &SupabaseStorage, image_url: &str) {
+    let Some(path) = storage.path_from_public_url(SESSION_IMAGE_BUCKET, image_url) else {
+        tracing::warn!("Not deleting session image outside the bucket: {}", image_url);
+        return;
+    };
+
+    if let Err(e) = storage.delete_file(SESSION_IMAGE_BUCKET, &path).await {
+        tracing::warn!("Failed to delete session image {}: {}", path, e);
+    }
+}
+
+/// Get a single expense of a session (owner or admin)
+pub async fn get_session_expense(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path((session_id, expense_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ExpenseResponse>, ApiError> {
+    find_editable_session(&state, &user, session_id).await?;
+
+    let expense = session_expenses::find_expense(&state.db, session_id, expense_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Expense"))?;
+
+    Ok(Json(expense.into()))
+}
+
+/// Update a single expense of a session (owner or admin)
+pub async fn update_session_expense(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path((session_id, expense_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<ExpenseInput>,
+) -> Result<Json<SessionExpensesResponse>, ApiError> {
+    let session = find_editable_session(&state, &user, session_id).await?;
+
+    validate_expense(&payload)?;
+
+    session_expenses::find_expense(&state.db, session_id, expense_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Expense"))?;
+
+    session_expenses::update_expense(
+        &state.db,
+        expense_id,
+        &payload.category,
+        payload.description.as_deref(),
+        &payload.cost_type,
+        payload.amount_vnd,
+    )
+    .await
+    .map_err(|e| response::internal_error_msg("Failed to update expense", e))?;
+
+    tracing::info!("User {} updated expense {} on session {}", user.id, expense_id, session_id);
+
+    Ok(Json(session_expenses_response(&state, &session).await?))
+}
+
+/// Delete a single expense of a session (owner or admin)
+pub async fn delete_session_expense(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path((session_id, expense_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SessionExpensesResponse>, ApiError> {
+    let session = find_editable_session(&state, &user, session_id).await?;
+
+    session_expenses::find_expense(&state.db, session_id, expense_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Expense"))?;
+
+    session_expenses::delete_expense(&state.db, expense_id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to delete expense", e))?;
+
+    tracing::info!("User {} deleted expense {} from session {}", user.id, expense_id, session_id);
+
+    Ok(Json(session_expenses_response(&state, &session).await?))
+}
+
+/// Fetch a session the user may edit: admins can edit any, organizers their own
+async fn find_editable_session(
+    state: &AppState,
+    user: &UserWithRole,
+    session_id: Uuid,
+) -> Result<Session, ApiError> {
+    let session = sessions::find_by_id(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    let is_owner = user.is_organizer() && session.organizer_id == user.id;
+    if !user.is_admin() && !is_owner {
+        return Err(response::forbidden("You can only edit your own sessions"));
+    }
+
+    Ok(session)
+}
+
+/// List a session's expenses with the recomputed total
+async fn session_expenses_response(
+    state: &AppState,
+    session: &Session,
+) -> Result<SessionExpensesResponse, ApiError> {
+    let expenses = session_expenses::list_expenses_for_session(&state.db, session.id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to fetch expenses", e))?;
+
+    let total_expenses_vnd = session_expenses::get_session_total_expenses(&state.db, session.id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to total expenses", e))?;
+
+    Ok(SessionExpensesResponse {
+        session_id: session.id,
+        expenses: expenses.into_iter().map(Into::into).collect(),
+        total_expenses_vnd,
+    })
+}
+
+/// Categories shared by session and fixed expenses
+pub(crate) const EXPENSE_CATEGORIES: [&str; 4] = ["court_rental", "equipment", "instructor", "custom"];
+
+/// Validate an expense's category, cost type, description and amount
+pub(crate) fn validate_expense(expense: &ExpenseInput) -> Result<(), ApiError> {
+    // Validate category
+    if !EXPENSE_CATEGORIES.contains(&expense.category.as_str()) {
+        return Err(response::bad_request(format!("Invalid expense category: {}", expense.category)));
+    }
+    // Validate cost_type
+    if !["per_court", "total"].contains(&expense.cost_type.as_str()) {
+        return Err(response::bad_request(format!("Invalid cost type: {}", expense.cost_type)));
+    }
+    // Custom category requires description
+    if expense.category == "custom" && expense.description.is_none() {
+        return Err(response::bad_request("Custom expenses require a description"));
+    }
+    // Amount must be positive
+    if expense.amount_vnd <= 0 {
+        return Err(response::bad_request("Expense amount must be positive"));
+    }
+    Ok(())
+}
+
+/// Query params for verifying a booking code
+#[derive(Debug, Deserialize)]
+pub struct VerifyCodeQuery {
+    pub code: String,
+}
+
+/// Check a booking code at the door (owner or admin)
+///
+/// Unknown codes return `valid: false` instead of 404 so scanners can show
+/// a result for every scan.
+pub async fn verify_booking_code(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+    Query(query): Query<VerifyCodeQuery>,
+) -> Result<Json<VerifyBookingCodeResponse>, ApiError> {
+    find_editable_session(&state, &user, session_id).await?;
+
+    let check = bookings::find_for_door_check(&state.db, session_id, &query.code)
+        .await
+        .map_err(response::db_error)?
+        .filter(|check| check.cancelled_at.is_none());
+
+    let response = match check {
+        Some(check) => VerifyBookingCodeResponse {
+            valid: true,
+            user_name: check.user_name,
+            guest_count: Some(check.guest_count),
+            payment_status: Some(check.payment_status.parse().unwrap_or(PaymentStatus::Pending)),
+            already_checked_in: check.checked_in_at.is_some(),
+        },
+        None => VerifyBookingCodeResponse {
+            valid: false,
+            user_name: None,
+            guest_count: None,
+            payment_status: None,
+            already_checked_in: false,
+        },
+    };
+
+    Ok(Json(response))
+}
+
+/// Check a player in by booking code (owner or admin)
+pub async fn check_in_booking(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+    Json(payload): Json<CheckInRequest>,
+) -> Result<Json<CheckInResponse>, ApiError> {
+    payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+
+    find_editable_session(&state, &user, session_id).await?;
+
+    let check = bookings::find_for_door_check(&state.db, session_id, &payload.code)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Booking"))?;
+
+    if check.cancelled_at.is_some() {
+        return Err(response::bad_request("Booking is cancelled"));
+    }
+
+    let checked_in_at = bookings::check_in(&state.db, check.booking_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::conflict(format!("Booking {} is already checked in", check.booking_code)))?;
+
+    Ok(Json(CheckInResponse {
+        booking_code: check.booking_code,
+        user_name: check.user_name,
+        guest_count: check.guest_count,
+        payment_status: check.payment_status.parse().unwrap_or(PaymentStatus::Pending),
+        checked_in_at,
+    }))
+}
+
+/// Maximum announcements per session in any 24 hours
+const MAX_ANNOUNCEMENTS_PER_DAY: i64 = 3;
+
+/// Email all confirmed participants of a session (owner or admin)
+pub async fn announce_session(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+    Json(payload): Json<AnnounceSessionRequest>,
+) -> Result<Json<SessionAnnouncementResponse>, ApiError> {
+    payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+    let subject = payload.subject.trim();
+    let body = payload.body.trim();
+    if subject.is_empty() || body.is_empty() {
+        return Err(response::bad_request("Subject and body cannot be empty"));
+    }
+
+    let session = find_editable_session(&state, &user, session_id).await?;
+
+    let recent = session_announcements::count_since(&state.db, session_id, Utc::now() - Duration::hours(24))
+        .await
+        .map_err(response::db_error)?;
+    if recent >= MAX_ANNOUNCEMENTS_PER_DAY {
+        return Err(ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::AnnouncementLimitReached,
+            format!("At most {} announcements per session per day", MAX_ANNOUNCEMENTS_PER_DAY),
+        ));
+    }
+
+    let recipients = sessions::list_participant_contacts(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?;
+    if recipients.is_empty() {
+        return Err(response::bad_request("Session has no confirmed participants"));
+    }
+
+    let email = EmailClient::from_env()
+        .ok_or_else(|| response::internal_error("Email is not configured"))?;
+
+    let announcement = session_announcements::create(
+        &state.db,
+        session_id,
+        user.id,
+        subject,
+        body,
+        recipients.len() as i32,
+    )
+    .await
+    .map_err(|e| response::internal_error_msg("Failed to record announcement", e))?;
+
+    let frontend_url = std::env::var("FRONTEND_URL")
+        .unwrap_or_else(|_| "http://localhost:5173".to_string());
+    let session_url = format!("{}/sessions/{}", frontend_url, session_id);
+
+    let email_subject = format!("{}: {}", session.title, subject);
+    let mut sent_count = 0;
+    for (address, name) in &recipients {
+        let html = format!(
+            "<p>Hi {},</p>\
+             <p>A message from the organizer of <strong>{}</strong> on {} at {}:</p>\
+             <p>{}</p>\
+             <p><a href=\"{}\">View session</a></p>\
+             <p>— Loafy Club</p>",
+            escape_html(name.as_deref().unwrap_or("there")),
+            escape_html(&session.title),
+            session.date.format("%Y-%m-%d"),
+            session.time.format("%H:%M"),
+            escape_html(body).replace('\n', "<br>"),
+            session_url,
+        );
+
+        match email.send(address, &email_subject, &html).await {
+            Ok(()) => sent_count += 1,
+            Err(e) => tracing::error!("Failed to send announcement for session {} to {}: {}", session_id, address, e),
+        }
+    }
+
+    tracing::info!(
+        "User {} sent announcement {} for session {} to {}/{} participants",
+        user.id,
+        announcement.id,
+        session_id,
+        sent_count,
+        recipients.len()
+    );
+
+    Ok(Json(SessionAnnouncementResponse {
+        id: announcement.id,
+        session_id,
+        subject: announcement.subject,
+        recipient_count: announcement.recipient_count,
+        sent_count,
+        created_at: announcement.created_at,
+    }))
+}
+
+/// Join a full session's waitlist
+pub async fn join_waitlist(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+    Json(payload): Json<JoinWaitlistRequest>,
+) -> Result<Json<WaitlistPositionResponse>, ApiError> {
+    payload.validate().map_err(|e| response::bad_request(format!("Validation error: {}", e)))?;
+
+    let session = sessions::find_by_id(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    if session.cancelled {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, ErrorCode::SessionCancelled, "Session is cancelled"));
+    }
+    if !session.waitlist_enabled {
+        return Err(response::bad_request("This session does not have a waitlist"));
+    }
+    let slots_needed = 1 + payload.guest_count;
+    if session.available_slots >= slots_needed {
+        return Err(response::bad_request("Session has free slots; book it directly"));
+    }
+
+    let has_booking = bookings::has_active_booking_for_session(&state.db, user.id, session_id)
+        .await
+        .map_err(response::db_error)?;
+    if has_booking {
+        return Err(ApiError::new(StatusCode::CONFLICT, ErrorCode::AlreadyBooked, "You already have a booking for this session"));
+    }
+
+    let already_waiting = waitlist::find_waiting(&state.db, session_id, user.id)
+        .await
+        .map_err(response::db_error)?;
+    if already_waiting.is_some() {
+        return Err(response::conflict("You are already on the waitlist for this session"));
+    }
+
+    waitlist::join(
+        &state.db,
+        session_id,
+        user.id,
+        payload.guest_count,
+        payload.payment_method.as_str(),
+    )
+    .await
+    .map_err(|e| response::internal_error_msg("Failed to join waitlist", e))?;
+
+    get_my_waitlist_position(AuthUser(user), State(state), Path(session_id)).await
+}
+
+/// Leave a session's waitlist
+pub async fn leave_waitlist(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let removed = waitlist::leave(&state.db, session_id, user.id)
+        .await
+        .map_err(response::db_error)?;
+
+    if !removed {
+        return Err(response::not_found("Waitlist entry"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the caller's position in a session's waitlist
+pub async fn get_my_waitlist_position(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<WaitlistPositionResponse>, ApiError> {
+    let position = get_waitlist_position(&state.db, session_id, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(WaitlistPositionResponse {
+        session_id,
+        position: position.position,
+        waitlist_length: position.waitlist_length,
+        guest_count: position.guest_count,
+        joined_at: position.joined_at,
+        estimated_wait_hours: position.estimated_wait_hours,
+    }))
+}
+
+/// Get all canonical session locations with session counts
 pub async fn list_locations(
     State(state): State<AppState>,
-) -> Result<Json<Vec<String>>, ApiError> {
-    let locations = sessions::list_locations(&state.db)
+) -> Result<Json<Vec<LocationResponse>>, ApiError> {
+    let locations = locations::list_with_counts(&state.db)
         .await
         .map_err(|e| response::internal_error_msg("Failed to fetch locations", e))?;
 
-    Ok(Json(locations))
+    Ok(Json(locations.into_iter().map(location_response).collect()))
+}
+
+/// Convert a location row into its API response
+pub fn location_response(l: LocationWithCount) -> LocationResponse {
+    LocationResponse {
+        id: l.id,
+        name: l.name,
+        session_count: l.session_count,
+    }
+}
+
+/// Resolve the session's location: a known location_id, or a name that is
+/// matched case-insensitively against existing locations (created if new)
+async fn resolve_location(
+    state: &AppState,
+    payload: &CreateSessionRequest,
+) -> Result<Location, ApiError> {
+    let mut tx = state.db.begin().await.map_err(response::db_error)?;
+    let location = resolve_location_in_tx(&mut tx, payload).await?;
+    tx.commit().await.map_err(response::db_error)?;
+    Ok(location)
+}
+
+/// [`resolve_location`] within a transaction, so a location created for a
+/// session that is never saved is rolled back with it
+async fn resolve_location_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    payload: &CreateSessionRequest,
+) -> Result<Location, ApiError> {
+    if let Some(location_id) = payload.location_id {
+        return locations::find_by_id_in_tx(tx, location_id)
+            .await
+            .map_err(response::db_error)?
+            .ok_or_else(|| response::not_found("Location"));
+    }
+
+    if payload.location.trim().is_empty() {
+        return Err(response::bad_request("Either location or location_id is required"));
+    }
+
+    locations::find_or_create_by_name_in_tx(tx, &payload.location)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to resolve location", e))
+}
+
+/// Resolve `(courts, max_players_per_court)` from the request
+///
+/// `max_players_per_court` wins when given; otherwise `max_slots` is split
+/// evenly across the courts. `default_courts` applies when `courts` is omitted.
+/// The resulting total capacity must be within `1..=MAX_SESSION_SLOTS`.
+fn resolve_court_layout(
+    payload: &CreateSessionRequest,
+    default_courts: i32,
+) -> Result<(i32, i32), ApiError> {
+    let courts = payload.courts.unwrap_or(default_courts).max(1);
+
+    let per_court = if let Some(per_court) = payload.max_players_per_court {
+        per_court
+    } else {
+        let Some(max_slots) = payload.max_slots else {
+            return Err(response::bad_request("Either max_players_per_court or max_slots is required"));
+        };
+        if max_slots % courts != 0 {
+            return Err(response::bad_request(format!(
+                "max_slots ({}) must divide evenly across {} courts; set max_players_per_court instead",
+                max_slots, courts
+            )));
+        }
+        max_slots / courts
+    };
+
+    let capacity = courts * per_court;
+    if !(1..=MAX_SESSION_SLOTS).contains(&capacity) {
+        return Err(response::unprocessable(format!(
+            "max_slots: total capacity must be between 1 and {} (got {} courts × {} players = {})",
+            MAX_SESSION_SLOTS, courts, per_court, capacity
+        )));
+    }
+
+    Ok((courts, per_court))
+}
+
+/// Get only the confirmed participant count for a session
+///
+/// Counts are public even when the roster itself is hidden.
+pub async fn get_session_participant_count(
+    State(state): State<AppState>,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<SessionParticipantCountResponse>, ApiError> {
+    sessions::find_by_id(&state.db, session_id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("Session"))?;
+
+    let count = sessions::count_session_participants(&state.db, session_id)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to count participants", e))?;
+
+    Ok(Json(SessionParticipantCountResponse {
+        session_id,
+        confirmed_count: count as i32,
+    }))
 }
 
 /// Get all participants for a session
 pub async fn get_session_participants(
+    OptionalAuthUser(user): OptionalAuthUser,
     State(state): State<AppState>,
     Path(session_id): Path<Uuid>,
 ) -> Result<Json<SessionParticipantsResponse>, ApiError> {
-    // Verify session exists
-    sessions::find_by_id(&state.db, session_id)
+    let session = sessions::find_by_id(&state.db, session_id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::not_found("Session"))?;
@@ -339,17 +1432,12 @@ pub async fn get_session_participants(
         .await
         .map_err(|e| response::internal_error_msg("Failed to fetch participants", e))?;
 
-    let participant_infos: Vec<ParticipantInfo> = participants
-        .into_iter()
-        .map(|p| ParticipantInfo {
-            id: p.user_id,
-            name: p.name,
-            avatar_url: p.avatar_url,
-            guest_count: p.guest_count,
-        })
-        .collect();
+    // Counted before filtering so hidden rosters still report how many are booked
+    let total_count = participants.len() as i32;
 
-    let total_count = participant_infos.len() as i32;
+    let viewer = roster_viewer(&state, user.as_ref(), &session).await;
+    let visibility = session.roster_visibility.parse().unwrap_or_default();
+    let participant_infos = visible_participants(participants, visibility, viewer);
 
     Ok(Json(SessionParticipantsResponse {
         session_id,
@@ -357,3 +1445,50 @@ pub async fn get_session_participants(
         total_count,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(category: &str, amount_vnd: i32) -> ExpenseInput {
+        ExpenseInput {
+            category: category.to_string(),
+            description: None,
+            cost_type: "total".to_string(),
+            amount_vnd,
+        }
+    }
+
+    fn template_item(position: i32, category: &str, amount_vnd: i32) -> ExpenseTemplateItem {
+        ExpenseTemplateItem {
+            id: Uuid::new_v4(),
+            template_id: Uuid::nil(),
+            position,
+            category: category.to_string(),
+            description: None,
+            cost_type: "per_court".to_string(),
+            amount_vnd,
+        }
+    }
+
+    #[test]
+    fn test_with_template_items_appends_after_explicit_expenses() {
+        let explicit = [expense("instructor", 300_000)];
+        let items = vec![template_item(0, "court_rental", 200_000), template_item(1, "equipment", 50_000)];
+
+        let expenses = with_template_items(Some(&explicit), items);
+        let categories: Vec<_> = expenses.iter().map(|e| e.category.as_str()).collect();
+        assert_eq!(categories, ["instructor", "court_rental", "equipment"]);
+        assert_eq!(expenses[1].cost_type, "per_court");
+        assert_eq!(expenses[1].amount_vnd, 200_000);
+    }
+
+    #[test]
+    fn test_with_template_items_without_explicit_expenses() {
+        let expenses = with_template_items(None, vec![template_item(0, "court_rental", 200_000)]);
+        assert_eq!(expenses.len(), 1);
+        assert_eq!(expenses[0].category, "court_rental");
+
+        assert!(with_template_items(None, Vec::new()).is_empty());
+    }
+}
@@ -1,12 +1,15 @@
 use axum::{extract::{Query, State}, Json};
-use loafy_db::queries::{bookings, subscriptions, ticket_transactions, users};
+use loafy_db::models::Subscription;
+use loafy_db::queries::{bookings, config, subscription_gifts, subscriptions, ticket_transactions, users};
 use loafy_integrations::stripe::StripeSubscriptions;
 use loafy_types::api::{
-    CreateCheckoutResponse, PageInfo, SubscriptionDetailResponse, TicketBalanceResponse,
+    BonusTicketResponse, BonusTicketsResponse, CreateCheckoutResponse, GiftSubscriptionRequest, SubscriptionDetailResponse, TicketBalanceResponse,
     TicketTransactionResponse, TicketTransactionsResponse,
 };
-use loafy_types::enums::SubscriptionStatus;
+use loafy_types::enums::{BonusTicketType, SubscriptionStatus};
+use loafy_types::{trim_keyset_page, Cursor, Pagination};
 use serde::Deserialize;
+use validator::Validate;
 
 use crate::middleware::AppState;
 use crate::response::{self, ApiError};
@@ -19,6 +22,16 @@ pub struct PaginationQuery {
     pub per_page: i64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TicketHistoryQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+    /// Keyset cursor from a previous `next_cursor`; takes precedence over `page`
+    pub after: Option<String>,
+}
+
 fn default_page() -> i64 {
     1
 }
@@ -27,21 +40,48 @@ fn default_per_page() -> i64 {
     20
 }
 
+/// Largest page size accepted by ticket history endpoints
+const MAX_PER_PAGE: i32 = 50;
+
 /// GET /api/subscriptions/tickets
 /// Get current user's ticket balance
 pub async fn get_ticket_balance(
     State(state): State<AppState>,
     crate::middleware::AuthUser(user): crate::middleware::AuthUser,
 ) -> Result<Json<TicketBalanceResponse>, ApiError> {
-    let subscription = subscriptions::find_by_user_id(&state.db, user.id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?;
 
-    Ok(Json(TicketBalanceResponse {
-        tickets_remaining: subscription.as_ref().map(|s| s.tickets_remaining).unwrap_or(0),
-        has_active_subscription: subscription.as_ref().map(|s| s.is_active()).unwrap_or(false),
+    let config = config::load(&state.db).await.map_err(response::db_error)?;
+    let tickets_remaining = subscription.as_ref().map(|s| s.tickets_remaining).unwrap_or(0);
+
+    Ok(Json(ticket_balance_response(&config, subscription.as_ref(), tickets_remaining)))
+}
+
+/// Build a balance response, including what the rollover cap would take from
+/// the next renewal
+pub(crate) fn ticket_balance_response(
+    config: &config::Config,
+    subscription: Option<&Subscription>,
+    tickets_remaining: i32,
+) -> TicketBalanceResponse {
+    let rollover_cap = config.rollover_cap();
+    let renews = subscription.is_some_and(|s| s.is_active() && s.auto_renew);
+    let tickets_forfeited_at_renewal = if renews {
+        let ticket_count = config.subscription_ticket_count;
+        ticket_count - subscriptions::renewal_tickets_granted(tickets_remaining, ticket_count, rollover_cap)
+    } else {
+        0
+    };
+
+    TicketBalanceResponse {
+        tickets_remaining,
+        has_active_subscription: subscription.is_some_and(|s| s.is_active()),
         current_period_end: subscription.and_then(|s| s.current_period_end.map(|dt| dt.naive_utc())),
-    }))
+        rollover_cap,
+        tickets_forfeited_at_renewal,
+    }
 }
 
 /// GET /api/subscriptions/tickets/history
@@ -49,15 +89,32 @@ pub async fn get_ticket_balance(
 pub async fn get_ticket_history(
     State(state): State<AppState>,
     crate::middleware::AuthUser(user): crate::middleware::AuthUser,
-    Query(query): Query<PaginationQuery>,
+    Query(query): Query<TicketHistoryQuery>,
 ) -> Result<Json<TicketTransactionsResponse>, ApiError> {
-    let page = query.page.max(1);
-    let per_page = query.per_page.clamp(1, 50);
+    let key = |tx: &loafy_db::models::TicketTransaction| Cursor { created_at: tx.created_at, id: tx.id };
 
-    let (transactions, total) =
-        ticket_transactions::list_user_transactions(&state.db, user.id, page, per_page)
-            .await
-            .map_err(response::db_error)?;
+    let (transactions, page_info, next_cursor) = if let Some(after) = query.after.as_deref() {
+        let after = Cursor::decode(after).ok_or_else(|| response::bad_request("Invalid cursor"))?;
+        let limit = Pagination::from_query(1, query.per_page, MAX_PER_PAGE).per_page;
+
+        let mut transactions =
+            ticket_transactions::list_user_transactions_after(&state.db, user.id, Some(after), limit + 1)
+                .await
+                .map_err(response::db_error)?;
+        let next_cursor = trim_keyset_page(&mut transactions, limit, key);
+        (transactions, None, next_cursor)
+    } else {
+        let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
+
+        let (transactions, total) =
+            ticket_transactions::list_user_transactions(&state.db, user.id, pagination)
+                .await
+                .map_err(response::db_error)?;
+        let next_cursor = (pagination.offset + (transactions.len() as i64) < total)
+            .then(|| transactions.last().map(|tx| key(tx).encode()))
+            .flatten();
+        (transactions, Some(pagination.page_info(total)), next_cursor)
+    };
 
     // Convert to response format with booking codes
     let mut responses = Vec::with_capacity(transactions.len());
@@ -83,21 +140,45 @@ pub async fn get_ticket_history(
         });
     }
 
-    let total_pages = (total as f64 / per_page as f64).ceil() as i32;
-
     Ok(Json(TicketTransactionsResponse {
         data: responses,
-        page_info: PageInfo {
-            page: page as i32,
-            per_page: per_page as i32,
-            total,
-            total_pages,
-        },
+        page_info,
+        next_cursor,
+    }))
+}
+
+/// Get current user's bonus ticket history
+pub async fn get_bonus_tickets(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<BonusTicketsResponse>, ApiError> {
+    let pagination = Pagination::from_query(query.page, query.per_page, MAX_PER_PAGE);
+
+    let (bonuses, total) =
+        ticket_transactions::list_user_bonus_tickets(&state.db, user.id, pagination)
+            .await
+            .map_err(response::db_error)?;
+
+    let data = bonuses
+        .into_iter()
+        .map(|b| BonusTicketResponse {
+            id: b.id,
+            bonus_type: b.bonus_type.parse().unwrap_or(BonusTicketType::Manual),
+            tickets: b.tickets,
+            note: b.note,
+            granted_at: b.created_at.naive_utc(),
+        })
+        .collect();
+
+    Ok(Json(BonusTicketsResponse {
+        data,
+        page_info: pagination.page_info(total),
     }))
 }
 
 /// Helper to get Stripe subscriptions client
-fn get_stripe_subscriptions() -> Result<StripeSubscriptions, ApiError> {
+pub(crate) fn get_stripe_subscriptions() -> Result<StripeSubscriptions, ApiError> {
     let secret_key = std::env::var("STRIPE_SECRET_KEY")
         .map_err(|_| response::internal_error("Stripe not configured"))?;
     let price_id = std::env::var("STRIPE_SUBSCRIPTION_PRICE_ID")
@@ -113,12 +194,12 @@ pub async fn create_checkout_session(
     crate::middleware::AuthUser(user): crate::middleware::AuthUser,
 ) -> Result<Json<CreateCheckoutResponse>, ApiError> {
     // Check if user already has active subscription
-    let existing = subscriptions::find_by_user_id(&state.db, user.id)
+    let existing = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?;
 
     if let Some(sub) = existing {
-        if sub.status == "active" {
+        if sub.is_active() {
             return Err(response::bad_request("You already have an active subscription"));
         }
     }
@@ -153,6 +234,86 @@ pub async fn create_checkout_session(
         .create_checkout_session(
             customer.id.as_str(),
             &user.id.to_string(),
+            None,
+            &success_url,
+            &cancel_url,
+        )
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to create checkout session", e))?;
+
+    let checkout_url = session
+        .url
+        .ok_or_else(|| response::internal_error("No URL in checkout session"))?;
+
+    Ok(Json(CreateCheckoutResponse { checkout_url }))
+}
+
+/// POST /api/subscriptions/gift
+/// Create a Stripe Checkout session paying for someone else's subscription
+///
+/// The recipient gets the subscription once the first invoice is paid, or on
+/// sign-up if they don't have an account yet.
+pub async fn create_gift_checkout_session(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+    Json(request): Json<GiftSubscriptionRequest>,
+) -> Result<Json<CreateCheckoutResponse>, ApiError> {
+    request.validate().map_err(|e| response::bad_request(e.to_string()))?;
+    let recipient_email = request.recipient_email.trim().to_lowercase();
+
+    let user_details = users::find_by_id(&state.db, user.id)
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::not_found("User"))?;
+
+    if user_details.email.to_lowercase() == recipient_email {
+        return Err(response::bad_request("Use the regular purchase to subscribe yourself"));
+    }
+
+    let recipient = users::find_by_email(&state.db, &recipient_email)
+        .await
+        .map_err(response::db_error)?;
+
+    if let Some(recipient) = recipient {
+        let existing = subscriptions::find_active_or_latest_by_user_id(&state.db, recipient.id)
+            .await
+            .map_err(response::db_error)?;
+
+        if existing.is_some_and(|sub| sub.is_active()) {
+            return Err(response::bad_request("Recipient already has an active subscription"));
+        }
+    }
+
+    let pending = subscription_gifts::has_unclaimed_for_email(&state.db, &recipient_email)
+        .await
+        .map_err(response::db_error)?;
+
+    if pending {
+        return Err(response::conflict("Recipient already has a gift subscription waiting"));
+    }
+
+    let stripe = get_stripe_subscriptions()?;
+
+    // The purchaser is the paying customer; the recipient only gets the grant
+    let customer = stripe
+        .get_or_create_customer(
+            &user.id.to_string(),
+            &user_details.email,
+            user_details.name.as_deref(),
+        )
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to create customer", e))?;
+
+    let frontend_url = std::env::var("FRONTEND_URL")
+        .unwrap_or_else(|_| "http://localhost:5173".to_string());
+    let success_url = format!("{}/subscriptions/success?session_id={{CHECKOUT_SESSION_ID}}&gift=1", frontend_url);
+    let cancel_url = format!("{}/subscriptions/cancelled", frontend_url);
+
+    let session = stripe
+        .create_checkout_session(
+            customer.id.as_str(),
+            &user.id.to_string(),
+            Some(&recipient_email),
             &success_url,
             &cancel_url,
         )
@@ -172,13 +333,15 @@ pub async fn get_current_subscription(
     State(state): State<AppState>,
     crate::middleware::AuthUser(user): crate::middleware::AuthUser,
 ) -> Result<Json<Option<SubscriptionDetailResponse>>, ApiError> {
-    let subscription = subscriptions::find_by_user_id(&state.db, user.id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?;
 
     let response = subscription.map(|sub| {
         let status = match sub.status.as_str() {
             "active" => SubscriptionStatus::Active,
+            "trialing" => SubscriptionStatus::Trialing,
+            "incomplete" => SubscriptionStatus::Incomplete,
             "expired" => SubscriptionStatus::Expired,
             "cancelled" => SubscriptionStatus::Cancelled,
             "past_due" => SubscriptionStatus::PastDue,
@@ -192,7 +355,7 @@ pub async fn get_current_subscription(
             current_period_start: sub.current_period_start.map(|dt| dt.naive_utc()),
             current_period_end: sub.current_period_end.map(|dt| dt.naive_utc()),
             auto_renew: sub.auto_renew,
-            cancel_at_period_end: !sub.auto_renew && sub.status == "active",
+            cancel_at_period_end: !sub.auto_renew && sub.is_active(),
             created_at: sub.created_at.naive_utc(),
         }
     });
@@ -207,7 +370,7 @@ pub async fn cancel_subscription(
     crate::middleware::AuthUser(user): crate::middleware::AuthUser,
 ) -> Result<Json<SubscriptionDetailResponse>, ApiError> {
     // Get subscription
-    let subscription = subscriptions::find_by_user_id(&state.db, user.id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::not_found("Subscription"))?;
@@ -236,13 +399,15 @@ pub async fn cancel_subscription(
         .map_err(response::db_error)?;
 
     // Fetch updated subscription
-    let updated = subscriptions::find_by_user_id(&state.db, user.id)
+    let updated = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::not_found("Subscription"))?;
 
     let status = match updated.status.as_str() {
         "active" => SubscriptionStatus::Active,
+        "trialing" => SubscriptionStatus::Trialing,
+        "incomplete" => SubscriptionStatus::Incomplete,
         "expired" => SubscriptionStatus::Expired,
         "cancelled" => SubscriptionStatus::Cancelled,
         "past_due" => SubscriptionStatus::PastDue,
@@ -256,7 +421,7 @@ pub async fn cancel_subscription(
         current_period_start: updated.current_period_start.map(|dt| dt.naive_utc()),
         current_period_end: updated.current_period_end.map(|dt| dt.naive_utc()),
         auto_renew: updated.auto_renew,
-        cancel_at_period_end: !updated.auto_renew && updated.status == "active",
+        cancel_at_period_end: !updated.auto_renew && updated.is_active(),
         created_at: updated.created_at.naive_utc(),
     }))
 }
@@ -268,13 +433,13 @@ pub async fn resume_subscription(
     crate::middleware::AuthUser(user): crate::middleware::AuthUser,
 ) -> Result<Json<SubscriptionDetailResponse>, ApiError> {
     // Get subscription
-    let subscription = subscriptions::find_by_user_id(&state.db, user.id)
+    let subscription = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::not_found("Subscription"))?;
 
     // Check status
-    if subscription.status != "active" {
+    if !subscription.is_active() {
         return Err(response::bad_request("Subscription is not active"));
     }
 
@@ -302,13 +467,15 @@ pub async fn resume_subscription(
         .map_err(response::db_error)?;
 
     // Fetch updated subscription
-    let updated = subscriptions::find_by_user_id(&state.db, user.id)
+    let updated = subscriptions::find_active_or_latest_by_user_id(&state.db, user.id)
         .await
         .map_err(response::db_error)?
         .ok_or_else(|| response::not_found("Subscription"))?;
 
     let status = match updated.status.as_str() {
         "active" => SubscriptionStatus::Active,
+        "trialing" => SubscriptionStatus::Trialing,
+        "incomplete" => SubscriptionStatus::Incomplete,
         "expired" => SubscriptionStatus::Expired,
         "cancelled" => SubscriptionStatus::Cancelled,
         "past_due" => SubscriptionStatus::PastDue,
@@ -322,7 +489,7 @@ pub async fn resume_subscription(
         current_period_start: updated.current_period_start.map(|dt| dt.naive_utc()),
         current_period_end: updated.current_period_end.map(|dt| dt.naive_utc()),
         auto_renew: updated.auto_renew,
-        cancel_at_period_end: !updated.auto_renew && updated.status == "active",
+        cancel_at_period_end: !updated.auto_renew && updated.is_active(),
         created_at: updated.created_at.naive_utc(),
     }))
 }
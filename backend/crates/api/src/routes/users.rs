@@ -1,10 +1,31 @@
-use axum::{extract::State, http::StatusCode, Json};
-use chrono::Utc;
-use loafy_db::queries::users;
-use loafy_types::api::{AuthUser, UpdateProfileRequest};
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use chrono::{DateTime, Utc};
+use loafy_db::models::NotificationPreferences;
+use loafy_db::queries::{bookings, config, notification_preferences, sessions, users};
+use loafy_core::booking::{
+    bookings_feed_ics, club_today, generate_feed_token, get_booking_quota, get_user_stats, hash_feed_token,
+    quota::BOOKING_WINDOW_DAYS,
+};
+use loafy_integrations::supabase::JwtClaims;
+use loafy_types::api::{
+    AuthSessionsResponse, AuthUser, BookingQuotaResponse, CalendarFeedTokenResponse, CurrentAuthSessionResponse,
+    NotificationPreferencesResponse, SignInMethodResponse, UpdateNotificationPreferencesRequest, UpdateProfileRequest,
+    UserStatsResponse,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::middleware::AppState;
+use crate::middleware::{is_impersonation_token, AppState};
 use crate::response::{self, ApiError};
 
 /// Update current user's profile
@@ -16,7 +37,11 @@ pub async fn update_profile(
     // Handle birthday if provided
     if let Some(birthday) = payload.birthday {
         // Validate birthday is in the past
-        let today = Utc::now().date_naive();
+        let offset = config::load(&state.db)
+            .await
+            .map_err(response::db_error)?
+            .session_utc_offset;
+        let today = club_today(offset);
         if birthday >= today {
             return Err(response::bad_request("Birthday must be a date in the past"));
         }
@@ -41,6 +66,7 @@ pub async fn update_profile(
         payload.name.as_deref(),
         payload.avatar_url.as_deref(),
         payload.phone.as_deref(),
+        payload.show_in_roster,
     )
     .await
     .map_err(|e| response::internal_error_msg("Failed to update profile", e))?;
@@ -88,3 +114,262 @@ pub async fn delete_account(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Get current user's weekly booking count and limit
+pub async fn get_booking_quota_route(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+) -> Result<Json<BookingQuotaResponse>, ApiError> {
+    let quota = get_booking_quota(&state.db, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(BookingQuotaResponse {
+        used: quota.used,
+        limit: quota.limit,
+        window_days: BOOKING_WINDOW_DAYS,
+        window_start: quota.window_start,
+    }))
+}
+
+/// Get current user's lifetime booking and attendance stats
+pub async fn get_user_stats_route(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+) -> Result<Json<UserStatsResponse>, ApiError> {
+    let stats = get_user_stats(&state.db, user.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(UserStatsResponse {
+        confirmed_bookings: stats.confirmed_bookings,
+        sessions_attended: stats.sessions_attended,
+        total_spent_vnd: stats.total_spent_vnd,
+        tickets_used: stats.tickets_used,
+        current_streak_weeks: stats.current_streak_weeks,
+    }))
+}
+
+/// Get current user's notification preferences
+pub async fn get_notification_preferences(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+) -> Result<Json<NotificationPreferencesResponse>, ApiError> {
+    let preferences = notification_preferences::get(&state.db, user.id)
+        .await
+        .map_err(response::db_error)?;
+
+    Ok(Json(preferences.into()))
+}
+
+/// Replace current user's notification preferences
+pub async fn update_notification_preferences(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+    Json(payload): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<NotificationPreferencesResponse>, ApiError> {
+    let preferences = NotificationPreferences {
+        user_id: user.id,
+        booking_confirmation_email: payload.booking_confirmation.email,
+        booking_confirmation_sms: payload.booking_confirmation.sms,
+        booking_confirmation_push: payload.booking_confirmation.push,
+        reminder_email: payload.reminder.email,
+        reminder_sms: payload.reminder.sms,
+        reminder_push: payload.reminder.push,
+        session_cancelled_email: payload.session_cancelled.email,
+        session_cancelled_sms: payload.session_cancelled.sms,
+        session_cancelled_push: payload.session_cancelled.push,
+        waitlist_promoted_email: payload.waitlist_promoted.email,
+        waitlist_promoted_sms: payload.waitlist_promoted.sms,
+        waitlist_promoted_push: payload.waitlist_promoted.push,
+        updated_at: Utc::now(),
+    };
+
+    let saved = notification_preferences::upsert(&state.db, &preferences)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to update notification preferences", e))?;
+
+    Ok(Json(saved.into()))
+}
+
+/// Path of the calendar feed; the token goes in the query string
+const CALENDAR_FEED_PATH: &str = "/api/users/me/calendar.ics";
+
+/// Query params for the calendar feed
+#[derive(Debug, Deserialize)]
+pub struct CalendarFeedQuery {
+    pub token: String,
+}
+
+/// Generate a calendar feed token, replacing (and invalidating) any previous one
+pub async fn create_calendar_feed_token(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+) -> Result<Json<CalendarFeedTokenResponse>, ApiError> {
+    let (token, token_hash) = generate_feed_token();
+
+    users::set_calendar_feed_token_hash(&state.db, user.id, Some(&token_hash))
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to create calendar token", e))?;
+
+    Ok(Json(CalendarFeedTokenResponse {
+        feed_path: format!("{}?token={}", CALENDAR_FEED_PATH, token),
+        token,
+    }))
+}
+
+/// Revoke the calendar feed token; the feed URL stops working
+pub async fn revoke_calendar_feed_token(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+) -> Result<StatusCode, ApiError> {
+    users::set_calendar_feed_token_hash(&state.db, user.id, None)
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to revoke calendar token", e))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Calendar feed of the token owner's upcoming confirmed bookings
+///
+/// Authenticated by the feed token rather than a bearer header, since
+/// calendar apps subscribing to the URL can't send one.
+pub async fn get_calendar_feed(
+    State(state): State<AppState>,
+    Query(query): Query<CalendarFeedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = users::find_by_calendar_feed_token_hash(&state.db, &hash_feed_token(&query.token))
+        .await
+        .map_err(response::db_error)?
+        .ok_or_else(|| response::unauthorized("Invalid calendar token"))?;
+
+    let settings = config::load(&state.db)
+        .await
+        .map_err(response::db_error)?;
+
+    let upcoming = bookings::list_upcoming_confirmed(&state.db, user.id, club_today(settings.session_utc_offset))
+        .await
+        .map_err(response::db_error)?;
+
+    let session_ids: Vec<Uuid> = upcoming.iter().map(|b| b.session_id).collect();
+    let sessions_by_id: HashMap<Uuid, _> = sessions::find_by_ids(&state.db, &session_ids)
+        .await
+        .map_err(response::db_error)?
+        .into_iter()
+        .map(|s| (s.id, s))
+        .collect();
+
+    let entries: Vec<_> = upcoming
+        .into_iter()
+        .filter_map(|booking| {
+            let session = sessions_by_id.get(&booking.session_id)?.clone();
+            Some((booking, session))
+        })
+        .collect();
+
+    let ics = bookings_feed_ics(
+        &entries,
+        settings.session_utc_offset,
+        settings.default_session_duration_minutes,
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, max-age=900"),
+        ],
+        ics,
+    ))
+}
+
+/// List where the current user is signed in
+///
+/// The current session comes from the token itself; other sign-ins are
+/// summarized from Supabase's admin user record. If that lookup fails or
+/// comes back incomplete, the rest is still returned with `complete: false`.
+pub async fn list_auth_sessions(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(user): crate::middleware::AuthUser,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<AuthSessionsResponse>, ApiError> {
+    // Sign-in details stay private even from support
+    if is_impersonation_token(bearer.token()) {
+        return Err(response::forbidden("Not available while impersonating a user"));
+    }
+
+    let claims = state
+        .supabase
+        .verify_token(bearer.token())
+        .await
+        .map_err(|e| response::unauthorized(format!("Invalid token: {}", e)))?;
+
+    let supabase_user_id: Uuid = user.auth_provider_id.parse()
+        .map_err(|_| response::internal_error("Invalid auth provider ID"))?;
+
+    let mut complete = true;
+    let mut sign_in_methods = Vec::new();
+    let mut last_sign_in_at = None;
+    match state.supabase.get_user_by_id(supabase_user_id).await {
+        Ok(auth_user) => {
+            last_sign_in_at = auth_user.last_sign_in_at;
+            complete &= auth_user.last_sign_in_at.is_some() && auth_user.identities.is_some();
+            for identity in auth_user.identities.unwrap_or_default() {
+                let Some(provider) = identity.provider else {
+                    complete = false;
+                    continue;
+                };
+                sign_in_methods.push(SignInMethodResponse {
+                    provider,
+                    linked_at: identity.created_at,
+                    last_sign_in_at: identity.last_sign_in_at,
+                });
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Failed to fetch Supabase sign-ins for user {}: {}", user.id, e);
+            complete = false;
+        }
+    }
+
+    Ok(Json(AuthSessionsResponse {
+        current: current_auth_session(&claims),
+        sign_in_methods,
+        last_sign_in_at,
+        complete,
+    }))
+}
+
+/// Sign out every other session, keeping the one making this request
+pub async fn revoke_other_auth_sessions(
+    State(state): State<AppState>,
+    crate::middleware::AuthUser(_user): crate::middleware::AuthUser,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .supabase
+        .sign_out_others(bearer.token())
+        .await
+        .map_err(|e| response::internal_error_msg("Failed to sign out other sessions", e))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Describe the session a token belongs to from its claims
+fn current_auth_session(claims: &JwtClaims) -> CurrentAuthSessionResponse {
+    let amr = claims.amr.as_deref().unwrap_or_default();
+
+    // The first authentication in the session, falling back to when this token was issued
+    let signed_in_at = amr
+        .iter()
+        .filter_map(|entry| entry.timestamp)
+        .min()
+        .or(Some(claims.iat as i64))
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0));
+
+    CurrentAuthSessionResponse {
+        session_id: claims.session_id.clone(),
+        methods: amr.iter().map(|entry| entry.method.clone()).collect(),
+        signed_in_at,
+        expires_at: DateTime::from_timestamp(claims.exp as i64, 0),
+    }
+}
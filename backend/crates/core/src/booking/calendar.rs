@@ -0,0 +1,179 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Utc};
+use loafy_db::models::{Booking, Session};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+/// Product identifier written to exported calendars
+const PRODUCT_ID: &str = "-//Loafy Club//Booking//EN";
+
+/// Display name calendar apps show for the subscribed feed
+const FEED_NAME: &str = "Loafy Club bookings";
+
+/// Length of generated feed tokens (alphanumeric, ~190 bits)
+const FEED_TOKEN_LENGTH: usize = 32;
+
+/// Build an RFC 5545 calendar with a single event for a booking
+///
+/// Session date/time are local to `utc_offset` and exported in UTC. Sessions
+/// without an end time last `default_duration_minutes`; an end time earlier
+/// than the start is taken to be past midnight.
+pub fn booking_ics(
+    booking: &Booking,
+    session: &Session,
+    utc_offset: FixedOffset,
+    default_duration_minutes: i32,
+) -> String {
+    let mut lines = calendar_header();
+    lines.extend(event_lines(booking, session, utc_offset, default_duration_minutes));
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Build a subscribable calendar feed with one event per booking
+///
+/// Events keep the same UID as the single-booking export, so importing both
+/// doesn't duplicate them.
+pub fn bookings_feed_ics(
+    entries: &[(Booking, Session)],
+    utc_offset: FixedOffset,
+    default_duration_minutes: i32,
+) -> String {
+    let mut lines = calendar_header();
+    lines.push(format!("X-WR-CALNAME:{}", FEED_NAME));
+    for (booking, session) in entries {
+        lines.extend(event_lines(booking, session, utc_offset, default_duration_minutes));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Opening lines of a VCALENDAR, up to its first component
+fn calendar_header() -> Vec<String> {
+    vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ]
+}
+
+/// Unfolded VEVENT lines for a booking
+fn event_lines(
+    booking: &Booking,
+    session: &Session,
+    utc_offset: FixedOffset,
+    default_duration_minutes: i32,
+) -> Vec<String> {
+    let start_local = NaiveDateTime::new(session.date, session.time);
+    let end_local = match session.end_time {
+        Some(end_time) if end_time > session.time => NaiveDateTime::new(session.date, end_time),
+        Some(end_time) => NaiveDateTime::new(session.date + Duration::days(1), end_time),
+        None => start_local + Duration::minutes(default_duration_minutes as i64),
+    };
+    let to_utc = |local: NaiveDateTime| (local - utc_offset).and_utc();
+
+    let status = if booking.cancelled_at.is_some() || session.cancelled {
+        "CANCELLED"
+    } else if booking.payment_status == "confirmed" {
+        "CONFIRMED"
+    } else {
+        "TENTATIVE"
+    };
+
+    let mut description = format!("Booking code: {}", booking.booking_code);
+    if booking.guest_count > 0 {
+        description.push_str(&format!("\nGuests: {}", booking.guest_count));
+    }
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@loafy.club", booking.id),
+        format!("DTSTAMP:{}", format_utc(Utc::now())),
+        format!("DTSTART:{}", format_utc(to_utc(start_local))),
+        format!("DTEND:{}", format_utc(to_utc(end_local))),
+        format!("SUMMARY:{}", escape_text(&session.title)),
+        format!("LOCATION:{}", escape_text(&session.location)),
+        format!("DESCRIPTION:{}", escape_text(&description)),
+        format!("STATUS:{}", status),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+/// Generate a calendar feed token, returning `(token, hash)`
+///
+/// Only the hash is stored; the token is shown to the user once, in the feed URL.
+pub fn generate_feed_token() -> (String, String) {
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(FEED_TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+    let hash = hash_feed_token(&token);
+    (token, hash)
+}
+
+/// SHA-256 of a feed token, hex encoded, as stored on the user
+pub fn hash_feed_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Format a UTC timestamp as an iCalendar DATE-TIME (`20260501T110000Z`)
+fn format_utc(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value (backslash, semicolon, comma and newlines)
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets and terminate it with CRLF
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("Court A, B; bring water\nThanks"), "Court A\\, B\\; bring water\\nThanks");
+    }
+
+    #[test]
+    fn test_fold_line_splits_long_lines() {
+        let folded = fold_line(&format!("DESCRIPTION:{}", "x".repeat(100)));
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert!(lines.iter().all(|l| l.len() <= 75));
+    }
+
+    #[test]
+    fn test_feed_token_hash_matches_token() {
+        let (token, hash) = generate_feed_token();
+        assert_eq!(token.len(), FEED_TOKEN_LENGTH);
+        assert_eq!(hash, hash_feed_token(&token));
+        assert_ne!(hash, hash_feed_token("other"));
+    }
+}
@@ -1,113 +1,555 @@
-use chrono::{NaiveDateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use loafy_db::{
-    models::{Booking, transaction_types},
-    queries::{bookings, sessions, subscriptions, ticket_transactions},
+    models::{Booking, Session, cancellation_reasons, transaction_types},
+    queries::{bookings, config, sessions, subscriptions, ticket_transactions},
     PgPool,
 };
-use loafy_types::AppError;
+use loafy_types::{AppError, ErrorCode};
+use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
 
+use crate::locking::{lock_bookings, lock_sessions};
+
+use super::utils::session_starts_at;
+
 /// Default cancellation hours if not set on session
 const DEFAULT_DROP_IN_CANCELLATION_HOURS: i32 = 48;
 const DEFAULT_SUBSCRIBER_CANCELLATION_HOURS: i32 = 24;
 
+/// Who is cancelling a booking
+#[derive(Debug, Clone, Copy)]
+pub enum CancelledBy {
+    /// The booking owner; ownership is enforced and refunds depend on the
+    /// cancellation deadline
+    User(Uuid),
+    /// An admin; may cancel any booking at any time, always with a refund
+    Admin(Uuid),
+}
+
+/// Result of cancelling a single booking
+#[derive(Debug, Clone)]
+pub struct CancelOutcome {
+    pub booking: Booking,
+    /// Cancelled before the deadline; the caller should refund any card payment
+    pub refund_eligible: bool,
+    /// Whether used tickets went back to the subscription
+    pub ticket_restored: bool,
+}
+
+/// Result of cancelling all of a user's pending bookings
+#[derive(Debug, Default)]
+pub struct BulkCancelResult {
+    /// Booking codes that were cancelled
+    pub cancelled: Vec<String>,
+    /// Booking codes that could not be cancelled, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// What cancelling a booking right now would do, without doing it
+#[derive(Debug, Clone)]
+pub struct CancelPreview {
+    /// Whether the user may still cancel (not cancelled, session not started)
+    pub can_cancel: bool,
+    /// Whether cancelling now is inside the refund window
+    pub refund_eligible: bool,
+    /// Amount refunded to the card; 0 unless refund-eligible with a confirmed Stripe payment
+    pub refund_amount_vnd: i32,
+    /// Whether used tickets go back to the subscription
+    pub ticket_restored: bool,
+    /// Last moment cancelling still refunds
+    pub deadline: DateTime<Utc>,
+}
+
 /// Cancel booking and return slots
-/// If a ticket was used for the booking, it will be restored to the subscription
+///
+/// Users may cancel up to session start, but only cancellations before the
+/// deadline are refund-eligible: used tickets are restored and the caller
+/// refunds any card payment. Admin cancellations are always refund-eligible.
+///
+/// This is the single cancellation path for both user and admin routes.
 pub async fn cancel_booking(
     pool: &PgPool,
     booking_id: Uuid,
-    user_id: Uuid,
-) -> Result<Booking, AppError> {
+    cancelled_by: CancelledBy,
+) -> Result<CancelOutcome, AppError> {
     // Get booking
     let booking = bookings::find_by_id(pool, booking_id)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Booking not found".to_string()))?;
+        .ok_or_else(|| AppError::NotFound(ErrorCode::BookingNotFound, "Booking not found".to_string()))?;
 
-    // Check ownership
-    if booking.user_id != user_id {
-        return Err(AppError::Forbidden);
+    // Check if already cancelled
+    if booking.cancelled_at.is_some() {
+        return Err(AppError::BadRequest(ErrorCode::BookingAlreadyCancelled, "Booking already cancelled".to_string()));
     }
 
-    // Check if already cancelled
+    let (admin_id, refund_eligible, reason) = match cancelled_by {
+        CancelledBy::User(user_id) => {
+            // Check ownership
+            if booking.user_id != user_id {
+                return Err(AppError::Forbidden);
+            }
+
+            // Get session to check start time and refund deadline
+            let session = sessions::find_by_id(pool, booking.session_id)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?
+                .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+
+            // Check if user has an active subscription
+            let is_subscriber = subscriptions::has_active_subscription(pool, user_id)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+
+            let utc_offset = club_utc_offset(pool).await?;
+            let now = Utc::now();
+            if now >= session_start(&session, utc_offset) {
+                return Err(AppError::BadRequest(ErrorCode::SessionInPast, "Session has already started".to_string()));
+            }
+
+            (None, now <= cancellation_deadline(&session, is_subscriber, utc_offset), cancellation_reasons::USER)
+        }
+        CancelledBy::Admin(admin_id) => (Some(admin_id), true, cancellation_reasons::ADMIN),
+    };
+
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    // Re-check under lock so concurrent cancels can't both succeed
+    let booking = bookings::find_by_id_for_update(&mut tx, booking_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::BookingNotFound, "Booking not found".to_string()))?;
     if booking.cancelled_at.is_some() {
-        return Err(AppError::BadRequest("Booking already cancelled".to_string()));
+        return Err(AppError::BadRequest(ErrorCode::BookingAlreadyCancelled, "Booking already cancelled".to_string()));
+    }
+
+    let cancelled_booking = cancel_in_tx(&mut tx, &booking, admin_id, refund_eligible, reason).await?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    crate::metrics::booking_cancelled(reason);
+
+    // Note: Stripe refund is handled in the API layer (routes/bookings.rs)
+    // after this function returns successfully
+
+    Ok(CancelOutcome {
+        booking: cancelled_booking,
+        refund_eligible,
+        ticket_restored: refund_eligible && booking.tickets_used > 0,
+    })
+}
+
+/// Cancel all of a user's pending (unpaid) bookings in one transaction
+///
+/// Confirmed bookings are never touched. As in `cancel_booking`, bookings can
+/// be cancelled until their session starts, but used tickets only come back
+/// before the deadline; bookings for started sessions are skipped and
+/// reported back instead of failing the whole batch.
+/// All bookings, then all their sessions, are locked up front in id order
+/// (see `crate::locking`).
+pub async fn cancel_pending_bookings(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<BulkCancelResult, AppError> {
+    let pending = bookings::list_user_pending_bookings(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut result = BulkCancelResult::default();
+    if pending.is_empty() {
+        return Ok(result);
+    }
+
+    let is_subscriber = subscriptions::has_active_subscription(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let utc_offset = club_utc_offset(pool).await?;
+
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    // Re-read under lock: some may have been paid or cancelled meanwhile
+    let mut locked = lock_bookings(&mut tx, pending.iter().map(|b| b.id)).await?;
+    let still_pending: Vec<Booking> = pending
+        .iter()
+        .filter_map(|candidate| locked.remove(&candidate.id))
+        .filter(|b| b.payment_status == "pending" && b.cancelled_at.is_none())
+        .collect();
+
+    let locked_sessions = lock_sessions(&mut tx, still_pending.iter().map(|b| b.session_id)).await?;
+
+    let now = Utc::now();
+    for booking in still_pending {
+        let session = locked_sessions
+            .get(&booking.session_id)
+            .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+
+        let preview = cancel_preview(&booking, session, is_subscriber, utc_offset, now);
+        if !preview.can_cancel {
+            result.skipped.push((booking.booking_code, "Session has already started".to_string()));
+            continue;
+        }
+
+        cancel_in_tx(&mut tx, &booking, None, preview.refund_eligible, cancellation_reasons::USER).await?;
+        result.cancelled.push(booking.booking_code);
+    }
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    for _ in &result.cancelled {
+        crate::metrics::booking_cancelled(cancellation_reasons::USER);
+    }
+
+    Ok(result)
+}
+
+/// Release an unpaid booking whose payment deadline passed before `cutoff`
+///
+/// Re-checks under lock that the booking is still pending, so a payment
+/// confirmed while the release job was running is never cancelled. Returns
+/// None if the booking was paid, cancelled or given more time meanwhile.
+pub async fn release_unpaid_booking(
+    pool: &PgPool,
+    booking_id: Uuid,
+    cutoff: DateTime<Utc>,
+) -> Result<Option<Booking>, AppError> {
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    let booking = bookings::find_by_id_for_update(&mut tx, booking_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(booking) = booking.filter(|b| {
+        b.payment_status == "pending"
+            && b.cancelled_at.is_none()
+            && b.payment_deadline.is_some_and(|deadline| deadline < cutoff)
+    }) else {
+        return Ok(None);
+    };
+
+    let released = cancel_in_tx(&mut tx, &booking, None, true, cancellation_reasons::PAYMENT_EXPIRED).await?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    crate::metrics::booking_cancelled(cancellation_reasons::PAYMENT_EXPIRED);
+
+    Ok(Some(released))
+}
+
+/// Record a refund issued outside our API (e.g. from the Stripe dashboard)
+///
+/// Cancels the booking paid by `stripe_payment_id`, returning its slots and
+/// any used ticket, and marks it `refunded`. Returns None if no booking
+/// matches or it was already cancelled (we issued the refund ourselves).
+pub async fn refund_booking_by_payment_id(
+    pool: &PgPool,
+    stripe_payment_id: &str,
+) -> Result<Option<Booking>, AppError> {
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    let booking = bookings::find_by_stripe_payment_id_for_update(&mut tx, stripe_payment_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(booking) = booking.filter(|b| b.cancelled_at.is_none()) else {
+        return Ok(None);
+    };
+
+    cancel_in_tx(&mut tx, &booking, None, true, cancellation_reasons::REFUNDED).await?;
+    let refunded = bookings::set_payment_status_in_tx(&mut tx, booking.id, "refunded")
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    crate::metrics::booking_cancelled(cancellation_reasons::REFUNDED);
+
+    Ok(Some(refunded))
+}
+
+/// Preview what `cancel_booking` would do for the booking owner
+///
+/// Runs the same ownership and timing checks but never mutates anything.
+pub async fn preview_cancellation(
+    pool: &PgPool,
+    booking_id: Uuid,
+    user_id: Uuid,
+) -> Result<CancelPreview, AppError> {
+    let booking = bookings::find_by_id(pool, booking_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::BookingNotFound, "Booking not found".to_string()))?;
+
+    if booking.user_id != user_id {
+        return Err(AppError::Forbidden);
     }
 
-    // Get session to check cancellation deadline
     let session = sessions::find_by_id(pool, booking.session_id)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Session not found".to_string()))?;
+        .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
 
-    // Check if user has an active subscription
     let is_subscriber = subscriptions::has_active_subscription(pool, user_id)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Get cancellation hours based on subscription status
-    let cancellation_hours = if is_subscriber {
+    let utc_offset = club_utc_offset(pool).await?;
+
+    Ok(cancel_preview(&booking, &session, is_subscriber, utc_offset, Utc::now()))
+}
+
+/// Outcome of cancelling `booking` at `now`
+pub fn cancel_preview(
+    booking: &Booking,
+    session: &Session,
+    is_subscriber: bool,
+    utc_offset: FixedOffset,
+    now: DateTime<Utc>,
+) -> CancelPreview {
+    let deadline = cancellation_deadline(session, is_subscriber, utc_offset);
+    let can_cancel = booking.cancelled_at.is_none() && now < session_start(session, utc_offset);
+    let refund_eligible = can_cancel && now <= deadline;
+
+    // Same condition the cancel route uses before issuing a full Stripe refund
+    let refundable = booking.payment_method == "stripe"
+        && booking.payment_status == "confirmed"
+        && booking.stripe_payment_id.is_some();
+
+    CancelPreview {
+        can_cancel,
+        refund_eligible,
+        refund_amount_vnd: if refund_eligible && refundable {
+            booking.price_paid_vnd + booking.guest_price_paid_vnd
+        } else {
+            0
+        },
+        ticket_restored: refund_eligible && booking.tickets_used > 0,
+        deadline,
+    }
+}
+
+/// Hours before the session start that this user must cancel by
+fn cancellation_hours(session: &Session, is_subscriber: bool) -> i32 {
+    if is_subscriber {
         session.subscriber_cancellation_hours
             .unwrap_or(DEFAULT_SUBSCRIBER_CANCELLATION_HOURS)
     } else {
         session.drop_in_cancellation_hours
             .unwrap_or(DEFAULT_DROP_IN_CANCELLATION_HOURS)
-    };
+    }
+}
 
-    // Calculate session start datetime
-    let session_start = NaiveDateTime::new(session.date, session.time)
-        .and_utc();
+/// The club's UTC offset, which session dates and times are local to
+async fn club_utc_offset(pool: &PgPool) -> Result<FixedOffset, AppError> {
+    config::load(pool)
+        .await
+        .map(|settings| settings.session_utc_offset)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
 
-    // Calculate cancellation deadline
-    let cancellation_deadline = session_start - chrono::Duration::hours(cancellation_hours as i64);
+/// When the session starts; cancellation is no longer possible after this
+fn session_start(session: &Session, utc_offset: FixedOffset) -> DateTime<Utc> {
+    session_starts_at(session.date, session.time, utc_offset)
+}
+
+/// Last moment this user may cancel a booking for the session with a refund
+pub fn cancellation_deadline(session: &Session, is_subscriber: bool, utc_offset: FixedOffset) -> DateTime<Utc> {
+    session_start(session, utc_offset) - chrono::Duration::hours(cancellation_hours(session, is_subscriber) as i64)
+}
+
+/// Cancel the booking and return its slots, restoring used tickets if `restore_tickets`
+///
+/// `reason` is one of `cancellation_reasons` and is stored on the booking.
+pub(crate) async fn cancel_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    booking: &Booking,
+    admin_id: Option<Uuid>,
+    restore_tickets: bool,
+    reason: &'static str,
+) -> Result<Booking, AppError> {
+    // Restore tickets used for this booking (one per slot covered), but never twice
+    let already_restored = ticket_transactions::has_restoration_for_booking(tx, booking.id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if restore_tickets && booking.tickets_used > 0 && !already_restored {
+        let subscription = subscriptions::find_by_user_id_for_update(tx, booking.user_id)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        if let Some(subscription) = subscription {
+            for _ in 0..booking.tickets_used {
+                let new_balance = subscriptions::restore_ticket_in_tx(tx, subscription.id)
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Check if cancellation is still allowed
-    let now = Utc::now();
-    if now > cancellation_deadline {
-        let hours_until_session = (session_start - now).num_hours();
-        return Err(AppError::BadRequest(format!(
-            "Cancellation deadline has passed. {} must cancel at least {} hours before the session. Session starts in {} hours.",
-            if is_subscriber { "Subscribers" } else { "Drop-in players" },
-            cancellation_hours,
-            hours_until_session.max(0)
-        )));
-    }
-
-    // Restore ticket if one was used for this booking
-    if booking.tickets_used > 0 {
-        if let Ok(Some(subscription)) = subscriptions::find_by_user_id(pool, user_id).await {
-            // Restore the ticket
-            if let Ok(new_balance) = subscriptions::restore_ticket(pool, subscription.id).await {
                 // Log the ticket restoration transaction
-                let _ = ticket_transactions::create_with_pool(
-                    pool,
-                    user_id,
+                ticket_transactions::create(
+                    tx,
+                    booking.user_id,
                     Some(subscription.id),
-                    Some(booking_id),
+                    Some(booking.id),
                     transaction_types::RESTORED,
                     1, // positive for restoration
                     new_balance,
                     Some("Restored from cancelled booking"),
-                    None,
+                    admin_id,
                 )
-                .await;
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
             }
         }
     }
 
     // Cancel booking
-    let cancelled_booking = bookings::cancel_booking(pool, booking_id)
+    let cancelled_booking = bookings::cancel_booking_in_tx(tx, booking.id, reason)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
     // Return slots to session
     let slots_to_return = 1 + booking.guest_count;
-    sessions::increment_available_slots(pool, booking.session_id, slots_to_return)
+    sessions::increment_available_slots_in_tx(tx, booking.session_id, slots_to_return)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Note: Stripe refund is handled in the API layer (routes/bookings.rs)
-    // after this function returns successfully
-
     Ok(cancelled_booking)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate, NaiveTime, TimeZone};
+
+    fn club_offset() -> FixedOffset {
+        FixedOffset::east_opt(7 * 3600).unwrap()
+    }
+
+    /// Session at 18:00 club time on 2030-01-10 (11:00 UTC), default cancellation hours
+    fn session() -> Session {
+        Session {
+            id: Uuid::nil(),
+            organizer_id: Uuid::nil(),
+            title: "Evening".to_string(),
+            date: NaiveDate::from_ymd_opt(2030, 1, 10).unwrap(),
+            time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            end_time: None,
+            location: "Court".to_string(),
+            location_id: None,
+            courts: 1,
+            max_players_per_court: None,
+            total_slots: 8,
+            available_slots: 4,
+            price_vnd: Some(100_000),
+            price_usd: None,
+            subscriber_early_access_hours: None,
+            drop_in_cancellation_hours: None,
+            subscriber_cancellation_hours: None,
+            qr_code_url: None,
+            max_guests_per_booking: None,
+            min_players: None,
+            min_players_deadline_hours: None,
+            booking_opens_at: None,
+            cancelled: false,
+            cancelled_at: None,
+            cancellation_reason: None,
+            image_url: None,
+            roster_visibility: "public".to_string(),
+            waitlist_enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn stripe_booking() -> Booking {
+        Booking {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            session_id: Uuid::nil(),
+            booking_code: "LOAFY-TEST".to_string(),
+            guest_count: 1,
+            tickets_used: 1,
+            discount_applied: "none".to_string(),
+            price_paid_vnd: 0,
+            price_paid_usd: None,
+            guest_price_paid_vnd: 100_000,
+            guest_price_paid_usd: None,
+            payment_method: "stripe".to_string(),
+            payment_status: "confirmed".to_string(),
+            verification_status: None,
+            payment_screenshot_url: None,
+            stripe_payment_id: Some("pi_123".to_string()),
+            payment_deadline: None,
+            cancelled_at: None,
+            cancellation_reason: None,
+            discount_code_id: None,
+            discount_code: None,
+            rescheduled_from: None,
+            verified_by: None,
+            verified_at: None,
+            received_amount_vnd: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn starts_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2030, 1, 10, 11, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_refund_eligible_before_deadline() {
+        let now = starts_at() - Duration::hours(49);
+        let preview = cancel_preview(&stripe_booking(), &session(), false, club_offset(), now);
+
+        assert!(preview.can_cancel);
+        assert!(preview.refund_eligible);
+        assert_eq!(preview.refund_amount_vnd, 100_000);
+        assert!(preview.ticket_restored);
+        assert_eq!(preview.deadline, starts_at() - Duration::hours(48));
+    }
+
+    #[test]
+    fn test_cancel_without_refund_after_deadline() {
+        let now = starts_at() - Duration::hours(30);
+        let preview = cancel_preview(&stripe_booking(), &session(), false, club_offset(), now);
+
+        assert!(preview.can_cancel);
+        assert!(!preview.refund_eligible);
+        assert_eq!(preview.refund_amount_vnd, 0);
+        assert!(!preview.ticket_restored);
+
+        // Subscribers have a shorter deadline
+        let preview = cancel_preview(&stripe_booking(), &session(), true, club_offset(), now);
+        assert!(preview.refund_eligible);
+        assert_eq!(preview.deadline, starts_at() - Duration::hours(24));
+    }
+
+    #[test]
+    fn test_no_cancel_once_started_or_cancelled() {
+        let preview = cancel_preview(&stripe_booking(), &session(), true, club_offset(), starts_at());
+        assert!(!preview.can_cancel);
+        assert!(!preview.refund_eligible);
+
+        let cancelled = Booking { cancelled_at: Some(Utc::now()), ..stripe_booking() };
+        let preview = cancel_preview(&cancelled, &session(), true, club_offset(), starts_at() - Duration::hours(72));
+        assert!(!preview.can_cancel);
+        assert!(!preview.refund_eligible);
+    }
+
+    #[test]
+    fn test_unpaid_booking_refunds_nothing() {
+        let pending = Booking { payment_status: "pending".to_string(), ..stripe_booking() };
+        let preview = cancel_preview(&pending, &session(), false, club_offset(), starts_at() - Duration::hours(72));
+
+        assert!(preview.refund_eligible);
+        assert_eq!(preview.refund_amount_vnd, 0);
+        assert!(preview.ticket_restored);
+    }
+}
@@ -1,13 +1,17 @@
-use chrono::{Duration, Utc};
+use chrono::{Duration, FixedOffset, Utc};
 use loafy_db::{
-    models::{Booking, transaction_types},
-    queries::{bookings, config, sessions, subscriptions, ticket_transactions},
+    models::{Booking, Session, transaction_types},
+    queries::{admin_notes, booking_invites, bookings, config, sessions, subscriptions, ticket_transactions},
     PgPool,
 };
-use loafy_types::AppError;
+use loafy_types::{enums::OrganizerSelfBooking, AppError, ErrorCode};
 use uuid::Uuid;
 
-use super::utils::generate_booking_code;
+use super::discount::apply_discount_code;
+use super::quota::{check_booking_quota_in_tx, get_booking_limit};
+use super::utils::{
+    booking_failure_reason, check_booking_window, check_guest_limit, generate_booking_code, session_starts_at,
+};
 
 /// Create booking with race condition protection
 /// CRITICAL: Uses SELECT FOR UPDATE to prevent overselling
@@ -16,51 +20,73 @@ use super::utils::generate_booking_code;
 /// - Subscribers with tickets: Use 1 ticket for user slot (user pays 0)
 /// - Subscribers without tickets: Apply out-of-ticket discount (10%)
 /// - Non-subscribers: Pay full price
-/// - Guests ALWAYS pay full price regardless of subscription
+/// - Guests pay full price, unless `use_tickets_for_guests` is set and the
+///   `guest_tickets_enabled` config allows it: then remaining tickets cover
+///   guest slots too, and only uncovered guests pay
+///
+/// `is_admin` and the session's organizer may book before `booking_opens_at`.
+/// An organizer booking their own session is handled per the
+/// `organizer_self_booking` config: charged as above, comped (own slot free,
+/// no ticket used) or rejected.
+///
+/// A `discount_code` comes off the resulting user price (and paying guests',
+/// if the code allows); its usage limits are checked under the code's lock.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_booking_with_lock(
     pool: &PgPool,
     user_id: Uuid,
     session_id: Uuid,
     guest_count: i32,
     payment_method: &str,
+    is_admin: bool,
+    use_tickets_for_guests: bool,
+    discount_code: Option<&str>,
 ) -> Result<Booking, AppError> {
+    // Pricing, payment hold and club timezone settings
+    let settings = config::load(pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let booking_limit = get_booking_limit(pool, user_id).await?;
+
     // Start transaction
     let mut tx = pool.begin().await
-        .map_err(|e| AppError::Database(e))?;
-
-    // Lock session row (CRITICAL: prevents concurrent bookings)
-    let session = sessions::find_by_id_for_update(&mut tx, session_id)
-        .await
-        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            e.to_string(),
-        ))))?
-        .ok_or_else(|| AppError::NotFound("Session not found".to_string()))?;
+        .map_err(AppError::Database)?;
 
-    // Check if user already has an active booking for this session
-    let has_existing = bookings::has_active_booking_for_session(pool, user_id, session_id)
-        .await
-        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            e.to_string(),
-        ))))?;
+    let session = lock_bookable_session(&mut tx, user_id, session_id, settings.session_utc_offset, false).await?;
 
-    if has_existing {
+    // Enforce per-user weekly booking limit
+    if let Err(e) = check_booking_quota_in_tx(&mut tx, user_id, booking_limit).await {
         tx.rollback().await.ok();
-        return Err(AppError::Conflict("You already have a booking for this session".to_string()));
+        return Err(e);
     }
 
-    // Check if session is cancelled
-    if session.cancelled {
+    let is_organizer = session.organizer_id == user_id;
+    if is_organizer && settings.organizer_self_booking == OrganizerSelfBooking::Reject {
         tx.rollback().await.ok();
-        return Err(AppError::BadRequest("Session is cancelled".to_string()));
+        return Err(AppError::BadRequest(
+            ErrorCode::OwnSessionBookingNotAllowed,
+            "Organizers cannot book their own sessions".to_string(),
+        ));
+    }
+
+    // Check the booking window (admins and the organizer may book early)
+    if !is_admin && !is_organizer {
+        if let Err(e) = check_booking_window(session.booking_opens_at, Utc::now()) {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
     }
 
-    // Check if session is in the past
-    let now_date = chrono::Local::now().naive_local().date();
-    if session.date < now_date {
+    // Enforce guest limit (session override or global default)
+    let max_guests = match session.max_guests_per_booking {
+        Some(max) => max,
+        None => config::get_max_guests_per_booking_in_tx(&mut tx)
+            .await
+            .unwrap_or(3),
+    };
+    if let Err(e) = check_guest_limit(guest_count, max_guests) {
         tx.rollback().await.ok();
-        return Err(AppError::BadRequest("Session is in the past".to_string()));
+        return Err(e);
     }
 
     // Calculate required slots (1 for user + guests)
@@ -69,7 +95,7 @@ pub async fn create_booking_with_lock(
     // Check availability
     if session.available_slots < slots_needed {
         tx.rollback().await.ok();
-        return Err(AppError::Conflict(format!(
+        return Err(AppError::Conflict(ErrorCode::SessionFull, format!(
             "Not enough slots available. Need {}, have {}",
             slots_needed, session.available_slots
         )));
@@ -78,73 +104,100 @@ pub async fn create_booking_with_lock(
     // Get base price (from session or global default)
     let base_price_vnd = session.price_vnd.unwrap_or(100000);
 
+    if use_tickets_for_guests && guest_count > 0 && !settings.guest_tickets_enabled {
+        tx.rollback().await.ok();
+        return Err(AppError::BadRequest(
+            ErrorCode::BadRequest,
+            "Tickets cannot be used for guests".to_string(),
+        ));
+    }
+
     // Check subscription status and calculate pricing
     let subscription = subscriptions::get_active_for_booking(&mut tx, user_id)
         .await
-        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
+        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
             e.to_string(),
         ))))?;
 
     // Determine ticket usage, discount, and user price
-    let (tickets_used, discount_applied, user_price_vnd, subscription_id) =
-        if let Some(sub) = subscription {
+    let mut ticket_transaction_ids = Vec::new();
+    let (tickets_used, discount_applied, mut user_price_vnd) =
+        if is_organizer && settings.organizer_self_booking == OrganizerSelfBooking::Comp {
+            // Organizer plays free without spending a ticket
+            (0, "organizer", 0)
+        } else if let Some(sub) = subscription {
             if sub.tickets_remaining > 0 {
-                // Has tickets - use 1 for user's slot
-                let new_balance = subscriptions::deduct_ticket(&mut tx, sub.id)
+                // Has tickets - use 1 for user's slot, plus guest slots if requested
+                let tickets_wanted = if use_tickets_for_guests { slots_needed } else { 1 };
+                let tickets_to_use = tickets_wanted.min(sub.tickets_remaining);
+
+                for _ in 0..tickets_to_use {
+                    let new_balance = subscriptions::deduct_ticket(&mut tx, sub.id)
+                        .await
+                        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
+                            e.to_string(),
+                        ))))?;
+
+                    // Log ticket transaction (booking_id will be updated after insert)
+                    let transaction = ticket_transactions::create(
+                        &mut tx,
+                        user_id,
+                        Some(sub.id),
+                        None, // booking_id set after booking created
+                        transaction_types::USED,
+                        -1,
+                        new_balance,
+                        Some("Used for booking"),
+                        None,
+                    )
                     .await
-                    .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::new(
-                        std::io::ErrorKind::Other,
+                    .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
                         e.to_string(),
                     ))))?;
+                    ticket_transaction_ids.push(transaction.id);
+                }
 
-                // Log ticket transaction (booking_id will be updated after insert)
-                ticket_transactions::create(
-                    &mut tx,
-                    user_id,
-                    Some(sub.id),
-                    None, // booking_id set after booking created
-                    transaction_types::USED,
-                    -1,
-                    new_balance,
-                    Some("Used for booking"),
-                    None,
-                )
-                .await
-                .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    e.to_string(),
-                ))))?;
-
-                (1, "ticket", 0, Some(sub.id)) // User pays 0 VND
+                (tickets_to_use, "ticket", 0) // User pays 0 VND
             } else {
                 // Subscriber but out of tickets - apply discount
-                let discount_percent = config::get_out_of_ticket_discount(&mut tx)
-                    .await
-                    .unwrap_or(10);
+                let discount_percent = settings.out_of_ticket_discount_percent;
                 let discounted_price = base_price_vnd * (100 - discount_percent) / 100;
-                (0, "out_of_ticket", discounted_price, Some(sub.id))
+                (0, "out_of_ticket", discounted_price)
             }
         } else {
             // Not a subscriber - full price
-            (0, "none", base_price_vnd, None)
+            (0, "none", base_price_vnd)
         };
 
-    // Guests ALWAYS pay full price (no subscription benefit)
-    let guest_price_vnd = base_price_vnd * guest_count;
+    // Guests not covered by a ticket pay full price (no subscription discount)
+    let guests_covered = (tickets_used - 1).max(0);
+    let paying_guests = guest_count - guests_covered;
+    let mut guest_price_vnd = base_price_vnd * paying_guests;
+
+    // Apply the discount code, if any
+    let applied_discount = match discount_code {
+        Some(code) => {
+            let applied =
+                apply_discount_code(&mut tx, code, user_id, user_price_vnd, base_price_vnd, paying_guests).await?;
+            user_price_vnd -= applied.user_discount_vnd;
+            guest_price_vnd -= applied.guest_discount_vnd;
+            Some(applied.code)
+        }
+        None => None,
+    };
 
     // Calculate total amount
     let total_amount = user_price_vnd + guest_price_vnd;
 
-    // If total is 0 (fully covered by ticket), auto-confirm the booking
+    // If total is 0 (fully covered by ticket or discount), auto-confirm the booking
     let payment_status = if total_amount == 0 { "confirmed" } else { "pending" };
 
     // Generate unique booking code
     let booking_code = generate_booking_code();
 
-    // Calculate payment deadline (30 minutes from now, only relevant if payment needed)
+    // Calculate payment deadline (only relevant if payment needed)
     let payment_deadline = if total_amount > 0 {
-        Some(Utc::now() + Duration::minutes(30))
+        Some(Utc::now() + Duration::minutes(settings.default_payment_hold_minutes as i64))
     } else {
         None // No deadline needed for free bookings
     };
@@ -156,9 +209,10 @@ pub async fn create_booking_with_lock(
             user_id, session_id, booking_code, guest_count,
             tickets_used, discount_applied,
             price_paid_vnd, guest_price_paid_vnd,
-            payment_method, payment_status, payment_deadline
+            payment_method, payment_status, payment_deadline,
+            discount_code_id, discount_code
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         RETURNING *
         "#
     )
@@ -173,48 +227,207 @@ pub async fn create_booking_with_lock(
     .bind(payment_method)
     .bind(payment_status)
     .bind(payment_deadline)
+    .bind(applied_discount.as_ref().map(|d| d.id))
+    .bind(applied_discount.as_ref().map(|d| d.code.as_str()))
     .fetch_one(&mut *tx)
     .await
-    .map_err(|e| AppError::Database(e))?;
-
-    // Update ticket transaction with booking_id if ticket was used
-    if tickets_used > 0 {
-        if let Some(sub_id) = subscription_id {
-            sqlx::query(
-                r#"
-                UPDATE ticket_transactions
-                SET booking_id = $1
-                WHERE id = (
-                    SELECT id FROM ticket_transactions
-                    WHERE user_id = $2
-                      AND subscription_id = $3
-                      AND transaction_type = 'used'
-                      AND booking_id IS NULL
-                    ORDER BY created_at DESC
-                    LIMIT 1
-                )
-                "#
-            )
-            .bind(booking.id)
-            .bind(user_id)
-            .bind(sub_id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| AppError::Database(e))?;
-        }
+    .map_err(|e| already_booked_or_db_error(e, already_booked_message(false)))?;
+
+    // Update ticket transactions with booking_id if tickets were used
+    if !ticket_transaction_ids.is_empty() {
+        sqlx::query(
+            r#"
+            UPDATE ticket_transactions
+            SET booking_id = $1
+            WHERE id = ANY($2)
+            "#
+        )
+        .bind(booking.id)
+        .bind(&ticket_transaction_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
     }
 
     // Decrement available slots atomically
-    sessions::decrement_available_slots(&mut tx, session_id, slots_needed)
+    let reserved = sessions::decrement_available_slots(&mut tx, session_id, slots_needed)
         .await
-        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
+        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
             e.to_string(),
         ))))?;
+    if !reserved {
+        return Err(AppError::Conflict(ErrorCode::SessionFull, "Not enough slots available".to_string()));
+    }
 
     // Commit transaction
     tx.commit().await
-        .map_err(|e| AppError::Database(e))?;
+        .map_err(AppError::Database)?;
+
+    crate::metrics::booking_created("regular");
 
     Ok(booking)
 }
+
+/// Record a booking attempt rejected by `create_booking_with_lock`
+///
+/// Conflicts (full, already booked, weekly limit) are counted and stored for
+/// the booking funnel; other errors are ignored. Best effort: a failure to
+/// record is logged, never returned.
+pub async fn record_failed_attempt(pool: &PgPool, user_id: Uuid, session_id: Uuid, error: &AppError) {
+    let Some(reason) = booking_failure_reason(error) else {
+        return;
+    };
+
+    crate::metrics::booking_attempt_failed(reason);
+    if let Err(e) = bookings::record_attempt_failure(pool, user_id, session_id, reason).await {
+        tracing::warn!("Failed to record {} booking attempt for session {}: {}", reason, session_id, e);
+    }
+}
+
+/// Create a free, confirmed booking on an admin's behalf
+///
+/// Shares the slot lock, availability and already-playing guards with regular
+/// bookings, but skips quota, booking window and guest limit checks, never
+/// touches the user's subscription tickets, and bypasses payment entirely.
+/// The reason is stored as a staff note on the user for the audit trail.
+pub async fn create_comp_booking(
+    pool: &PgPool,
+    user_id: Uuid,
+    session_id: Uuid,
+    guest_count: i32,
+    admin_id: Uuid,
+    reason: &str,
+) -> Result<Booking, AppError> {
+    let utc_offset = config::load(pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .session_utc_offset;
+
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    let session = lock_bookable_session(&mut tx, user_id, session_id, utc_offset, true).await?;
+
+    let slots_needed = 1 + guest_count;
+    if session.available_slots < slots_needed {
+        tx.rollback().await.ok();
+        return Err(AppError::Conflict(ErrorCode::SessionFull, format!(
+            "Not enough slots available. Need {}, have {}",
+            slots_needed, session.available_slots
+        )));
+    }
+
+    let booking_code = generate_booking_code();
+
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        INSERT INTO bookings (
+            user_id, session_id, booking_code, guest_count,
+            tickets_used, discount_applied,
+            price_paid_vnd, guest_price_paid_vnd,
+            payment_method, payment_status
+        )
+        VALUES ($1, $2, $3, $4, 0, 'comp', 0, 0, 'free', 'confirmed')
+        RETURNING *
+        "#
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .bind(&booking_code)
+    .bind(guest_count)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| already_booked_or_db_error(e, already_booked_message(true)))?;
+
+    let reserved = sessions::decrement_available_slots(&mut tx, session_id, slots_needed)
+        .await
+        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
+            e.to_string(),
+        ))))?;
+    if !reserved {
+        return Err(AppError::Conflict(ErrorCode::SessionFull, "Not enough slots available".to_string()));
+    }
+
+    let note = format!("Comped booking {} for \"{}\" on {}: {}", booking_code, session.title, session.date, reason);
+    admin_notes::create_note_in_tx(&mut tx, user_id, admin_id, &note)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    crate::metrics::booking_created("comp");
+
+    Ok(booking)
+}
+
+/// Lock the session row and run the checks every booking shares
+///
+/// The session must exist, not be cancelled and not have started, and the
+/// user must not already be playing in it, booked or as a redeemed guest.
+/// `on_behalf` words the conflicts for an admin booking someone else in.
+async fn lock_bookable_session(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    session_id: Uuid,
+    utc_offset: FixedOffset,
+    on_behalf: bool,
+) -> Result<Session, AppError> {
+    // Lock session row (CRITICAL: prevents concurrent bookings)
+    let session = sessions::find_by_id_for_update(tx, session_id)
+        .await
+        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
+            e.to_string(),
+        ))))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+
+    let has_existing = bookings::has_active_booking_for_session_in_tx(tx, user_id, session_id)
+        .await
+        .map_err(|e| AppError::Database(sqlx::Error::Io(std::io::Error::other(
+            e.to_string(),
+        ))))?;
+    if has_existing {
+        return Err(AppError::Conflict(ErrorCode::AlreadyBooked, already_booked_message(on_behalf).to_string()));
+    }
+
+    // A redeemed invite already has them playing as someone's guest
+    let is_guest = booking_invites::is_guest_in_session_in_tx(tx, user_id, session_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if is_guest {
+        let message = if on_behalf {
+            "User is already playing in this session as a guest"
+        } else {
+            "You're already playing in this session as a guest"
+        };
+        return Err(AppError::Conflict(ErrorCode::AlreadyBooked, message.to_string()));
+    }
+
+    if session.cancelled {
+        return Err(AppError::BadRequest(ErrorCode::SessionCancelled, "Session is cancelled".to_string()));
+    }
+
+    // Session times are club-local
+    if session_starts_at(session.date, session.time, utc_offset) <= Utc::now() {
+        return Err(AppError::BadRequest(ErrorCode::SessionInPast, "Session is in the past".to_string()));
+    }
+
+    Ok(session)
+}
+
+fn already_booked_message(on_behalf: bool) -> &'static str {
+    if on_behalf {
+        "User already has a booking for this session"
+    } else {
+        "You already have a booking for this session"
+    }
+}
+
+/// Map a unique-index hit on the user's active booking to `AlreadyBooked`
+fn already_booked_or_db_error(e: sqlx::Error, message: &str) -> AppError {
+    if bookings::is_duplicate_active_booking(&e) {
+        AppError::Conflict(ErrorCode::AlreadyBooked, message.to_string())
+    } else {
+        AppError::Database(e)
+    }
+}
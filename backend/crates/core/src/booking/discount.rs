@@ -0,0 +1,85 @@
+use chrono::Utc;
+use loafy_db::{models::DiscountCode, queries::discount_codes};
+use loafy_types::{AppError, ErrorCode};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// A discount code checked and priced for one booking
+#[derive(Debug, Clone)]
+pub(crate) struct AppliedDiscount {
+    pub code: DiscountCode,
+    /// Off the booker's own slot
+    pub user_discount_vnd: i32,
+    /// Off all paying guest slots together
+    pub guest_discount_vnd: i32,
+}
+
+/// Check a discount code against its validity window and usage limits, and
+/// work out what it takes off this booking
+///
+/// Locks the code row, so the use recorded by the caller's booking insert is
+/// counted before any concurrent booking checks the limits. Uses are counted
+/// from non-cancelled bookings.
+pub(crate) async fn apply_discount_code(
+    tx: &mut Transaction<'_, Postgres>,
+    code: &str,
+    user_id: Uuid,
+    user_price_vnd: i32,
+    guest_unit_price_vnd: i32,
+    paying_guests: i32,
+) -> Result<AppliedDiscount, AppError> {
+    let normalized = code.trim().to_uppercase();
+    let discount = discount_codes::find_by_code_for_update(tx, &normalized)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .filter(|d| d.active)
+        .ok_or_else(|| AppError::BadRequest(
+            ErrorCode::DiscountCodeInvalid,
+            format!("Discount code {} is not valid", normalized),
+        ))?;
+
+    let now = Utc::now();
+    if !discount.is_valid_at(now) {
+        let message = if discount.valid_from.is_some_and(|from| now < from) {
+            format!("Discount code {} is not active yet", discount.code)
+        } else {
+            format!("Discount code {} has expired", discount.code)
+        };
+        return Err(AppError::BadRequest(ErrorCode::DiscountCodeExpired, message));
+    }
+
+    let (total_uses, user_uses) = discount_codes::count_uses_in_tx(tx, discount.id, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if discount.max_uses.is_some_and(|max| total_uses >= max as i64) {
+        return Err(AppError::Conflict(
+            ErrorCode::DiscountCodeExhausted,
+            format!("Discount code {} has been fully redeemed", discount.code),
+        ));
+    }
+    if discount.max_uses_per_user.is_some_and(|max| user_uses >= max as i64) {
+        return Err(AppError::Conflict(
+            ErrorCode::DiscountCodeLimitReached,
+            format!("You have already used discount code {}", discount.code),
+        ));
+    }
+
+    let user_discount_vnd = discount.discount_vnd(user_price_vnd);
+    let guest_discount_vnd = if discount.applies_to_guests {
+        discount.discount_vnd(guest_unit_price_vnd) * paying_guests
+    } else {
+        0
+    };
+    if user_discount_vnd + guest_discount_vnd == 0 {
+        return Err(AppError::BadRequest(
+            ErrorCode::DiscountCodeNotApplicable,
+            format!("Discount code {} doesn't reduce the price of this booking", discount.code),
+        ));
+    }
+
+    Ok(AppliedDiscount {
+        code: discount,
+        user_discount_vnd,
+        guest_discount_vnd,
+    })
+}
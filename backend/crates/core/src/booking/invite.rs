@@ -0,0 +1,258 @@
+use chrono::{DateTime, Utc};
+use loafy_db::{
+    models::{Booking, BookingInvite, Session},
+    queries::{booking_invites, bookings, config, sessions},
+    PgPool,
+};
+use loafy_types::{AppError, ErrorCode};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::utils::session_starts_at;
+
+/// Length of generated invite tokens (alphanumeric, ~190 bits)
+const INVITE_TOKEN_LENGTH: usize = 32;
+
+/// A newly created invite; the token is only available here
+#[derive(Debug, Clone)]
+pub struct NewInvite {
+    pub token: String,
+    pub invite: BookingInvite,
+}
+
+/// A redeemed invite with the booking and session the guest joined
+#[derive(Debug, Clone)]
+pub struct RedeemedInvite {
+    pub invite: BookingInvite,
+    pub booking: Booking,
+    pub session: Session,
+}
+
+/// Create a single-use invite for one of the booking's guest slots
+///
+/// Only the booking owner may invite, and only while the session hasn't
+/// started. Each guest slot (capped at the session's guest limit) backs at
+/// most one redeemed or still-redeemable invite. The invite expires when the
+/// session starts. The booking is locked while its invites are counted, so
+/// two requests can't both take the last guest slot.
+pub async fn create_booking_invite(
+    pool: &PgPool,
+    booking_id: Uuid,
+    user_id: Uuid,
+) -> Result<NewInvite, AppError> {
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    let booking = bookings::find_by_id_for_update(&mut tx, booking_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::BookingNotFound, "Booking not found".to_string()))?;
+
+    if booking.user_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+    if booking.cancelled_at.is_some() {
+        return Err(AppError::BadRequest(ErrorCode::BookingAlreadyCancelled, "Booking is cancelled".to_string()));
+    }
+
+    let session = sessions::find_by_id(pool, booking.session_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+    let expires_at = check_session_open(pool, &session).await?;
+
+    let guest_slots = guest_slots(pool, &booking, &session).await?;
+    if guest_slots == 0 {
+        return Err(AppError::BadRequest(
+            ErrorCode::NoGuestSlotsAvailable,
+            "This booking has no guest slots to share".to_string(),
+        ));
+    }
+
+    let claimed = booking_invites::count_claimed_for_booking(&mut tx, booking.id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if claimed >= guest_slots as i64 {
+        return Err(AppError::Conflict(
+            ErrorCode::NoGuestSlotsAvailable,
+            "All guest slots on this booking have already been shared".to_string(),
+        ));
+    }
+
+    let (token, token_hash) = generate_invite_token();
+    let invite = booking_invites::create(&mut tx, booking.id, user_id, &token_hash, expires_at)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    Ok(NewInvite { token, invite })
+}
+
+/// Redeem an invite, joining the booking as a named guest
+///
+/// The invite, the booking, then the session are locked so two friends can't
+/// claim the last guest slot at once, and so the guest's own booking of the
+/// session (which locks the session first) can't slip in alongside. The guest must not already be playing in the
+/// session, either with their own booking or as someone else's guest.
+pub async fn redeem_booking_invite(
+    pool: &PgPool,
+    token: &str,
+    user_id: Uuid,
+) -> Result<RedeemedInvite, AppError> {
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    let invite = booking_invites::find_by_token_hash_for_update(&mut tx, &hash_invite_token(token))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::InviteNotFound, "Invite not found".to_string()))?;
+
+    if invite.redeemed_at.is_some() || invite.expires_at <= Utc::now() {
+        return Err(AppError::BadRequest(
+            ErrorCode::InviteExpired,
+            "This invite has already been used or has expired".to_string(),
+        ));
+    }
+
+    let booking = bookings::find_by_id_for_update(&mut tx, invite.booking_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::BookingNotFound, "Booking not found".to_string()))?;
+
+    if booking.cancelled_at.is_some() {
+        return Err(AppError::BadRequest(
+            ErrorCode::BookingAlreadyCancelled,
+            "The booking for this invite was cancelled".to_string(),
+        ));
+    }
+    if booking.user_id == user_id {
+        return Err(AppError::BadRequest(ErrorCode::BadRequest, "You can't redeem your own invite".to_string()));
+    }
+
+    let session = sessions::find_by_id_for_update(&mut tx, booking.session_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+    check_session_open(pool, &session).await?;
+
+    let has_booking = bookings::has_active_booking_for_session_in_tx(&mut tx, user_id, session.id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let is_guest = booking_invites::is_guest_in_session_in_tx(&mut tx, user_id, session.id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if has_booking || is_guest {
+        return Err(AppError::Conflict(ErrorCode::AlreadyBooked, "You're already playing in this session".to_string()));
+    }
+
+    let guest_slots = guest_slots(pool, &booking, &session).await?;
+    let redeemed = booking_invites::count_redeemed_for_booking(&mut tx, booking.id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if redeemed >= guest_slots as i64 {
+        return Err(AppError::Conflict(
+            ErrorCode::NoGuestSlotsAvailable,
+            "All guest slots on this booking are taken".to_string(),
+        ));
+    }
+
+    let invite = booking_invites::mark_redeemed(&mut tx, invite.id, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    Ok(RedeemedInvite { invite, booking, session })
+}
+
+/// Reject cancelled or started sessions, returning when the session starts
+async fn check_session_open(pool: &PgPool, session: &Session) -> Result<DateTime<Utc>, AppError> {
+    if session.cancelled {
+        return Err(AppError::BadRequest(ErrorCode::SessionCancelled, "Session is cancelled".to_string()));
+    }
+
+    let settings = config::load(pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let starts_at = session_starts_at(session.date, session.time, settings.session_utc_offset);
+    if starts_at <= Utc::now() {
+        return Err(AppError::BadRequest(ErrorCode::SessionInPast, "Session has already started".to_string()));
+    }
+
+    Ok(starts_at)
+}
+
+/// Guest slots the booking may share, per the session's guest limit
+async fn guest_slots(pool: &PgPool, booking: &Booking, session: &Session) -> Result<i32, AppError> {
+    let max_guests = match session.max_guests_per_booking {
+        Some(max) => max,
+        None => config::get_max_guests_per_booking(pool)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?,
+    };
+
+    Ok(shareable_guest_slots(booking.guest_count, max_guests))
+}
+
+/// A booking's guests, capped at the guest limit (never negative)
+fn shareable_guest_slots(guest_count: i32, max_guests: i32) -> i32 {
+    guest_count.min(max_guests).max(0)
+}
+
+/// Generate an invite token, returning `(token, hash)`
+///
+/// Only the hash is stored; the token is shown to the booker once, to share.
+fn generate_invite_token() -> (String, String) {
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(INVITE_TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+    let hash = hash_invite_token(&token);
+    (token, hash)
+}
+
+/// SHA-256 of an invite token, hex encoded, as stored on the invite
+fn hash_invite_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invite_token_hash() {
+        let (token, hash) = generate_invite_token();
+        assert_eq!(token.len(), INVITE_TOKEN_LENGTH);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        // Stored hash is the hex SHA-256 of the token, stable across calls
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_invite_token(&token));
+        assert_ne!(hash, token);
+        assert_eq!(
+            hash_invite_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let (other_token, other_hash) = generate_invite_token();
+        assert_ne!(token, other_token);
+        assert_ne!(hash, other_hash);
+    }
+
+    #[test]
+    fn test_shareable_guest_slots() {
+        assert_eq!(shareable_guest_slots(0, 3), 0);
+        assert_eq!(shareable_guest_slots(2, 3), 2);
+        assert_eq!(shareable_guest_slots(3, 3), 3);
+        // Guests above a since-lowered limit can't be shared
+        assert_eq!(shareable_guest_slots(5, 3), 3);
+        assert_eq!(shareable_guest_slots(2, 0), 0);
+        assert_eq!(shareable_guest_slots(-1, 3), 0);
+    }
+}
@@ -1,7 +1,24 @@
+pub mod calendar;
 pub mod create;
+pub mod discount;
 pub mod cancel;
+pub mod invite;
+pub mod quota;
+pub mod reschedule;
+pub mod stats;
 pub mod utils;
 
-pub use create::create_booking_with_lock;
-pub use cancel::cancel_booking;
-pub use utils::generate_booking_code;
+pub use calendar::{booking_ics, bookings_feed_ics, generate_feed_token, hash_feed_token};
+pub use create::{create_booking_with_lock, create_comp_booking, record_failed_attempt};
+pub use cancel::{
+    cancel_booking, cancel_pending_bookings, cancellation_deadline, preview_cancellation,
+    refund_booking_by_payment_id, release_unpaid_booking, BulkCancelResult, CancelOutcome, CancelPreview, CancelledBy,
+};
+pub use invite::{create_booking_invite, redeem_booking_invite, NewInvite, RedeemedInvite};
+pub use quota::{check_booking_quota_in_tx, get_booking_limit, get_booking_quota, BookingQuota};
+pub use reschedule::reschedule_booking;
+pub use stats::{get_user_stats, UserStats};
+pub use utils::{
+    booking_failure_reason, check_booking_window, check_guest_limit, club_now, club_today, generate_booking_code,
+    session_starts_at,
+};
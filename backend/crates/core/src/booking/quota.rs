@@ -0,0 +1,86 @@
+use chrono::{DateTime, Duration, Utc};
+use loafy_db::{
+    queries::{bookings, config, subscriptions},
+    PgPool,
+};
+use loafy_types::{AppError, ErrorCode};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Length of the rolling booking window in days
+pub const BOOKING_WINDOW_DAYS: i64 = 7;
+
+/// A user's booking usage within the current rolling window
+#[derive(Debug, Clone)]
+pub struct BookingQuota {
+    /// Active bookings made within the window
+    pub used: i64,
+    /// Maximum allowed in the window (None = unlimited)
+    pub limit: Option<i32>,
+    /// Start of the rolling window
+    pub window_start: DateTime<Utc>,
+}
+
+impl BookingQuota {
+    /// Whether the user may make another booking
+    pub fn has_room(&self) -> bool {
+        match self.limit {
+            Some(limit) => self.used < limit as i64,
+            None => true,
+        }
+    }
+}
+
+/// The user's weekly booking limit (None = unlimited)
+pub async fn get_booking_limit(pool: &PgPool, user_id: Uuid) -> Result<Option<i32>, AppError> {
+    let is_subscriber = subscriptions::has_active_subscription(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let limit = config::get_max_bookings_per_week(pool, is_subscriber)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok((limit > 0).then_some(limit))
+}
+
+/// Get the user's current booking count and limit for the rolling week
+pub async fn get_booking_quota(pool: &PgPool, user_id: Uuid) -> Result<BookingQuota, AppError> {
+    let limit = get_booking_limit(pool, user_id).await?;
+
+    let window_start = Utc::now() - Duration::days(BOOKING_WINDOW_DAYS);
+    let used = bookings::count_active_bookings_in_window(pool, user_id, window_start)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(BookingQuota {
+        used,
+        limit,
+        window_start,
+    })
+}
+
+/// Return a Conflict error if the user has reached their weekly booking limit
+///
+/// Counts within the booking transaction under a per-user lock, so concurrent
+/// bookings by the same user can't both pass on the same count.
+pub async fn check_booking_quota_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    limit: Option<i32>,
+) -> Result<(), AppError> {
+    let window_start = Utc::now() - Duration::days(BOOKING_WINDOW_DAYS);
+    let used = bookings::count_active_bookings_in_window_locked(tx, user_id, window_start)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let quota = BookingQuota { used, limit, window_start };
+    if !quota.has_room() {
+        return Err(AppError::Conflict(ErrorCode::BookingLimitReached, format!(
+            "Booking limit reached. You can have at most {} active bookings made within {} days",
+            quota.limit.unwrap_or_default(),
+            BOOKING_WINDOW_DAYS
+        )));
+    }
+    Ok(())
+}
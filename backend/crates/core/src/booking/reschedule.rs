@@ -0,0 +1,146 @@
+use loafy_db::{
+    models::Booking,
+    queries::{bookings, config, sessions, subscriptions},
+    PgPool,
+};
+use loafy_types::{AppError, ErrorCode};
+use uuid::Uuid;
+
+use crate::locking::lock_sessions;
+
+use super::cancel::cancellation_deadline;
+use super::utils::{calculate_total_slots, check_booking_window, check_guest_limit, session_starts_at};
+
+/// Move a booking to another session, keeping its ticket and payment intact
+///
+/// Both session rows are locked with SELECT FOR UPDATE in id order (see
+/// `crate::locking`) so that two concurrent reschedules between the same
+/// sessions can't deadlock.
+///
+/// Moving out of a session is held to the same cancellation deadline as
+/// cancelling, so it can't be used to dodge the refund policy or to free a
+/// slot in a session that has already started.
+pub async fn reschedule_booking(
+    pool: &PgPool,
+    booking_id: Uuid,
+    user_id: Uuid,
+    target_session_id: Uuid,
+) -> Result<Booking, AppError> {
+    let utc_offset = config::load(pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .session_utc_offset;
+    let is_subscriber = subscriptions::has_active_subscription(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut tx = pool.begin().await
+        .map_err(AppError::Database)?;
+
+    // Lock booking row
+    let booking = bookings::find_by_id_for_update(&mut tx, booking_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::BookingNotFound, "Booking not found".to_string()))?;
+
+    // Check ownership
+    if booking.user_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    if booking.cancelled_at.is_some() {
+        return Err(AppError::BadRequest(ErrorCode::BookingAlreadyCancelled, "Booking is cancelled".to_string()));
+    }
+
+    if booking.session_id == target_session_id {
+        return Err(AppError::BadRequest(ErrorCode::BadRequest, "Booking is already for this session".to_string()));
+    }
+
+    // Lock both sessions in a consistent (id) order
+    let mut locked = lock_sessions(&mut tx, [booking.session_id, target_session_id]).await?;
+
+    let source = locked.remove(&booking.session_id).ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+    let target = locked.remove(&target_session_id).ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Target session not found".to_string()))?;
+
+    let now = chrono::Utc::now();
+
+    // Validate source session
+    if session_starts_at(source.date, source.time, utc_offset) <= now {
+        return Err(AppError::BadRequest(ErrorCode::SessionInPast, "Session has already started".to_string()));
+    }
+    if now > cancellation_deadline(&source, is_subscriber, utc_offset) {
+        return Err(AppError::BadRequest(
+            ErrorCode::CancellationDeadlinePassed,
+            "The cancellation deadline for this session has passed".to_string(),
+        ));
+    }
+
+    // Validate target session
+    if target.cancelled {
+        return Err(AppError::BadRequest(ErrorCode::SessionCancelled, "Target session is cancelled".to_string()));
+    }
+
+    if session_starts_at(target.date, target.time, utc_offset) <= now {
+        return Err(AppError::BadRequest(ErrorCode::SessionInPast, "Target session is in the past".to_string()));
+    }
+
+    if target.organizer_id != user_id {
+        check_booking_window(target.booking_opens_at, now)?;
+    }
+
+    let has_existing = bookings::has_active_booking_for_session_in_tx(&mut tx, user_id, target.id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if has_existing {
+        return Err(AppError::Conflict(ErrorCode::AlreadyBooked, "You already have a booking for the target session".to_string()));
+    }
+
+    let max_guests = match target.max_guests_per_booking {
+        Some(max) => max,
+        None => config::get_max_guests_per_booking_in_tx(&mut tx)
+            .await
+            .unwrap_or(3),
+    };
+    check_guest_limit(booking.guest_count, max_guests)?;
+
+    let slots = calculate_total_slots(booking.guest_count);
+    if target.available_slots < slots {
+        return Err(AppError::Conflict(ErrorCode::SessionFull, format!(
+            "Not enough slots available. Need {}, have {}",
+            slots, target.available_slots
+        )));
+    }
+
+    // Move slots from the old session to the new one
+    sessions::increment_available_slots_in_tx(&mut tx, source.id, slots)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let reserved = sessions::decrement_available_slots(&mut tx, target.id, slots)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if !reserved {
+        return Err(AppError::Conflict(ErrorCode::SessionFull, "Not enough slots available in the target session".to_string()));
+    }
+
+    let rescheduled = bookings::move_to_session(&mut tx, booking.id, target.id)
+        .await
+        .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+            Some(db) if bookings::is_duplicate_active_booking(db) => AppError::Conflict(
+                ErrorCode::AlreadyBooked,
+                "You already have a booking for the target session".to_string(),
+            ),
+            _ => AppError::Internal(e.to_string()),
+        })?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    tracing::info!(
+        "Rescheduled booking {} from session {} to {}",
+        rescheduled.booking_code,
+        source.id,
+        target.id
+    );
+
+    Ok(rescheduled)
+}
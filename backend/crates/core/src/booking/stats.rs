@@ -0,0 +1,113 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use loafy_db::{
+    queries::{bookings, config, ticket_transactions},
+    PgPool,
+};
+use loafy_types::AppError;
+use uuid::Uuid;
+
+use super::utils::club_today;
+
+/// A user's lifetime activity
+#[derive(Debug, Clone)]
+pub struct UserStats {
+    pub confirmed_bookings: i64,
+    pub sessions_attended: i64,
+    pub total_spent_vnd: i64,
+    pub tickets_used: i64,
+    /// Consecutive weeks, up to the current one, with an attended session
+    pub current_streak_weeks: i32,
+}
+
+/// Get the user's activity stats
+///
+/// A session counts as attended once the user has been checked in for it.
+/// The streak's current week is the club's, not the server's.
+pub async fn get_user_stats(pool: &PgPool, user_id: Uuid) -> Result<UserStats, AppError> {
+    let utc_offset = config::load(pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .session_utc_offset;
+
+    let totals = bookings::get_user_booking_totals(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let tickets_used = ticket_transactions::count_tickets_used(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let attended_weeks = bookings::list_attended_weeks(pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(UserStats {
+        confirmed_bookings: totals.confirmed_bookings,
+        sessions_attended: totals.sessions_attended,
+        total_spent_vnd: totals.total_spent_vnd,
+        tickets_used,
+        current_streak_weeks: week_streak(&attended_weeks, club_today(utc_offset)),
+    })
+}
+
+/// Count consecutive attended weeks ending this week (or last week, so a
+/// streak isn't lost before this week's session has been played)
+///
+/// `weeks` are Monday week starts, newest first.
+fn week_streak(weeks: &[NaiveDate], today: NaiveDate) -> i32 {
+    let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+
+    let mut expected = match weeks.first() {
+        Some(&latest) if latest == this_week || latest == this_week - Duration::weeks(1) => latest,
+        _ => return 0,
+    };
+
+    let mut streak = 0;
+    for &week in weeks {
+        if week != expected {
+            break;
+        }
+        streak += 1;
+        expected = week - Duration::weeks(1);
+    }
+    streak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone, Utc};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_week_streak_counts_consecutive_weeks() {
+        // Mondays, newest first
+        let weeks = [date(2026, 10, 12), date(2026, 10, 5), date(2026, 9, 28), date(2026, 9, 14)];
+        assert_eq!(week_streak(&weeks, date(2026, 10, 15)), 3);
+        assert_eq!(week_streak(&[], date(2026, 10, 15)), 0);
+    }
+
+    #[test]
+    fn test_week_streak_week_boundaries() {
+        let weeks = [date(2026, 10, 5), date(2026, 9, 28)];
+        // Last week still counts until this week is over...
+        assert_eq!(week_streak(&weeks, date(2026, 10, 12)), 2);
+        assert_eq!(week_streak(&weeks, date(2026, 10, 18)), 2);
+        // ...but not once another Monday starts
+        assert_eq!(week_streak(&weeks, date(2026, 10, 19)), 0);
+    }
+
+    #[test]
+    fn test_week_streak_uses_club_date() {
+        let weeks = [date(2026, 10, 12), date(2026, 10, 5)];
+        // Sunday 20:00 UTC is already the next Monday at a UTC+7 club
+        let now = Utc.with_ymd_and_hms(2026, 10, 25, 20, 0, 0).unwrap();
+        let club = FixedOffset::east_opt(7 * 3600).unwrap();
+
+        assert_eq!(week_streak(&weeks, now.date_naive()), 2);
+        assert_eq!(week_streak(&weeks, now.with_timezone(&club).date_naive()), 0);
+    }
+}
@@ -1,3 +1,6 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use loafy_db::models::booking_failure_reasons;
+use loafy_types::{AppError, ErrorCode};
 use rand::{distributions::Alphanumeric, Rng};
 
 /// Calculate the total number of slots needed for a booking.
@@ -14,6 +17,73 @@ pub fn calculate_total_slots(guest_count: i32) -> i32 {
     1 + guest_count
 }
 
+/// Check that a booking's guest count is within the session's guest limit.
+///
+/// # Arguments
+/// * `guest_count` - Number of guests the user is bringing
+/// * `max_guests` - Maximum guests allowed per booking for the session
+pub fn check_guest_limit(guest_count: i32, max_guests: i32) -> Result<(), AppError> {
+    if guest_count < 0 {
+        return Err(AppError::BadRequest(ErrorCode::InvalidGuestCount, "Guest count cannot be negative".to_string()));
+    }
+    if guest_count > max_guests {
+        return Err(AppError::BadRequest(ErrorCode::TooManyGuests, format!(
+            "Too many guests. This session allows at most {} guest(s) per booking",
+            max_guests
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a session's booking window has opened.
+///
+/// # Arguments
+/// * `booking_opens_at` - When bookings open for the session (None = always open)
+/// * `now` - Current time
+pub fn check_booking_window(
+    booking_opens_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    match booking_opens_at {
+        Some(opens_at) if now < opens_at => Err(AppError::BadRequest(
+            ErrorCode::BookingNotOpen,
+            format!("Booking opens at {}", opens_at.format("%Y-%m-%d %H:%M UTC")),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// When a session starts.
+///
+/// Session dates and times are wall-clock times at the club, so they are read
+/// in the club's `session_utc_offset` rather than the server's timezone.
+pub fn session_starts_at(date: NaiveDate, time: NaiveTime, utc_offset: FixedOffset) -> DateTime<Utc> {
+    (NaiveDateTime::new(date, time) - utc_offset).and_utc()
+}
+
+/// Today's date at the club.
+pub fn club_today(utc_offset: FixedOffset) -> NaiveDate {
+    Utc::now().with_timezone(&utc_offset).date_naive()
+}
+
+/// The club's current wall-clock time, comparable with a session's `date + time`.
+pub fn club_now(utc_offset: FixedOffset) -> NaiveDateTime {
+    Utc::now().with_timezone(&utc_offset).naive_local()
+}
+
+/// Funnel reason for a rejected booking attempt.
+///
+/// Only conflicts are tracked; validation errors (bad guest count, window not
+/// open, ...) return None.
+pub fn booking_failure_reason(error: &AppError) -> Option<&'static str> {
+    match error {
+        AppError::Conflict(ErrorCode::SessionFull, _) => Some(booking_failure_reasons::FULL),
+        AppError::Conflict(ErrorCode::AlreadyBooked, _) => Some(booking_failure_reasons::ALREADY_BOOKED),
+        AppError::Conflict(ErrorCode::BookingLimitReached, _) => Some(booking_failure_reasons::LIMIT_REACHED),
+        _ => None,
+    }
+}
+
 /// Generate unique booking code (LB-XXXXX)
 pub fn generate_booking_code() -> String {
     let suffix: String = rand::thread_rng()
@@ -24,3 +94,33 @@ pub fn generate_booking_code() -> String {
 
     format!("LB-{}", suffix.to_uppercase())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_starts_at_reads_club_offset() {
+        let ict = FixedOffset::east_opt(7 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+
+        // A 06:00 session in Ho Chi Minh City starts the previous evening in UTC
+        let starts_at = session_starts_at(date, NaiveTime::from_hms_opt(6, 0, 0).unwrap(), ict);
+        assert_eq!(starts_at.to_rfc3339(), "2026-03-09T23:00:00+00:00");
+
+        let starts_at = session_starts_at(date, NaiveTime::from_hms_opt(20, 30, 0).unwrap(), ict);
+        assert_eq!(starts_at.to_rfc3339(), "2026-03-10T13:30:00+00:00");
+    }
+
+    #[test]
+    fn test_booking_failure_reason_only_tracks_conflicts() {
+        let full = AppError::Conflict(ErrorCode::SessionFull, "full".to_string());
+        assert_eq!(booking_failure_reason(&full), Some("full"));
+
+        let booked = AppError::Conflict(ErrorCode::AlreadyBooked, "booked".to_string());
+        assert_eq!(booking_failure_reason(&booked), Some("already_booked"));
+
+        let cancelled = AppError::BadRequest(ErrorCode::SessionCancelled, "cancelled".to_string());
+        assert_eq!(booking_failure_reason(&cancelled), None);
+    }
+}
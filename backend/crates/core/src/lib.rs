@@ -1,3 +1,8 @@
 pub mod booking;
+pub mod locking;
+pub mod metrics;
+pub mod session;
+pub mod shutdown;
+pub mod waitlist;
 
 pub use booking::*;
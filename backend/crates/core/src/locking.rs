@@ -0,0 +1,69 @@
+//! Row lock ordering
+//!
+//! Invariant: a transaction that locks several rows of one table takes the
+//! locks in ascending id order. Two transactions needing overlapping rows then
+//! queue on the first row they share instead of each holding a row the other
+//! is waiting for, which is how Postgres deadlocks arise.
+//!
+//! Across tables, bookings are locked before the sessions they belong to
+//! (cancel, reschedule, admin booking edits, cancelling a whole session).
+//!
+//! Updating a row takes its lock implicitly, so flows that touch several
+//! sessions (e.g. returning slots for many bookings) lock them here up front.
+
+use std::collections::HashMap;
+
+use loafy_db::{
+    models::{Booking, Session},
+    queries::{bookings, sessions},
+};
+use loafy_types::AppError;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Sorted, de-duplicated ids: the order their rows must be locked in
+pub fn lock_order(ids: impl IntoIterator<Item = Uuid>) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = ids.into_iter().collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Lock sessions FOR UPDATE in id order; missing ids are left out of the map
+pub async fn lock_sessions(
+    tx: &mut Transaction<'_, Postgres>,
+    ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, Session>, AppError> {
+    let locked = sessions::find_by_ids_for_update(tx, &lock_order(ids))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(locked.into_iter().map(|s| (s.id, s)).collect())
+}
+
+/// Lock bookings FOR UPDATE in id order; missing ids are left out of the map
+pub async fn lock_bookings(
+    tx: &mut Transaction<'_, Postgres>,
+    ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, Booking>, AppError> {
+    let locked = bookings::find_by_ids_for_update(tx, &lock_order(ids))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(locked.into_iter().map(|b| (b.id, b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_order_sorts_and_dedups() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let c = Uuid::from_u128(3);
+
+        assert_eq!(lock_order([c, a, b, a]), vec![a, b, c]);
+        assert!(lock_order([]).is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+//! Business event counters
+//!
+//! Recorded through the `metrics` facade, so they're exported wherever a
+//! recorder is installed (the API's `/metrics`) and are no-ops elsewhere.
+
+/// A booking was created; `kind` is "regular" or "comp"
+pub fn booking_created(kind: &'static str) {
+    metrics::counter!("loafy_bookings_created_total", "kind" => kind).increment(1);
+}
+
+/// A booking attempt was rejected; `reason` is one of `booking_failure_reasons`
+pub fn booking_attempt_failed(reason: &'static str) {
+    metrics::counter!("loafy_booking_attempts_failed_total", "reason" => reason).increment(1);
+}
+
+/// A booking was cancelled; `reason` is one of `cancellation_reasons`
+pub fn booking_cancelled(reason: &'static str) {
+    metrics::counter!("loafy_bookings_cancelled_total", "reason" => reason).increment(1);
+}
+
+/// A booking's payment was confirmed; `method` is "stripe" or "bank_transfer"
+pub fn payment_confirmed(method: &'static str) {
+    metrics::counter!("loafy_payments_confirmed_total", "method" => method).increment(1);
+}
+
+/// Tickets were added to a balance; `source` is e.g. "subscription", "renewal", "gift" or "admin"
+pub fn tickets_granted(source: &'static str, amount: i32) {
+    if amount > 0 {
+        metrics::counter!("loafy_tickets_granted_total", "source" => source).increment(amount as u64);
+    }
+}
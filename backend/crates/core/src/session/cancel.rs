@@ -0,0 +1,172 @@
+use loafy_db::{
+    models::{Booking, Session, cancellation_reasons},
+    queries::{bookings, sessions},
+    PgPool,
+};
+use loafy_types::{AppError, ErrorCode};
+use uuid::Uuid;
+
+use crate::booking::cancel::cancel_in_tx;
+
+/// How often to retry when bookings keep arriving while the session is being locked
+const MAX_LOCK_ATTEMPTS: usize = 3;
+
+/// A cancelled session together with the bookings that were cancelled with it
+#[derive(Debug)]
+pub struct CancelledSession {
+    pub session: Session,
+    /// Bookings as they were *before* cancellation (payment status intact),
+    /// so callers can decide on refunds and notify the participants
+    pub bookings: Vec<Booking>,
+}
+
+/// Cancel a session and all its active bookings in one transaction
+///
+/// Tickets used for the bookings are restored. Stripe refunds and participant
+/// notifications are left to the caller.
+///
+/// The bookings are locked before the session, as in every other flow (see
+/// `crate::locking`). A booking made between the two locks would escape
+/// cancellation, so the attempt is rolled back and retried when one appears.
+pub async fn cancel_session(
+    pool: &PgPool,
+    session_id: Uuid,
+    reason: &str,
+) -> Result<CancelledSession, AppError> {
+    for _ in 0..MAX_LOCK_ATTEMPTS {
+        let mut tx = pool.begin().await
+            .map_err(AppError::Database)?;
+
+        let active_bookings = bookings::list_active_for_session_for_update(&mut tx, session_id)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        // Holding the session row keeps new bookings out while cancelling
+        let session = sessions::find_by_id_for_update(&mut tx, session_id)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(ErrorCode::SessionNotFound, "Session not found".to_string()))?;
+
+        if session.cancelled {
+            return Err(AppError::BadRequest(ErrorCode::SessionCancelled, "Session is already cancelled".to_string()));
+        }
+
+        let active_now = bookings::count_active_for_session_in_tx(&mut tx, session_id)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        if active_now != active_bookings.len() as i64 {
+            continue;
+        }
+
+        return cancel_locked(tx, session_id, reason, active_bookings).await;
+    }
+
+    Err(AppError::Conflict(
+        ErrorCode::Conflict,
+        "Session is receiving bookings, please try again".to_string(),
+    ))
+}
+
+/// Cancel a session whose row and active bookings are locked by `tx`
+async fn cancel_locked(
+    mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+    session_id: Uuid,
+    reason: &str,
+    active_bookings: Vec<Booking>,
+) -> Result<CancelledSession, AppError> {
+    for booking in &active_bookings {
+        cancel_in_tx(&mut tx, booking, None, true, cancellation_reasons::SESSION_CANCELLED).await?;
+    }
+
+    let session = sessions::cancel_session_in_tx(&mut tx, session_id, reason)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tx.commit().await
+        .map_err(AppError::Database)?;
+
+    for _ in &active_bookings {
+        crate::metrics::booking_cancelled(cancellation_reasons::SESSION_CANCELLED);
+    }
+
+    Ok(CancelledSession {
+        session,
+        bookings: active_bookings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Runs against `DATABASE_URL` when set. Two connections only contend on
+    /// committed rows, so the fixtures are committed and deleted at the end.
+    ///
+    /// Holds a booking lock as `cancel_booking` does, lets `cancel_session`
+    /// queue behind it, then takes the session lock. Had `cancel_session`
+    /// locked the session first, Postgres would abort one side as a deadlock.
+    #[tokio::test]
+    async fn test_cancel_session_locks_bookings_before_session() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+
+        let (user_id,): (Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO users (email, role_id, auth_provider, auth_provider_id)
+            SELECT 'cancel-lock-order-test@example.com', id, 'email', 'cancel-lock-order-test' FROM roles WHERE name = 'user'
+            RETURNING id
+            "#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let (session_id,): (Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO sessions (organizer_id, title, date, time, location, total_slots, available_slots)
+            VALUES ($1, 'Lock order test', CURRENT_DATE + 7, '18:00', 'Test court', 6, 5)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let (booking_id,): (Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO bookings (user_id, session_id, payment_method, payment_status, price_paid_vnd, booking_code)
+            VALUES ($1, $2, 'cash', 'confirmed', 0, 'LOCKORDERTEST')
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let mut canceller = pool.begin().await.unwrap();
+        bookings::find_by_id_for_update(&mut canceller, booking_id).await.unwrap();
+
+        let cancel = tokio::spawn({
+            let pool = pool.clone();
+            async move { cancel_session(&pool, session_id, "Lock order test").await }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let session_locked = sessions::find_by_id_for_update(&mut canceller, session_id).await;
+        drop(canceller);
+        let cancelled = tokio::time::timeout(Duration::from_secs(5), cancel).await;
+
+        sqlx::query("DELETE FROM bookings WHERE id = $1").bind(booking_id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM sessions WHERE id = $1").bind(session_id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user_id).execute(&pool).await.unwrap();
+
+        assert!(session_locked.is_ok(), "booking canceller deadlocked: {:?}", session_locked.err());
+        let cancelled = cancelled.expect("cancel_session finished").unwrap().unwrap();
+        assert!(cancelled.session.cancelled);
+        assert_eq!(cancelled.bookings.len(), 1);
+    }
+}
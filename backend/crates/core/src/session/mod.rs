@@ -0,0 +1,9 @@
+pub mod cancel;
+pub mod payout;
+pub mod profit_report;
+pub mod roster;
+
+pub use cancel::{cancel_session, CancelledSession};
+pub use payout::{organizer_payout_vnd, platform_fee_vnd};
+pub use profit_report::{render_profit_report, ProfitReport};
+pub use roster::{visible_participants, RosterViewer};
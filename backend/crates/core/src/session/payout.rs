@@ -0,0 +1,40 @@
+/// Platform fee on a session's net revenue, rounded half up to the nearest VND
+///
+/// Sessions that made no profit are not charged a fee.
+pub fn platform_fee_vnd(net_vnd: i64, fee_percent: i32) -> i64 {
+    if net_vnd <= 0 || fee_percent <= 0 {
+        return 0;
+    }
+
+    (net_vnd * fee_percent as i64 + 50) / 100
+}
+
+/// What the organizer receives after the platform fee
+pub fn organizer_payout_vnd(net_vnd: i64, fee_percent: i32) -> i64 {
+    net_vnd - platform_fee_vnd(net_vnd, fee_percent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_fee_rounds_half_up() {
+        assert_eq!(platform_fee_vnd(1_000_000, 10), 100_000);
+        assert_eq!(platform_fee_vnd(15, 10), 2); // 1.5 -> 2
+        assert_eq!(platform_fee_vnd(14, 10), 1); // 1.4 -> 1
+    }
+
+    #[test]
+    fn test_no_fee_without_profit() {
+        assert_eq!(platform_fee_vnd(0, 10), 0);
+        assert_eq!(platform_fee_vnd(-50_000, 10), 0);
+        assert_eq!(organizer_payout_vnd(-50_000, 10), -50_000);
+    }
+
+    #[test]
+    fn test_payout_is_net_minus_fee() {
+        assert_eq!(organizer_payout_vnd(1_000_000, 15), 850_000);
+        assert_eq!(organizer_payout_vnd(1_000_000, 0), 1_000_000);
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use loafy_db::{
+    models::{Session, SessionExpense},
+    queries::admin::SessionProfitSummary,
+};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+use unicode_normalization::UnicodeNormalization;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 6.0;
+
+/// Expense table columns (x offsets in mm)
+const COL_CATEGORY: f32 = MARGIN;
+const COL_DESCRIPTION: f32 = 55.0;
+const COL_BASIS: f32 = 115.0;
+const COL_TOTAL: f32 = 160.0;
+
+/// Everything that goes into a session's profit report
+pub struct ProfitReport<'a> {
+    pub session: &'a Session,
+    pub summary: &'a SessionProfitSummary,
+    pub expenses: &'a [SessionExpense],
+    pub participant_count: i64,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Render a session's profit report as an A4 PDF
+///
+/// Uses the built-in Helvetica fonts, which only cover Windows-1252, so
+/// Vietnamese text is folded to its unaccented form rather than dropped.
+pub fn render_profit_report(report: &ProfitReport) -> Result<Vec<u8>> {
+    let title = format!("Profit report - {}", pdf_text(&report.session.title));
+    let (doc, page, layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+
+    let mut writer = PageWriter {
+        doc: &doc,
+        layer: doc.get_page(page).get_layer(layer),
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    let session = report.session;
+    let summary = report.summary;
+
+    writer.text(&title, 16.0, MARGIN, &bold);
+    writer.skip(1.0);
+    writer.text(
+        &format!(
+            "{} at {} - {}",
+            session.date.format("%Y-%m-%d"),
+            session.time.format("%H:%M"),
+            pdf_text(&session.location)
+        ),
+        10.0,
+        MARGIN,
+        &regular,
+    );
+    if session.cancelled {
+        writer.text("This session was cancelled", 10.0, MARGIN, &bold);
+    }
+    writer.skip(1.0);
+
+    writer.text("Summary", 12.0, MARGIN, &bold);
+    for (label, value) in [
+        ("Participants", report.participant_count.to_string()),
+        ("Courts", session.courts.to_string()),
+        ("Revenue", format_vnd(summary.revenue_vnd)),
+        ("Expenses", format_vnd(summary.expenses_vnd)),
+        ("Net profit", format_vnd(summary.profit_vnd)),
+        ("Margin", format!("{:.1}%", summary.profit_margin_percent)),
+    ] {
+        writer.row(&[(label, COL_CATEGORY), (&value, COL_BASIS)], &regular);
+    }
+    writer.skip(1.0);
+
+    writer.text("Expenses", 12.0, MARGIN, &bold);
+    if report.expenses.is_empty() {
+        writer.text("No expenses recorded", 10.0, MARGIN, &regular);
+    } else {
+        writer.row(
+            &[
+                ("Category", COL_CATEGORY),
+                ("Description", COL_DESCRIPTION),
+                ("Basis", COL_BASIS),
+                ("Total", COL_TOTAL),
+            ],
+            &bold,
+        );
+        for expense in report.expenses {
+            let basis = if expense.cost_type == "per_court" {
+                format!("{} x {} courts", format_vnd(expense.amount_vnd as i64), session.courts)
+            } else {
+                "flat".to_string()
+            };
+            let description = truncate(&pdf_text(expense.description.as_deref().unwrap_or("")), 32);
+            writer.row(
+                &[
+                    (&pdf_text(&expense.category), COL_CATEGORY),
+                    (&description, COL_DESCRIPTION),
+                    (&basis, COL_BASIS),
+                    (&format_vnd(expense.amount_for_courts(session.courts)), COL_TOTAL),
+                ],
+                &regular,
+            );
+        }
+    }
+    writer.skip(1.0);
+
+    writer.text(
+        &format!("Generated {} UTC", report.generated_at.format("%Y-%m-%d %H:%M")),
+        8.0,
+        MARGIN,
+        &regular,
+    );
+
+    Ok(doc.save_to_bytes()?)
+}
+
+/// Writes lines top to bottom, starting a new page when the current one is full
+struct PageWriter<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    y: f32,
+}
+
+impl PageWriter<'_> {
+    fn text(&mut self, text: &str, size: f32, x: f32, font: &IndirectFontRef) {
+        self.ensure_room();
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), font);
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn row(&mut self, cells: &[(&str, f32)], font: &IndirectFontRef) {
+        self.ensure_room();
+        for (text, x) in cells {
+            self.layer.use_text(*text, 10.0, Mm(*x), Mm(self.y), font);
+        }
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn skip(&mut self, lines: f32) {
+        self.y -= LINE_HEIGHT * lines;
+    }
+
+    fn ensure_room(&mut self) {
+        if self.y < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+}
+
+/// Fold text to what the built-in fonts can draw ("Sân Đa Năng" -> "San Da Nang")
+fn pdf_text(text: &str) -> String {
+    text.nfd()
+        .filter_map(|c| match c {
+            'đ' => Some('d'),
+            'Đ' => Some('D'),
+            c if c.is_ascii() => Some(c),
+            // Combining accents left over from decomposition
+            '\u{0300}'..='\u{036f}' => None,
+            _ => Some('?'),
+        })
+        .collect()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars - 3).collect();
+    out.push_str("...");
+    out
+}
+
+/// Format a VND amount with thousands separators
+fn format_vnd(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if value < 0 {
+        format!("-{} VND", out)
+    } else {
+        format!("{} VND", out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_text_folds_vietnamese() {
+        assert_eq!(pdf_text("Sân Đa Năng Quận 1"), "San Da Nang Quan 1");
+        assert_eq!(pdf_text("Cầu lông 🏸"), "Cau long ?");
+    }
+
+    #[test]
+    fn test_format_vnd() {
+        assert_eq!(format_vnd(0), "0 VND");
+        assert_eq!(format_vnd(1_250_000), "1,250,000 VND");
+        assert_eq!(format_vnd(-80_000), "-80,000 VND");
+    }
+}
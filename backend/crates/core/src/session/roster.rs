@@ -0,0 +1,105 @@
+use loafy_db::queries::sessions::SessionParticipant;
+use loafy_types::{api::sessions::ParticipantInfo, enums::RosterVisibility};
+use uuid::Uuid;
+
+/// Who is looking at a session's roster
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RosterViewer {
+    pub user_id: Option<Uuid>,
+    /// Admins and the session's organizer always see the full roster
+    pub privileged: bool,
+    /// The viewer has an active booking for the session
+    pub is_participant: bool,
+}
+
+/// Filter a session's participants down to what `viewer` may see
+///
+/// Unprivileged viewers get nothing when the roster is hidden (or
+/// participants-only and they are not booked). Players who opted out of
+/// rosters are anonymized rather than dropped, so previews still add up to the
+/// confirmed count; a player always sees themselves.
+pub fn visible_participants(
+    participants: Vec<SessionParticipant>,
+    visibility: RosterVisibility,
+    viewer: RosterViewer,
+) -> Vec<ParticipantInfo> {
+    if viewer.privileged {
+        return participants.into_iter().map(Into::into).collect();
+    }
+
+    let can_view = match visibility {
+        RosterVisibility::Public => true,
+        RosterVisibility::Participants => viewer.is_participant,
+        RosterVisibility::Hidden => false,
+    };
+    if !can_view {
+        return Vec::new();
+    }
+
+    participants
+        .into_iter()
+        .map(|p| {
+            if p.show_in_roster || viewer.user_id == Some(p.user_id) {
+                p.into()
+            } else {
+                ParticipantInfo {
+                    id: Uuid::nil(),
+                    name: None,
+                    avatar_url: None,
+                    guest_count: p.guest_count,
+                    anonymous: true,
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn participant(show_in_roster: bool) -> SessionParticipant {
+        SessionParticipant {
+            user_id: Uuid::new_v4(),
+            name: Some("Player".to_string()),
+            avatar_url: None,
+            guest_count: 1,
+            show_in_roster,
+        }
+    }
+
+    #[test]
+    fn test_privileged_viewer_sees_everyone() {
+        let viewer = RosterViewer { privileged: true, ..Default::default() };
+        let roster = visible_participants(vec![participant(true), participant(false)], RosterVisibility::Hidden, viewer);
+        assert_eq!(roster.len(), 2);
+        assert!(roster.iter().all(|p| !p.anonymous && p.name.is_some()));
+    }
+
+    #[test]
+    fn test_opted_out_players_are_anonymized() {
+        let roster = visible_participants(vec![participant(true), participant(false)], RosterVisibility::Public, RosterViewer::default());
+        assert_eq!(roster.len(), 2);
+        assert!(!roster[0].anonymous);
+        assert!(roster[1].anonymous);
+        assert_eq!(roster[1].id, Uuid::nil());
+        assert_eq!(roster[1].name, None);
+        assert_eq!(roster[1].guest_count, 1);
+    }
+
+    #[test]
+    fn test_opted_out_player_sees_themselves() {
+        let me = participant(false);
+        let viewer = RosterViewer { user_id: Some(me.user_id), is_participant: true, ..Default::default() };
+        let roster = visible_participants(vec![me], RosterVisibility::Public, viewer);
+        assert!(!roster[0].anonymous);
+    }
+
+    #[test]
+    fn test_restricted_rosters() {
+        let booked = RosterViewer { user_id: Some(Uuid::new_v4()), is_participant: true, ..Default::default() };
+        assert_eq!(visible_participants(vec![participant(true)], RosterVisibility::Participants, booked).len(), 1);
+        assert!(visible_participants(vec![participant(true)], RosterVisibility::Participants, RosterViewer::default()).is_empty());
+        assert!(visible_participants(vec![participant(true)], RosterVisibility::Hidden, booked).is_empty());
+    }
+}
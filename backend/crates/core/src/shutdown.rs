@@ -0,0 +1,62 @@
+//! Graceful shutdown helpers shared by the API server and the job runner
+
+use std::time::Duration;
+
+use loafy_db::PgPool;
+
+/// How long `close_pool` waits for connections to be returned
+const POOL_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolve on SIGINT (Ctrl+C) or SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// How long to wait for in-flight work after a shutdown signal
+/// (`SHUTDOWN_TIMEOUT_SECS`, default 30)
+pub fn shutdown_timeout() -> Duration {
+    std::env::var("SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(30))
+}
+
+/// Close the pool, giving up after a few seconds
+///
+/// `PgPool::close` waits for every checked-out connection, so work still
+/// running past the drain timeout would otherwise hang the shutdown.
+pub async fn close_pool(pool: &PgPool) {
+    if tokio::time::timeout(POOL_CLOSE_TIMEOUT, pool.close()).await.is_err() {
+        tracing::warn!(
+            "Database connections still in use after {}s, exiting without closing them",
+            POOL_CLOSE_TIMEOUT.as_secs()
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod promote;
+pub mod position;
+
+pub use position::{get_waitlist_position, WaitlistPosition};
+pub use promote::{promote_waitlisted, Promotion};
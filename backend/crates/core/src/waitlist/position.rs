@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use loafy_db::{
+    queries::{config, waitlist},
+    PgPool,
+};
+use loafy_types::{AppError, ErrorCode};
+use uuid::Uuid;
+
+/// A user's place in a session's waitlist
+#[derive(Debug, Clone)]
+pub struct WaitlistPosition {
+    /// 1-based position in the queue
+    pub position: i64,
+    /// Number of people currently waiting
+    pub waitlist_length: i64,
+    pub guest_count: i32,
+    pub joined_at: DateTime<Utc>,
+    /// Worst-case hours until an offer once a slot frees up: everyone
+    /// ahead may hold it for the full payment window
+    pub estimated_wait_hours: i64,
+}
+
+/// Get the user's position in a session's waitlist
+pub async fn get_waitlist_position(
+    pool: &PgPool,
+    session_id: Uuid,
+    user_id: Uuid,
+) -> Result<WaitlistPosition, AppError> {
+    let entry = waitlist::find_waiting(pool, session_id, user_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(ErrorCode::NotFound, "You are not on the waitlist for this session".to_string()))?;
+
+    let ahead = waitlist::count_ahead(pool, session_id, entry.created_at)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let waitlist_length = waitlist::count_waiting(pool, session_id)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let hold_hours = config::get_waitlist_exclusive_hours(pool)
+        .await
+        .unwrap_or(1);
+
+    Ok(WaitlistPosition {
+        position: ahead + 1,
+        waitlist_length,
+        guest_count: entry.guest_count,
+        joined_at: entry.created_at,
+        estimated_wait_hours: ahead * hold_hours as i64,
+    })
+}
@@ -0,0 +1,153 @@
+use chrono::{Duration, Utc};
+use loafy_db::{
+    models::{Booking, Session, WaitlistEntry},
+    queries::{bookings, config, waitlist},
+    PgPool,
+};
+use loafy_types::{AppError, ErrorCode};
+
+use crate::booking::{club_now, create_booking_with_lock};
+
+/// A waitlisted user who was given a booking
+#[derive(Debug, Clone)]
+pub struct Promotion {
+    pub session: Session,
+    pub entry: WaitlistEntry,
+    /// Pending booking (or confirmed, if fully covered by a ticket)
+    pub booking: Booking,
+}
+
+/// Promote waitlisted users into sessions that have free slots
+///
+/// Entries are taken in queue order; one that needs more slots than are free
+/// keeps its place and later, smaller entries may go first. Promoted bookings
+/// get `waitlist_exclusive_hours` to pay; if that lapses the unpaid-release
+/// job frees the slot and the next run promotes the next person.
+pub async fn promote_waitlisted(pool: &PgPool) -> Result<Vec<Promotion>, AppError> {
+    let hold_hours = config::get_waitlist_exclusive_hours(pool)
+        .await
+        .unwrap_or(1);
+
+    let utc_offset = config::load(pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .session_utc_offset;
+
+    let sessions = waitlist::list_sessions_to_promote(pool, club_now(utc_offset))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut promotions = Vec::new();
+
+    for session in sessions {
+        let entries = waitlist::list_waiting(pool, session.id)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let mut free_slots = session.available_slots;
+        for entry in entries {
+            if free_slots <= 0 {
+                break;
+            }
+            if 1 + entry.guest_count > free_slots {
+                continue;
+            }
+
+            let booking = match create_booking_with_lock(
+                pool,
+                entry.user_id,
+                session.id,
+                entry.guest_count,
+                &entry.payment_method,
+                false,
+                false,
+                None,
+            )
+            .await
+            {
+                Ok(booking) => booking,
+                // Someone else took the slots meanwhile; retry next run
+                Err(AppError::Conflict(ErrorCode::SessionFull, _)) => break,
+                Err(e) if can_never_be_booked(&e) => {
+                    tracing::info!(
+                        "Dropping waitlist entry {} for session {}: {}",
+                        entry.id,
+                        session.id,
+                        e
+                    );
+                    waitlist::remove(pool, entry.id)
+                        .await
+                        .map_err(|e| AppError::Internal(e.to_string()))?;
+                    continue;
+                }
+                // Possibly transient (database trouble, weekly limit, ...); the
+                // entry keeps its place and is retried next run
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping waitlist entry {} for session {} this run: {}",
+                        entry.id,
+                        session.id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            free_slots -= 1 + entry.guest_count;
+
+            let booking = if booking.payment_status == "pending" {
+                let deadline = Utc::now() + Duration::hours(hold_hours as i64);
+                bookings::set_payment_deadline(pool, booking.id, deadline)
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))?
+            } else {
+                booking
+            };
+
+            let entry = waitlist::mark_promoted(pool, entry.id, booking.id)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+
+            promotions.push(Promotion {
+                session: session.clone(),
+                entry,
+                booking,
+            });
+        }
+    }
+
+    Ok(promotions)
+}
+
+/// Whether a failed promotion means the entry can never turn into a booking
+fn can_never_be_booked(error: &AppError) -> bool {
+    match error {
+        AppError::Forbidden => true,
+        AppError::NotFound(code, _) | AppError::BadRequest(code, _) | AppError::Conflict(code, _) => matches!(
+            code,
+            ErrorCode::AlreadyBooked
+                | ErrorCode::SessionNotFound
+                | ErrorCode::SessionCancelled
+                | ErrorCode::SessionInPast
+                | ErrorCode::OwnSessionBookingNotAllowed
+                | ErrorCode::TooManyGuests
+                | ErrorCode::InvalidGuestCount
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_never_be_booked() {
+        assert!(can_never_be_booked(&AppError::Conflict(ErrorCode::AlreadyBooked, String::new())));
+        assert!(can_never_be_booked(&AppError::BadRequest(ErrorCode::SessionInPast, String::new())));
+        assert!(can_never_be_booked(&AppError::Forbidden));
+
+        assert!(!can_never_be_booked(&AppError::Internal("pool timed out".to_string())));
+        assert!(!can_never_be_booked(&AppError::Database(sqlx::Error::PoolTimedOut)));
+        assert!(!can_never_be_booked(&AppError::Conflict(ErrorCode::BookingLimitReached, String::new())));
+    }
+}
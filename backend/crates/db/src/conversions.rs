@@ -3,11 +3,18 @@
 //! These From implementations centralize the conversion logic that was
 //! previously duplicated across multiple route handlers.
 
-use crate::models::{Booking, BookingWithSession, Session, SessionExpense, UserWithRole};
+use crate::models::{
+    BookingWithSession, DiscountCodeWithUsage, ExpenseTemplateItem, FixedExpense, NotificationPreferences, Session,
+    SessionExpense, UserWithRole,
+};
+use crate::queries::{admin::BookingWithDetails, sessions::SessionParticipant};
 use loafy_types::{
     api::{
-        admin::{AdminUserRestriction, AdminUserResponse},
-        AuthUser, BookingResponse, ExpenseResponse, ParticipantInfo, SessionResponse,
+        admin::{
+            AdminBookingResponse, AdminUserRestriction, AdminUserResponse, DiscountCodeResponse, FixedExpenseResponse,
+        },
+        AuthUser, BookingResponse, ChannelPreferences, ExpenseInput, ExpenseResponse, NotificationPreferencesResponse,
+        ParticipantInfo, SessionResponse,
     },
     enums::{DiscountType, PaymentMethod, PaymentStatus, UserRole, VerificationStatus},
 };
@@ -26,6 +33,7 @@ impl From<UserWithRole> for AuthUser {
             avatar_url: user.avatar_url,
             role: user.role_name.parse().unwrap_or(UserRole::User),
             birthday: user.birthday,
+            show_in_roster: user.show_in_roster,
         }
     }
 }
@@ -40,6 +48,7 @@ impl From<&UserWithRole> for AuthUser {
             avatar_url: user.avatar_url.clone(),
             role: user.role_name.parse().unwrap_or(UserRole::User),
             birthday: user.birthday,
+            show_in_roster: user.show_in_roster,
         }
     }
 }
@@ -67,6 +76,7 @@ impl From<UserWithRole> for AdminUserResponse {
                 suspension_reason: u.user_suspension_reason,
                 suspended_by_name: None,
             },
+            notes_count: None,
         }
     }
 }
@@ -90,16 +100,17 @@ impl From<&UserWithRole> for AdminUserResponse {
                 suspension_reason: u.user_suspension_reason.clone(),
                 suspended_by_name: None,
             },
+            notes_count: None,
         }
     }
 }
 
 // ============================================================================
-// Booking -> BookingResponse
+// BookingWithSession -> BookingResponse
 // ============================================================================
 
-impl From<Booking> for BookingResponse {
-    fn from(b: Booking) -> Self {
+impl From<BookingWithSession> for BookingResponse {
+    fn from(b: BookingWithSession) -> Self {
         Self {
             id: b.id,
             user_id: b.user_id,
@@ -108,7 +119,7 @@ impl From<Booking> for BookingResponse {
             guest_count: b.guest_count,
             tickets_used: b.tickets_used,
             discount_applied: b.discount_applied.parse().unwrap_or(DiscountType::None),
-            session_price_vnd: DEFAULT_PRICE_VND, // Not available from basic Booking
+            session_price_vnd: b.session_price_vnd,
             price_paid_vnd: b.price_paid_vnd,
             guest_price_paid_vnd: b.guest_price_paid_vnd,
             total_paid_vnd: b.price_paid_vnd + b.guest_price_paid_vnd,
@@ -120,18 +131,17 @@ impl From<Booking> for BookingResponse {
             payment_deadline: b.payment_deadline,
             cancelled_at: b.cancelled_at,
             created_at: b.created_at,
-            // Session details not available when converting from basic Booking
-            session_title: String::new(),
-            session_date: chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
-            session_time: chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-            session_end_time: None,
-            session_location: String::new(),
+            session_title: b.session_title,
+            session_date: b.session_date,
+            session_time: b.session_time,
+            session_end_time: b.session_end_time,
+            session_location: b.session_location,
         }
     }
 }
 
-impl From<&Booking> for BookingResponse {
-    fn from(b: &Booking) -> Self {
+impl From<&BookingWithSession> for BookingResponse {
+    fn from(b: &BookingWithSession) -> Self {
         Self {
             id: b.id,
             user_id: b.user_id,
@@ -140,7 +150,7 @@ impl From<&Booking> for BookingResponse {
             guest_count: b.guest_count,
             tickets_used: b.tickets_used,
             discount_applied: b.discount_applied.parse().unwrap_or(DiscountType::None),
-            session_price_vnd: DEFAULT_PRICE_VND, // Not available from basic Booking
+            session_price_vnd: b.session_price_vnd,
             price_paid_vnd: b.price_paid_vnd,
             guest_price_paid_vnd: b.guest_price_paid_vnd,
             total_paid_vnd: b.price_paid_vnd + b.guest_price_paid_vnd,
@@ -153,79 +163,38 @@ impl From<&Booking> for BookingResponse {
             payment_deadline: b.payment_deadline,
             cancelled_at: b.cancelled_at,
             created_at: b.created_at,
-            // Session details not available when converting from basic Booking
-            session_title: String::new(),
-            session_date: chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
-            session_time: chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-            session_end_time: None,
-            session_location: String::new(),
+            session_title: b.session_title.clone(),
+            session_date: b.session_date,
+            session_time: b.session_time,
+            session_end_time: b.session_end_time,
+            session_location: b.session_location.clone(),
         }
     }
 }
 
 // ============================================================================
-// BookingWithSession -> BookingResponse
+// BookingWithDetails -> AdminBookingResponse
 // ============================================================================
 
-impl From<BookingWithSession> for BookingResponse {
-    fn from(b: BookingWithSession) -> Self {
+impl From<BookingWithDetails> for AdminBookingResponse {
+    fn from(b: BookingWithDetails) -> Self {
         Self {
             id: b.id,
             user_id: b.user_id,
             session_id: b.session_id,
             booking_code: b.booking_code,
             guest_count: b.guest_count,
-            tickets_used: b.tickets_used,
-            discount_applied: b.discount_applied.parse().unwrap_or(DiscountType::None),
-            session_price_vnd: b.session_price_vnd,
-            price_paid_vnd: b.price_paid_vnd,
-            guest_price_paid_vnd: b.guest_price_paid_vnd,
-            total_paid_vnd: b.price_paid_vnd + b.guest_price_paid_vnd,
-            payment_method: b.payment_method.parse().unwrap_or(PaymentMethod::Stripe),
-            payment_status: b.payment_status.parse().unwrap_or(PaymentStatus::Pending),
-            verification_status: b
-                .verification_status
-                .map(|s| s.parse().unwrap_or(VerificationStatus::Pending)),
+            total_price_vnd: b.price_paid_vnd + b.guest_price_paid_vnd,
+            payment_method: b.payment_method,
+            payment_status: b.payment_status,
             payment_deadline: b.payment_deadline,
             cancelled_at: b.cancelled_at,
             created_at: b.created_at,
+            user_email: b.user_email,
+            user_name: b.user_name,
             session_title: b.session_title,
             session_date: b.session_date,
             session_time: b.session_time,
-            session_end_time: b.session_end_time,
-            session_location: b.session_location,
-        }
-    }
-}
-
-impl From<&BookingWithSession> for BookingResponse {
-    fn from(b: &BookingWithSession) -> Self {
-        Self {
-            id: b.id,
-            user_id: b.user_id,
-            session_id: b.session_id,
-            booking_code: b.booking_code.clone(),
-            guest_count: b.guest_count,
-            tickets_used: b.tickets_used,
-            discount_applied: b.discount_applied.parse().unwrap_or(DiscountType::None),
-            session_price_vnd: b.session_price_vnd,
-            price_paid_vnd: b.price_paid_vnd,
-            guest_price_paid_vnd: b.guest_price_paid_vnd,
-            total_paid_vnd: b.price_paid_vnd + b.guest_price_paid_vnd,
-            payment_method: b.payment_method.parse().unwrap_or(PaymentMethod::Stripe),
-            payment_status: b.payment_status.parse().unwrap_or(PaymentStatus::Pending),
-            verification_status: b
-                .verification_status
-                .as_ref()
-                .map(|s| s.parse().unwrap_or(VerificationStatus::Pending)),
-            payment_deadline: b.payment_deadline,
-            cancelled_at: b.cancelled_at,
-            created_at: b.created_at,
-            session_title: b.session_title.clone(),
-            session_date: b.session_date,
-            session_time: b.session_time,
-            session_end_time: b.session_end_time,
-            session_location: b.session_location.clone(),
         }
     }
 }
@@ -235,59 +204,16 @@ impl From<&BookingWithSession> for BookingResponse {
 // ============================================================================
 
 /// Default values for session fields
-const DEFAULT_MAX_PLAYERS_PER_COURT: i32 = 6;
+pub const DEFAULT_MAX_PLAYERS_PER_COURT: i32 = 6;
 const DEFAULT_PRICE_VND: i32 = 100_000;
 
-impl From<Session> for SessionResponse {
-    fn from(s: Session) -> Self {
-        Self {
-            id: s.id,
-            organizer_id: s.organizer_id,
-            organizer_name: None, // Must be set explicitly if needed
-            title: s.title,
-            date: s.date,
-            time: s.time,
-            end_time: s.end_time,
-            location: s.location,
-            courts: s.courts,
-            max_players_per_court: s.max_players_per_court.unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT),
-            total_slots: s.total_slots,
-            available_slots: s.available_slots,
-            price_vnd: s.price_vnd.unwrap_or(DEFAULT_PRICE_VND),
-            price_usd: s.price_usd.map(|d| d.to_string()),
-            cancelled: s.cancelled,
-            expenses: None, // Must be set explicitly if needed
-            total_expenses_vnd: None, // Must be set explicitly if needed
-            participants_preview: None, // Must be set explicitly if needed
-            confirmed_count: None, // Must be set explicitly if needed
-        }
-    }
-}
-
-impl From<&Session> for SessionResponse {
-    fn from(s: &Session) -> Self {
-        Self {
-            id: s.id,
-            organizer_id: s.organizer_id,
-            organizer_name: None, // Must be set explicitly if needed
-            title: s.title.clone(),
-            date: s.date,
-            time: s.time,
-            end_time: s.end_time,
-            location: s.location.clone(),
-            courts: s.courts,
-            max_players_per_court: s.max_players_per_court.unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT),
-            total_slots: s.total_slots,
-            available_slots: s.available_slots,
-            price_vnd: s.price_vnd.unwrap_or(DEFAULT_PRICE_VND),
-            price_usd: s.price_usd.map(|d| d.to_string()),
-            cancelled: s.cancelled,
-            expenses: None, // Must be set explicitly if needed
-            total_expenses_vnd: None, // Must be set explicitly if needed
-            participants_preview: None, // Must be set explicitly if needed
-            confirmed_count: None, // Must be set explicitly if needed
-        }
-    }
+/// Booked players per court for a session
+fn court_fill(s: &Session) -> Vec<i32> {
+    crate::queries::sessions::court_fill(
+        s.courts,
+        s.max_players_per_court.unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT),
+        s.total_slots - s.available_slots,
+    )
 }
 
 // ============================================================================
@@ -318,14 +244,129 @@ impl From<&SessionExpense> for ExpenseResponse {
     }
 }
 
-/// Extension trait for SessionResponse to set organizer name, expenses, and participants
+// ============================================================================
+// ExpenseTemplateItem -> ExpenseInput
+// ============================================================================
+
+impl From<ExpenseTemplateItem> for ExpenseInput {
+    fn from(item: ExpenseTemplateItem) -> Self {
+        Self {
+            category: item.category,
+            description: item.description,
+            cost_type: item.cost_type,
+            amount_vnd: item.amount_vnd,
+        }
+    }
+}
+
+// ============================================================================
+// FixedExpense -> FixedExpenseResponse
+// ============================================================================
+
+impl From<FixedExpense> for FixedExpenseResponse {
+    fn from(e: FixedExpense) -> Self {
+        Self {
+            id: e.id,
+            category: e.category,
+            description: e.description,
+            amount_vnd: e.amount_vnd,
+            recurrence: e.recurrence,
+            starts_on: e.starts_on,
+            ends_on: e.ends_on,
+            created_at: e.created_at,
+            updated_at: e.updated_at,
+        }
+    }
+}
+
+// ============================================================================
+// DiscountCodeWithUsage -> DiscountCodeResponse
+// ============================================================================
+
+impl From<DiscountCodeWithUsage> for DiscountCodeResponse {
+    fn from(d: DiscountCodeWithUsage) -> Self {
+        let code = d.code;
+        Self {
+            id: code.id,
+            code: code.code,
+            percent_off: code.percent_off,
+            amount_off_vnd: code.amount_off_vnd,
+            applies_to_guests: code.applies_to_guests,
+            max_uses: code.max_uses,
+            max_uses_per_user: code.max_uses_per_user,
+            valid_from: code.valid_from,
+            valid_until: code.valid_until,
+            active: code.active,
+            times_used: d.times_used,
+            created_at: code.created_at,
+            updated_at: code.updated_at,
+        }
+    }
+}
+
+// ============================================================================
+// SessionParticipant -> ParticipantInfo
+// ============================================================================
+
+impl From<SessionParticipant> for ParticipantInfo {
+    fn from(p: SessionParticipant) -> Self {
+        Self {
+            id: p.user_id,
+            name: p.name,
+            avatar_url: p.avatar_url,
+            guest_count: p.guest_count,
+            anonymous: false,
+        }
+    }
+}
+
+/// Extension trait for SessionResponse to build it from a session and set organizer name, expenses, and participants
 pub trait SessionResponseExt {
+    /// `default_max_guests` is the configured guest limit, used when the session has none of its own
+    fn from_session(s: &Session, default_max_guests: i32) -> Self;
     fn with_organizer_name(self, name: Option<String>) -> Self;
     fn with_expenses(self, expenses: Vec<ExpenseResponse>, total: i64) -> Self;
     fn with_participants(self, participants: Vec<ParticipantInfo>, count: i32) -> Self;
+    fn with_waitlist_count(self, count: i32) -> Self;
+    fn with_my_booking_status(self, status: Option<String>) -> Self;
 }
 
 impl SessionResponseExt for SessionResponse {
+    fn from_session(s: &Session, default_max_guests: i32) -> Self {
+        Self {
+            id: s.id,
+            organizer_id: s.organizer_id,
+            organizer_name: None, // Must be set explicitly if needed
+            title: s.title.clone(),
+            date: s.date,
+            time: s.time,
+            end_time: s.end_time,
+            location: s.location.clone(),
+            location_id: s.location_id,
+            courts: s.courts,
+            max_players_per_court: s.max_players_per_court.unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT),
+            total_slots: s.total_slots,
+            available_slots: s.available_slots,
+            court_fill: court_fill(s),
+            price_vnd: s.price_vnd.unwrap_or(DEFAULT_PRICE_VND),
+            price_usd: s.price_usd.map(|d| d.to_string()),
+            max_guests_per_booking: s.max_guests_per_booking.unwrap_or(default_max_guests),
+            min_players: s.min_players,
+            min_players_deadline_hours: s.min_players_deadline_hours,
+            booking_opens_at: s.booking_opens_at,
+            cancelled: s.cancelled,
+            image_url: s.image_url.clone(),
+            roster_visibility: s.roster_visibility.parse().unwrap_or_default(),
+            waitlist_enabled: s.waitlist_enabled,
+            expenses: None, // Must be set explicitly if needed
+            total_expenses_vnd: None, // Must be set explicitly if needed
+            participants_preview: None, // Must be set explicitly if needed
+            confirmed_count: None, // Must be set explicitly if needed
+            waitlist_count: None, // Must be set explicitly if needed
+            my_booking_status: None, // Must be set explicitly if needed
+        }
+    }
+
     fn with_organizer_name(mut self, name: Option<String>) -> Self {
         self.organizer_name = name;
         self
@@ -342,11 +383,45 @@ impl SessionResponseExt for SessionResponse {
         self.confirmed_count = Some(count);
         self
     }
-}
-
-
-
 
+    fn with_waitlist_count(mut self, count: i32) -> Self {
+        self.waitlist_count = Some(count);
+        self
+    }
 
+    fn with_my_booking_status(mut self, status: Option<String>) -> Self {
+        self.my_booking_status = status;
+        self
+    }
+}
 
+// ============================================================================
+// NotificationPreferences -> NotificationPreferencesResponse
+// ============================================================================
 
+impl From<NotificationPreferences> for NotificationPreferencesResponse {
+    fn from(p: NotificationPreferences) -> Self {
+        Self {
+            booking_confirmation: ChannelPreferences {
+                email: p.booking_confirmation_email,
+                sms: p.booking_confirmation_sms,
+                push: p.booking_confirmation_push,
+            },
+            reminder: ChannelPreferences {
+                email: p.reminder_email,
+                sms: p.reminder_sms,
+                push: p.reminder_push,
+            },
+            session_cancelled: ChannelPreferences {
+                email: p.session_cancelled_email,
+                sms: p.session_cancelled_sms,
+                push: p.session_cancelled_push,
+            },
+            waitlist_promoted: ChannelPreferences {
+                email: p.waitlist_promoted_email,
+                sms: p.waitlist_promoted_sms,
+                push: p.waitlist_promoted_push,
+            },
+        }
+    }
+}
@@ -3,4 +3,4 @@ pub mod models;
 pub mod queries;
 pub mod pool;
 
-pub use pool::{create_pool, Pool as PgPool};
+pub use pool::{create_pool, Pool as PgPool, PoolSettings};
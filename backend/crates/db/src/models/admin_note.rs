@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Internal staff note about a user (never shown to the user)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AdminNote {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub author_id: Uuid,
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Admin note with the author's name for display
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AdminNoteWithAuthor {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub author_id: Uuid,
+    pub author_name: Option<String>,
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -23,6 +23,18 @@ pub struct Booking {
     pub stripe_payment_id: Option<String>,
     pub payment_deadline: Option<DateTime<Utc>>,
     pub cancelled_at: Option<DateTime<Utc>>,
+    /// One of `cancellation_reasons`; None if not cancelled or cancelled before reasons were tracked
+    pub cancellation_reason: Option<String>,
+    /// Discount code applied at booking time (None if the code was since deleted)
+    pub discount_code_id: Option<Uuid>,
+    /// The applied code's text, kept even if the code is deleted
+    pub discount_code: Option<String>,
+    pub rescheduled_from: Option<Uuid>,
+    /// Admin who manually reviewed the payment proof
+    pub verified_by: Option<Uuid>,
+    pub verified_at: Option<DateTime<Utc>>,
+    /// Amount the reviewer saw arrive, when it was recorded
+    pub received_amount_vnd: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -53,3 +65,24 @@ pub struct BookingWithSession {
     pub session_location: String,
     pub session_price_vnd: i32,
 }
+
+/// Why a booking was cancelled
+pub mod cancellation_reasons {
+    /// The booking owner cancelled it
+    pub const USER: &str = "user";
+    pub const ADMIN: &str = "admin";
+    /// Released by the job after the payment deadline passed
+    pub const PAYMENT_EXPIRED: &str = "payment_expired";
+    /// Refunded outside our API (e.g. from the Stripe dashboard)
+    pub const REFUNDED: &str = "refunded";
+    pub const SESSION_CANCELLED: &str = "session_cancelled";
+    pub const ACCOUNT_DELETED: &str = "account_deleted";
+}
+
+/// Why a booking attempt was rejected
+pub mod booking_failure_reasons {
+    pub const FULL: &str = "full";
+    pub const ALREADY_BOOKED: &str = "already_booked";
+    /// The user hit their weekly booking limit
+    pub const LIMIT_REACHED: &str = "limit_reached";
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Single-use invite to join a booking as a named guest
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BookingInvite {
+    pub id: Uuid,
+    pub booking_id: Uuid,
+    pub created_by: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// Session start; unredeemed invites stop working then
+    pub expires_at: DateTime<Utc>,
+    /// Friend who joined as a guest
+    pub redeemed_by: Option<Uuid>,
+    pub redeemed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
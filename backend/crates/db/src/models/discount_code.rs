@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Promo code taking a percentage or fixed amount off a booking
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DiscountCode {
+    pub id: Uuid,
+    /// Uppercase; matched case-insensitively
+    pub code: String,
+    /// Set when the code takes a percentage off
+    pub percent_off: Option<i32>,
+    /// Set when the code takes a fixed amount off each discounted slot
+    pub amount_off_vnd: Option<i32>,
+    /// Also discount paying guest slots, not just the booker's own
+    pub applies_to_guests: bool,
+    /// None = unlimited
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Discount code with how many active bookings use it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DiscountCodeWithUsage {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub code: DiscountCode,
+    pub times_used: i64,
+}
+
+impl DiscountCode {
+    /// Amount taken off a slot priced at `price_vnd`, never more than the price
+    pub fn discount_vnd(&self, price_vnd: i32) -> i32 {
+        let discount = match (self.percent_off, self.amount_off_vnd) {
+            (Some(percent), _) => price_vnd * percent / 100,
+            (None, Some(amount)) => amount,
+            (None, None) => 0,
+        };
+        discount.clamp(0, price_vnd.max(0))
+    }
+
+    /// Whether the code can be used at `now`
+    pub fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        self.valid_from.is_none_or(|from| now >= from) && self.valid_until.is_none_or(|until| now < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(percent_off: Option<i32>, amount_off_vnd: Option<i32>) -> DiscountCode {
+        DiscountCode {
+            id: Uuid::nil(),
+            code: "FIRST50".to_string(),
+            percent_off,
+            amount_off_vnd,
+            applies_to_guests: false,
+            max_uses: None,
+            max_uses_per_user: None,
+            valid_from: None,
+            valid_until: None,
+            active: true,
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_percent_discount() {
+        assert_eq!(code(Some(50), None).discount_vnd(100_000), 50_000);
+        assert_eq!(code(Some(100), None).discount_vnd(90_000), 90_000);
+        assert_eq!(code(Some(50), None).discount_vnd(0), 0);
+    }
+
+    #[test]
+    fn test_fixed_discount_is_capped_at_price() {
+        assert_eq!(code(None, Some(30_000)).discount_vnd(100_000), 30_000);
+        assert_eq!(code(None, Some(30_000)).discount_vnd(20_000), 20_000);
+    }
+
+    #[test]
+    fn test_validity_window() {
+        let now = Utc::now();
+        let mut promo = code(Some(10), None);
+        assert!(promo.is_valid_at(now));
+
+        promo.valid_from = Some(now + chrono::Duration::hours(1));
+        assert!(!promo.is_valid_at(now));
+
+        promo.valid_from = None;
+        promo.valid_until = Some(now);
+        assert!(!promo.is_valid_at(now));
+    }
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Named set of expenses an organizer reuses across sessions
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExpenseTemplate {
+    pub id: Uuid,
+    pub organizer_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One expense line of a template, mirroring `SessionExpense`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExpenseTemplateItem {
+    pub id: Uuid,
+    pub template_id: Uuid,
+    pub position: i32,
+    pub category: String,
+    pub description: Option<String>,
+    pub cost_type: String,
+    pub amount_vnd: i32,
+}
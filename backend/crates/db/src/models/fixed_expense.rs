@@ -0,0 +1,106 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Recurring cost not tied to a session, e.g. a coach retainer or storage rental
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FixedExpense {
+    pub id: Uuid,
+    pub category: String,
+    pub description: Option<String>,
+    /// Amount per recurrence period
+    pub amount_vnd: i32,
+    /// `weekly` or `monthly`
+    pub recurrence: String,
+    pub starts_on: NaiveDate,
+    /// Last day the expense applies (inclusive); None while ongoing
+    pub ends_on: Option<NaiveDate>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FixedExpense {
+    /// Share of the expense falling within `from..=to`
+    ///
+    /// Each period's amount is spread evenly over its days (7 for weekly, the
+    /// calendar month's length for monthly), so a fully covered month costs
+    /// exactly `amount_vnd`.
+    pub fn prorated_vnd(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let start = from.max(self.starts_on);
+        let end = self.ends_on.map_or(to, |ends_on| to.min(ends_on));
+        if start > end {
+            return 0;
+        }
+
+        let amount = self.amount_vnd as i64;
+        if self.recurrence == "weekly" {
+            return amount * days_between(start, end) / 7;
+        }
+
+        let mut total = 0;
+        let mut month_start = start.with_day(1).expect("day 1 exists");
+        while month_start <= end {
+            let next_month = month_start + Months::new(1);
+            let month_end = next_month.pred_opt().expect("valid date");
+            let covered = days_between(start.max(month_start), end.min(month_end));
+            total += amount * covered / days_between(month_start, month_end);
+            month_start = next_month;
+        }
+        total
+    }
+}
+
+/// Days in `start..=end`
+fn days_between(start: NaiveDate, end: NaiveDate) -> i64 {
+    (end - start).num_days() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn expense(recurrence: &str, amount_vnd: i32, starts_on: NaiveDate, ends_on: Option<NaiveDate>) -> FixedExpense {
+        FixedExpense {
+            id: Uuid::nil(),
+            category: "instructor".to_string(),
+            description: None,
+            amount_vnd,
+            recurrence: recurrence.to_string(),
+            starts_on,
+            ends_on,
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_monthly_prorates_by_month_length() {
+        let rent = expense("monthly", 3_000_000, date(2026, 1, 1), None);
+        assert_eq!(rent.prorated_vnd(date(2026, 6, 1), date(2026, 6, 30)), 3_000_000);
+        assert_eq!(rent.prorated_vnd(date(2026, 6, 1), date(2026, 6, 15)), 1_500_000);
+        // Half of June plus all of July
+        assert_eq!(rent.prorated_vnd(date(2026, 6, 16), date(2026, 7, 31)), 4_500_000);
+    }
+
+    #[test]
+    fn test_weekly_prorates_per_day() {
+        let coach = expense("weekly", 700_000, date(2026, 1, 1), None);
+        assert_eq!(coach.prorated_vnd(date(2026, 3, 1), date(2026, 3, 14)), 1_400_000);
+        assert_eq!(coach.prorated_vnd(date(2026, 3, 1), date(2026, 3, 1)), 100_000);
+    }
+
+    #[test]
+    fn test_only_effective_dates_count() {
+        let storage = expense("monthly", 310_000, date(2026, 5, 10), Some(date(2026, 5, 19)));
+        assert_eq!(storage.prorated_vnd(date(2026, 5, 1), date(2026, 5, 31)), 100_000);
+        assert_eq!(storage.prorated_vnd(date(2026, 6, 1), date(2026, 6, 30)), 0);
+        assert_eq!(storage.prorated_vnd(date(2026, 4, 1), date(2026, 4, 30)), 0);
+    }
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Audit record of a support impersonation token
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ImpersonationGrant {
+    /// Also the token's `jti`
+    pub id: Uuid,
+    pub admin_id: Uuid,
+    /// User being impersonated
+    pub user_id: Uuid,
+    pub reason: String,
+    pub expires_at: DateTime<Utc>,
+    /// Requests made with the token
+    pub request_count: i32,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
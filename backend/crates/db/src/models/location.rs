@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Canonical session location
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Location {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Location with the number of (non-cancelled) sessions held there
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LocationWithCount {
+    pub id: Uuid,
+    pub name: String,
+    pub session_count: i64,
+}
@@ -1,13 +1,37 @@
 pub mod user;
 pub mod session;
 pub mod booking;
+pub mod booking_invite;
 pub mod session_expense;
+pub mod expense_template;
+pub mod discount_code;
+pub mod fixed_expense;
+pub mod impersonation_grant;
 pub mod subscription;
+pub mod subscription_gift;
 pub mod ticket_transaction;
+pub mod admin_note;
+pub mod location;
+pub mod waitlist_entry;
+pub mod session_announcement;
+pub mod notification_preference;
+pub mod permission;
 
 pub use user::{User, Role, UserWithRole};
 pub use session::Session;
-pub use booking::{Booking, BookingWithSession};
+pub use booking::{Booking, BookingWithSession, booking_failure_reasons, cancellation_reasons};
+pub use booking_invite::BookingInvite;
 pub use session_expense::SessionExpense;
+pub use expense_template::{ExpenseTemplate, ExpenseTemplateItem};
+pub use fixed_expense::FixedExpense;
+pub use impersonation_grant::ImpersonationGrant;
+pub use discount_code::{DiscountCode, DiscountCodeWithUsage};
 pub use subscription::Subscription;
+pub use subscription_gift::SubscriptionGift;
 pub use ticket_transaction::{TicketTransaction, BonusTicket, transaction_types, bonus_types};
+pub use admin_note::{AdminNote, AdminNoteWithAuthor};
+pub use location::{Location, LocationWithCount};
+pub use waitlist_entry::WaitlistEntry;
+pub use session_announcement::SessionAnnouncement;
+pub use notification_preference::{NotificationKind, NotificationPreferences};
+pub use permission::{Permission, permissions};
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Notification types a user can opt in or out of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    BookingConfirmation,
+    Reminder,
+    SessionCancelled,
+    WaitlistPromoted,
+}
+
+/// A user's channel choices per notification type
+///
+/// Users without a stored row get `NotificationPreferences::defaults`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationPreferences {
+    pub user_id: Uuid,
+    pub booking_confirmation_email: bool,
+    pub booking_confirmation_sms: bool,
+    pub booking_confirmation_push: bool,
+    pub reminder_email: bool,
+    pub reminder_sms: bool,
+    pub reminder_push: bool,
+    pub session_cancelled_email: bool,
+    pub session_cancelled_sms: bool,
+    pub session_cancelled_push: bool,
+    pub waitlist_promoted_email: bool,
+    pub waitlist_promoted_sms: bool,
+    pub waitlist_promoted_push: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationPreferences {
+    /// Preferences for a user who never changed them: email on, SMS and push off
+    pub fn defaults(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            booking_confirmation_email: true,
+            booking_confirmation_sms: false,
+            booking_confirmation_push: false,
+            reminder_email: true,
+            reminder_sms: false,
+            reminder_push: false,
+            session_cancelled_email: true,
+            session_cancelled_sms: false,
+            session_cancelled_push: false,
+            waitlist_promoted_email: true,
+            waitlist_promoted_sms: false,
+            waitlist_promoted_push: false,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Whether the user wants this notification by email
+    pub fn email_enabled(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::BookingConfirmation => self.booking_confirmation_email,
+            NotificationKind::Reminder => self.reminder_email,
+            NotificationKind::SessionCancelled => self.session_cancelled_email,
+            NotificationKind::WaitlistPromoted => self.waitlist_promoted_email,
+        }
+    }
+}
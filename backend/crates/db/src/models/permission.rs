@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Named ability that can be granted to roles
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Permission {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Permission name constants, seeded in the role_permissions migration
+pub mod permissions {
+    pub const SESSIONS_CREATE: &str = "sessions.create";
+    pub const SESSIONS_DELETE: &str = "sessions.delete";
+    pub const SESSIONS_IMPORT: &str = "sessions.import";
+    pub const ANALYTICS_VIEW: &str = "analytics.view";
+    pub const BOOKINGS_MANAGE: &str = "bookings.manage";
+    pub const TICKETS_MANAGE: &str = "tickets.manage";
+    pub const CONFIG_MANAGE: &str = "config.manage";
+    pub const ROLES_MANAGE: &str = "roles.manage";
+}
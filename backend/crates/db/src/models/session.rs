@@ -12,6 +12,7 @@ pub struct Session {
     pub time: NaiveTime,
     pub end_time: Option<NaiveTime>,
     pub location: String,
+    pub location_id: Option<Uuid>,
     pub courts: i32,
     pub max_players_per_court: Option<i32>,
     pub total_slots: i32,
@@ -22,8 +23,18 @@ pub struct Session {
     pub drop_in_cancellation_hours: Option<i32>,
     pub subscriber_cancellation_hours: Option<i32>,
     pub qr_code_url: Option<String>,
+    pub max_guests_per_booking: Option<i32>,
+    pub min_players: Option<i32>,
+    pub min_players_deadline_hours: Option<i32>,
+    pub booking_opens_at: Option<DateTime<Utc>>,
     pub cancelled: bool,
     pub cancelled_at: Option<DateTime<Utc>>,
+    pub cancellation_reason: Option<String>,
+    pub image_url: Option<String>,
+    /// `public`, `participants` or `hidden` (see `RosterVisibility`)
+    pub roster_visibility: String,
+    /// Players may queue once the session is full
+    pub waitlist_enabled: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
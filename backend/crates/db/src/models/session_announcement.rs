@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Message an organizer sent to a session's confirmed participants
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SessionAnnouncement {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub author_id: Uuid,
+    pub subject: String,
+    pub body: String,
+    pub recipient_count: i32,
+    pub created_at: DateTime<Utc>,
+}
@@ -14,3 +14,15 @@ pub struct SessionExpense {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+impl SessionExpense {
+    /// Actual cost for a session with `courts` courts: per-court amounts are
+    /// multiplied out
+    pub fn amount_for_courts(&self, courts: i32) -> i64 {
+        if self.cost_type == "per_court" {
+            self.amount_vnd as i64 * courts as i64
+        } else {
+            self.amount_vnd as i64
+        }
+    }
+}
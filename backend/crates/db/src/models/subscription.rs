@@ -14,13 +14,20 @@ pub struct Subscription {
     pub stripe_subscription_id: Option<String>,
     pub stripe_customer_id: Option<String>,
     pub auto_renew: bool,
+    /// Stripe plan interval: `day`, `week`, `month` or `year` (None if unknown)
+    pub billing_interval: Option<String>,
+    /// Number of `billing_interval`s per billing period
+    pub billing_interval_count: Option<i32>,
+    /// Last Stripe invoice whose tickets were granted
+    pub last_invoice_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl Subscription {
-    /// Check if subscription is currently active
+    /// Check if subscription is currently active (paid or trialing).
+    /// Both are ticket-eligible; use `status` to tell them apart.
     pub fn is_active(&self) -> bool {
-        self.status == "active"
+        self.status == "active" || self.status == "trialing"
     }
 }
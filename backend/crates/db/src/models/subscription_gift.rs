@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A paid gift subscription waiting for its recipient to sign up
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SubscriptionGift {
+    pub id: Uuid,
+    /// Lowercased email the gift was bought for
+    pub recipient_email: String,
+    pub purchaser_id: Uuid,
+    pub stripe_subscription_id: String,
+    pub stripe_customer_id: String,
+    /// Tickets to grant on claim (renewals while unclaimed add up)
+    pub tickets: i32,
+    pub current_period_start: DateTime<Utc>,
+    pub current_period_end: DateTime<Utc>,
+    pub claimed_by: Option<Uuid>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
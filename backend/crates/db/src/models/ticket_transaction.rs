@@ -44,6 +44,7 @@ pub mod transaction_types {
     pub const BONUS_MANUAL: &str = "bonus_manual";
     pub const EXPIRED: &str = "expired";
     pub const REVOKED: &str = "revoked";
+    pub const MERGED: &str = "merged";
 }
 
 /// Bonus type constants
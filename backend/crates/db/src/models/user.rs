@@ -14,6 +14,8 @@ pub struct User {
     pub auth_provider: String,
     pub auth_provider_id: String,
     pub birthday: Option<NaiveDate>,
+    /// Appear by name in session rosters (otherwise anonymized)
+    pub show_in_roster: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
@@ -42,6 +44,7 @@ pub struct UserWithRole {
     pub auth_provider: String,
     pub auth_provider_id: String,
     pub birthday: Option<NaiveDate>,
+    pub show_in_roster: bool,
     pub user_created_at: DateTime<Utc>,
     pub user_updated_at: DateTime<Utc>,
     pub user_deleted_at: Option<DateTime<Utc>>,
@@ -51,6 +54,8 @@ pub struct UserWithRole {
     pub user_suspended_by: Option<Uuid>,
     // Role fields
     pub role_name: String,
+    /// Names of the permissions granted to the role
+    pub permissions: Vec<String>,
 }
 
 impl UserWithRole {
@@ -66,6 +71,11 @@ impl UserWithRole {
         self.role_name == "organizer" || self.is_admin()
     }
 
+    /// Check if the user's role grants `permission` (see `models::permissions`)
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
     pub fn is_deleted(&self) -> bool {
         self.user_deleted_at.is_some()
     }
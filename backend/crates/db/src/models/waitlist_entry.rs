@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A user queued for a full session
+///
+/// Entries are waiting until `promoted_at` is set, at which point
+/// `booking_id` points at the pending booking created for them.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WaitlistEntry {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub guest_count: i32,
+    pub payment_method: String,
+    pub booking_id: Option<Uuid>,
+    pub promoted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,12 +1,93 @@
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::ConnectOptions;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub use sqlx::PgPool as Pool;
 
+/// Connection pool tuning, read from `DB_*` environment variables
+#[derive(Debug, Clone, Copy)]
+pub struct PoolSettings {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    /// Idle connections above `min_connections` are closed after this long
+    pub idle_timeout: Duration,
+    /// Statements running longer than this are logged as warnings
+    pub slow_query_threshold: Duration,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: 20,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(600),
+            slow_query_threshold: Duration::from_millis(500),
+        }
+    }
+}
+
+impl PoolSettings {
+    /// Read settings from the environment, falling back to defaults for
+    /// anything unset or unparsable
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let max_connections = env_u32("DB_MAX_CONNECTIONS").unwrap_or(defaults.max_connections).max(1);
+
+        Self {
+            max_connections,
+            min_connections: env_u32("DB_MIN_CONNECTIONS")
+                .unwrap_or(defaults.min_connections)
+                .min(max_connections),
+            acquire_timeout: env_u32("DB_ACQUIRE_TIMEOUT_SECS")
+                .map(|secs| Duration::from_secs(secs.into()))
+                .unwrap_or(defaults.acquire_timeout),
+            idle_timeout: env_u32("DB_IDLE_TIMEOUT_SECS")
+                .map(|secs| Duration::from_secs(secs.into()))
+                .unwrap_or(defaults.idle_timeout),
+            slow_query_threshold: env_u32("DB_SLOW_QUERY_MS")
+                .map(|ms| Duration::from_millis(ms.into()))
+                .unwrap_or(defaults.slow_query_threshold),
+        }
+    }
+}
+
+fn env_u32(key: &str) -> Option<u32> {
+    let value = std::env::var(key).ok()?;
+    match value.trim().parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid {}={:?}, using default", key, value);
+            None
+        }
+    }
+}
+
 pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+    let settings = PoolSettings::from_env();
+
+    tracing::info!(
+        "Database pool: max_connections={}, min_connections={}, acquire_timeout={}s, idle_timeout={}s, slow_query_threshold={}ms",
+        settings.max_connections,
+        settings.min_connections,
+        settings.acquire_timeout.as_secs(),
+        settings.idle_timeout.as_secs(),
+        settings.slow_query_threshold.as_millis()
+    );
+
+    // Every query goes through these connections, so slow ones are logged
+    // (target `sqlx::query`, with the SQL and elapsed time) inside the
+    // caller's span, which carries the request id for API requests
+    let connect_options = PgConnectOptions::from_str(database_url)?
+        .log_slow_statements(log::LevelFilter::Warn, settings.slow_query_threshold);
+
     PgPoolOptions::new()
-        .max_connections(20)
-        .acquire_timeout(Duration::from_secs(30))
-        .connect(database_url)
+        .max_connections(settings.max_connections)
+        .min_connections(settings.min_connections)
+        .acquire_timeout(settings.acquire_timeout)
+        .idle_timeout(settings.idle_timeout)
+        .connect_with(connect_options)
         .await
 }
@@ -1,22 +1,10 @@
-use crate::models::UserWithRole;
+use crate::models::{transaction_types, Permission, UserWithRole};
+use crate::queries::{admin_notes, bookings, fixed_expenses, sessions::TimeOfDay, subscriptions, ticket_transactions, users};
 use anyhow::Result;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use loafy_types::Pagination;
 use sqlx::PgPool;
 
-/// SQL fragment for calculating expense amounts, accounting for per-court multiplier.
-///
-/// Usage in queries:
-/// ```sql
-/// SUM({EXPENSE_AMOUNT_CALC})
-/// ```
-/// where `e` is the session_expenses alias and `s` is the sessions alias.
-const EXPENSE_AMOUNT_CALC: &str = r#"
-    CASE
-        WHEN e.cost_type = 'per_court' THEN e.amount_vnd * s.courts
-        ELSE e.amount_vnd
-    END
-"#;
-
 /// Admin statistics for dashboard
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct AdminStats {
@@ -49,7 +37,8 @@ pub struct DailyDataPoint {
 
 /// Get admin dashboard statistics with optional time period filter
 /// `since` - If provided, filters time-based stats to this date onwards
-pub async fn get_admin_stats(pool: &PgPool, since: Option<DateTime<Utc>>) -> Result<AdminStats> {
+/// `today` - The club's current date, for counting upcoming sessions
+pub async fn get_admin_stats(pool: &PgPool, since: Option<DateTime<Utc>>, today: NaiveDate) -> Result<AdminStats> {
     // Total users (excluding deleted) - always all-time for context
     let (total_users,): (i64,) = sqlx::query_as(
         "SELECT COUNT(*) FROM users WHERE deleted_at IS NULL"
@@ -79,8 +68,9 @@ pub async fn get_admin_stats(pool: &PgPool, since: Option<DateTime<Utc>>) -> Res
 
     // Upcoming sessions - always future sessions
     let (upcoming_sessions,): (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM sessions WHERE date >= CURRENT_DATE AND cancelled = false"
+        "SELECT COUNT(*) FROM sessions WHERE date >= $1 AND cancelled = false"
     )
+    .bind(today)
     .fetch_one(pool)
     .await?;
 
@@ -176,34 +166,13 @@ pub async fn get_admin_stats(pool: &PgPool, since: Option<DateTime<Utc>>) -> Res
 
 /// List all users with their roles (admin only)
 pub async fn list_all_users(pool: &PgPool) -> Result<Vec<UserWithRole>> {
-    let users = sqlx::query_as::<_, UserWithRole>(
-        r#"
-        SELECT
-            u.id,
-            u.email,
-            u.name,
-            u.avatar_url,
-            u.phone,
-            u.role_id,
-            u.auth_provider,
-            u.auth_provider_id,
-            u.birthday,
-            u.created_at as user_created_at,
-            u.updated_at as user_updated_at,
-            u.deleted_at as user_deleted_at,
-            u.suspended_at as user_suspended_at,
-            u.suspended_until as user_suspended_until,
-            u.suspension_reason as user_suspension_reason,
-            u.suspended_by as user_suspended_by,
-            r.name as role_name
-        FROM users u
-        JOIN roles r ON u.role_id = r.id
-        WHERE u.deleted_at IS NULL
-        ORDER BY u.created_at DESC
-        "#
-    )
-    .fetch_all(pool)
-    .await?;
+    let query = format!(
+        "{} WHERE u.deleted_at IS NULL ORDER BY u.created_at DESC",
+        users::USER_WITH_ROLE_SELECT
+    );
+    let users = sqlx::query_as::<_, UserWithRole>(&query)
+        .fetch_all(pool)
+        .await?;
 
     Ok(users)
 }
@@ -278,6 +247,41 @@ pub async fn list_roles(pool: &PgPool) -> Result<Vec<(uuid::Uuid, String)>> {
     Ok(roles)
 }
 
+/// List all permissions
+pub async fn list_permissions(pool: &PgPool) -> Result<Vec<Permission>> {
+    let permissions = sqlx::query_as::<_, Permission>(
+        "SELECT * FROM permissions ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(permissions)
+}
+
+/// List every role with the names of the permissions it grants
+pub async fn list_roles_with_permissions(pool: &PgPool) -> Result<Vec<(uuid::Uuid, String, Vec<String>)>> {
+    let roles: Vec<(uuid::Uuid, String, Vec<String>)> = sqlx::query_as(
+        r#"
+        SELECT
+            r.id,
+            r.name,
+            ARRAY(
+                SELECT p.name
+                FROM role_permissions rp
+                JOIN permissions p ON p.id = rp.permission_id
+                WHERE rp.role_id = r.id
+                ORDER BY p.name
+            ) as permissions
+        FROM roles r
+        ORDER BY r.name
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(roles)
+}
+
 /// Get statistics for the previous period (for comparison)
 /// `period_start` - Start of the current period
 /// `period_end` - End of the previous period (same as current period start)
@@ -398,7 +402,12 @@ pub async fn get_daily_stats(
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ProfitStats {
     pub total_revenue_vnd: i64,
+    /// Variable plus fixed expenses
     pub total_expenses_vnd: i64,
+    /// Per-session expenses for sessions in the period
+    pub variable_expenses_vnd: i64,
+    /// Prorated share of recurring fixed expenses for the period
+    pub fixed_expenses_vnd: i64,
     pub net_profit_vnd: i64,
     pub profit_margin_percent: f64,
 }
@@ -415,6 +424,19 @@ pub struct SessionProfitSummary {
     pub profit_margin_percent: f64,
 }
 
+/// Historical fill for one weekday + start hour slot
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SlotFillStats {
+    /// ISO weekday: 1 = Monday .. 7 = Sunday
+    pub weekday: i32,
+    pub hour: i32,
+    pub session_count: i64,
+    pub cancelled_count: i64,
+    /// Over sessions that went ahead; None if every session was cancelled
+    pub avg_fill_percent: Option<f64>,
+    pub avg_revenue_vnd: Option<f64>,
+}
+
 /// Expense breakdown by category
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ExpenseByCategory {
@@ -428,12 +450,75 @@ pub struct ExpenseByCategory {
 pub struct DailyProfitDataPoint {
     pub date: String,
     pub revenue: i64,
+    /// Variable plus fixed expenses
     pub expenses: i64,
+    /// Prorated fixed expenses included in `expenses`
+    pub fixed_expenses: i64,
     pub profit: i64,
 }
 
+/// Bucket granularity for profit trend charts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfitBucket {
+    Day,
+    Week,
+    Month,
+}
+
+impl ProfitBucket {
+    /// Ranges longer than this many days default to monthly buckets
+    const MONTHLY_THRESHOLD_DAYS: i64 = 180;
+
+    /// Parse "day", "week" or "month"
+    pub fn parse(value: &str) -> Option<ProfitBucket> {
+        match value.trim().to_lowercase().as_str() {
+            "day" => Some(ProfitBucket::Day),
+            "week" => Some(ProfitBucket::Week),
+            "month" => Some(ProfitBucket::Month),
+            _ => None,
+        }
+    }
+
+    /// Default bucket for a range of `days` (None = all time)
+    pub fn for_range(days: Option<i64>) -> ProfitBucket {
+        match days {
+            Some(days) if days <= Self::MONTHLY_THRESHOLD_DAYS => ProfitBucket::Day,
+            _ => ProfitBucket::Month,
+        }
+    }
+
+    /// `DATE_TRUNC` field name
+    fn trunc_field(self) -> &'static str {
+        match self {
+            ProfitBucket::Day => "day",
+            ProfitBucket::Week => "week",
+            ProfitBucket::Month => "month",
+        }
+    }
+
+    /// First day of the bucket containing `date`, matching `DATE_TRUNC` (weeks start Monday)
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            ProfitBucket::Day => date,
+            ProfitBucket::Week => date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64),
+            ProfitBucket::Month => date.with_day(1).expect("day 1 exists"),
+        }
+    }
+
+    /// First day of the bucket after the one starting at `start`
+    fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            ProfitBucket::Day => start + chrono::Duration::days(1),
+            ProfitBucket::Week => start + chrono::Duration::days(7),
+            ProfitBucket::Month => start + chrono::Months::new(1),
+        }
+    }
+}
+
 /// Get profit statistics for a period
-pub async fn get_profit_stats(pool: &PgPool, since: Option<DateTime<Utc>>) -> Result<ProfitStats> {
+///
+/// Fixed expenses are prorated through `today`, the club's current date.
+pub async fn get_profit_stats(pool: &PgPool, since: Option<DateTime<Utc>>, today: NaiveDate) -> Result<ProfitStats> {
     // Get total revenue from confirmed bookings
     let revenue_result: Option<(Option<i64>,)> = if let Some(since_date) = since {
         sqlx::query_as(
@@ -489,8 +574,17 @@ pub async fn get_profit_stats(pool: &PgPool, since: Option<DateTime<Utc>>) -> Re
         .fetch_optional(pool)
         .await?
     };
-    let total_expenses_vnd = expenses_result.and_then(|(sum,)| sum).unwrap_or(0);
+    let variable_expenses_vnd = expenses_result.and_then(|(sum,)| sum).unwrap_or(0);
+
+    // Fixed costs accrue per day, through today
+    let from = since.map(|s| s.date_naive());
+    let fixed_expenses_vnd: i64 = fixed_expenses::list_effective_between(pool, from, today)
+        .await?
+        .iter()
+        .map(|e| e.prorated_vnd(from.unwrap_or(NaiveDate::MIN), today))
+        .sum();
 
+    let total_expenses_vnd = variable_expenses_vnd + fixed_expenses_vnd;
     let net_profit_vnd = total_revenue_vnd - total_expenses_vnd;
     let profit_margin_percent = if total_revenue_vnd > 0 {
         (net_profit_vnd as f64 / total_revenue_vnd as f64) * 100.0
@@ -501,109 +595,190 @@ pub async fn get_profit_stats(pool: &PgPool, since: Option<DateTime<Utc>>) -> Re
     Ok(ProfitStats {
         total_revenue_vnd,
         total_expenses_vnd,
+        variable_expenses_vnd,
+        fixed_expenses_vnd,
         net_profit_vnd,
         profit_margin_percent,
     })
 }
 
-/// Get per-session profit breakdown
+/// Get per-session profit breakdown, optionally for one organizer's sessions
+///
+/// `limit` of None returns every session in the period.
+#[allow(clippy::type_complexity)]
 pub async fn get_sessions_profit(
     pool: &PgPool,
     since: Option<DateTime<Utc>>,
-    limit: i32,
+    organizer_id: Option<uuid::Uuid>,
+    limit: Option<i32>,
 ) -> Result<Vec<SessionProfitSummary>> {
-    let rows: Vec<(uuid::Uuid, String, NaiveDate, Option<i64>, Option<i64>)> = if let Some(since_date) = since {
-        sqlx::query_as(
-            r#"
-            SELECT
-                s.id,
-                s.title,
-                s.date,
-                (
-                    SELECT COALESCE(SUM(b.price_paid_vnd + b.guest_price_paid_vnd), 0)
-                    FROM bookings b
-                    WHERE b.session_id = s.id AND b.payment_status = 'confirmed'
-                ) as revenue,
-                (
-                    SELECT COALESCE(SUM(
-                        CASE
-                            WHEN e.cost_type = 'per_court' THEN e.amount_vnd * s.courts
-                            ELSE e.amount_vnd
-                        END
-                    ), 0)
-                    FROM session_expenses e
-                    WHERE e.session_id = s.id
-                ) as expenses
-            FROM sessions s
-            WHERE s.date >= DATE($1) AND s.cancelled = false
-            ORDER BY s.date DESC
-            LIMIT $2
-            "#
-        )
-        .bind(since_date)
-        .bind(limit)
-        .fetch_all(pool)
-        .await?
+    let rows: Vec<(uuid::Uuid, String, NaiveDate, Option<i64>, Option<i64>)> = sqlx::query_as(
+        r#"
+        SELECT
+            s.id,
+            s.title,
+            s.date,
+            (
+                SELECT COALESCE(SUM(b.price_paid_vnd + b.guest_price_paid_vnd), 0)
+                FROM bookings b
+                WHERE b.session_id = s.id AND b.payment_status = 'confirmed'
+            ) as revenue,
+            (
+                SELECT COALESCE(SUM(
+                    CASE
+                        WHEN e.cost_type = 'per_court' THEN e.amount_vnd * s.courts
+                        ELSE e.amount_vnd
+                    END
+                ), 0)
+                FROM session_expenses e
+                WHERE e.session_id = s.id
+            ) as expenses
+        FROM sessions s
+        WHERE s.cancelled = false
+          AND ($1::timestamptz IS NULL OR s.date >= DATE($1))
+          AND ($2::uuid IS NULL OR s.organizer_id = $2)
+        ORDER BY s.date DESC, s.id DESC
+        LIMIT $3
+        "#
+    )
+    .bind(since)
+    .bind(organizer_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let summaries = rows.into_iter().map(profit_summary).collect();
+
+    Ok(summaries)
+}
+
+/// Profit breakdown for a single session, including cancelled ones
+#[allow(clippy::type_complexity)]
+pub async fn get_session_profit(pool: &PgPool, session_id: uuid::Uuid) -> Result<Option<SessionProfitSummary>> {
+    let row: Option<(uuid::Uuid, String, NaiveDate, Option<i64>, Option<i64>)> = sqlx::query_as(
+        r#"
+        SELECT
+            s.id,
+            s.title,
+            s.date,
+            (
+                SELECT COALESCE(SUM(b.price_paid_vnd + b.guest_price_paid_vnd), 0)
+                FROM bookings b
+                WHERE b.session_id = s.id AND b.payment_status = 'confirmed'
+            ) as revenue,
+            (
+                SELECT COALESCE(SUM(
+                    CASE
+                        WHEN e.cost_type = 'per_court' THEN e.amount_vnd * s.courts
+                        ELSE e.amount_vnd
+                    END
+                ), 0)
+                FROM session_expenses e
+                WHERE e.session_id = s.id
+            ) as expenses
+        FROM sessions s
+        WHERE s.id = $1
+        "#
+    )
+    .bind(session_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(profit_summary))
+}
+
+/// Derive profit and margin from a session's revenue and expense totals
+fn profit_summary(
+    (session_id, title, date, revenue, expenses): (uuid::Uuid, String, NaiveDate, Option<i64>, Option<i64>),
+) -> SessionProfitSummary {
+    let revenue_vnd = revenue.unwrap_or(0);
+    let expenses_vnd = expenses.unwrap_or(0);
+    let profit_vnd = revenue_vnd - expenses_vnd;
+    let profit_margin_percent = if revenue_vnd > 0 {
+        (profit_vnd as f64 / revenue_vnd as f64) * 100.0
+    } else if expenses_vnd > 0 {
+        -100.0 // All expenses, no revenue
     } else {
-        sqlx::query_as(
-            r#"
+        0.0
+    };
+
+    SessionProfitSummary {
+        session_id,
+        title,
+        date,
+        revenue_vnd,
+        expenses_vnd,
+        profit_vnd,
+        profit_margin_percent,
+    }
+}
+
+/// Aggregate past sessions of the last `weeks` weeks by weekday and start hour
+///
+/// Fill is booked slots over capacity; revenue counts confirmed payments.
+/// Both are averaged over sessions that were not cancelled. `today` is the
+/// club's current date; sessions on it are not past yet.
+pub async fn get_slot_fill_analytics(
+    pool: &PgPool,
+    today: NaiveDate,
+    weeks: i32,
+    organizer_id: Option<uuid::Uuid>,
+    location: Option<&str>,
+    time_of_day: &[TimeOfDay],
+) -> Result<Vec<SlotFillStats>> {
+    let time_condition = if time_of_day.is_empty() {
+        String::new()
+    } else {
+        let conditions: Vec<&str> = time_of_day.iter().map(|b| b.hour_condition()).collect();
+        format!("AND ({})", conditions.join(" OR "))
+    };
+
+    let query = format!(
+        r#"
+        WITH past_sessions AS (
             SELECT
-                s.id,
-                s.title,
-                s.date,
+                EXTRACT(ISODOW FROM s.date)::int AS weekday,
+                EXTRACT(HOUR FROM s.time)::int AS hour,
+                s.cancelled,
+                CASE
+                    WHEN s.total_slots > 0
+                    THEN (s.total_slots - s.available_slots)::float8 * 100 / s.total_slots
+                END AS fill_percent,
                 (
                     SELECT COALESCE(SUM(b.price_paid_vnd + b.guest_price_paid_vnd), 0)
                     FROM bookings b
                     WHERE b.session_id = s.id AND b.payment_status = 'confirmed'
-                ) as revenue,
-                (
-                    SELECT COALESCE(SUM(
-                        CASE
-                            WHEN e.cost_type = 'per_court' THEN e.amount_vnd * s.courts
-                            ELSE e.amount_vnd
-                        END
-                    ), 0)
-                    FROM session_expenses e
-                    WHERE e.session_id = s.id
-                ) as expenses
+                ) AS revenue
             FROM sessions s
-            WHERE s.cancelled = false
-            ORDER BY s.date DESC
-            LIMIT $1
-            "#
+            WHERE s.date < $4
+              AND s.date >= $4 - ($1::int * 7)
+              AND ($2::uuid IS NULL OR s.organizer_id = $2)
+              AND ($3::text IS NULL OR LOWER(s.location) LIKE LOWER($3))
+              {}
         )
-        .bind(limit)
-        .fetch_all(pool)
-        .await?
-    };
-
-    let summaries = rows
-        .into_iter()
-        .map(|(session_id, title, date, revenue, expenses)| {
-            let revenue_vnd = revenue.unwrap_or(0);
-            let expenses_vnd = expenses.unwrap_or(0);
-            let profit_vnd = revenue_vnd - expenses_vnd;
-            let profit_margin_percent = if revenue_vnd > 0 {
-                (profit_vnd as f64 / revenue_vnd as f64) * 100.0
-            } else if expenses_vnd > 0 {
-                -100.0 // All expenses, no revenue
-            } else {
-                0.0
-            };
+        SELECT
+            weekday,
+            hour,
+            COUNT(*) AS session_count,
+            COUNT(*) FILTER (WHERE cancelled) AS cancelled_count,
+            AVG(fill_percent) FILTER (WHERE NOT cancelled) AS avg_fill_percent,
+            (AVG(revenue) FILTER (WHERE NOT cancelled))::float8 AS avg_revenue_vnd
+        FROM past_sessions
+        GROUP BY weekday, hour
+        ORDER BY weekday, hour
+        "#,
+        time_condition
+    );
 
-            SessionProfitSummary {
-                session_id,
-                title,
-                date,
-                revenue_vnd,
-                expenses_vnd,
-                profit_vnd,
-                profit_margin_percent,
-            }
-        })
-        .collect();
+    let stats = sqlx::query_as::<_, SlotFillStats>(&query)
+        .bind(weeks)
+        .bind(organizer_id)
+        .bind(location.map(|loc| format!("%{}%", loc)))
+        .bind(today)
+        .fetch_all(pool)
+        .await?;
 
-    Ok(summaries)
+    Ok(stats)
 }
 
 /// Get expense breakdown by category
@@ -676,29 +851,35 @@ pub async fn get_expenses_by_category(
     Ok(categories)
 }
 
-/// Get daily profit data for trend charts
+/// Get profit data for trend charts, grouped into day/week/month buckets
+///
+/// Each point's `date` is the first day of its bucket. `since` of None covers all time;
+/// fixed expenses are prorated through `today`, the club's current date.
 pub async fn get_daily_profit_data(
     pool: &PgPool,
-    since: DateTime<Utc>,
+    since: Option<DateTime<Utc>>,
+    today: NaiveDate,
+    bucket: ProfitBucket,
 ) -> Result<Vec<DailyProfitDataPoint>> {
-    // Get daily revenue
+    // Get revenue per bucket
     let revenue_rows: Vec<(String, i64)> = sqlx::query_as(
         r#"
-        SELECT DATE(created_at)::text as date, COALESCE(SUM(price_paid_vnd + guest_price_paid_vnd), 0) as value
+        SELECT DATE_TRUNC($2, created_at)::date::text as date, COALESCE(SUM(price_paid_vnd + guest_price_paid_vnd), 0) as value
         FROM bookings
-        WHERE payment_status = 'confirmed' AND created_at >= $1
-        GROUP BY DATE(created_at)
-        ORDER BY DATE(created_at)
+        WHERE payment_status = 'confirmed' AND ($1::timestamptz IS NULL OR created_at >= $1)
+        GROUP BY 1
+        ORDER BY 1
         "#
     )
     .bind(since)
+    .bind(bucket.trunc_field())
     .fetch_all(pool)
     .await?;
 
-    // Get daily expenses (based on session date)
+    // Get expenses per bucket (based on session date)
     let expense_rows: Vec<(String, i64)> = sqlx::query_as(
         r#"
-        SELECT s.date::text as date, COALESCE(SUM(
+        SELECT DATE_TRUNC($2, s.date::timestamp)::date::text as date, COALESCE(SUM(
             CASE
                 WHEN e.cost_type = 'per_court' THEN e.amount_vnd * s.courts
                 ELSE e.amount_vnd
@@ -706,25 +887,49 @@ pub async fn get_daily_profit_data(
         ), 0) as value
         FROM session_expenses e
         JOIN sessions s ON e.session_id = s.id
-        WHERE s.date >= DATE($1)
-        GROUP BY s.date
-        ORDER BY s.date
+        WHERE ($1::timestamptz IS NULL OR s.date >= DATE($1))
+        GROUP BY 1
+        ORDER BY 1
         "#
     )
     .bind(since)
+    .bind(bucket.trunc_field())
     .fetch_all(pool)
     .await?;
 
-    // Merge revenue and expense data by date
+    // Prorate fixed expenses into each bucket from the period start (or the
+    // earliest fixed expense) through today
+    let from = since.map(|s| s.date_naive());
+    let fixed = fixed_expenses::list_effective_between(pool, from, today).await?;
+    let mut fixed_rows: Vec<(String, i64)> = Vec::new();
+    if let Some(first_day) = from.or_else(|| fixed.iter().map(|e| e.starts_on).min()) {
+        let mut bucket_start = bucket.start_of(first_day);
+        while bucket_start <= today {
+            let next = bucket.next(bucket_start);
+            let range_start = bucket_start.max(first_day);
+            let range_end = next.pred_opt().expect("valid date").min(today);
+            let amount: i64 = fixed.iter().map(|e| e.prorated_vnd(range_start, range_end)).sum();
+            if amount > 0 {
+                fixed_rows.push((bucket_start.format("%Y-%m-%d").to_string(), amount));
+            }
+            bucket_start = next;
+        }
+    }
+
+    // Merge revenue, variable and fixed expense data by bucket
     use std::collections::HashMap;
-    let mut data_by_date: HashMap<String, (i64, i64)> = HashMap::new();
+    let mut data_by_date: HashMap<String, (i64, i64, i64)> = HashMap::new();
 
     for (date, revenue) in revenue_rows {
-        data_by_date.entry(date).or_insert((0, 0)).0 = revenue;
+        data_by_date.entry(date).or_insert((0, 0, 0)).0 = revenue;
     }
 
     for (date, expenses) in expense_rows {
-        data_by_date.entry(date).or_insert((0, 0)).1 = expenses;
+        data_by_date.entry(date).or_insert((0, 0, 0)).1 = expenses;
+    }
+
+    for (date, fixed_expenses) in fixed_rows {
+        data_by_date.entry(date).or_insert((0, 0, 0)).2 = fixed_expenses;
     }
 
     // Sort by date and convert to result
@@ -733,25 +938,77 @@ pub async fn get_daily_profit_data(
 
     let result = dates
         .into_iter()
-        .map(|(date, (revenue, expenses))| DailyProfitDataPoint {
-            date,
-            revenue,
-            expenses,
-            profit: revenue - expenses,
+        .map(|(date, (revenue, variable_expenses, fixed_expenses))| {
+            let expenses = variable_expenses + fixed_expenses;
+            DailyProfitDataPoint {
+                date,
+                revenue,
+                expenses,
+                fixed_expenses,
+                profit: revenue - expenses,
+            }
         })
         .collect();
 
     Ok(result)
 }
 
+// =============================================================================
+// Booking Funnel
+// =============================================================================
+
+/// Booking outcomes for bookings (and rejected attempts) since a date
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct BookingFunnelStats {
+    pub created: i64,
+    /// Ever confirmed (paid, ticket-covered or comp), including bookings
+    /// later refunded or cancelled
+    pub confirmed: i64,
+    /// Released by the job after the payment deadline passed
+    pub expired_unpaid: i64,
+    pub cancelled_by_user: i64,
+    pub failed_full: i64,
+    pub failed_already_booked: i64,
+    pub failed_limit_reached: i64,
+}
+
+/// Count bookings and rejected booking attempts since `since` (None = all time)
+pub async fn get_booking_funnel(pool: &PgPool, since: Option<DateTime<Utc>>) -> Result<BookingFunnelStats> {
+    let stats = sqlx::query_as::<_, BookingFunnelStats>(
+        r#"
+        WITH b AS (
+            SELECT
+                COUNT(*) AS created,
+                COUNT(*) FILTER (WHERE confirmed_at IS NOT NULL) AS confirmed,
+                COUNT(*) FILTER (WHERE cancellation_reason = 'payment_expired') AS expired_unpaid,
+                COUNT(*) FILTER (WHERE cancellation_reason = 'user') AS cancelled_by_user
+            FROM bookings
+            WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+        ), f AS (
+            SELECT
+                COUNT(*) FILTER (WHERE reason = 'full') AS failed_full,
+                COUNT(*) FILTER (WHERE reason = 'already_booked') AS failed_already_booked,
+                COUNT(*) FILTER (WHERE reason = 'limit_reached') AS failed_limit_reached
+            FROM booking_attempt_failures
+            WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+        )
+        SELECT * FROM b, f
+        "#
+    )
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(stats)
+}
+
 // =============================================================================
 // Paginated Query Functions
 // =============================================================================
 
 /// Parameters for paginated users query
 pub struct UsersQueryParams {
-    pub page: i32,
-    pub per_page: i32,
+    pub pagination: Pagination,
     pub search: Option<String>,
     pub role: Option<String>,
     pub status: Option<String>, // "active", "suspended"
@@ -764,7 +1021,7 @@ pub async fn list_users_paginated(
     pool: &PgPool,
     params: UsersQueryParams,
 ) -> Result<(Vec<UserWithRole>, i64)> {
-    let offset = (params.page - 1) * params.per_page;
+    let Pagination { per_page, offset, .. } = params.pagination;
 
     // Build WHERE clauses
     let mut conditions = vec!["u.deleted_at IS NULL".to_string()];
@@ -780,7 +1037,7 @@ pub async fn list_users_paginated(
     }
 
     if params.role.is_some() {
-        conditions.push(format!("r.name = ${}", bind_idx));
+        conditions.push(format!("COALESCE(r.name, 'user') = ${}", bind_idx));
         bind_idx += 1;
     }
 
@@ -794,12 +1051,12 @@ pub async fn list_users_paginated(
 
     let where_clause = conditions.join(" AND ");
 
-    // Build ORDER BY clause
+    // Build ORDER BY clause; id breaks ties so rows can't shift between pages
     let order_column = match params.sort_by.as_deref() {
         Some("name") => "u.name",
         Some("email") => "u.email",
-        Some("role") => "r.name",
-        Some("created_at") | _ => "u.created_at",
+        Some("role") => "COALESCE(r.name, 'user')",
+        _ => "u.created_at",
     };
     let order_dir = match params.sort_order.as_deref() {
         Some("asc") => "ASC",
@@ -811,7 +1068,7 @@ pub async fn list_users_paginated(
         r#"
         SELECT COUNT(*)
         FROM users u
-        JOIN roles r ON u.role_id = r.id
+        LEFT JOIN roles r ON u.role_id = r.id
         WHERE {}
         "#,
         where_clause
@@ -820,33 +1077,16 @@ pub async fn list_users_paginated(
     // Data query
     let data_query = format!(
         r#"
-        SELECT
-            u.id,
-            u.email,
-            u.name,
-            u.avatar_url,
-            u.phone,
-            u.role_id,
-            u.auth_provider,
-            u.auth_provider_id,
-            u.birthday,
-            u.created_at as user_created_at,
-            u.updated_at as user_updated_at,
-            u.deleted_at as user_deleted_at,
-            u.suspended_at as user_suspended_at,
-            u.suspended_until as user_suspended_until,
-            u.suspension_reason as user_suspension_reason,
-            u.suspended_by as user_suspended_by,
-            r.name as role_name
-        FROM users u
-        JOIN roles r ON u.role_id = r.id
+        {}
         WHERE {}
-        ORDER BY {} {} NULLS LAST
+        ORDER BY {} {} NULLS LAST, u.id {}
         LIMIT ${} OFFSET ${}
         "#,
+        users::USER_WITH_ROLE_SELECT,
         where_clause,
         order_column,
         order_dir,
+        order_dir,
         bind_idx,
         bind_idx + 1
     );
@@ -869,7 +1109,7 @@ pub async fn list_users_paginated(
     if let Some(ref role) = params.role {
         data_builder = data_builder.bind(role);
     }
-    data_builder = data_builder.bind(params.per_page).bind(offset);
+    data_builder = data_builder.bind(per_page).bind(offset);
     let users = data_builder.fetch_all(pool).await?;
 
     Ok((users, total))
@@ -877,8 +1117,7 @@ pub async fn list_users_paginated(
 
 /// Parameters for paginated bookings query
 pub struct BookingsQueryParams {
-    pub page: i32,
-    pub per_page: i32,
+    pub pagination: Pagination,
     pub search: Option<String>,
     pub payment_status: Option<String>,
     pub session_id: Option<uuid::Uuid>,
@@ -891,7 +1130,7 @@ pub async fn list_bookings_paginated(
     pool: &PgPool,
     params: BookingsQueryParams,
 ) -> Result<(Vec<BookingWithDetails>, i64)> {
-    let offset = (params.page - 1) * params.per_page;
+    let Pagination { per_page, offset, .. } = params.pagination;
 
     // Build WHERE clauses
     let mut conditions = vec!["1=1".to_string()];
@@ -917,14 +1156,14 @@ pub async fn list_bookings_paginated(
 
     let where_clause = conditions.join(" AND ");
 
-    // Build ORDER BY clause
+    // Build ORDER BY clause; id breaks ties so rows can't shift between pages
     let order_column = match params.sort_by.as_deref() {
         Some("booking_code") => "b.booking_code",
         Some("user") => "u.name",
         Some("session") => "s.date",
         Some("amount") => "(b.price_paid_vnd + b.guest_price_paid_vnd)",
         Some("status") => "b.payment_status",
-        Some("created_at") | _ => "b.created_at",
+        _ => "b.created_at",
     };
     let order_dir = match params.sort_order.as_deref() {
         Some("asc") => "ASC",
@@ -968,12 +1207,13 @@ pub async fn list_bookings_paginated(
         JOIN users u ON b.user_id = u.id
         JOIN sessions s ON b.session_id = s.id
         WHERE {}
-        ORDER BY {} {} NULLS LAST
+        ORDER BY {} {} NULLS LAST, b.id {}
         LIMIT ${} OFFSET ${}
         "#,
         where_clause,
         order_column,
         order_dir,
+        order_dir,
         bind_idx,
         bind_idx + 1
     );
@@ -1002,7 +1242,7 @@ pub async fn list_bookings_paginated(
     if let Some(session_id) = params.session_id {
         data_builder = data_builder.bind(session_id);
     }
-    data_builder = data_builder.bind(params.per_page).bind(offset);
+    data_builder = data_builder.bind(per_page).bind(offset);
     let bookings = data_builder.fetch_all(pool).await?;
 
     Ok((bookings, total))
@@ -1029,13 +1269,14 @@ pub struct SessionWithOrganizer {
 
 /// Parameters for paginated sessions query
 pub struct SessionsQueryParams {
-    pub page: i32,
-    pub per_page: i32,
+    pub pagination: Pagination,
     pub search: Option<String>,
     pub status: Option<String>, // "upcoming", "past", "cancelled"
     pub organizer_id: Option<uuid::Uuid>,
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
+    /// The club's current date, splitting "upcoming" from "past"
+    pub today: NaiveDate,
 }
 
 /// List sessions with pagination, filtering, and sorting (admin only)
@@ -1043,7 +1284,7 @@ pub async fn list_sessions_paginated(
     pool: &PgPool,
     params: SessionsQueryParams,
 ) -> Result<(Vec<SessionWithOrganizer>, i64)> {
-    let offset = (params.page - 1) * params.per_page;
+    let Pagination { per_page, offset, .. } = params.pagination;
 
     // Build WHERE clauses
     let mut conditions = vec!["1=1".to_string()];
@@ -1057,10 +1298,15 @@ pub async fn list_sessions_paginated(
         bind_idx += 1;
     }
 
+    let mut bind_today = false;
     if let Some(ref status) = params.status {
         match status.as_str() {
-            "upcoming" => conditions.push("s.date >= CURRENT_DATE AND s.cancelled = false".to_string()),
-            "past" => conditions.push("s.date < CURRENT_DATE AND s.cancelled = false".to_string()),
+            "upcoming" | "past" => {
+                let op = if status == "upcoming" { ">=" } else { "<" };
+                conditions.push(format!("s.date {} ${} AND s.cancelled = false", op, bind_idx));
+                bind_idx += 1;
+                bind_today = true;
+            }
             "cancelled" => conditions.push("s.cancelled = true".to_string()),
             _ => {}
         }
@@ -1073,13 +1319,13 @@ pub async fn list_sessions_paginated(
 
     let where_clause = conditions.join(" AND ");
 
-    // Build ORDER BY clause
+    // Build ORDER BY clause; id breaks ties so rows can't shift between pages
     let order_column = match params.sort_by.as_deref() {
         Some("title") => "s.title",
         Some("location") => "s.location",
         Some("slots") => "s.available_slots",
         Some("price") => "s.price_vnd",
-        Some("date") | _ => "s.date",
+        _ => "s.date",
     };
     let order_dir = match params.sort_order.as_deref() {
         Some("asc") => "ASC",
@@ -1118,12 +1364,13 @@ pub async fn list_sessions_paginated(
         FROM sessions s
         JOIN users u ON s.organizer_id = u.id
         WHERE {}
-        ORDER BY {} {} NULLS LAST
+        ORDER BY {} {} NULLS LAST, s.id {}
         LIMIT ${} OFFSET ${}
         "#,
         where_clause,
         order_column,
         order_dir,
+        order_dir,
         bind_idx,
         bind_idx + 1
     );
@@ -1133,6 +1380,9 @@ pub async fn list_sessions_paginated(
     if let Some(ref search) = params.search {
         count_builder = count_builder.bind(search);
     }
+    if bind_today {
+        count_builder = count_builder.bind(params.today);
+    }
     if let Some(organizer_id) = params.organizer_id {
         count_builder = count_builder.bind(organizer_id);
     }
@@ -1143,10 +1393,13 @@ pub async fn list_sessions_paginated(
     if let Some(ref search) = params.search {
         data_builder = data_builder.bind(search);
     }
+    if bind_today {
+        data_builder = data_builder.bind(params.today);
+    }
     if let Some(organizer_id) = params.organizer_id {
         data_builder = data_builder.bind(organizer_id);
     }
-    data_builder = data_builder.bind(params.per_page).bind(offset);
+    data_builder = data_builder.bind(per_page).bind(offset);
     let sessions = data_builder.fetch_all(pool).await?;
 
     Ok((sessions, total))
@@ -1276,31 +1529,7 @@ pub async fn update_user(
     tx.commit().await?;
 
     // Fetch and return updated user with role
-    let query = format!(
-        r#"
-        SELECT
-            u.id,
-            u.email,
-            u.name,
-            u.avatar_url,
-            u.phone,
-            u.role_id,
-            u.auth_provider,
-            u.auth_provider_id,
-            u.birthday,
-            u.created_at as user_created_at,
-            u.updated_at as user_updated_at,
-            u.deleted_at as user_deleted_at,
-            u.suspended_at as user_suspended_at,
-            u.suspended_until as user_suspended_until,
-            u.suspension_reason as user_suspension_reason,
-            u.suspended_by as user_suspended_by,
-            r.name as role_name
-        FROM users u
-        JOIN roles r ON u.role_id = r.id
-        WHERE u.id = $1
-        "#
-    );
+    let query = format!("{} WHERE u.id = $1", users::USER_WITH_ROLE_SELECT);
 
     let user = sqlx::query_as::<_, UserWithRole>(&query)
         .bind(user_id)
@@ -1366,7 +1595,7 @@ pub async fn delete_user(
     sqlx::query(
         r#"
         UPDATE bookings
-        SET payment_status = 'cancelled', cancelled_at = NOW()
+        SET payment_status = 'cancelled', cancelled_at = NOW(), cancellation_reason = 'account_deleted'
         WHERE user_id = $1 AND payment_status = 'pending'
         "#
     )
@@ -1395,6 +1624,302 @@ pub async fn delete_user(
     Ok(())
 }
 
+/// Why `merge_users` refused to merge two accounts
+#[derive(Debug, thiserror::Error)]
+pub enum UserMergeError {
+    #[error("User not found or already deleted")]
+    UserNotFound,
+    #[error("Both accounts have active Stripe subscriptions")]
+    BothStripeActive,
+    #[error("Both accounts have an active booking for the same session")]
+    SameSessionBooked,
+}
+
+/// What moved from the duplicate account in `merge_users`
+#[derive(Debug, Clone, Default)]
+pub struct UserMergeSummary {
+    pub bookings_moved: u64,
+    pub ticket_transactions_moved: u64,
+    pub bonus_tickets_moved: u64,
+    pub waitlist_entries_moved: u64,
+    /// Whether the duplicate's notification preferences replaced the defaults
+    /// (only when the primary account had none of its own)
+    pub notification_preferences_moved: bool,
+    /// Ticket balance added to the primary account's subscription
+    pub tickets_moved: i32,
+    /// Whether the primary account took over the duplicate's subscription
+    pub subscription_moved: bool,
+}
+
+/// Merge a duplicate account into the primary one (admin only)
+///
+/// Reassigns the duplicate's bookings, ticket history, bonus tickets, waitlist
+/// entries and notification preferences, adds its ticket balance to the
+/// primary's subscription (taking the duplicate's subscription over if the
+/// primary has none or only an inactive one), soft-deletes the duplicate and
+/// records a staff note on the primary. Where both accounts have a birthday
+/// bonus for the same year, a waitlist entry for the same session or
+/// notification preferences, the primary's is kept.
+///
+/// Fails with a [`UserMergeError`] if either account is gone, both have an
+/// active Stripe subscription or both have an active booking for the same
+/// session.
+pub async fn merge_users(
+    pool: &PgPool,
+    primary_id: uuid::Uuid,
+    duplicate_id: uuid::Uuid,
+    admin_id: uuid::Uuid,
+) -> Result<UserMergeSummary> {
+    let mut tx = pool.begin().await?;
+
+    // Lock both users in id order
+    let users: Vec<(uuid::Uuid, String)> = sqlx::query_as(
+        "SELECT id, email FROM users WHERE id = ANY($1) AND deleted_at IS NULL ORDER BY id FOR UPDATE"
+    )
+    .bind([primary_id, duplicate_id])
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let duplicate_email = users
+        .iter()
+        .find(|(id, _)| *id == duplicate_id)
+        .map(|(_, email)| email.clone());
+    let Some(duplicate_email) = duplicate_email.filter(|_| users.len() == 2) else {
+        return Err(UserMergeError::UserNotFound.into());
+    };
+
+    // Subscriptions are locked in the same (user id) order
+    let (primary_sub, duplicate_sub) = if primary_id < duplicate_id {
+        let primary = subscriptions::find_by_user_id_for_update(&mut tx, primary_id).await?;
+        (primary, subscriptions::find_by_user_id_for_update(&mut tx, duplicate_id).await?)
+    } else {
+        let duplicate = subscriptions::find_by_user_id_for_update(&mut tx, duplicate_id).await?;
+        (subscriptions::find_by_user_id_for_update(&mut tx, primary_id).await?, duplicate)
+    };
+
+    let is_stripe_active = |sub: &Option<crate::models::Subscription>| {
+        sub.as_ref().is_some_and(|s| s.is_active() && s.stripe_subscription_id.is_some())
+    };
+    let primary_stripe_active = is_stripe_active(&primary_sub);
+    let duplicate_stripe_active = is_stripe_active(&duplicate_sub);
+    if primary_stripe_active && duplicate_stripe_active {
+        return Err(UserMergeError::BothStripeActive.into());
+    }
+
+    let mut summary = UserMergeSummary {
+        bookings_moved: sqlx::query("UPDATE bookings SET user_id = $1, updated_at = NOW() WHERE user_id = $2")
+            .bind(primary_id)
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                if bookings::is_duplicate_active_booking(&e) {
+                    anyhow::Error::from(UserMergeError::SameSessionBooked)
+                } else {
+                    e.into()
+                }
+            })?
+            .rows_affected(),
+        ticket_transactions_moved: sqlx::query("UPDATE ticket_transactions SET user_id = $1 WHERE user_id = $2")
+            .bind(primary_id)
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected(),
+        // Birthday bonuses are unique per user and year; a year the primary
+        // already has stays on the duplicate
+        bonus_tickets_moved: sqlx::query(
+            r#"
+            UPDATE bonus_tickets b
+            SET user_id = $1
+            WHERE b.user_id = $2
+              AND NOT (
+                  b.bonus_type = 'birthday'
+                  AND EXISTS (
+                      SELECT 1 FROM bonus_tickets p
+                      WHERE p.user_id = $1 AND p.bonus_type = 'birthday' AND p.year = b.year
+                  )
+              )
+            "#
+        )
+        .bind(primary_id)
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected(),
+        // Waitlist entries are unique per session and user; where the primary
+        // already queues for a session its own entry is kept
+        waitlist_entries_moved: sqlx::query(
+            r#"
+            UPDATE waitlist_entries w
+            SET user_id = $1
+            WHERE w.user_id = $2
+              AND NOT EXISTS (
+                  SELECT 1 FROM waitlist_entries p
+                  WHERE p.user_id = $1 AND p.session_id = w.session_id
+              )
+            "#
+        )
+        .bind(primary_id)
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected(),
+        notification_preferences_moved: sqlx::query(
+            r#"
+            UPDATE notification_preferences
+            SET user_id = $1, updated_at = NOW()
+            WHERE user_id = $2
+              AND NOT EXISTS (SELECT 1 FROM notification_preferences WHERE user_id = $1)
+            "#
+        )
+        .bind(primary_id)
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0,
+        ..Default::default()
+    };
+
+    // The soft-deleted duplicate mustn't be promoted from the waitlist
+    sqlx::query("DELETE FROM waitlist_entries WHERE user_id = $1")
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?;
+
+    match (primary_sub, duplicate_sub) {
+        (_, None) => {}
+        (None, Some(duplicate_sub)) => {
+            // subscriptions.user_id is unique, so the row itself moves over
+            sqlx::query("UPDATE subscriptions SET user_id = $1, updated_at = NOW() WHERE id = $2")
+                .bind(primary_id)
+                .bind(duplicate_sub.id)
+                .execute(&mut *tx)
+                .await?;
+            summary.subscription_moved = true;
+            summary.tickets_moved = duplicate_sub.tickets_remaining;
+        }
+        (Some(primary_sub), Some(duplicate_sub)) => {
+            if duplicate_stripe_active {
+                // The primary row takes over the live Stripe subscription so
+                // renewal webhooks land on the primary account
+                sqlx::query(
+                    r#"
+                    UPDATE subscriptions
+                    SET status = $2,
+                        stripe_subscription_id = $3,
+                        stripe_customer_id = $4,
+                        current_period_start = $5,
+                        current_period_end = $6,
+                        auto_renew = $7,
+                        billing_interval = $8,
+                        billing_interval_count = $9,
+                        updated_at = NOW()
+                    WHERE id = $1
+                    "#
+                )
+                .bind(primary_sub.id)
+                .bind(&duplicate_sub.status)
+                .bind(&duplicate_sub.stripe_subscription_id)
+                .bind(&duplicate_sub.stripe_customer_id)
+                .bind(duplicate_sub.current_period_start)
+                .bind(duplicate_sub.current_period_end)
+                .bind(duplicate_sub.auto_renew)
+                .bind(&duplicate_sub.billing_interval)
+                .bind(duplicate_sub.billing_interval_count)
+                .execute(&mut *tx)
+                .await?;
+                summary.subscription_moved = true;
+            }
+
+            // The duplicate's row is emptied and detached from Stripe
+            sqlx::query(
+                r#"
+                UPDATE subscriptions
+                SET status = 'cancelled',
+                    tickets_remaining = 0,
+                    stripe_subscription_id = NULL,
+                    auto_renew = false,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(duplicate_sub.id)
+            .execute(&mut *tx)
+            .await?;
+
+            if duplicate_sub.tickets_remaining > 0 {
+                let (new_balance,): (i32,) = sqlx::query_as(
+                    r#"
+                    UPDATE subscriptions
+                    SET tickets_remaining = tickets_remaining + $2, updated_at = NOW()
+                    WHERE id = $1
+                    RETURNING tickets_remaining
+                    "#
+                )
+                .bind(primary_sub.id)
+                .bind(duplicate_sub.tickets_remaining)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                ticket_transactions::create(
+                    &mut tx,
+                    primary_id,
+                    Some(primary_sub.id),
+                    None,
+                    transaction_types::MERGED,
+                    duplicate_sub.tickets_remaining,
+                    new_balance,
+                    Some(&format!("Merged from duplicate account {}", duplicate_email)),
+                    Some(admin_id),
+                )
+                .await?;
+                summary.tickets_moved = duplicate_sub.tickets_remaining;
+            }
+        }
+    }
+
+    // Soft delete the duplicate, clearing PII as in `delete_user`
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET deleted_at = NOW(),
+            name = NULL,
+            avatar_url = NULL,
+            phone = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(duplicate_id)
+    .execute(&mut *tx)
+    .await?;
+
+    admin_notes::create_note_in_tx(
+        &mut tx,
+        primary_id,
+        admin_id,
+        &format!(
+            "Merged duplicate account {} ({}): {} bookings, {} ticket transactions, {} bonus tickets, \
+             {} waitlist entries, {} tickets{}",
+            duplicate_email,
+            duplicate_id,
+            summary.bookings_moved,
+            summary.ticket_transactions_moved,
+            summary.bonus_tickets_moved,
+            summary.waitlist_entries_moved,
+            summary.tickets_moved,
+            if summary.subscription_moved { ", subscription taken over" } else { "" }
+        ),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(summary)
+}
+
 // =============================================================================
 // Admin Booking Edit Operations (existing)
 // =============================================================================
@@ -1410,7 +1935,8 @@ pub async fn update_booking(
     // Start a transaction for atomic updates
     let mut tx = pool.begin().await?;
 
-    // Get current booking with lock
+    // Get current booking with lock; the booking is locked before its session
+    // (lock order documented in `loafy_core::locking`)
     let current: (i32, uuid::Uuid) = sqlx::query_as(
         "SELECT guest_count, session_id FROM bookings WHERE id = $1 FOR UPDATE"
     )
@@ -1515,3 +2041,97 @@ pub async fn update_booking(
         .await?
         .ok_or_else(|| anyhow::anyhow!("Failed to fetch updated booking"))
 }
+
+/// A verified booking whose recorded received amount differs from its price
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PaymentDiscrepancy {
+    pub booking_id: uuid::Uuid,
+    pub booking_code: String,
+    pub user_id: uuid::Uuid,
+    pub user_email: String,
+    pub user_name: Option<String>,
+    pub session_id: uuid::Uuid,
+    pub session_title: String,
+    pub session_date: NaiveDate,
+    pub expected_vnd: i32,
+    pub received_vnd: i32,
+    pub verified_by: Option<uuid::Uuid>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+/// Verified bookings where the amount received didn't match the price, newest first
+///
+/// `since` filters on the verification time; None covers all time.
+pub async fn get_payment_discrepancies(
+    pool: &PgPool,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<PaymentDiscrepancy>> {
+    let rows = sqlx::query_as::<_, PaymentDiscrepancy>(
+        r#"
+        SELECT
+            b.id as booking_id,
+            b.booking_code,
+            b.user_id,
+            u.email as user_email,
+            u.name as user_name,
+            b.session_id,
+            s.title as session_title,
+            s.date as session_date,
+            b.price_paid_vnd + b.guest_price_paid_vnd as expected_vnd,
+            b.received_amount_vnd as received_vnd,
+            b.verified_by,
+            b.verified_at
+        FROM bookings b
+        JOIN users u ON u.id = b.user_id
+        JOIN sessions s ON s.id = b.session_id
+        WHERE b.received_amount_vnd IS NOT NULL
+          AND b.received_amount_vnd <> b.price_paid_vnd + b.guest_price_paid_vnd
+          AND ($1::timestamptz IS NULL OR b.verified_at >= $1)
+        ORDER BY b.verified_at DESC
+        "#
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// What suspending a user would touch: their active bookings and organized sessions from `today` on
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SuspensionImpact {
+    pub upcoming_confirmed_bookings: i64,
+    pub pending_bookings: i64,
+    pub organized_sessions: i64,
+}
+
+/// Count a user's upcoming active bookings and the upcoming sessions they organize
+pub async fn get_suspension_impact(
+    pool: &PgPool,
+    user_id: uuid::Uuid,
+    today: NaiveDate,
+) -> Result<SuspensionImpact> {
+    let impact = sqlx::query_as::<_, SuspensionImpact>(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE b.payment_status = 'confirmed') as upcoming_confirmed_bookings,
+            COUNT(*) FILTER (WHERE b.payment_status = 'pending') as pending_bookings,
+            (
+                SELECT COUNT(*) FROM sessions
+                WHERE organizer_id = $1 AND cancelled = false AND date >= $2
+            ) as organized_sessions
+        FROM bookings b
+        JOIN sessions s ON s.id = b.session_id
+        WHERE b.user_id = $1
+          AND b.cancelled_at IS NULL
+          AND s.cancelled = false
+          AND s.date >= $2
+        "#
+    )
+    .bind(user_id)
+    .bind(today)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(impact)
+}
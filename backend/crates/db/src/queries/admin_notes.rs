@@ -0,0 +1,77 @@
+use crate::models::{AdminNote, AdminNoteWithAuthor};
+use anyhow::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Append a staff note to a user
+pub async fn create_note(
+    pool: &PgPool,
+    user_id: Uuid,
+    author_id: Uuid,
+    note: &str,
+) -> Result<AdminNote> {
+    let mut tx = pool.begin().await?;
+    let note = create_note_in_tx(&mut tx, user_id, author_id, note).await?;
+    tx.commit().await?;
+
+    Ok(note)
+}
+
+/// Append a staff note to a user within a transaction
+pub async fn create_note_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    author_id: Uuid,
+    note: &str,
+) -> Result<AdminNote> {
+    let note = sqlx::query_as::<_, AdminNote>(
+        r#"
+        INSERT INTO admin_notes (user_id, author_id, note)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#
+    )
+    .bind(user_id)
+    .bind(author_id)
+    .bind(note)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(note)
+}
+
+/// List all notes for a user, newest first
+pub async fn list_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<AdminNoteWithAuthor>> {
+    let notes = sqlx::query_as::<_, AdminNoteWithAuthor>(
+        r#"
+        SELECT n.id, n.user_id, n.author_id, a.name as author_name, n.note, n.created_at
+        FROM admin_notes n
+        JOIN users a ON a.id = n.author_id
+        WHERE n.user_id = $1
+        ORDER BY n.created_at DESC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(notes)
+}
+
+/// Count notes for a batch of users
+pub async fn count_for_users(pool: &PgPool, user_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+    let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+        r#"
+        SELECT user_id, COUNT(*)
+        FROM admin_notes
+        WHERE user_id = ANY($1)
+        GROUP BY user_id
+        "#
+    )
+    .bind(user_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
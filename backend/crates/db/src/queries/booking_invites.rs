@@ -0,0 +1,139 @@
+use crate::models::BookingInvite;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// Create an invite for one of a booking's guest slots
+pub async fn create(
+    tx: &mut Transaction<'_, Postgres>,
+    booking_id: Uuid,
+    created_by: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<BookingInvite> {
+    let invite = sqlx::query_as::<_, BookingInvite>(
+        r#"
+        INSERT INTO booking_invites (booking_id, created_by, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#
+    )
+    .bind(booking_id)
+    .bind(created_by)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(invite)
+}
+
+/// Count a booking's invites holding a guest slot: redeemed, or still redeemable
+pub async fn count_claimed_for_booking(tx: &mut Transaction<'_, Postgres>, booking_id: Uuid) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM booking_invites
+        WHERE booking_id = $1
+          AND (redeemed_at IS NOT NULL OR expires_at > NOW())
+        "#
+    )
+    .bind(booking_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Count a booking's redeemed invites within a transaction
+pub async fn count_redeemed_for_booking(tx: &mut Transaction<'_, Postgres>, booking_id: Uuid) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM booking_invites WHERE booking_id = $1 AND redeemed_at IS NOT NULL"
+    )
+    .bind(booking_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Find an invite by token hash, locking it for update
+pub async fn find_by_token_hash_for_update(
+    tx: &mut Transaction<'_, Postgres>,
+    token_hash: &str,
+) -> Result<Option<BookingInvite>> {
+    let invite = sqlx::query_as::<_, BookingInvite>(
+        "SELECT * FROM booking_invites WHERE token_hash = $1 FOR UPDATE"
+    )
+    .bind(token_hash)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(invite)
+}
+
+/// Check if the user joined an active booking for the session as a guest
+pub async fn is_guest_in_session(pool: &PgPool, user_id: Uuid, session_id: Uuid) -> Result<bool> {
+    let exists: (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM booking_invites i
+            JOIN bookings b ON b.id = i.booking_id
+            WHERE i.redeemed_by = $1
+              AND b.session_id = $2
+              AND b.cancelled_at IS NULL
+        )
+        "#
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists.0)
+}
+
+/// Check if the user already joined an active booking for the session as a guest,
+/// within a transaction
+pub async fn is_guest_in_session_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<bool> {
+    let exists: (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM booking_invites i
+            JOIN bookings b ON b.id = i.booking_id
+            WHERE i.redeemed_by = $1
+              AND b.session_id = $2
+              AND b.cancelled_at IS NULL
+        )
+        "#
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(exists.0)
+}
+
+/// Mark an invite redeemed by `user_id`
+pub async fn mark_redeemed(tx: &mut Transaction<'_, Postgres>, id: Uuid, user_id: Uuid) -> Result<BookingInvite> {
+    let invite = sqlx::query_as::<_, BookingInvite>(
+        r#"
+        UPDATE booking_invites
+        SET redeemed_by = $2,
+            redeemed_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(invite)
+}
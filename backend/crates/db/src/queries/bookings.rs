@@ -1,7 +1,9 @@
 use crate::models::{Booking, BookingWithSession};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use loafy_types::{Cursor, Pagination};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Find booking by ID (basic, without session info)
@@ -16,6 +18,51 @@ pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Booking>> {
     Ok(booking)
 }
 
+/// Find booking by ID with FOR UPDATE lock
+pub async fn find_by_id_for_update(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+) -> Result<Option<Booking>> {
+    let booking = sqlx::query_as::<_, Booking>(
+        "SELECT * FROM bookings WHERE id = $1 FOR UPDATE"
+    )
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Lock several bookings FOR UPDATE, in ascending id order
+pub async fn find_by_ids_for_update(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ids: &[Uuid],
+) -> Result<Vec<Booking>> {
+    let bookings = sqlx::query_as::<_, Booking>(
+        "SELECT * FROM bookings WHERE id = ANY($1) ORDER BY id FOR UPDATE"
+    )
+    .bind(ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(bookings)
+}
+
+/// Find booking by Stripe PaymentIntent ID and lock it (SELECT FOR UPDATE)
+pub async fn find_by_stripe_payment_id_for_update(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    stripe_payment_id: &str,
+) -> Result<Option<Booking>> {
+    let booking = sqlx::query_as::<_, Booking>(
+        "SELECT * FROM bookings WHERE stripe_payment_id = $1 FOR UPDATE"
+    )
+    .bind(stripe_payment_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(booking)
+}
+
 /// Find booking by ID with session details
 pub async fn find_by_id_with_session(pool: &PgPool, id: Uuid) -> Result<Option<BookingWithSession>> {
     let booking = sqlx::query_as::<_, BookingWithSession>(
@@ -55,6 +102,107 @@ pub async fn find_by_code(pool: &PgPool, code: &str) -> Result<Option<Booking>>
     Ok(booking)
 }
 
+/// What door staff see for a booking code: no contact details
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DoorCheck {
+    pub booking_id: Uuid,
+    pub booking_code: String,
+    pub user_name: Option<String>,
+    pub guest_count: i32,
+    pub payment_status: String,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+}
+
+/// Look up a booking code within a session (case-insensitive)
+pub async fn find_for_door_check(pool: &PgPool, session_id: Uuid, code: &str) -> Result<Option<DoorCheck>> {
+    let check = sqlx::query_as::<_, DoorCheck>(
+        r#"
+        SELECT
+            b.id as booking_id,
+            b.booking_code,
+            u.name as user_name,
+            b.guest_count,
+            b.payment_status,
+            b.cancelled_at,
+            b.checked_in_at
+        FROM bookings b
+        JOIN users u ON u.id = b.user_id
+        WHERE b.session_id = $1 AND UPPER(b.booking_code) = UPPER($2)
+        "#
+    )
+    .bind(session_id)
+    .bind(code.trim())
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(check)
+}
+
+/// Mark a booking as checked in; returns None if it already was
+pub async fn check_in(pool: &PgPool, id: Uuid) -> Result<Option<DateTime<Utc>>> {
+    let checked_in_at: Option<(DateTime<Utc>,)> = sqlx::query_as(
+        r#"
+        UPDATE bookings
+        SET checked_in_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND checked_in_at IS NULL
+        RETURNING checked_in_at
+        "#
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(checked_in_at.map(|(at,)| at))
+}
+
+/// List a user's confirmed, active bookings for sessions from `today` on, soonest first
+pub async fn list_upcoming_confirmed(pool: &PgPool, user_id: Uuid, today: NaiveDate) -> Result<Vec<Booking>> {
+    let bookings = sqlx::query_as::<_, Booking>(
+        r#"
+        SELECT b.* FROM bookings b
+        JOIN sessions s ON s.id = b.session_id
+        WHERE b.user_id = $1
+          AND b.payment_status = 'confirmed'
+          AND b.cancelled_at IS NULL
+          AND s.cancelled = false
+          AND s.date >= $2
+        ORDER BY s.date, s.time
+        "#
+    )
+    .bind(user_id)
+    .bind(today)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bookings)
+}
+
+/// List a user's pending and confirmed bookings for sessions that haven't
+/// started yet, soonest first
+///
+/// `club_now` is the club's wall-clock time, as session dates and times are.
+pub async fn list_upcoming_active(pool: &PgPool, user_id: Uuid, club_now: NaiveDateTime) -> Result<Vec<Booking>> {
+    let bookings = sqlx::query_as::<_, Booking>(
+        r#"
+        SELECT b.* FROM bookings b
+        JOIN sessions s ON s.id = b.session_id
+        WHERE b.user_id = $1
+          AND b.payment_status IN ('pending', 'confirmed')
+          AND b.cancelled_at IS NULL
+          AND s.cancelled = false
+          AND s.date + s.time > $2
+        ORDER BY s.date, s.time
+        "#
+    )
+    .bind(user_id)
+    .bind(club_now)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bookings)
+}
+
 /// List user's bookings
 pub async fn list_user_bookings(
     pool: &PgPool,
@@ -74,20 +222,38 @@ pub async fn list_user_bookings(
     Ok(bookings)
 }
 
+/// Filters for listing a user's bookings
+#[derive(Debug, Clone, Default)]
+pub struct UserBookingsFilter<'a> {
+    /// Only bookings with this payment_status
+    pub status: Option<&'a str>,
+    /// Only active bookings for sessions on or after this date (the club's today)
+    pub upcoming_from: Option<NaiveDate>,
+}
+
 /// List user's bookings with pagination
 pub async fn list_user_bookings_paginated(
     pool: &PgPool,
     user_id: Uuid,
-    page: i32,
-    per_page: i32,
+    filter: UserBookingsFilter<'_>,
+    pagination: Pagination,
 ) -> Result<(Vec<BookingWithSession>, i64)> {
-    let offset = (page - 1) * per_page;
+    let Pagination { per_page, offset, .. } = pagination;
 
     // Get total count
     let total: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM bookings WHERE user_id = $1"
+        r#"
+        SELECT COUNT(*)
+        FROM bookings b
+        JOIN sessions s ON s.id = b.session_id
+        WHERE b.user_id = $1
+          AND ($2::text IS NULL OR b.payment_status = $2)
+          AND ($3::date IS NULL OR (s.date >= $3 AND b.cancelled_at IS NULL))
+        "#
     )
     .bind(user_id)
+    .bind(filter.status)
+    .bind(filter.upcoming_from)
     .fetch_one(pool)
     .await?;
 
@@ -108,11 +274,15 @@ pub async fn list_user_bookings_paginated(
         FROM bookings b
         JOIN sessions s ON s.id = b.session_id
         WHERE b.user_id = $1
-        ORDER BY b.created_at DESC
-        LIMIT $2 OFFSET $3
+          AND ($2::text IS NULL OR b.payment_status = $2)
+          AND ($3::date IS NULL OR (s.date >= $3 AND b.cancelled_at IS NULL))
+        ORDER BY b.created_at DESC, b.id DESC
+        LIMIT $4 OFFSET $5
         "#
     )
     .bind(user_id)
+    .bind(filter.status)
+    .bind(filter.upcoming_from)
     .bind(per_page)
     .bind(offset)
     .fetch_all(pool)
@@ -121,6 +291,49 @@ pub async fn list_user_bookings_paginated(
     Ok((bookings, total.0))
 }
 
+/// List user's bookings strictly after a keyset cursor, newest first
+pub async fn list_user_bookings_after(
+    pool: &PgPool,
+    user_id: Uuid,
+    filter: UserBookingsFilter<'_>,
+    after: Option<Cursor>,
+    limit: i32,
+) -> Result<Vec<BookingWithSession>> {
+    let bookings = sqlx::query_as::<_, BookingWithSession>(
+        r#"
+        SELECT
+            b.id, b.user_id, b.session_id, b.booking_code, b.guest_count,
+            b.tickets_used, b.discount_applied, b.price_paid_vnd, b.guest_price_paid_vnd,
+            b.payment_method, b.payment_status, b.verification_status,
+            b.payment_deadline, b.cancelled_at, b.created_at,
+            s.title as session_title,
+            s.date as session_date,
+            s.time as session_time,
+            s.end_time as session_end_time,
+            s.location as session_location,
+            COALESCE(s.price_vnd, 100000) as session_price_vnd
+        FROM bookings b
+        JOIN sessions s ON s.id = b.session_id
+        WHERE b.user_id = $1
+          AND ($2::text IS NULL OR b.payment_status = $2)
+          AND ($3::date IS NULL OR (s.date >= $3 AND b.cancelled_at IS NULL))
+          AND ($4::timestamptz IS NULL OR (b.created_at, b.id) < ($4, $5))
+        ORDER BY b.created_at DESC, b.id DESC
+        LIMIT $6
+        "#
+    )
+    .bind(user_id)
+    .bind(filter.status)
+    .bind(filter.upcoming_from)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bookings)
+}
+
 /// List bookings for a session
 pub async fn list_session_bookings(
     pool: &PgPool,
@@ -141,6 +354,130 @@ pub async fn list_session_bookings(
     Ok(bookings)
 }
 
+/// Count a user's non-cancelled bookings created since the given time
+pub async fn count_active_bookings_in_window(
+    pool: &PgPool,
+    user_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM bookings
+        WHERE user_id = $1
+          AND created_at >= $2
+          AND cancelled_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Count a user's active bookings created since `since` within a transaction,
+/// after taking a per-user lock held until the transaction ends
+///
+/// Two concurrent bookings by the same user then count one after the other,
+/// so neither can slip past the weekly limit on a stale count. The advisory
+/// lock keeps this out of the row lock ordering in `loafy_core::locking`.
+pub async fn count_active_bookings_in_window_locked(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<i64> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended('booking_quota:' || $1::text, 0))")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM bookings
+        WHERE user_id = $1
+          AND created_at >= $2
+          AND cancelled_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Lifetime booking totals for a user
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct UserBookingTotals {
+    /// Confirmed, non-cancelled bookings
+    pub confirmed_bookings: i64,
+    /// Confirmed bookings the user checked in for
+    pub sessions_attended: i64,
+    /// Amount paid across confirmed bookings, including guests
+    pub total_spent_vnd: i64,
+}
+
+/// Get a user's lifetime booking totals
+pub async fn get_user_booking_totals(pool: &PgPool, user_id: Uuid) -> Result<UserBookingTotals> {
+    let totals = sqlx::query_as::<_, UserBookingTotals>(
+        r#"
+        SELECT
+            COUNT(*) AS confirmed_bookings,
+            COUNT(*) FILTER (WHERE b.checked_in_at IS NOT NULL) AS sessions_attended,
+            COALESCE(SUM(b.price_paid_vnd + b.guest_price_paid_vnd), 0)::BIGINT AS total_spent_vnd
+        FROM bookings b
+        JOIN sessions s ON b.session_id = s.id
+        WHERE b.user_id = $1
+          AND b.payment_status = 'confirmed'
+          AND b.cancelled_at IS NULL
+          AND s.cancelled = false
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(totals)
+}
+
+/// List the (Monday) start of each week in which the user checked in to a session, newest first
+pub async fn list_attended_weeks(pool: &PgPool, user_id: Uuid) -> Result<Vec<NaiveDate>> {
+    let weeks: Vec<(NaiveDate,)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT DATE_TRUNC('week', s.date)::DATE AS week
+        FROM bookings b
+        JOIN sessions s ON b.session_id = s.id
+        WHERE b.user_id = $1
+          AND b.payment_status = 'confirmed'
+          AND b.cancelled_at IS NULL
+          AND s.cancelled = false
+          AND b.checked_in_at IS NOT NULL
+        ORDER BY week DESC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(weeks.into_iter().map(|(week,)| week).collect())
+}
+
+/// Partial unique index allowing one active booking per user and session
+pub const ONE_ACTIVE_PER_SESSION_INDEX: &str = "idx_bookings_one_active_per_session";
+
+/// Whether an insert or update failed because the user already has an
+/// active booking for the session
+pub fn is_duplicate_active_booking(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(db) => {
+            db.is_unique_violation() && db.constraint() == Some(ONE_ACTIVE_PER_SESSION_INDEX)
+        }
+        _ => false,
+    }
+}
+
 /// Check if user has an active booking for a session
 pub async fn has_active_booking_for_session(
     pool: &PgPool,
@@ -163,6 +500,54 @@ pub async fn has_active_booking_for_session(
     Ok(count.0 > 0)
 }
 
+/// Check if user has an active booking for a session, within a transaction
+pub async fn has_active_booking_for_session_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<bool> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM bookings
+        WHERE user_id = $1
+          AND session_id = $2
+          AND cancelled_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count.0 > 0)
+}
+
+/// Payment status of a user's active booking in each of the given sessions
+///
+/// Sessions the user hasn't booked are absent from the map.
+pub async fn active_statuses_for_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+    session_ids: &[Uuid],
+) -> Result<HashMap<Uuid, String>> {
+    let rows: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (session_id) session_id, payment_status
+        FROM bookings
+        WHERE user_id = $1
+          AND session_id = ANY($2)
+          AND cancelled_at IS NULL
+        ORDER BY session_id, created_at DESC
+        "#
+    )
+    .bind(user_id)
+    .bind(session_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
 /// Cancel booking
 pub async fn cancel_booking(pool: &PgPool, id: Uuid) -> Result<Booking> {
     let booking = sqlx::query_as::<_, Booking>(
@@ -182,6 +567,205 @@ pub async fn cancel_booking(pool: &PgPool, id: Uuid) -> Result<Booking> {
     Ok(booking)
 }
 
+/// List a session's non-cancelled bookings, locking them for update
+pub async fn list_active_for_session_for_update(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    session_id: Uuid,
+) -> Result<Vec<Booking>> {
+    let bookings = sqlx::query_as::<_, Booking>(
+        r#"
+        SELECT * FROM bookings
+        WHERE session_id = $1
+          AND cancelled_at IS NULL
+        ORDER BY id
+        FOR UPDATE
+        "#
+    )
+    .bind(session_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(bookings)
+}
+
+/// Count a session's active bookings, within a transaction
+pub async fn count_active_for_session_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    session_id: Uuid,
+) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM bookings WHERE session_id = $1 AND cancelled_at IS NULL"
+    )
+    .bind(session_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Move booking to another session, remembering the session it came from
+pub async fn move_to_session(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    target_session_id: Uuid,
+) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET rescheduled_from = session_id,
+            session_id = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(target_session_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Cancel booking within a transaction, recording why (see `cancellation_reasons`)
+pub async fn cancel_booking_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    reason: &str,
+) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET cancelled_at = NOW(),
+            cancellation_reason = $2,
+            payment_status = 'cancelled',
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(reason)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Set a booking's payment status
+pub async fn set_payment_status(pool: &PgPool, id: Uuid, status: &str) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET payment_status = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(status)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Set a booking's payment status within a transaction
+pub async fn set_payment_status_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    status: &str,
+) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET payment_status = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(status)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Set a pending booking's payment deadline
+pub async fn set_payment_deadline(
+    pool: &PgPool,
+    id: Uuid,
+    payment_deadline: DateTime<Utc>,
+) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET payment_deadline = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(payment_deadline)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Keep a pending booking alive while the user completes card authentication
+///
+/// Records the PaymentIntent and moves the payment deadline to at least
+/// `hold_until`. Returns None if the booking is no longer pending.
+pub async fn hold_for_authentication(
+    pool: &PgPool,
+    id: Uuid,
+    stripe_payment_id: &str,
+    hold_until: DateTime<Utc>,
+) -> Result<Option<Booking>> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET stripe_payment_id = $2,
+            payment_deadline = GREATEST(payment_deadline, $3),
+            updated_at = NOW()
+        WHERE id = $1
+          AND payment_status = 'pending'
+          AND cancelled_at IS NULL
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(stripe_payment_id)
+    .bind(hold_until)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// List user's pending (unpaid, not cancelled) bookings
+pub async fn list_user_pending_bookings(pool: &PgPool, user_id: Uuid) -> Result<Vec<Booking>> {
+    let bookings = sqlx::query_as::<_, Booking>(
+        r#"
+        SELECT * FROM bookings
+        WHERE user_id = $1
+          AND payment_status = 'pending'
+          AND cancelled_at IS NULL
+        ORDER BY id
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bookings)
+}
+
 /// Update payment status
 pub async fn update_payment_status(
     pool: &PgPool,
@@ -227,3 +811,168 @@ pub async fn find_unpaid_expired_bookings(
 
     Ok(bookings)
 }
+
+/// Store the uploaded payment screenshot path and mark the booking as awaiting verification
+pub async fn set_payment_screenshot(pool: &PgPool, id: Uuid, path: &str) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET payment_screenshot_url = $2,
+            verification_status = 'pending',
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(path)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Update verification status, optionally updating payment status at the same time
+pub async fn update_verification_status(
+    pool: &PgPool,
+    id: Uuid,
+    verification_status: &str,
+    payment_status: Option<&str>,
+) -> Result<Booking> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET verification_status = $2,
+            payment_status = COALESCE($3, payment_status),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(verification_status)
+    .bind(payment_status)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Confirm a payment that OCR matched against the uploaded screenshot
+///
+/// Only transitions active bookings still awaiting payment; returns None when
+/// the booking was cancelled, released or already paid while OCR ran.
+pub async fn auto_confirm_payment(pool: &PgPool, id: Uuid) -> Result<Option<Booking>> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET verification_status = 'auto_confirmed',
+            payment_status = 'confirmed',
+            updated_at = NOW()
+        WHERE id = $1
+          AND cancelled_at IS NULL
+          AND payment_status = 'pending'
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Record an admin's manual review of a payment proof
+///
+/// Only transitions active, unpaid bookings still in `pending_review`; returns
+/// None when the booking is missing, another review already landed, or it was
+/// cancelled or released (its slots handed back) while the proof was in review.
+pub async fn review_payment_proof(
+    pool: &PgPool,
+    id: Uuid,
+    reviewer_id: Uuid,
+    verification_status: &str,
+    payment_status: Option<&str>,
+    received_amount_vnd: Option<i32>,
+) -> Result<Option<Booking>> {
+    let booking = sqlx::query_as::<_, Booking>(
+        r#"
+        UPDATE bookings
+        SET verification_status = $3,
+            payment_status = COALESCE($4, payment_status),
+            received_amount_vnd = COALESCE($5, received_amount_vnd),
+            verified_by = $2,
+            verified_at = NOW(),
+            updated_at = NOW()
+        WHERE id = $1
+          AND verification_status = 'pending_review'
+          AND cancelled_at IS NULL
+          AND payment_status = 'pending'
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(reviewer_id)
+    .bind(verification_status)
+    .bind(payment_status)
+    .bind(received_amount_vnd)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(booking)
+}
+
+/// Find settled (confirmed or cancelled) bookings with payment screenshots older than the cutoff
+pub async fn find_expired_payment_screenshots(
+    pool: &PgPool,
+    before: DateTime<Utc>,
+) -> Result<Vec<Booking>> {
+    let bookings = sqlx::query_as::<_, Booking>(
+        r#"
+        SELECT * FROM bookings
+        WHERE payment_screenshot_url IS NOT NULL
+          AND payment_status IN ('confirmed', 'cancelled')
+          AND created_at < $1
+        ORDER BY created_at ASC
+        "#
+    )
+    .bind(before)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bookings)
+}
+
+/// Clear the stored payment screenshot reference (file already deleted)
+pub async fn clear_payment_screenshot(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE bookings
+        SET payment_screenshot_url = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a rejected booking attempt (see `booking_failure_reasons`)
+pub async fn record_attempt_failure(pool: &PgPool, user_id: Uuid, session_id: Uuid, reason: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO booking_attempt_failures (user_id, session_id, reason)
+        VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,5 +1,160 @@
 use anyhow::Result;
+use chrono::FixedOffset;
+use loafy_types::enums::OrganizerSelfBooking;
 use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long a loaded [`Config`] is reused before re-reading the table.
+/// Writes through [`set_value`] invalidate immediately; the TTL only matters
+/// for changes made by another process (e.g. the API updating, jobs reading).
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+static CACHE: RwLock<Option<(Instant, Arc<Config>)>> = RwLock::new(None);
+
+/// Config keys backing the typed [`Config`] fields
+pub mod keys {
+    pub const OUT_OF_TICKET_DISCOUNT_PERCENT: &str = "subscriber_out_of_ticket_discount_percent";
+    pub const SUBSCRIPTION_TICKET_COUNT: &str = "subscription_ticket_count";
+    pub const DEFAULT_PAYMENT_HOLD_MINUTES: &str = "payment_deadline_minutes";
+    pub const BIRTHDAY_TICKET_COUNT: &str = "birthday_bonus_tickets";
+    pub const GUEST_TICKETS_ENABLED: &str = "guest_tickets_enabled";
+    pub const SESSION_UTC_OFFSET: &str = "session_utc_offset";
+    pub const DEFAULT_SESSION_DURATION_MINUTES: &str = "default_session_duration_minutes";
+    pub const PLATFORM_FEE_PERCENT: &str = "platform_fee_percent";
+    pub const MAINTENANCE_MODE: &str = "maintenance_mode";
+    pub const MAINTENANCE_MESSAGE: &str = "maintenance_message";
+    pub const TICKET_ROLLOVER_CAP: &str = "ticket_rollover_cap";
+    pub const DEFAULT_SUBSCRIPTION_PERIOD_MONTHS: &str = "default_subscription_period_months";
+    pub const ORGANIZER_SELF_BOOKING: &str = "organizer_self_booking";
+    pub const ADMIN_DIGEST_CRON: &str = "admin_digest_cron";
+    pub const MAX_GUESTS_PER_BOOKING: &str = "max_guests_per_booking";
+}
+
+/// Admin digest schedule used when none is set or the stored one is invalid
+pub const DEFAULT_ADMIN_DIGEST_CRON: &str = "0 0 8 * * Mon";
+
+/// Typed snapshot of the pricing, ticket and calendar settings in the config table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Discount for subscribers who are out of tickets (e.g. 10 for 10%)
+    pub out_of_ticket_discount_percent: i32,
+    /// Tickets granted per subscription purchase/renewal
+    pub subscription_ticket_count: i32,
+    /// Minutes a pending booking is held for payment
+    pub default_payment_hold_minutes: i32,
+    /// Bonus tickets granted on a user's birthday
+    pub birthday_ticket_count: i32,
+    /// Whether subscribers may spend tickets on their guests' slots
+    pub guest_tickets_enabled: bool,
+    /// UTC offset of the club's local time, in which session date/time are stored
+    pub session_utc_offset: FixedOffset,
+    /// Length assumed for sessions without an end time
+    pub default_session_duration_minutes: i32,
+    /// Share of session net revenue withheld from organizer payouts (e.g. 10 for 10%)
+    pub platform_fee_percent: i32,
+    /// Whether new bookings, sessions and subscription purchases are paused
+    pub maintenance_mode: bool,
+    /// Message shown to users while in maintenance mode
+    pub maintenance_message: Option<String>,
+    /// Maximum balance a renewal tops up to; 0 disables the cap
+    pub ticket_rollover_cap: i32,
+    /// Subscription period assumed when Stripe doesn't tell us the billing interval
+    pub default_subscription_period_months: i32,
+    /// Whether organizers booking their own sessions pay, play free or are refused
+    pub organizer_self_booking: OrganizerSelfBooking,
+    /// Guests allowed per booking for sessions without their own limit
+    pub max_guests_per_booking: i32,
+}
+
+impl Config {
+    /// Build from raw key/value rows, falling back to defaults for missing or invalid values
+    fn from_values(values: &HashMap<String, String>) -> Self {
+        let int = |key: &str, default: i32| {
+            values.get(key).and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        Self {
+            out_of_ticket_discount_percent: int(keys::OUT_OF_TICKET_DISCOUNT_PERCENT, 10),
+            subscription_ticket_count: int(keys::SUBSCRIPTION_TICKET_COUNT, 10),
+            default_payment_hold_minutes: int(keys::DEFAULT_PAYMENT_HOLD_MINUTES, 30),
+            birthday_ticket_count: int(keys::BIRTHDAY_TICKET_COUNT, 1),
+            guest_tickets_enabled: values
+                .get(keys::GUEST_TICKETS_ENABLED)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(true),
+            session_utc_offset: values
+                .get(keys::SESSION_UTC_OFFSET)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(|| FixedOffset::east_opt(7 * 3600).expect("valid offset")),
+            default_session_duration_minutes: int(keys::DEFAULT_SESSION_DURATION_MINUTES, 90),
+            platform_fee_percent: int(keys::PLATFORM_FEE_PERCENT, 0),
+            maintenance_mode: values
+                .get(keys::MAINTENANCE_MODE)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false),
+            maintenance_message: values
+                .get(keys::MAINTENANCE_MESSAGE)
+                .filter(|v| !v.trim().is_empty())
+                .cloned(),
+            ticket_rollover_cap: int(keys::TICKET_ROLLOVER_CAP, 20),
+            default_subscription_period_months: int(keys::DEFAULT_SUBSCRIPTION_PERIOD_MONTHS, 3),
+            organizer_self_booking: values
+                .get(keys::ORGANIZER_SELF_BOOKING)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            max_guests_per_booking: int(keys::MAX_GUESTS_PER_BOOKING, 3),
+        }
+    }
+
+    /// The renewal rollover cap, or None when disabled
+    pub fn rollover_cap(&self) -> Option<i32> {
+        Some(self.ticket_rollover_cap).filter(|cap| *cap > 0)
+    }
+}
+
+/// Load the typed config, reusing the cached copy while it is fresh
+pub async fn load(pool: &PgPool) -> Result<Arc<Config>> {
+    if let Some((loaded_at, config)) = CACHE.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        if loaded_at.elapsed() < CACHE_TTL {
+            return Ok(config.clone());
+        }
+    }
+
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM config")
+        .fetch_all(pool)
+        .await?;
+    let config = Arc::new(Config::from_values(&rows.into_iter().collect()));
+
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), config.clone()));
+
+    Ok(config)
+}
+
+/// Drop the cached config so the next [`load`] re-reads the table
+pub fn invalidate() {
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Insert or update a config value and invalidate the cache
+pub async fn set_value(pool: &PgPool, key: &str, value: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO config (key, value)
+        VALUES ($1, $2)
+        ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+        "#
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+
+    invalidate();
+
+    Ok(())
+}
 
 /// Get config value by key
 pub async fn get_value(pool: &PgPool, key: &str) -> Result<Option<String>> {
@@ -28,15 +183,6 @@ pub async fn get_value_in_tx(
     Ok(result.map(|(v,)| v))
 }
 
-/// Get subscriber out-of-ticket discount percentage
-/// Returns the discount percentage (e.g., 10 for 10%)
-pub async fn get_out_of_ticket_discount(tx: &mut Transaction<'_, Postgres>) -> Result<i32> {
-    let value = get_value_in_tx(tx, "subscriber_out_of_ticket_discount_percent")
-        .await?
-        .unwrap_or_else(|| "10".to_string());
-    Ok(value.parse().unwrap_or(10))
-}
-
 /// Get referral bonus tickets amount
 pub async fn get_referral_bonus_tickets(pool: &PgPool) -> Result<i32> {
     let value = get_value(pool, "referral_bonus_tickets")
@@ -45,14 +191,6 @@ pub async fn get_referral_bonus_tickets(pool: &PgPool) -> Result<i32> {
     Ok(value.parse().unwrap_or(1))
 }
 
-/// Get birthday bonus tickets amount
-pub async fn get_birthday_bonus_tickets(pool: &PgPool) -> Result<i32> {
-    let value = get_value(pool, "birthday_bonus_tickets")
-        .await?
-        .unwrap_or_else(|| "1".to_string());
-    Ok(value.parse().unwrap_or(1))
-}
-
 /// Get birthday account age requirement in days
 pub async fn get_birthday_account_age_days(pool: &PgPool) -> Result<i32> {
     let value = get_value(pool, "birthday_account_age_days")
@@ -60,3 +198,118 @@ pub async fn get_birthday_account_age_days(pool: &PgPool) -> Result<i32> {
         .unwrap_or_else(|| "30".to_string());
     Ok(value.parse().unwrap_or(30))
 }
+
+/// Get default maximum guests per booking (used when a session has no own limit)
+pub async fn get_max_guests_per_booking(pool: &PgPool) -> Result<i32> {
+    let value = get_value(pool, "max_guests_per_booking")
+        .await?
+        .unwrap_or_else(|| "3".to_string());
+    Ok(value.parse().unwrap_or(3))
+}
+
+/// Get default maximum guests per booking within a transaction
+pub async fn get_max_guests_per_booking_in_tx(tx: &mut Transaction<'_, Postgres>) -> Result<i32> {
+    let value = get_value_in_tx(tx, "max_guests_per_booking")
+        .await?
+        .unwrap_or_else(|| "3".to_string());
+    Ok(value.parse().unwrap_or(3))
+}
+
+/// Get maximum OCR payment verifications per user per month
+pub async fn get_ocr_monthly_limit(pool: &PgPool) -> Result<i32> {
+    let value = get_value(pool, "ocr_monthly_limit_per_user")
+        .await?
+        .unwrap_or_else(|| "5".to_string());
+    Ok(value.parse().unwrap_or(5))
+}
+
+/// Get retention period for payment screenshots in days
+pub async fn get_payment_screenshot_retention_days(pool: &PgPool) -> Result<i32> {
+    let value = get_value(pool, "payment_screenshot_retention_days")
+        .await?
+        .unwrap_or_else(|| "90".to_string());
+    Ok(value.parse().unwrap_or(90))
+}
+
+/// Get maximum active bookings per user in a rolling week (0 = unlimited)
+pub async fn get_max_bookings_per_week(pool: &PgPool, is_subscriber: bool) -> Result<i32> {
+    let (key, default) = if is_subscriber {
+        ("max_bookings_per_week_subscriber", 10)
+    } else {
+        ("max_bookings_per_week", 5)
+    };
+    let value = get_value(pool, key).await?;
+    Ok(value.and_then(|v| v.parse().ok()).unwrap_or(default))
+}
+
+/// Get maximum accepted age of a Stripe webhook event in seconds
+///
+/// Measured from the event's `created`, which Stripe keeps on retries.
+pub async fn get_stripe_webhook_max_event_age_secs(pool: &PgPool) -> Result<i64> {
+    let value = get_value(pool, "stripe_webhook_max_event_age_seconds")
+        .await?
+        .unwrap_or_else(|| "600".to_string());
+    Ok(value.parse().unwrap_or(600))
+}
+
+/// Get default hours before start at which session min-players is checked
+pub async fn get_min_players_deadline_hours(pool: &PgPool) -> Result<i32> {
+    let value = get_value(pool, "min_players_deadline_hours")
+        .await?
+        .unwrap_or_else(|| "24".to_string());
+    Ok(value.parse().unwrap_or(24))
+}
+
+/// Get hours a promoted waitlist user has to pay before the slot moves on
+pub async fn get_waitlist_exclusive_hours(pool: &PgPool) -> Result<i32> {
+    let value = get_value(pool, "waitlist_exclusive_hours")
+        .await?
+        .unwrap_or_else(|| "1".to_string());
+    Ok(value.parse().unwrap_or(1))
+}
+
+/// Get explicit admin digest recipients (comma-separated); empty means all admins
+pub async fn get_admin_digest_recipients(pool: &PgPool) -> Result<Vec<String>> {
+    let value = get_value(pool, "admin_digest_recipients").await?.unwrap_or_default();
+    Ok(value
+        .split(',')
+        .map(str::trim)
+        .filter(|email| !email.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Get cron schedule for the weekly admin digest email
+pub async fn get_admin_digest_cron(pool: &PgPool) -> Result<String> {
+    let value = get_value(pool, keys::ADMIN_DIGEST_CRON)
+        .await?
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_ADMIN_DIGEST_CRON.to_string());
+    Ok(value)
+}
+
+/// Check a 6-field cron (sec min hour day month weekday) the way the jobs
+/// scheduler parses it
+pub fn validate_cron(expression: &str) -> Result<()> {
+    croner::Cron::new(expression)
+        .with_seconds_required()
+        .with_dom_and_dow()
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", expression, e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_cron() {
+        assert!(validate_cron(DEFAULT_ADMIN_DIGEST_CRON).is_ok());
+        assert!(validate_cron("0 30 7 * * Mon-Fri").is_ok());
+        // Seconds are required, as in the scheduler
+        assert!(validate_cron("0 8 * * Mon").is_err());
+        assert!(validate_cron("every monday").is_err());
+        assert!(validate_cron("0 0 25 * * *").is_err());
+    }
+}
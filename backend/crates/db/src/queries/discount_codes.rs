@@ -0,0 +1,166 @@
+use crate::models::{DiscountCode, DiscountCodeWithUsage};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// Fields of a discount code as entered by an admin
+pub struct DiscountCodeParams<'a> {
+    /// Uppercase
+    pub code: &'a str,
+    pub percent_off: Option<i32>,
+    pub amount_off_vnd: Option<i32>,
+    pub applies_to_guests: bool,
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub active: bool,
+}
+
+/// Columns of `discount_codes` plus its count of non-cancelled bookings
+const SELECT_WITH_USAGE: &str = r#"
+    SELECT d.*,
+        (SELECT COUNT(*) FROM bookings b
+         WHERE b.discount_code_id = d.id AND b.cancelled_at IS NULL) AS times_used
+    FROM discount_codes d
+"#;
+
+/// Create a discount code
+pub async fn create(pool: &PgPool, params: &DiscountCodeParams<'_>, created_by: Uuid) -> Result<DiscountCode> {
+    let code = sqlx::query_as::<_, DiscountCode>(
+        r#"
+        INSERT INTO discount_codes (
+            code, percent_off, amount_off_vnd, applies_to_guests,
+            max_uses, max_uses_per_user, valid_from, valid_until, active, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING *
+        "#
+    )
+    .bind(params.code)
+    .bind(params.percent_off)
+    .bind(params.amount_off_vnd)
+    .bind(params.applies_to_guests)
+    .bind(params.max_uses)
+    .bind(params.max_uses_per_user)
+    .bind(params.valid_from)
+    .bind(params.valid_until)
+    .bind(params.active)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await
+    .map_err(duplicate_code_error)?;
+
+    Ok(code)
+}
+
+/// Replace a discount code's fields; None if it doesn't exist
+pub async fn update(pool: &PgPool, id: Uuid, params: &DiscountCodeParams<'_>) -> Result<Option<DiscountCode>> {
+    let code = sqlx::query_as::<_, DiscountCode>(
+        r#"
+        UPDATE discount_codes
+        SET code = $2, percent_off = $3, amount_off_vnd = $4, applies_to_guests = $5,
+            max_uses = $6, max_uses_per_user = $7, valid_from = $8, valid_until = $9, active = $10
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(params.code)
+    .bind(params.percent_off)
+    .bind(params.amount_off_vnd)
+    .bind(params.applies_to_guests)
+    .bind(params.max_uses)
+    .bind(params.max_uses_per_user)
+    .bind(params.valid_from)
+    .bind(params.valid_until)
+    .bind(params.active)
+    .fetch_optional(pool)
+    .await
+    .map_err(duplicate_code_error)?;
+
+    Ok(code)
+}
+
+/// Delete a discount code; false if it didn't exist
+///
+/// Bookings that used it keep the code text.
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM discount_codes WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get a discount code with its usage
+pub async fn find_with_usage(pool: &PgPool, id: Uuid) -> Result<Option<DiscountCodeWithUsage>> {
+    let code = sqlx::query_as::<_, DiscountCodeWithUsage>(&format!("{} WHERE d.id = $1", SELECT_WITH_USAGE))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(code)
+}
+
+/// List all discount codes with their usage, newest first
+pub async fn list_with_usage(pool: &PgPool) -> Result<Vec<DiscountCodeWithUsage>> {
+    let codes = sqlx::query_as::<_, DiscountCodeWithUsage>(&format!("{} ORDER BY d.created_at DESC", SELECT_WITH_USAGE))
+        .fetch_all(pool)
+        .await?;
+
+    Ok(codes)
+}
+
+/// Find a discount code by (uppercase) code, locking it so concurrent
+/// bookings count its uses one at a time
+pub async fn find_by_code_for_update(
+    tx: &mut Transaction<'_, Postgres>,
+    code: &str,
+) -> Result<Option<DiscountCode>> {
+    let code = sqlx::query_as::<_, DiscountCode>(
+        "SELECT * FROM discount_codes WHERE code = $1 FOR UPDATE"
+    )
+    .bind(code)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(code)
+}
+
+/// Count non-cancelled bookings using a code: `(total, by this user)`
+pub async fn count_uses_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<(i64, i64)> {
+    let counts: (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COUNT(*) FILTER (WHERE user_id = $2)
+        FROM bookings
+        WHERE discount_code_id = $1
+          AND cancelled_at IS NULL
+        "#
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(counts)
+}
+
+/// Another discount code already uses this code
+#[derive(Debug, thiserror::Error)]
+#[error("Discount code already exists")]
+pub struct DuplicateDiscountCode;
+
+/// Report a clash with an existing code as [`DuplicateDiscountCode`]
+fn duplicate_code_error(e: sqlx::Error) -> anyhow::Error {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => DuplicateDiscountCode.into(),
+        e => e.into(),
+    }
+}
@@ -0,0 +1,164 @@
+use crate::models::{ExpenseTemplate, ExpenseTemplateItem};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// (category, description, cost_type, amount_vnd), as in `session_expenses::create_expenses_batch`
+pub type TemplateItemRow = (String, Option<String>, String, i32);
+
+/// Create a template together with its items
+pub async fn create_template(
+    pool: &PgPool,
+    organizer_id: Uuid,
+    name: &str,
+    items: &[TemplateItemRow],
+) -> Result<(ExpenseTemplate, Vec<ExpenseTemplateItem>)> {
+    let mut tx = pool.begin().await?;
+
+    let template = sqlx::query_as::<_, ExpenseTemplate>(
+        r#"
+        INSERT INTO expense_templates (organizer_id, name)
+        VALUES ($1, $2)
+        RETURNING *
+        "#
+    )
+    .bind(organizer_id)
+    .bind(name)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(duplicate_name)?;
+
+    let items = insert_items(&mut tx, template.id, items).await?;
+    tx.commit().await?;
+
+    Ok((template, items))
+}
+
+/// Find a template by id
+pub async fn find_template(pool: &PgPool, template_id: Uuid) -> Result<Option<ExpenseTemplate>> {
+    let template = sqlx::query_as::<_, ExpenseTemplate>(
+        "SELECT * FROM expense_templates WHERE id = $1"
+    )
+    .bind(template_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(template)
+}
+
+/// List an organizer's templates, alphabetically
+pub async fn list_templates_for_organizer(pool: &PgPool, organizer_id: Uuid) -> Result<Vec<ExpenseTemplate>> {
+    let templates = sqlx::query_as::<_, ExpenseTemplate>(
+        "SELECT * FROM expense_templates WHERE organizer_id = $1 ORDER BY name"
+    )
+    .bind(organizer_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(templates)
+}
+
+/// Items of a template in their saved order
+pub async fn list_items(pool: &PgPool, template_id: Uuid) -> Result<Vec<ExpenseTemplateItem>> {
+    let items = sqlx::query_as::<_, ExpenseTemplateItem>(
+        "SELECT * FROM expense_template_items WHERE template_id = $1 ORDER BY position"
+    )
+    .bind(template_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Items of several templates in one query, each list in saved order
+pub async fn list_items_for_templates(pool: &PgPool, template_ids: &[Uuid]) -> Result<Vec<ExpenseTemplateItem>> {
+    let items = sqlx::query_as::<_, ExpenseTemplateItem>(
+        "SELECT * FROM expense_template_items WHERE template_id = ANY($1) ORDER BY template_id, position"
+    )
+    .bind(template_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Rename a template and replace all of its items
+pub async fn update_template(
+    pool: &PgPool,
+    template_id: Uuid,
+    name: &str,
+    items: &[TemplateItemRow],
+) -> Result<(ExpenseTemplate, Vec<ExpenseTemplateItem>)> {
+    let mut tx = pool.begin().await?;
+
+    let template = sqlx::query_as::<_, ExpenseTemplate>(
+        "UPDATE expense_templates SET name = $2 WHERE id = $1 RETURNING *"
+    )
+    .bind(template_id)
+    .bind(name)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(duplicate_name)?;
+
+    sqlx::query("DELETE FROM expense_template_items WHERE template_id = $1")
+        .bind(template_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let items = insert_items(&mut tx, template_id, items).await?;
+    tx.commit().await?;
+
+    Ok((template, items))
+}
+
+/// Delete a template (its items cascade)
+pub async fn delete_template(pool: &PgPool, template_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM expense_templates WHERE id = $1")
+        .bind(template_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// An organizer already has a template with this name
+#[derive(Debug, thiserror::Error)]
+#[error("Expense template already exists")]
+pub struct DuplicateTemplateName;
+
+fn duplicate_name(e: sqlx::Error) -> anyhow::Error {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => DuplicateTemplateName.into(),
+        e => e.into(),
+    }
+}
+
+async fn insert_items(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    template_id: Uuid,
+    items: &[TemplateItemRow],
+) -> Result<Vec<ExpenseTemplateItem>> {
+    let mut created = Vec::with_capacity(items.len());
+
+    for (position, (category, description, cost_type, amount_vnd)) in items.iter().enumerate() {
+        let item = sqlx::query_as::<_, ExpenseTemplateItem>(
+            r#"
+            INSERT INTO expense_template_items (template_id, position, category, description, cost_type, amount_vnd)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(template_id)
+        .bind(position as i32)
+        .bind(category)
+        .bind(description.as_deref())
+        .bind(cost_type)
+        .bind(*amount_vnd)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        created.push(item);
+    }
+
+    Ok(created)
+}
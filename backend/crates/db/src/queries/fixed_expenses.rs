@@ -0,0 +1,103 @@
+use crate::models::FixedExpense;
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Fields of a fixed expense as entered by an admin
+pub struct FixedExpenseParams<'a> {
+    pub category: &'a str,
+    pub description: Option<&'a str>,
+    pub amount_vnd: i32,
+    pub recurrence: &'a str,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+}
+
+/// Create a fixed expense
+pub async fn create(pool: &PgPool, params: &FixedExpenseParams<'_>, created_by: Uuid) -> Result<FixedExpense> {
+    let expense = sqlx::query_as::<_, FixedExpense>(
+        r#"
+        INSERT INTO fixed_expenses (category, description, amount_vnd, recurrence, starts_on, ends_on, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#
+    )
+    .bind(params.category)
+    .bind(params.description)
+    .bind(params.amount_vnd)
+    .bind(params.recurrence)
+    .bind(params.starts_on)
+    .bind(params.ends_on)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(expense)
+}
+
+/// Replace a fixed expense's fields; None if it doesn't exist
+pub async fn update(pool: &PgPool, id: Uuid, params: &FixedExpenseParams<'_>) -> Result<Option<FixedExpense>> {
+    let expense = sqlx::query_as::<_, FixedExpense>(
+        r#"
+        UPDATE fixed_expenses
+        SET category = $2, description = $3, amount_vnd = $4,
+            recurrence = $5, starts_on = $6, ends_on = $7
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(params.category)
+    .bind(params.description)
+    .bind(params.amount_vnd)
+    .bind(params.recurrence)
+    .bind(params.starts_on)
+    .bind(params.ends_on)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(expense)
+}
+
+/// Delete a fixed expense; false if it didn't exist
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM fixed_expenses WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// List all fixed expenses, most recently started first
+pub async fn list_all(pool: &PgPool) -> Result<Vec<FixedExpense>> {
+    let expenses = sqlx::query_as::<_, FixedExpense>(
+        "SELECT * FROM fixed_expenses ORDER BY starts_on DESC, created_at DESC"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(expenses)
+}
+
+/// List fixed expenses in effect on any day from `from` (None = the beginning) through `to`
+pub async fn list_effective_between(
+    pool: &PgPool,
+    from: Option<NaiveDate>,
+    to: NaiveDate,
+) -> Result<Vec<FixedExpense>> {
+    let expenses = sqlx::query_as::<_, FixedExpense>(
+        r#"
+        SELECT * FROM fixed_expenses
+        WHERE starts_on <= $2
+          AND ($1::date IS NULL OR ends_on IS NULL OR ends_on >= $1)
+        "#
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(expenses)
+}
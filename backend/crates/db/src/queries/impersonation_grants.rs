@@ -0,0 +1,50 @@
+use crate::models::ImpersonationGrant;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Record that an admin was issued a token to impersonate a user
+pub async fn create(
+    pool: &PgPool,
+    admin_id: Uuid,
+    user_id: Uuid,
+    reason: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<ImpersonationGrant> {
+    let grant = sqlx::query_as::<_, ImpersonationGrant>(
+        r#"
+        INSERT INTO impersonation_grants (admin_id, user_id, reason, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#
+    )
+    .bind(admin_id)
+    .bind(user_id)
+    .bind(reason)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(grant)
+}
+
+/// Count a request made with an unexpired grant's token
+///
+/// Returns None if the grant doesn't exist or has expired.
+pub async fn record_use(pool: &PgPool, id: Uuid) -> Result<Option<ImpersonationGrant>> {
+    let grant = sqlx::query_as::<_, ImpersonationGrant>(
+        r#"
+        UPDATE impersonation_grants
+        SET request_count = request_count + 1,
+            last_used_at = NOW()
+        WHERE id = $1 AND expires_at > NOW()
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(grant)
+}
@@ -0,0 +1,152 @@
+use crate::models::{Location, LocationWithCount};
+use anyhow::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// List all locations with their session counts (see `count_sessions`)
+pub async fn list_with_counts(pool: &PgPool) -> Result<Vec<LocationWithCount>> {
+    let locations = sqlx::query_as::<_, LocationWithCount>(
+        r#"
+        SELECT l.id, l.name, COUNT(s.id) AS session_count
+        FROM locations l
+        LEFT JOIN sessions s ON s.location_id = l.id AND s.cancelled = false
+        GROUP BY l.id, l.name
+        ORDER BY l.name ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(locations)
+}
+
+/// Get location by ID
+pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Location>> {
+    let location = sqlx::query_as::<_, Location>(
+        "SELECT * FROM locations WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(location)
+}
+
+/// Get location by ID within a transaction
+pub async fn find_by_id_in_tx(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<Option<Location>> {
+    let location = sqlx::query_as::<_, Location>(
+        "SELECT * FROM locations WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(location)
+}
+
+/// Find a location by name (case-insensitive), creating it if missing, within a transaction
+pub async fn find_or_create_by_name_in_tx(tx: &mut Transaction<'_, Postgres>, name: &str) -> Result<Location> {
+    let name = name.trim();
+
+    // No-op update on conflict so RETURNING yields the existing row
+    let location = sqlx::query_as::<_, Location>(
+        r#"
+        INSERT INTO locations (name)
+        VALUES ($1)
+        ON CONFLICT (LOWER(name)) DO UPDATE SET name = locations.name
+        RETURNING *
+        "#
+    )
+    .bind(name)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(location)
+}
+
+/// Why a location couldn't be created or renamed
+#[derive(Debug, thiserror::Error)]
+pub enum LocationWriteError {
+    #[error("Location already exists")]
+    AlreadyExists,
+    #[error("Location not found")]
+    NotFound,
+}
+
+/// Create a new location; fails with [`LocationWriteError::AlreadyExists`] if
+/// the name already exists (case-insensitive)
+pub async fn create(pool: &PgPool, name: &str) -> Result<Location> {
+    let location = sqlx::query_as::<_, Location>(
+        r#"
+        INSERT INTO locations (name)
+        VALUES ($1)
+        ON CONFLICT (LOWER(name)) DO NOTHING
+        RETURNING *
+        "#
+    )
+    .bind(name.trim())
+    .fetch_optional(pool)
+    .await?;
+
+    location.ok_or_else(|| LocationWriteError::AlreadyExists.into())
+}
+
+/// Rename a location and update the text on its linked sessions
+pub async fn rename(pool: &PgPool, id: Uuid, name: &str) -> Result<Location> {
+    let mut tx = pool.begin().await?;
+
+    let location = sqlx::query_as::<_, Location>(
+        r#"
+        UPDATE locations
+        SET name = $2, updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(name.trim())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+            anyhow::Error::from(LocationWriteError::AlreadyExists)
+        }
+        e => e.into(),
+    })?
+    .ok_or(LocationWriteError::NotFound)?;
+
+    sqlx::query("UPDATE sessions SET location = $2, updated_at = NOW() WHERE location_id = $1")
+        .bind(id)
+        .bind(&location.name)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(location)
+}
+
+/// Count sessions linked to a location, leaving out cancelled ones
+///
+/// Deleting a location only unlinks its cancelled sessions, which keep their
+/// location text.
+pub async fn count_sessions(pool: &PgPool, id: Uuid) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM sessions WHERE location_id = $1 AND cancelled = false"
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Delete a location
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM locations WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
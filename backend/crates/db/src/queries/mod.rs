@@ -1,8 +1,21 @@
 pub mod users;
 pub mod sessions;
 pub mod bookings;
+pub mod booking_invites;
 pub mod admin;
 pub mod session_expenses;
+pub mod expense_templates;
+pub mod fixed_expenses;
+pub mod discount_codes;
+pub mod impersonation_grants;
 pub mod subscriptions;
+pub mod subscription_gifts;
 pub mod ticket_transactions;
 pub mod config;
+pub mod ocr_usage;
+pub mod admin_notes;
+pub mod stripe_events;
+pub mod locations;
+pub mod waitlist;
+pub mod session_announcements;
+pub mod notification_preferences;
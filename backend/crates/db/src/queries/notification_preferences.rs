@@ -0,0 +1,81 @@
+use crate::models::{NotificationKind, NotificationPreferences};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Get a user's notification preferences, falling back to the defaults
+pub async fn get(pool: &PgPool, user_id: Uuid) -> Result<NotificationPreferences> {
+    let preferences = sqlx::query_as::<_, NotificationPreferences>(
+        "SELECT * FROM notification_preferences WHERE user_id = $1"
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(preferences.unwrap_or_else(|| NotificationPreferences::defaults(user_id)))
+}
+
+/// Store a user's notification preferences, replacing any previous choice
+pub async fn upsert(pool: &PgPool, preferences: &NotificationPreferences) -> Result<NotificationPreferences> {
+    let saved = sqlx::query_as::<_, NotificationPreferences>(
+        r#"
+        INSERT INTO notification_preferences (
+            user_id,
+            booking_confirmation_email, booking_confirmation_sms, booking_confirmation_push,
+            reminder_email, reminder_sms, reminder_push,
+            session_cancelled_email, session_cancelled_sms, session_cancelled_push,
+            waitlist_promoted_email, waitlist_promoted_sms, waitlist_promoted_push
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (user_id) DO UPDATE SET
+            booking_confirmation_email = EXCLUDED.booking_confirmation_email,
+            booking_confirmation_sms = EXCLUDED.booking_confirmation_sms,
+            booking_confirmation_push = EXCLUDED.booking_confirmation_push,
+            reminder_email = EXCLUDED.reminder_email,
+            reminder_sms = EXCLUDED.reminder_sms,
+            reminder_push = EXCLUDED.reminder_push,
+            session_cancelled_email = EXCLUDED.session_cancelled_email,
+            session_cancelled_sms = EXCLUDED.session_cancelled_sms,
+            session_cancelled_push = EXCLUDED.session_cancelled_push,
+            waitlist_promoted_email = EXCLUDED.waitlist_promoted_email,
+            waitlist_promoted_sms = EXCLUDED.waitlist_promoted_sms,
+            waitlist_promoted_push = EXCLUDED.waitlist_promoted_push,
+            updated_at = NOW()
+        RETURNING *
+        "#
+    )
+    .bind(preferences.user_id)
+    .bind(preferences.booking_confirmation_email)
+    .bind(preferences.booking_confirmation_sms)
+    .bind(preferences.booking_confirmation_push)
+    .bind(preferences.reminder_email)
+    .bind(preferences.reminder_sms)
+    .bind(preferences.reminder_push)
+    .bind(preferences.session_cancelled_email)
+    .bind(preferences.session_cancelled_sms)
+    .bind(preferences.session_cancelled_push)
+    .bind(preferences.waitlist_promoted_email)
+    .bind(preferences.waitlist_promoted_sms)
+    .bind(preferences.waitlist_promoted_push)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(saved)
+}
+
+/// Whether a user wants this notification by email
+pub async fn wants_email(pool: &PgPool, user_id: Uuid, kind: NotificationKind) -> Result<bool> {
+    Ok(get(pool, user_id).await?.email_enabled(kind))
+}
+
+/// Whether to email a user about a change made to their booking
+///
+/// Follows the user's email preference, but still sends when it can't be
+/// loaded: missing a cancellation or promotion email costs more than an
+/// unwanted one.
+pub async fn should_email(pool: &PgPool, user_id: Uuid, kind: NotificationKind) -> bool {
+    wants_email(pool, user_id, kind).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load notification preferences for {}: {}", user_id, e);
+        true
+    })
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Consume one OCR call for the user if they are under the monthly limit.
+/// Returns false (and records nothing) when the limit is already reached.
+pub async fn try_consume(pool: &PgPool, user_id: Uuid, monthly_limit: i32) -> Result<bool> {
+    if monthly_limit <= 0 {
+        return Ok(false);
+    }
+
+    let result: Option<(i32,)> = sqlx::query_as(
+        r#"
+        INSERT INTO ocr_usage (user_id, call_count)
+        VALUES ($1, 1)
+        ON CONFLICT (user_id) DO UPDATE
+        SET call_count = ocr_usage.call_count + 1,
+            updated_at = NOW()
+        WHERE ocr_usage.call_count < $2
+        RETURNING call_count
+        "#
+    )
+    .bind(user_id)
+    .bind(monthly_limit)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(result.is_some())
+}
+
+/// Reset all OCR counters for a new monthly period.
+/// Returns the number of counters reset.
+pub async fn reset_all(pool: &PgPool) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        UPDATE ocr_usage
+        SET call_count = 0,
+            period_start = date_trunc('month', NOW())::date,
+            updated_at = NOW()
+        WHERE call_count > 0
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
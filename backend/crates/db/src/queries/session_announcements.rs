@@ -0,0 +1,45 @@
+use crate::models::SessionAnnouncement;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Record an announcement sent to a session's participants
+pub async fn create(
+    pool: &PgPool,
+    session_id: Uuid,
+    author_id: Uuid,
+    subject: &str,
+    body: &str,
+    recipient_count: i32,
+) -> Result<SessionAnnouncement> {
+    let announcement = sqlx::query_as::<_, SessionAnnouncement>(
+        r#"
+        INSERT INTO session_announcements (session_id, author_id, subject, body, recipient_count)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#
+    )
+    .bind(session_id)
+    .bind(author_id)
+    .bind(subject)
+    .bind(body)
+    .bind(recipient_count)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(announcement)
+}
+
+/// Count announcements sent for a session since the given time
+pub async fn count_since(pool: &PgPool, session_id: Uuid, since: DateTime<Utc>) -> Result<i64> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM session_announcements WHERE session_id = $1 AND created_at >= $2"
+    )
+    .bind(session_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
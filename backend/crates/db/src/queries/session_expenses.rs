@@ -30,6 +30,54 @@ pub async fn create_expense(
     Ok(expense)
 }
 
+/// Find an expense belonging to a session
+pub async fn find_expense(
+    pool: &PgPool,
+    session_id: Uuid,
+    expense_id: Uuid,
+) -> Result<Option<SessionExpense>> {
+    let expense = sqlx::query_as::<_, SessionExpense>(
+        "SELECT * FROM session_expenses WHERE id = $1 AND session_id = $2"
+    )
+    .bind(expense_id)
+    .bind(session_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(expense)
+}
+
+/// Update a single expense
+pub async fn update_expense(
+    pool: &PgPool,
+    expense_id: Uuid,
+    category: &str,
+    description: Option<&str>,
+    cost_type: &str,
+    amount_vnd: i32,
+) -> Result<SessionExpense> {
+    let expense = sqlx::query_as::<_, SessionExpense>(
+        r#"
+        UPDATE session_expenses
+        SET category = $2,
+            description = $3,
+            cost_type = $4,
+            amount_vnd = $5
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(expense_id)
+    .bind(category)
+    .bind(description)
+    .bind(cost_type)
+    .bind(amount_vnd)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(expense)
+}
+
 /// Create multiple expenses in a transaction
 pub async fn create_expenses_batch(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
@@ -1,7 +1,11 @@
-use crate::models::Session;
+use crate::conversions::DEFAULT_MAX_PLAYERS_PER_COURT;
+use crate::models::{permissions, Session};
+use crate::queries::users;
 use anyhow::Result;
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
 use sqlx::{FromRow, PgPool, QueryBuilder, Postgres};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Participant info from joined booking + user query
@@ -11,6 +15,47 @@ pub struct SessionParticipant {
     pub name: Option<String>,
     pub avatar_url: Option<String>,
     pub guest_count: i32,
+    /// The user's roster preference; opted-out users are anonymized for the public
+    pub show_in_roster: bool,
+}
+
+/// Time-of-day bucket for session filtering
+/// Morning: 06:00-11:59, Afternoon: 12:00-16:59, Evening: 17:00-05:59
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeOfDay {
+    Morning,
+    Afternoon,
+    Evening,
+}
+
+impl TimeOfDay {
+    /// Parse a comma-separated list like "morning,evening".
+    /// Unknown tokens are ignored; "all" (or nothing valid) means no filter.
+    pub fn parse_list(value: &str) -> Vec<TimeOfDay> {
+        let mut buckets = Vec::new();
+        for token in value.split(',') {
+            let bucket = match token.trim().to_lowercase().as_str() {
+                "all" => return Vec::new(),
+                "morning" => TimeOfDay::Morning,
+                "afternoon" => TimeOfDay::Afternoon,
+                "evening" => TimeOfDay::Evening,
+                _ => continue,
+            };
+            if !buckets.contains(&bucket) {
+                buckets.push(bucket);
+            }
+        }
+        buckets
+    }
+
+    /// SQL predicate on the session start hour
+    pub fn hour_condition(&self) -> &'static str {
+        match self {
+            TimeOfDay::Morning => "(EXTRACT(HOUR FROM time) BETWEEN 6 AND 11)",
+            TimeOfDay::Afternoon => "(EXTRACT(HOUR FROM time) BETWEEN 12 AND 16)",
+            TimeOfDay::Evening => "(EXTRACT(HOUR FROM time) >= 17 OR EXTRACT(HOUR FROM time) < 6)",
+        }
+    }
 }
 
 /// Query filters for listing sessions
@@ -22,24 +67,44 @@ pub struct SessionQueryFilters {
     pub location: Option<String>,
     pub organizer_id: Option<Uuid>,
     pub available_only: bool,
+    /// Skip sessions whose organizer is deleted or currently suspended
+    pub active_organizers_only: bool,
+    /// Only sessions on exactly this date (past ones included); overrides the
+    /// default of upcoming sessions
+    pub on_date: Option<NaiveDate>,
+    /// Include cancelled sessions
+    pub include_cancelled: bool,
+    /// Only full sessions that still take waitlist entries
+    pub waitlist_available: bool,
 }
 
 /// List upcoming sessions with optional filters
+///
+/// `today` is the club's current date, which bounds the default listing.
 pub async fn list_sessions(
     pool: &PgPool,
     filters: SessionQueryFilters,
+    today: NaiveDate,
 ) -> Result<Vec<Session>> {
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
-        "SELECT * FROM sessions WHERE cancelled = false"
+        if filters.include_cancelled {
+            "SELECT * FROM sessions WHERE TRUE"
+        } else {
+            "SELECT * FROM sessions WHERE cancelled = false"
+        }
     );
 
-    // Add from_date filter with parameterized query
-    if let Some(date) = filters.from_date {
+    // Add exact date or from_date filter with parameterized query
+    if let Some(date) = filters.on_date {
+        query_builder.push(" AND date = ");
+        query_builder.push_bind(date);
+    } else if let Some(date) = filters.from_date {
         query_builder.push(" AND date >= ");
         query_builder.push_bind(date);
     } else {
         // Default: only future sessions
-        query_builder.push(" AND date >= CURRENT_DATE");
+        query_builder.push(" AND date >= ");
+        query_builder.push_bind(today);
     }
 
     // Add to_date filter (for date range filtering)
@@ -48,25 +113,14 @@ pub async fn list_sessions(
         query_builder.push_bind(date);
     }
 
-    // Add time of day filter
-    // Morning: 06:00-12:00, Afternoon: 12:00-17:00, Evening: 17:00+
+    // Add time of day filter (OR-combined buckets, unknown tokens ignored)
     if let Some(ref time_of_day) = filters.time_of_day {
-        let times: Vec<&str> = time_of_day.split(',').collect();
-        if !times.is_empty() && !times.contains(&"all") {
-            let mut time_conditions = Vec::new();
-            for t in times {
-                match t.trim() {
-                    "morning" => time_conditions.push("(time >= '06:00' AND time < '12:00')"),
-                    "afternoon" => time_conditions.push("(time >= '12:00' AND time < '17:00')"),
-                    "evening" => time_conditions.push("(time >= '17:00' OR time < '06:00')"),
-                    _ => {}
-                }
-            }
-            if !time_conditions.is_empty() {
-                query_builder.push(" AND (");
-                query_builder.push(time_conditions.join(" OR "));
-                query_builder.push(")");
-            }
+        let buckets = TimeOfDay::parse_list(time_of_day);
+        if !buckets.is_empty() {
+            let conditions: Vec<&str> = buckets.iter().map(|b| b.hour_condition()).collect();
+            query_builder.push(" AND (");
+            query_builder.push(conditions.join(" OR "));
+            query_builder.push(")");
         }
     }
 
@@ -90,6 +144,19 @@ pub async fn list_sessions(
         query_builder.push(" AND available_slots > 0");
     }
 
+    if filters.waitlist_available {
+        query_builder.push(" AND available_slots = 0 AND waitlist_enabled");
+    }
+
+    // Add organizer status filter (suspension counts until suspended_until passes)
+    if filters.active_organizers_only {
+        query_builder.push(
+            " AND EXISTS (SELECT 1 FROM users o WHERE o.id = sessions.organizer_id \
+             AND o.deleted_at IS NULL \
+             AND (o.suspended_at IS NULL OR o.suspended_until <= NOW()))"
+        );
+    }
+
     query_builder.push(" ORDER BY date ASC, time ASC");
 
     let sessions = query_builder
@@ -100,20 +167,16 @@ pub async fn list_sessions(
     Ok(sessions)
 }
 
-/// Get distinct locations from all sessions
-pub async fn list_locations(pool: &PgPool) -> Result<Vec<String>> {
-    let locations: Vec<(String,)> = sqlx::query_as(
-        r#"
-        SELECT DISTINCT location
-        FROM sessions
-        WHERE cancelled = false
-        ORDER BY location ASC
-        "#
+/// Get sessions by ID (missing IDs are skipped)
+pub async fn find_by_ids(pool: &PgPool, ids: &[Uuid]) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as::<_, Session>(
+        "SELECT * FROM sessions WHERE id = ANY($1)"
     )
+    .bind(ids)
     .fetch_all(pool)
     .await?;
 
-    Ok(locations.into_iter().map(|(l,)| l).collect())
+    Ok(sessions)
 }
 
 /// Get session by ID
@@ -143,7 +206,50 @@ pub async fn find_by_id_for_update(
     Ok(session)
 }
 
+/// Lock several sessions FOR UPDATE, in ascending id order
+pub async fn find_by_ids_for_update(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ids: &[Uuid],
+) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as::<_, Session>(
+        "SELECT * FROM sessions WHERE id = ANY($1) ORDER BY id FOR UPDATE"
+    )
+    .bind(ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Why a user can't be assigned as a session's organizer
+#[derive(Debug, thiserror::Error)]
+pub enum CannotOrganize {
+    #[error("User {0} not found")]
+    UserNotFound(Uuid),
+    #[error("User {0} is not allowed to create sessions and cannot organize them")]
+    NotPermitted(Uuid),
+}
+
+/// Ensure `user_id` belongs to an active user whose role grants `sessions.create`
+///
+/// Every `sessions.organizer_id` must reference someone allowed to run
+/// sessions; call this before assigning a session to a user. Fails with
+/// [`CannotOrganize`] when they aren't.
+pub async fn assert_can_organize(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    let user = users::find_with_role_by_id(pool, user_id)
+        .await?
+        .filter(|user| !user.is_deleted())
+        .ok_or(CannotOrganize::UserNotFound(user_id))?;
+
+    if !user.has_permission(permissions::SESSIONS_CREATE) {
+        return Err(CannotOrganize::NotPermitted(user_id).into());
+    }
+
+    Ok(())
+}
+
 /// Create new session
+#[allow(clippy::too_many_arguments)]
 pub async fn create_session(
     pool: &PgPool,
     organizer_id: Uuid,
@@ -152,21 +258,71 @@ pub async fn create_session(
     time: NaiveTime,
     end_time: Option<NaiveTime>,
     location: &str,
+    location_id: Option<Uuid>,
+    courts: i32,
+    max_players_per_court: Option<i32>,
+    price_vnd: Option<i32>,
+    max_guests_per_booking: Option<i32>,
+    min_players: Option<i32>,
+    min_players_deadline_hours: Option<i32>,
+    booking_opens_at: Option<DateTime<Utc>>,
+) -> Result<Session> {
+    let mut tx = pool.begin().await?;
+    let session = create_session_in_tx(
+        &mut tx,
+        organizer_id,
+        title,
+        date,
+        time,
+        end_time,
+        location,
+        location_id,
+        courts,
+        max_players_per_court,
+        price_vnd,
+        max_guests_per_booking,
+        min_players,
+        min_players_deadline_hours,
+        booking_opens_at,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(session)
+}
+
+/// Create new session within an existing transaction
+#[allow(clippy::too_many_arguments)]
+pub async fn create_session_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    organizer_id: Uuid,
+    title: &str,
+    date: NaiveDate,
+    time: NaiveTime,
+    end_time: Option<NaiveTime>,
+    location: &str,
+    location_id: Option<Uuid>,
     courts: i32,
     max_players_per_court: Option<i32>,
     price_vnd: Option<i32>,
+    max_guests_per_booking: Option<i32>,
+    min_players: Option<i32>,
+    min_players_deadline_hours: Option<i32>,
+    booking_opens_at: Option<DateTime<Utc>>,
 ) -> Result<Session> {
     // Calculate total slots
-    let max_players = max_players_per_court.unwrap_or(6);
+    let max_players = max_players_per_court.unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT);
     let total_slots = courts * max_players;
 
     let session = sqlx::query_as::<_, Session>(
         r#"
         INSERT INTO sessions (
             organizer_id, title, date, time, end_time, location, courts,
-            max_players_per_court, total_slots, available_slots, price_vnd
+            max_players_per_court, total_slots, available_slots, price_vnd,
+            max_guests_per_booking, location_id, min_players,
+            min_players_deadline_hours, booking_opens_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, $10)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, $10, $11, $12, $13, $14, $15)
         RETURNING *
         "#
     )
@@ -180,13 +336,78 @@ pub async fn create_session(
     .bind(max_players_per_court)
     .bind(total_slots)
     .bind(price_vnd)
-    .fetch_one(pool)
+    .bind(max_guests_per_booking)
+    .bind(location_id)
+    .bind(min_players)
+    .bind(min_players_deadline_hours)
+    .bind(booking_opens_at)
+    .fetch_one(&mut **tx)
     .await?;
 
     Ok(session)
 }
 
+/// List an organizer's non-cancelled sessions from `today` on, soonest first
+pub async fn list_upcoming_for_organizer(pool: &PgPool, organizer_id: Uuid, today: NaiveDate) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as::<_, Session>(
+        r#"
+        SELECT * FROM sessions
+        WHERE organizer_id = $1 AND cancelled = false AND date >= $2
+        ORDER BY date, time
+        "#
+    )
+    .bind(organizer_id)
+    .bind(today)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// A capacity change that would leave booked slots without a place
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Capacity too low: {booked_slots} slots are booked but new capacity is {new_total}. Cancel bookings for at least {} slot(s) first",
+    booked_slots - new_total
+)]
+pub struct CapacityTooLow {
+    pub booked_slots: i32,
+    pub new_total: i32,
+}
+
+/// Recalculate available slots after a capacity change
+///
+/// Increases add the delta to the free slots; decreases are rejected with
+/// [`CapacityTooLow`] if the new capacity would no longer cover the slots that
+/// are already booked.
+pub fn recalculate_available_slots(
+    current_total: i32,
+    current_available: i32,
+    new_total: i32,
+) -> Result<i32> {
+    let booked_slots = current_total - current_available;
+    if new_total < booked_slots {
+        return Err(CapacityTooLow { booked_slots, new_total }.into());
+    }
+    Ok(current_available + (new_total - current_total))
+}
+
+/// Split booked slots across courts, filling each court before the next
+///
+/// Returns one entry per court with the number of players assigned to it.
+pub fn court_fill(courts: i32, max_players_per_court: i32, booked_slots: i32) -> Vec<i32> {
+    let mut remaining = booked_slots.max(0);
+    (0..courts.max(1))
+        .map(|_| {
+            let on_court = remaining.min(max_players_per_court);
+            remaining -= on_court;
+            on_court
+        })
+        .collect()
+}
+
 /// Update session (admin only)
+#[allow(clippy::too_many_arguments)]
 pub async fn update_session(
     pool: &PgPool,
     id: Uuid,
@@ -195,24 +416,35 @@ pub async fn update_session(
     time: Option<NaiveTime>,
     end_time: Option<NaiveTime>,
     location: Option<&str>,
+    location_id: Option<Uuid>,
     courts: Option<i32>,
     max_players_per_court: Option<i32>,
     price_vnd: Option<i32>,
+    max_guests_per_booking: Option<i32>,
+    min_players: Option<i32>,
+    min_players_deadline_hours: Option<i32>,
+    booking_opens_at: Option<DateTime<Utc>>,
 ) -> Result<Session> {
-    // Get current session to recalculate slots if needed
-    let current = find_by_id(pool, id).await?
+    let mut tx = pool.begin().await?;
+
+    // Lock the row so bookings and cancellations can't change the free slots
+    // between reading them and writing the recalculated count
+    let current = find_by_id_for_update(&mut tx, id).await?
         .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
 
     // Calculate new total slots if courts or max_players changed
     let new_courts = courts.unwrap_or(current.courts);
     let new_max_players = max_players_per_court
         .or(current.max_players_per_court)
-        .unwrap_or(6);
+        .unwrap_or(DEFAULT_MAX_PLAYERS_PER_COURT);
     let new_total_slots = new_courts * new_max_players;
 
-    // Calculate new available slots (preserve the diff)
-    let booked_slots = current.total_slots - current.available_slots;
-    let new_available_slots = (new_total_slots - booked_slots).max(0);
+    // Calculate new available slots (refuses to drop below booked slots)
+    let new_available_slots = recalculate_available_slots(
+        current.total_slots,
+        current.available_slots,
+        new_total_slots,
+    )?;
 
     let session = sqlx::query_as::<_, Session>(
         r#"
@@ -227,6 +459,11 @@ pub async fn update_session(
             total_slots = $9,
             available_slots = $10,
             price_vnd = COALESCE($11, price_vnd),
+            max_guests_per_booking = COALESCE($12, max_guests_per_booking),
+            location_id = COALESCE($13, location_id),
+            min_players = $14,
+            min_players_deadline_hours = $15,
+            booking_opens_at = $16,
             updated_at = NOW()
         WHERE id = $1
         RETURNING *
@@ -243,6 +480,38 @@ pub async fn update_session(
     .bind(new_total_slots)
     .bind(new_available_slots)
     .bind(price_vnd)
+    .bind(max_guests_per_booking)
+    .bind(location_id)
+    .bind(min_players)
+    .bind(min_players_deadline_hours)
+    .bind(booking_opens_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(session)
+}
+
+/// Compute USD price from a VND price and a VND-per-USD rate (rounded to cents)
+pub fn compute_price_usd(price_vnd: i32, vnd_per_usd: f64) -> Option<Decimal> {
+    let rate = Decimal::from_f64(vnd_per_usd).filter(|r| !r.is_zero())?;
+    Some((Decimal::from(price_vnd) / rate).round_dp(2))
+}
+
+/// Set the stored USD price for a session
+pub async fn update_price_usd(pool: &PgPool, id: Uuid, price_usd: Option<Decimal>) -> Result<Session> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        UPDATE sessions
+        SET price_usd = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(price_usd)
     .fetch_one(pool)
     .await?;
 
@@ -268,6 +537,149 @@ pub async fn cancel_session(pool: &PgPool, id: Uuid) -> Result<Session> {
     Ok(session)
 }
 
+/// Set or clear a session's cover image URL
+pub async fn set_image_url(pool: &PgPool, id: Uuid, image_url: Option<&str>) -> Result<Session> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        UPDATE sessions
+        SET image_url = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(image_url)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(session)
+}
+
+/// Set who can see a session's participant list
+pub async fn set_roster_visibility(pool: &PgPool, id: Uuid, visibility: &str) -> Result<Session> {
+    let mut tx = pool.begin().await?;
+    let session = set_roster_visibility_in_tx(&mut tx, id, visibility).await?;
+    tx.commit().await?;
+
+    Ok(session)
+}
+
+/// Set who can see a session's participant list within an existing transaction
+pub async fn set_roster_visibility_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    visibility: &str,
+) -> Result<Session> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        UPDATE sessions
+        SET roster_visibility = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(visibility)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(session)
+}
+
+/// Turn a session's waitlist on or off
+pub async fn set_waitlist_enabled(pool: &PgPool, id: Uuid, enabled: bool) -> Result<Session> {
+    let mut tx = pool.begin().await?;
+    let session = set_waitlist_enabled_in_tx(&mut tx, id, enabled).await?;
+    tx.commit().await?;
+
+    Ok(session)
+}
+
+/// Turn a session's waitlist on or off within an existing transaction
+pub async fn set_waitlist_enabled_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    enabled: bool,
+) -> Result<Session> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        UPDATE sessions
+        SET waitlist_enabled = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(enabled)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(session)
+}
+
+/// Cancel session within a transaction, recording why
+pub async fn cancel_session_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    reason: &str,
+) -> Result<Session> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        UPDATE sessions
+        SET cancelled = true,
+            cancelled_at = NOW(),
+            cancellation_reason = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(reason)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(session)
+}
+
+/// Find sessions that reached their min-players cutoff without enough
+/// confirmed players (user + guests of confirmed, non-cancelled bookings).
+/// `now` is the club's wall-clock time (see `club_now`), comparable with a
+/// session's `date + time`.
+pub async fn find_undersubscribed_at_cutoff(
+    pool: &PgPool,
+    now: chrono::NaiveDateTime,
+    default_deadline_hours: i32,
+) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as::<_, Session>(
+        r#"
+        SELECT s.* FROM sessions s
+        WHERE s.cancelled = false
+          AND s.min_players IS NOT NULL
+          AND (s.date + s.time) > $1
+          AND (s.date + s.time)
+              - make_interval(hours => COALESCE(s.min_players_deadline_hours, $2)) <= $1
+          AND (
+              SELECT COALESCE(SUM(1 + b.guest_count), 0)
+              FROM bookings b
+              WHERE b.session_id = s.id
+                AND b.payment_status = 'confirmed'
+                AND b.cancelled_at IS NULL
+          ) < s.min_players
+        ORDER BY s.date, s.time
+        "#
+    )
+    .bind(now)
+    .bind(default_deadline_hours)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
 /// Delete session (admin only)
 pub async fn delete_session(pool: &PgPool, id: Uuid) -> Result<()> {
     sqlx::query("DELETE FROM sessions WHERE id = $1")
@@ -279,20 +691,23 @@ pub async fn delete_session(pool: &PgPool, id: Uuid) -> Result<()> {
 }
 
 /// Decrement available slots (atomic)
+///
+/// Returns false and leaves the session untouched if fewer than `count` slots
+/// are free, so callers can reject the booking instead of overselling.
 pub async fn decrement_available_slots(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     session_id: Uuid,
     count: i32,
-) -> Result<()> {
-    sqlx::query(
-        "UPDATE sessions SET available_slots = available_slots - $2 WHERE id = $1"
+) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE sessions SET available_slots = available_slots - $2 WHERE id = $1 AND available_slots >= $2"
     )
     .bind(session_id)
     .bind(count)
     .execute(&mut **tx)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
 /// Increment available slots (atomic)
@@ -312,8 +727,28 @@ pub async fn increment_available_slots(
     Ok(())
 }
 
+/// Increment available slots within a transaction
+pub async fn increment_available_slots_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    session_id: Uuid,
+    count: i32,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE sessions SET available_slots = available_slots + $2 WHERE id = $1"
+    )
+    .bind(session_id)
+    .bind(count)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
 /// Get confirmed participants for a session (paid bookings only)
 /// Deduplicates users - if a user has multiple bookings, aggregates their guest counts
+///
+/// Guests who redeemed an invite are listed in their own right, and no longer
+/// counted among the booker's guests.
 pub async fn get_session_participants(
     pool: &PgPool,
     session_id: Uuid,
@@ -323,30 +758,72 @@ pub async fn get_session_participants(
 
     let participants = sqlx::query_as::<_, SessionParticipant>(
         r#"
+        WITH players AS (
+            SELECT
+                b.user_id,
+                GREATEST(b.guest_count - (
+                    SELECT COUNT(*) FROM booking_invites i
+                    WHERE i.booking_id = b.id AND i.redeemed_by IS NOT NULL
+                ), 0) AS guest_count,
+                b.created_at AS joined_at
+            FROM bookings b
+            WHERE b.session_id = $1
+              AND b.payment_status = 'confirmed'
+              AND b.cancelled_at IS NULL
+            UNION ALL
+            SELECT i.redeemed_by, 0, i.redeemed_at
+            FROM booking_invites i
+            JOIN bookings b ON b.id = i.booking_id
+            WHERE b.session_id = $1
+              AND b.payment_status = 'confirmed'
+              AND b.cancelled_at IS NULL
+              AND i.redeemed_by IS NOT NULL
+        )
         SELECT
             u.id as user_id,
             u.name,
             u.avatar_url,
-            COALESCE(SUM(b.guest_count), 0)::int4 as guest_count
+            COALESCE(SUM(p.guest_count), 0)::int4 as guest_count,
+            u.show_in_roster
+        FROM players p
+        JOIN users u ON u.id = p.user_id
+        GROUP BY u.id, u.name, u.avatar_url, u.show_in_roster
+        ORDER BY MIN(p.joined_at) ASC
+        LIMIT $2
+        "#
+    )
+    .bind(session_id)
+    .bind(limit_val)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(participants)
+}
+
+/// Email and name of every confirmed participant of a session
+pub async fn list_participant_contacts(
+    pool: &PgPool,
+    session_id: Uuid,
+) -> Result<Vec<(String, Option<String>)>> {
+    let contacts = sqlx::query_as(
+        r#"
+        SELECT DISTINCT u.email, u.name
         FROM bookings b
         JOIN users u ON u.id = b.user_id
         WHERE b.session_id = $1
           AND b.payment_status = 'confirmed'
           AND b.cancelled_at IS NULL
-        GROUP BY u.id, u.name, u.avatar_url
-        ORDER BY MIN(b.created_at) ASC
-        LIMIT $2
+          AND u.deleted_at IS NULL
         "#
     )
     .bind(session_id)
-    .bind(limit_val)
     .fetch_all(pool)
     .await?;
 
-    Ok(participants)
+    Ok(contacts)
 }
 
-/// Count unique confirmed participants for a session
+/// Count unique confirmed participants for a session, redeemed guests included
 pub async fn count_session_participants(
     pool: &PgPool,
     session_id: Uuid,
@@ -354,10 +831,21 @@ pub async fn count_session_participants(
     let count: (i64,) = sqlx::query_as(
         r#"
         SELECT COUNT(DISTINCT user_id) as count
-        FROM bookings
-        WHERE session_id = $1
-          AND payment_status = 'confirmed'
-          AND cancelled_at IS NULL
+        FROM (
+            SELECT b.user_id
+            FROM bookings b
+            WHERE b.session_id = $1
+              AND b.payment_status = 'confirmed'
+              AND b.cancelled_at IS NULL
+            UNION ALL
+            SELECT i.redeemed_by
+            FROM booking_invites i
+            JOIN bookings b ON b.id = i.booking_id
+            WHERE b.session_id = $1
+              AND b.payment_status = 'confirmed'
+              AND b.cancelled_at IS NULL
+              AND i.redeemed_by IS NOT NULL
+        ) players
         "#
     )
     .bind(session_id)
@@ -366,3 +854,126 @@ pub async fn count_session_participants(
 
     Ok(count.0)
 }
+
+/// Count unique confirmed participants (redeemed guests included) for a batch of sessions
+///
+/// Sessions with no confirmed bookings are absent from the map.
+pub async fn counts_for_sessions(pool: &PgPool, session_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+    let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+        r#"
+        SELECT session_id, COUNT(DISTINCT user_id)
+        FROM (
+            SELECT b.session_id, b.user_id
+            FROM bookings b
+            WHERE b.session_id = ANY($1)
+              AND b.payment_status = 'confirmed'
+              AND b.cancelled_at IS NULL
+            UNION ALL
+            SELECT b.session_id, i.redeemed_by
+            FROM booking_invites i
+            JOIN bookings b ON b.id = i.booking_id
+            WHERE b.session_id = ANY($1)
+              AND b.payment_status = 'confirmed'
+              AND b.cancelled_at IS NULL
+              AND i.redeemed_by IS NOT NULL
+        ) players
+        GROUP BY session_id
+        "#
+    )
+    .bind(session_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recalculate_available_slots_increase() {
+        // 12 total, 4 booked -> 18 total keeps 4 booked
+        assert_eq!(recalculate_available_slots(12, 8, 18).unwrap(), 14);
+    }
+
+    #[test]
+    fn test_recalculate_available_slots_decrease() {
+        assert_eq!(recalculate_available_slots(12, 8, 6).unwrap(), 2);
+
+        let err = recalculate_available_slots(12, 4, 6).unwrap_err();
+        assert!(err.to_string().contains("at least 2 slot(s)"));
+        assert!(err.downcast_ref::<CapacityTooLow>().is_some());
+    }
+
+    #[test]
+    fn test_recalculate_available_slots_exact_fit() {
+        // New capacity exactly equals booked slots
+        assert_eq!(recalculate_available_slots(12, 4, 8).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_court_fill() {
+        assert_eq!(court_fill(3, 4, 6), vec![4, 2, 0]);
+        assert_eq!(court_fill(2, 4, 0), vec![0, 0]);
+        // Overbooked sessions never exceed a court's capacity
+        assert_eq!(court_fill(2, 4, 9), vec![4, 4]);
+    }
+
+    #[test]
+    fn test_time_of_day_parse_list() {
+        assert_eq!(
+            TimeOfDay::parse_list("morning,evening"),
+            vec![TimeOfDay::Morning, TimeOfDay::Evening]
+        );
+        assert_eq!(
+            TimeOfDay::parse_list(" Afternoon , bogus,afternoon"),
+            vec![TimeOfDay::Afternoon]
+        );
+        assert!(TimeOfDay::parse_list("all").is_empty());
+        assert!(TimeOfDay::parse_list("night").is_empty());
+    }
+
+    /// Runs against `DATABASE_URL` when set, inside a transaction that is rolled back
+    #[tokio::test]
+    async fn test_create_session_in_tx_with_waitlist_and_roster_settings() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+
+        let (organizer_id,): (Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO users (email, role_id, auth_provider, auth_provider_id)
+            SELECT 'session-settings-test@example.com', id, 'email', 'session-settings-test' FROM roles WHERE name = 'organizer'
+            RETURNING id
+            "#,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+        let date = Utc::now().date_naive() + chrono::Duration::days(7);
+        let time = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let session = create_session_in_tx(
+            &mut tx, organizer_id, "Settings test", date, time, None, "Court 1", None, 2, Some(4), None, None, None,
+            None, None,
+        )
+        .await
+        .unwrap();
+        // Column defaults when the request leaves the settings out
+        assert!(session.waitlist_enabled);
+        assert_eq!(session.roster_visibility, "public");
+
+        let session = set_waitlist_enabled_in_tx(&mut tx, session.id, false).await.unwrap();
+        let session = set_roster_visibility_in_tx(&mut tx, session.id, "participants").await.unwrap();
+        assert!(!session.waitlist_enabled);
+        assert_eq!(session.roster_visibility, "participants");
+
+        let stored = find_by_id_for_update(&mut tx, session.id).await.unwrap().unwrap();
+        assert!(!stored.waitlist_enabled);
+        assert_eq!(stored.roster_visibility, "participants");
+        assert_eq!(stored.total_slots, 8);
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Record a Stripe event as received.
+/// Returns false if the event was already recorded (duplicate delivery).
+pub async fn try_record(
+    pool: &PgPool,
+    event_id: &str,
+    event_type: &str,
+    event_created_at: DateTime<Utc>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO stripe_events (id, event_type, event_created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (id) DO NOTHING
+        "#
+    )
+    .bind(event_id)
+    .bind(event_type)
+    .bind(event_created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Forget a recorded event so a Stripe retry can be processed again
+pub async fn remove(pool: &PgPool, event_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM stripe_events WHERE id = $1")
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
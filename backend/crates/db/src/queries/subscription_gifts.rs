@@ -0,0 +1,137 @@
+use crate::models::{transaction_types, Subscription, SubscriptionGift};
+use crate::queries::{subscriptions, ticket_transactions};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Hold a paid gift for a recipient without an account
+///
+/// Idempotent per Stripe subscription: a renewal paid before the recipient
+/// signs up (new period end) adds its tickets to the held gift, while a
+/// duplicate delivery of the same invoice changes nothing. Returns whether the
+/// held gift was created or extended.
+#[allow(clippy::too_many_arguments)]
+pub async fn hold(
+    pool: &PgPool,
+    recipient_email: &str,
+    purchaser_id: Uuid,
+    stripe_subscription_id: &str,
+    stripe_customer_id: &str,
+    tickets: i32,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO subscription_gifts (
+            recipient_email, purchaser_id, stripe_subscription_id, stripe_customer_id,
+            tickets, current_period_start, current_period_end
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (stripe_subscription_id) DO UPDATE
+        SET tickets = subscription_gifts.tickets + EXCLUDED.tickets,
+            current_period_end = EXCLUDED.current_period_end
+        WHERE subscription_gifts.claimed_at IS NULL
+          AND ABS(EXTRACT(EPOCH FROM EXCLUDED.current_period_end - subscription_gifts.current_period_end)) >= 60
+        "#
+    )
+    .bind(recipient_email)
+    .bind(purchaser_id)
+    .bind(stripe_subscription_id)
+    .bind(stripe_customer_id)
+    .bind(tickets)
+    .bind(period_start)
+    .bind(period_end)
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(result) => Ok(result.rows_affected() > 0),
+        // A concurrent hold of the same subscription got there first
+        Err(sqlx::Error::Database(db)) if db.is_unique_violation() => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether a gift for this email is still waiting to be claimed
+pub async fn has_unclaimed_for_email(pool: &PgPool, recipient_email: &str) -> Result<bool> {
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM subscription_gifts WHERE recipient_email = $1 AND claimed_at IS NULL)"
+    )
+    .bind(recipient_email)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists)
+}
+
+/// Turn the oldest unclaimed gift for `email` into a subscription for `user_id`
+///
+/// Returns None if there is nothing to claim. A lapsed subscription is
+/// reactivated by the gift; a user whose subscription is still active keeps
+/// the gift held, the same rule gift checkout applies.
+pub async fn claim_for_user(pool: &PgPool, user_id: Uuid, email: &str) -> Result<Option<Subscription>> {
+    let mut tx = pool.begin().await?;
+
+    let gift = sqlx::query_as::<_, SubscriptionGift>(
+        r#"
+        SELECT * FROM subscription_gifts
+        WHERE recipient_email = $1 AND claimed_at IS NULL
+        ORDER BY created_at, id
+        LIMIT 1
+        FOR UPDATE
+        "#
+    )
+    .bind(email.trim().to_lowercase())
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(gift) = gift else {
+        return Ok(None);
+    };
+
+    let Some(subscription) = subscriptions::create_in_tx(
+        &mut tx,
+        user_id,
+        &gift.stripe_subscription_id,
+        &gift.stripe_customer_id,
+        gift.tickets,
+        gift.current_period_start,
+        gift.current_period_end,
+    )
+    .await?
+    else {
+        tracing::warn!(
+            "User {} already has an active subscription; gift {} stays held",
+            user_id,
+            gift.id
+        );
+        return Ok(None);
+    };
+
+    ticket_transactions::create(
+        &mut tx,
+        user_id,
+        Some(subscription.id),
+        None,
+        transaction_types::SUBSCRIPTION_GRANT,
+        gift.tickets,
+        gift.tickets,
+        Some("Gift subscription"),
+        None,
+    )
+    .await?;
+
+    sqlx::query(
+        "UPDATE subscription_gifts SET claimed_by = $2, claimed_at = NOW() WHERE id = $1"
+    )
+    .bind(gift.id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(subscription))
+}
@@ -1,13 +1,22 @@
 use crate::models::Subscription;
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Months, Utc};
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
-/// Find subscription by user ID
-pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Option<Subscription>> {
+/// Find a user's subscription, preferring an active (or trialing) one and
+/// otherwise the most recently created
+///
+/// Use this for balance and pricing decisions so a returning subscriber is
+/// never matched to a stale row.
+pub async fn find_active_or_latest_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Option<Subscription>> {
     let subscription = sqlx::query_as::<_, Subscription>(
-        "SELECT * FROM subscriptions WHERE user_id = $1"
+        r#"
+        SELECT * FROM subscriptions
+        WHERE user_id = $1
+        ORDER BY (status IN ('active', 'trialing')) DESC, created_at DESC, id DESC
+        LIMIT 1
+        "#
     )
     .bind(user_id)
     .fetch_optional(pool)
@@ -22,7 +31,7 @@ pub async fn has_active_subscription(pool: &PgPool, user_id: Uuid) -> Result<boo
         r#"
         SELECT COUNT(*) FROM subscriptions
         WHERE user_id = $1
-          AND status = 'active'
+          AND status IN ('active', 'trialing')
         "#
     )
     .bind(user_id)
@@ -42,7 +51,7 @@ pub async fn get_active_for_booking(
         r#"
         SELECT * FROM subscriptions
         WHERE user_id = $1
-          AND status = 'active'
+          AND status IN ('active', 'trialing')
         FOR UPDATE
         "#
     )
@@ -95,6 +104,50 @@ pub async fn restore_ticket(pool: &PgPool, subscription_id: Uuid) -> Result<i32>
     Ok(result.0)
 }
 
+/// Find user's subscription with FOR UPDATE lock, with the same preference
+/// as `find_active_or_latest_by_user_id`
+pub async fn find_by_user_id_for_update(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<Option<Subscription>> {
+    let subscription = sqlx::query_as::<_, Subscription>(
+        r#"
+        SELECT * FROM subscriptions
+        WHERE user_id = $1
+        ORDER BY (status IN ('active', 'trialing')) DESC, created_at DESC, id DESC
+        LIMIT 1
+        FOR UPDATE
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(subscription)
+}
+
+/// Restore one ticket to subscription within a transaction
+/// Returns the new ticket balance
+pub async fn restore_ticket_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    subscription_id: Uuid,
+) -> Result<i32> {
+    let result: (i32,) = sqlx::query_as(
+        r#"
+        UPDATE subscriptions
+        SET tickets_remaining = tickets_remaining + 1,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING tickets_remaining
+        "#
+    )
+    .bind(subscription_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(result.0)
+}
+
 /// Add bonus tickets to subscription
 /// Returns the new ticket balance
 pub async fn add_bonus_tickets(pool: &PgPool, subscription_id: Uuid, amount: i32) -> Result<i32> {
@@ -135,7 +188,9 @@ pub async fn revoke_tickets(pool: &PgPool, subscription_id: Uuid, amount: i32) -
     Ok(result.0)
 }
 
-/// Create a new subscription record
+/// Create a user's subscription, or reactivate their lapsed one
+///
+/// Returns None if the user's subscription is still active. See `create_in_tx`.
 pub async fn create(
     pool: &PgPool,
     user_id: Uuid,
@@ -144,7 +199,38 @@ pub async fn create(
     tickets: i32,
     period_start: DateTime<Utc>,
     period_end: DateTime<Utc>,
-) -> Result<Subscription> {
+) -> Result<Option<Subscription>> {
+    let mut tx = pool.begin().await?;
+    let subscription = create_in_tx(
+        &mut tx,
+        user_id,
+        stripe_subscription_id,
+        stripe_customer_id,
+        tickets,
+        period_start,
+        period_end,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(subscription)
+}
+
+/// Create an active subscription within a transaction
+///
+/// A user has one subscription row (`user_id` is unique), so a returning
+/// subscriber's expired or cancelled row is reactivated with the new Stripe
+/// ids, period and tickets. Returns None, changing nothing, if the user's
+/// subscription is still active or trialing.
+pub async fn create_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    stripe_subscription_id: &str,
+    stripe_customer_id: &str,
+    tickets: i32,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+) -> Result<Option<Subscription>> {
     let subscription = sqlx::query_as::<_, Subscription>(
         r#"
         INSERT INTO subscriptions (
@@ -154,6 +240,19 @@ pub async fn create(
             auto_renew
         )
         VALUES ($1, 'active', $2, $3, $4, $5, $6, true)
+        ON CONFLICT (user_id) DO UPDATE
+        SET status = 'active',
+            tickets_remaining = EXCLUDED.tickets_remaining,
+            stripe_subscription_id = EXCLUDED.stripe_subscription_id,
+            stripe_customer_id = EXCLUDED.stripe_customer_id,
+            current_period_start = EXCLUDED.current_period_start,
+            current_period_end = EXCLUDED.current_period_end,
+            auto_renew = true,
+            billing_interval = NULL,
+            billing_interval_count = NULL,
+            last_invoice_id = NULL,
+            updated_at = NOW()
+        WHERE subscriptions.status NOT IN ('active', 'trialing')
         RETURNING *
         "#,
     )
@@ -163,7 +262,7 @@ pub async fn create(
     .bind(stripe_customer_id)
     .bind(period_start)
     .bind(period_end)
-    .fetch_one(pool)
+    .fetch_optional(&mut **tx)
     .await?;
 
     Ok(subscription)
@@ -231,18 +330,63 @@ pub async fn update_from_stripe(
     Ok(subscription)
 }
 
-/// Renew subscription by adding tickets and extending period
+/// Result of a renewal under the rollover cap
+#[derive(Debug, Clone)]
+pub struct Renewal {
+    pub subscription: Subscription,
+    /// Renewal tickets that didn't fit under the cap
+    pub tickets_forfeited: i32,
+}
+
+/// Renewal tickets that fit under `cap` on top of `balance`
+///
+/// A balance already over the cap (e.g. from bonus tickets) is kept, it just
+/// gets nothing added.
+pub fn renewal_tickets_granted(balance: i32, tickets: i32, cap: Option<i32>) -> i32 {
+    match cap {
+        Some(cap) => tickets.min(cap - balance).max(0),
+        None => tickets,
+    }
+}
+
+/// Renew subscription by adding tickets (up to `rollover_cap`) and extending period
+///
+/// Returns None, changing nothing, if `invoice_id` was already applied or the
+/// period already ends at `new_period_end` (within a minute). Both are checked
+/// under the row lock, so a webhook retry racing the reconcile job can't
+/// grant the same invoice twice.
 pub async fn renew_subscription(
     pool: &PgPool,
     subscription_id: Uuid,
+    invoice_id: &str,
     tickets_to_add: i32,
+    rollover_cap: Option<i32>,
     new_period_end: DateTime<Utc>,
-) -> Result<Subscription> {
+) -> Result<Option<Renewal>> {
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query_as::<_, Subscription>(
+        "SELECT * FROM subscriptions WHERE id = $1 FOR UPDATE"
+    )
+    .bind(subscription_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let same_period = current
+        .current_period_end
+        .is_some_and(|end| (new_period_end - end).num_seconds().abs() < 60);
+    if current.last_invoice_id.as_deref() == Some(invoice_id) || same_period {
+        return Ok(None);
+    }
+
+    let granted = renewal_tickets_granted(current.tickets_remaining, tickets_to_add, rollover_cap);
+
     let subscription = sqlx::query_as::<_, Subscription>(
         r#"
         UPDATE subscriptions
         SET tickets_remaining = tickets_remaining + $2,
             current_period_end = $3,
+            last_invoice_id = $4,
             status = 'active',
             updated_at = NOW()
         WHERE id = $1
@@ -250,12 +394,77 @@ pub async fn renew_subscription(
         "#,
     )
     .bind(subscription_id)
-    .bind(tickets_to_add)
+    .bind(granted)
     .bind(new_period_end)
-    .fetch_one(pool)
+    .bind(invoice_id)
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(subscription)
+    tx.commit().await?;
+
+    Ok(Some(Renewal {
+        subscription,
+        tickets_forfeited: tickets_to_add - granted,
+    }))
+}
+
+/// Record the Stripe invoice a new subscription was granted from
+pub async fn set_last_invoice_id(pool: &PgPool, stripe_subscription_id: &str, invoice_id: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE subscriptions
+        SET last_invoice_id = $2,
+            updated_at = NOW()
+        WHERE stripe_subscription_id = $1
+        "#,
+    )
+    .bind(stripe_subscription_id)
+    .bind(invoice_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// End of a billing period of `count` × `interval` starting at `start`
+///
+/// `interval` is a Stripe plan interval (`day`, `week`, `month`, `year`);
+/// returns None for anything else. Month arithmetic clamps to the end of
+/// shorter months, like Stripe does.
+pub fn billing_period_end(start: DateTime<Utc>, interval: &str, count: i32) -> Option<DateTime<Utc>> {
+    let count = u32::try_from(count).ok().filter(|c| *c > 0)?;
+    match interval {
+        "day" => Some(start + Duration::days(count as i64)),
+        "week" => Some(start + Duration::weeks(count as i64)),
+        "month" => start.checked_add_months(Months::new(count)),
+        "year" => start.checked_add_months(Months::new(count * 12)),
+        _ => None,
+    }
+}
+
+/// Store the Stripe billing interval of a subscription's plan
+pub async fn set_billing_interval(
+    pool: &PgPool,
+    stripe_subscription_id: &str,
+    interval: &str,
+    interval_count: i32,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE subscriptions
+        SET billing_interval = $2,
+            billing_interval_count = $3,
+            updated_at = NOW()
+        WHERE stripe_subscription_id = $1
+        "#,
+    )
+    .bind(stripe_subscription_id)
+    .bind(interval)
+    .bind(interval_count)
+    .execute(pool)
+    .await?;
+
+    Ok(())
 }
 
 /// Update auto-renew flag
@@ -315,3 +524,101 @@ pub async fn mark_expired(pool: &PgPool, stripe_subscription_id: &str) -> Result
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_renewal_tickets_granted_under_cap() {
+        assert_eq!(renewal_tickets_granted(5, 10, Some(20)), 10);
+        assert_eq!(renewal_tickets_granted(5, 10, None), 10);
+    }
+
+    #[test]
+    fn test_renewal_tickets_granted_at_cap() {
+        // Tops up exactly to the cap, or grants nothing when already there
+        assert_eq!(renewal_tickets_granted(10, 10, Some(20)), 10);
+        assert_eq!(renewal_tickets_granted(20, 10, Some(20)), 0);
+    }
+
+    #[test]
+    fn test_renewal_tickets_granted_over_cap() {
+        assert_eq!(renewal_tickets_granted(15, 10, Some(20)), 5);
+        // A balance already over the cap is kept, with nothing added
+        assert_eq!(renewal_tickets_granted(25, 10, Some(20)), 0);
+    }
+
+    #[test]
+    fn test_billing_period_end_by_interval() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 15, 10, 0, 0).unwrap();
+        assert_eq!(billing_period_end(start, "month", 1), Some(Utc.with_ymd_and_hms(2026, 2, 15, 10, 0, 0).unwrap()));
+        assert_eq!(billing_period_end(start, "month", 3), Some(Utc.with_ymd_and_hms(2026, 4, 15, 10, 0, 0).unwrap()));
+        assert_eq!(billing_period_end(start, "year", 1), Some(Utc.with_ymd_and_hms(2027, 1, 15, 10, 0, 0).unwrap()));
+        assert_eq!(billing_period_end(start, "week", 2), Some(Utc.with_ymd_and_hms(2026, 1, 29, 10, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_billing_period_end_clamps_to_month_end() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 31, 0, 0, 0).unwrap();
+        assert_eq!(billing_period_end(start, "month", 1), Some(Utc.with_ymd_and_hms(2026, 2, 28, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_billing_period_end_rejects_unknown_input() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 15, 0, 0, 0).unwrap();
+        assert_eq!(billing_period_end(start, "fortnight", 1), None);
+        assert_eq!(billing_period_end(start, "month", 0), None);
+    }
+
+    /// Runs against `DATABASE_URL` when set, inside a transaction that is rolled back
+    #[tokio::test]
+    async fn test_resubscribe_after_expiry_reactivates_row() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+
+        let (user_id,): (Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO users (email, role_id, auth_provider, auth_provider_id)
+            SELECT 'resubscribe-test@example.com', id, 'email', 'resubscribe-test' FROM roles WHERE name = 'user'
+            RETURNING id
+            "#,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+        let start = Utc::now();
+        let first = create_in_tx(&mut tx, user_id, "sub_old", "cus_old", 4, start, start + Duration::days(30))
+            .await
+            .unwrap()
+            .expect("first subscription is created");
+
+        // Still active: a second checkout must not replace it
+        assert!(create_in_tx(&mut tx, user_id, "sub_new", "cus_new", 8, start, start + Duration::days(30))
+            .await
+            .unwrap()
+            .is_none());
+
+        sqlx::query("UPDATE subscriptions SET status = 'expired' WHERE id = $1")
+            .bind(first.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let renewed_start = start + Duration::days(60);
+        let second = create_in_tx(&mut tx, user_id, "sub_new", "cus_new", 8, renewed_start, renewed_start + Duration::days(30))
+            .await
+            .unwrap()
+            .expect("expired subscription is reactivated");
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.status, "active");
+        assert_eq!(second.tickets_remaining, 8);
+        assert_eq!(second.stripe_subscription_id.as_deref(), Some("sub_new"));
+        assert!(second.current_period_end > first.current_period_end);
+    }
+}
@@ -1,9 +1,12 @@
-use crate::models::{TicketTransaction, BonusTicket};
+use crate::models::{transaction_types, TicketTransaction, BonusTicket};
 use anyhow::Result;
-use sqlx::{PgPool, Postgres, Transaction};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use loafy_types::{Cursor, Pagination};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Transaction};
 use uuid::Uuid;
 
 /// Create a ticket transaction record
+#[allow(clippy::too_many_arguments)]
 pub async fn create(
     tx: &mut Transaction<'_, Postgres>,
     user_id: Uuid,
@@ -39,7 +42,27 @@ pub async fn create(
     Ok(transaction)
 }
 
+/// Check whether a ticket has already been restored for a booking
+pub async fn has_restoration_for_booking(
+    tx: &mut Transaction<'_, Postgres>,
+    booking_id: Uuid,
+) -> Result<bool> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM ticket_transactions
+        WHERE booking_id = $1
+          AND transaction_type = 'restored'
+        "#
+    )
+    .bind(booking_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count.0 > 0)
+}
+
 /// Create a ticket transaction record (non-transaction version for pool)
+#[allow(clippy::too_many_arguments)]
 pub async fn create_with_pool(
     pool: &PgPool,
     user_id: Uuid,
@@ -79,16 +102,15 @@ pub async fn create_with_pool(
 pub async fn list_user_transactions(
     pool: &PgPool,
     user_id: Uuid,
-    page: i64,
-    per_page: i64,
+    pagination: Pagination,
 ) -> Result<(Vec<TicketTransaction>, i64)> {
-    let offset = (page - 1) * per_page;
+    let Pagination { per_page, offset, .. } = pagination;
 
     let transactions = sqlx::query_as::<_, TicketTransaction>(
         r#"
         SELECT * FROM ticket_transactions
         WHERE user_id = $1
-        ORDER BY created_at DESC
+        ORDER BY created_at DESC, id DESC
         LIMIT $2 OFFSET $3
         "#
     )
@@ -108,7 +130,102 @@ pub async fn list_user_transactions(
     Ok((transactions, total.0))
 }
 
+/// List a user's transactions strictly after a keyset cursor, newest first
+pub async fn list_user_transactions_after(
+    pool: &PgPool,
+    user_id: Uuid,
+    after: Option<Cursor>,
+    limit: i32,
+) -> Result<Vec<TicketTransaction>> {
+    let transactions = sqlx::query_as::<_, TicketTransaction>(
+        r#"
+        SELECT * FROM ticket_transactions
+        WHERE user_id = $1
+          AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $4
+        "#
+    )
+    .bind(user_id)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transactions)
+}
+
+/// Count tickets a user has spent on bookings, net of restorations
+pub async fn count_tickets_used(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let (used,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(-SUM(amount), 0)::BIGINT
+        FROM ticket_transactions
+        WHERE user_id = $1 AND transaction_type IN ($2, $3)
+        "#
+    )
+    .bind(user_id)
+    .bind(transaction_types::USED)
+    .bind(transaction_types::RESTORED)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(used.max(0))
+}
+
+/// Lifetime ticket totals for a user, summed from the ledger
+///
+/// Debits (used, expired, revoked) are reported as positive counts.
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct TicketSummary {
+    pub subscription_granted: i64,
+    pub bonus_granted: i64,
+    pub used: i64,
+    pub restored: i64,
+    pub expired: i64,
+    pub revoked: i64,
+    /// Net tickets moved in from merged duplicate accounts
+    pub merged: i64,
+    /// Sum of every transaction amount
+    pub ledger_balance: i64,
+}
+
+/// Aggregate a user's whole ticket ledger in one pass
+pub async fn summarize_for_user(pool: &PgPool, user_id: Uuid) -> Result<TicketSummary> {
+    let summary = sqlx::query_as::<_, TicketSummary>(
+        r#"
+        SELECT
+            COALESCE(SUM(amount) FILTER (WHERE transaction_type = $2), 0)::BIGINT as subscription_granted,
+            COALESCE(SUM(amount) FILTER (WHERE transaction_type IN ($3, $4, $5)), 0)::BIGINT as bonus_granted,
+            COALESCE(-SUM(amount) FILTER (WHERE transaction_type = $6), 0)::BIGINT as used,
+            COALESCE(SUM(amount) FILTER (WHERE transaction_type = $7), 0)::BIGINT as restored,
+            COALESCE(-SUM(amount) FILTER (WHERE transaction_type = $8), 0)::BIGINT as expired,
+            COALESCE(-SUM(amount) FILTER (WHERE transaction_type = $9), 0)::BIGINT as revoked,
+            COALESCE(SUM(amount) FILTER (WHERE transaction_type = $10), 0)::BIGINT as merged,
+            COALESCE(SUM(amount), 0)::BIGINT as ledger_balance
+        FROM ticket_transactions
+        WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .bind(transaction_types::SUBSCRIPTION_GRANT)
+    .bind(transaction_types::BONUS_REFERRAL)
+    .bind(transaction_types::BONUS_BIRTHDAY)
+    .bind(transaction_types::BONUS_MANUAL)
+    .bind(transaction_types::USED)
+    .bind(transaction_types::RESTORED)
+    .bind(transaction_types::EXPIRED)
+    .bind(transaction_types::REVOKED)
+    .bind(transaction_types::MERGED)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(summary)
+}
+
 /// Create a bonus ticket record
+#[allow(clippy::too_many_arguments)]
 pub async fn create_bonus_ticket(
     pool: &PgPool,
     user_id: Uuid,
@@ -163,21 +280,134 @@ pub async fn has_birthday_bonus_for_year(
     Ok(count.0 > 0)
 }
 
-/// List user's bonus tickets
+/// List user's bonus tickets with pagination
 pub async fn list_user_bonus_tickets(
     pool: &PgPool,
     user_id: Uuid,
-) -> Result<Vec<BonusTicket>> {
+    pagination: Pagination,
+) -> Result<(Vec<BonusTicket>, i64)> {
+    let Pagination { per_page, offset, .. } = pagination;
+
     let bonuses = sqlx::query_as::<_, BonusTicket>(
         r#"
         SELECT * FROM bonus_tickets
         WHERE user_id = $1
-        ORDER BY created_at DESC
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2 OFFSET $3
         "#
     )
     .bind(user_id)
+    .bind(per_page)
+    .bind(offset)
     .fetch_all(pool)
     .await?;
 
-    Ok(bonuses)
+    let total: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM bonus_tickets WHERE user_id = $1"
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((bonuses, total.0))
+}
+
+/// Ticket transaction with user email and booking code for the admin ledger
+#[derive(Debug, Clone, FromRow)]
+pub struct TicketTransactionWithDetails {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub transaction_type: String,
+    pub amount: i32,
+    pub balance_after: i32,
+    pub notes: Option<String>,
+    pub booking_code: Option<String>,
+    pub admin_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filters for the admin ticket transaction ledger
+#[derive(Debug, Clone, Default)]
+pub struct TicketTransactionFilters {
+    pub transaction_type: Option<String>,
+    pub user_id: Option<Uuid>,
+    /// Inclusive start date (UTC)
+    pub from_date: Option<NaiveDate>,
+    /// Inclusive end date (UTC)
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Append WHERE conditions for the ledger filters
+fn push_transaction_filters(query_builder: &mut QueryBuilder<'_, Postgres>, filters: &TicketTransactionFilters) {
+    query_builder.push(" WHERE 1=1");
+
+    if let Some(ref transaction_type) = filters.transaction_type {
+        query_builder.push(" AND tt.transaction_type = ");
+        query_builder.push_bind(transaction_type.clone());
+    }
+
+    if let Some(user_id) = filters.user_id {
+        query_builder.push(" AND tt.user_id = ");
+        query_builder.push_bind(user_id);
+    }
+
+    if let Some(date) = filters.from_date {
+        query_builder.push(" AND tt.created_at >= ");
+        query_builder.push_bind(date.and_time(NaiveTime::MIN).and_utc());
+    }
+
+    if let Some(date) = filters.to_date {
+        query_builder.push(" AND tt.created_at < ");
+        query_builder.push_bind((date + Duration::days(1)).and_time(NaiveTime::MIN).and_utc());
+    }
+}
+
+/// List ticket transactions across all users with filters and pagination (admin ledger)
+pub async fn list_transactions_filtered(
+    pool: &PgPool,
+    filters: &TicketTransactionFilters,
+    pagination: Pagination,
+) -> Result<(Vec<TicketTransactionWithDetails>, i64)> {
+    let Pagination { per_page, offset, .. } = pagination;
+
+    let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+        r#"
+        SELECT
+            tt.id,
+            tt.user_id,
+            u.email as user_email,
+            tt.transaction_type,
+            tt.amount,
+            tt.balance_after,
+            tt.notes,
+            b.booking_code,
+            tt.admin_id,
+            tt.created_at
+        FROM ticket_transactions tt
+        JOIN users u ON tt.user_id = u.id
+        LEFT JOIN bookings b ON tt.booking_id = b.id
+        "#
+    );
+    push_transaction_filters(&mut query_builder, filters);
+    query_builder.push(" ORDER BY tt.created_at DESC, tt.id DESC LIMIT ");
+    query_builder.push_bind(per_page);
+    query_builder.push(" OFFSET ");
+    query_builder.push_bind(offset);
+
+    let transactions = query_builder
+        .build_query_as::<TicketTransactionWithDetails>()
+        .fetch_all(pool)
+        .await?;
+
+    let mut count_builder: QueryBuilder<Postgres> =
+        QueryBuilder::new("SELECT COUNT(*) FROM ticket_transactions tt");
+    push_transaction_filters(&mut count_builder, filters);
+
+    let total: (i64,) = count_builder
+        .build_query_as()
+        .fetch_one(pool)
+        .await?;
+
+    Ok((transactions, total.0))
 }
@@ -5,8 +5,9 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 /// Base SQL query for selecting user with role.
-/// Reused across multiple query functions to avoid duplication.
-const USER_WITH_ROLE_SELECT: &str = r#"
+/// Reused across the user and admin queries so the role's permission list is built in one place.
+/// A user whose role row is missing is treated as a plain 'user' rather than dropped.
+pub(crate) const USER_WITH_ROLE_SELECT: &str = r#"
     SELECT
         u.id,
         u.email,
@@ -17,6 +18,7 @@ const USER_WITH_ROLE_SELECT: &str = r#"
         u.auth_provider,
         u.auth_provider_id,
         u.birthday,
+        u.show_in_roster,
         u.created_at as user_created_at,
         u.updated_at as user_updated_at,
         u.deleted_at as user_deleted_at,
@@ -24,9 +26,16 @@ const USER_WITH_ROLE_SELECT: &str = r#"
         u.suspended_until as user_suspended_until,
         u.suspension_reason as user_suspension_reason,
         u.suspended_by as user_suspended_by,
-        r.name as role_name
+        COALESCE(r.name, 'user') as role_name,
+        ARRAY(
+            SELECT p.name
+            FROM role_permissions rp
+            JOIN permissions p ON p.id = rp.permission_id
+            WHERE rp.role_id = COALESCE(r.id, (SELECT id FROM roles WHERE name = 'user'))
+            ORDER BY p.name
+        ) as permissions
     FROM users u
-    JOIN roles r ON u.role_id = r.id
+    LEFT JOIN roles r ON u.role_id = r.id
 "#;
 
 /// Find user by email
@@ -60,6 +69,19 @@ pub async fn find_with_role_by_id(pool: &PgPool, id: Uuid) -> Result<Option<User
     Ok(user)
 }
 
+/// Users whose `role_id` points at no role; they read as plain users until an admin fixes them
+pub async fn find_orphaned_role_users(pool: &PgPool) -> Result<Vec<UserWithRole>> {
+    let query = format!(
+        "{} WHERE r.id IS NULL AND u.deleted_at IS NULL ORDER BY u.created_at",
+        USER_WITH_ROLE_SELECT
+    );
+    let users = sqlx::query_as::<_, UserWithRole>(&query)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(users)
+}
+
 /// Find user with role by auth provider ID (Supabase user ID)
 /// Excludes soft-deleted users
 pub async fn find_with_role_by_auth_provider_id(
@@ -139,6 +161,7 @@ pub async fn update_user(
     name: Option<&str>,
     avatar_url: Option<&str>,
     phone: Option<&str>,
+    show_in_roster: Option<bool>,
 ) -> Result<User> {
     let user = sqlx::query_as::<_, User>(
         r#"
@@ -146,6 +169,7 @@ pub async fn update_user(
         SET name = COALESCE($2, name),
             avatar_url = COALESCE($3, avatar_url),
             phone = COALESCE($4, phone),
+            show_in_roster = COALESCE($5, show_in_roster),
             updated_at = NOW()
         WHERE id = $1
         RETURNING *
@@ -155,6 +179,7 @@ pub async fn update_user(
     .bind(name)
     .bind(avatar_url)
     .bind(phone)
+    .bind(show_in_roster)
     .fetch_one(pool)
     .await?;
 
@@ -292,7 +317,7 @@ pub async fn delete_user(pool: &PgPool, user_id: Uuid) -> Result<()> {
     sqlx::query(
         r#"
         UPDATE bookings
-        SET payment_status = 'cancelled', cancelled_at = NOW()
+        SET payment_status = 'cancelled', cancelled_at = NOW(), cancellation_reason = 'account_deleted'
         WHERE user_id = $1 AND payment_status = 'pending'
         "#
     )
@@ -372,6 +397,31 @@ pub async fn unsuspend_user(pool: &PgPool, user_id: Uuid) -> Result<User> {
     Ok(user)
 }
 
+/// Store (or clear, with None) the hash of the user's calendar feed token
+pub async fn set_calendar_feed_token_hash(pool: &PgPool, user_id: Uuid, token_hash: Option<&str>) -> Result<()> {
+    sqlx::query(
+        "UPDATE users SET calendar_feed_token_hash = $2, updated_at = NOW() WHERE id = $1"
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Find the active user owning a calendar feed token hash
+pub async fn find_by_calendar_feed_token_hash(pool: &PgPool, token_hash: &str) -> Result<Option<User>> {
+    let user = sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE calendar_feed_token_hash = $1 AND deleted_at IS NULL"
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user)
+}
+
 /// Set user birthday (can only be set once)
 pub async fn set_birthday(pool: &PgPool, user_id: Uuid, birthday: NaiveDate) -> Result<User> {
     // First check if birthday is already set
@@ -418,7 +468,7 @@ pub async fn find_birthday_bonus_eligible(
     let users = sqlx::query_as::<_, User>(
         r#"
         SELECT u.* FROM users u
-        JOIN subscriptions s ON s.user_id = u.id AND s.status = 'active'
+        JOIN subscriptions s ON s.user_id = u.id AND s.status IN ('active', 'trialing')
         LEFT JOIN bonus_tickets bt ON bt.user_id = u.id
             AND bt.bonus_type = 'birthday'
             AND bt.year = $4
@@ -439,3 +489,20 @@ pub async fn find_birthday_bonus_eligible(
 
     Ok(users)
 }
+
+/// List emails of all active admin users
+pub async fn list_admin_emails(pool: &PgPool) -> Result<Vec<String>> {
+    let emails: Vec<(String,)> = sqlx::query_as(
+        r#"
+        SELECT u.email
+        FROM users u
+        JOIN roles r ON u.role_id = r.id
+        WHERE r.name = 'admin' AND u.deleted_at IS NULL
+        ORDER BY u.email
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(emails.into_iter().map(|(email,)| email).collect())
+}
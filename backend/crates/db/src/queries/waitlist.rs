@@ -0,0 +1,197 @@
+use crate::models::{Session, WaitlistEntry};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Add a user to a session's waitlist
+///
+/// A previous promoted entry for the same user is replaced, so a user whose
+/// hold lapsed can queue again.
+pub async fn join(
+    pool: &PgPool,
+    session_id: Uuid,
+    user_id: Uuid,
+    guest_count: i32,
+    payment_method: &str,
+) -> Result<WaitlistEntry> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM waitlist_entries WHERE session_id = $1 AND user_id = $2 AND promoted_at IS NOT NULL"
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let entry = sqlx::query_as::<_, WaitlistEntry>(
+        r#"
+        INSERT INTO waitlist_entries (session_id, user_id, guest_count, payment_method)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(guest_count)
+    .bind(payment_method)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(entry)
+}
+
+/// Remove a user's waiting entry; returns false if they weren't waiting
+pub async fn leave(pool: &PgPool, session_id: Uuid, user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM waitlist_entries WHERE session_id = $1 AND user_id = $2 AND promoted_at IS NULL"
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Find a user's waiting (not yet promoted) entry for a session
+pub async fn find_waiting(
+    pool: &PgPool,
+    session_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<WaitlistEntry>> {
+    let entry = sqlx::query_as::<_, WaitlistEntry>(
+        r#"
+        SELECT * FROM waitlist_entries
+        WHERE session_id = $1 AND user_id = $2 AND promoted_at IS NULL
+        "#
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(entry)
+}
+
+/// Count waiting entries queued before the given time (people ahead)
+pub async fn count_ahead(pool: &PgPool, session_id: Uuid, joined_at: DateTime<Utc>) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM waitlist_entries
+        WHERE session_id = $1 AND promoted_at IS NULL AND created_at < $2
+        "#
+    )
+    .bind(session_id)
+    .bind(joined_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Count waiting entries for a session
+pub async fn count_waiting(pool: &PgPool, session_id: Uuid) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM waitlist_entries WHERE session_id = $1 AND promoted_at IS NULL"
+    )
+    .bind(session_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Count waiting entries for a batch of sessions
+///
+/// Sessions with nobody waiting are absent from the map.
+pub async fn counts_for_sessions(pool: &PgPool, session_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+    let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+        r#"
+        SELECT session_id, COUNT(*)
+        FROM waitlist_entries
+        WHERE session_id = ANY($1) AND promoted_at IS NULL
+        GROUP BY session_id
+        "#
+    )
+    .bind(session_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// List waiting entries for a session in queue order
+pub async fn list_waiting(pool: &PgPool, session_id: Uuid) -> Result<Vec<WaitlistEntry>> {
+    let entries = sqlx::query_as::<_, WaitlistEntry>(
+        r#"
+        SELECT * FROM waitlist_entries
+        WHERE session_id = $1 AND promoted_at IS NULL
+        ORDER BY created_at ASC
+        "#
+    )
+    .bind(session_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+/// List sessions starting after `club_now` that have free slots and people waiting
+///
+/// Sessions with their waitlist turned off are skipped. `club_now` is the
+/// club's wall-clock time, as session dates and times are.
+pub async fn list_sessions_to_promote(pool: &PgPool, club_now: NaiveDateTime) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as::<_, Session>(
+        r#"
+        SELECT s.* FROM sessions s
+        WHERE s.cancelled = false
+          AND s.waitlist_enabled
+          AND s.available_slots > 0
+          AND s.date + s.time > $1
+          AND EXISTS (
+              SELECT 1 FROM waitlist_entries w
+              WHERE w.session_id = s.id AND w.promoted_at IS NULL
+          )
+        ORDER BY s.date ASC, s.time ASC
+        "#
+    )
+    .bind(club_now)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Mark an entry as promoted to the given booking
+pub async fn mark_promoted(pool: &PgPool, entry_id: Uuid, booking_id: Uuid) -> Result<WaitlistEntry> {
+    let entry = sqlx::query_as::<_, WaitlistEntry>(
+        r#"
+        UPDATE waitlist_entries
+        SET promoted_at = NOW(),
+            booking_id = $2
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(entry_id)
+    .bind(booking_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(entry)
+}
+
+/// Remove an entry (e.g. the user can no longer be booked)
+pub async fn remove(pool: &PgPool, entry_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM waitlist_entries WHERE id = $1")
+        .bind(entry_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
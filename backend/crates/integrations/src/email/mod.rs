@@ -1 +1,74 @@
-// Resend email integration will be added here
+//! Transactional email via the Resend HTTP API.
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::Serialize;
+
+const RESEND_API_URL: &str = "https://api.resend.com/emails";
+
+#[derive(Clone)]
+pub struct EmailClient {
+    api_key: String,
+    from: String,
+    client: Client,
+}
+
+#[derive(Serialize)]
+struct SendEmailRequest<'a> {
+    from: &'a str,
+    to: &'a [&'a str],
+    subject: &'a str,
+    html: &'a str,
+}
+
+impl EmailClient {
+    pub fn new(api_key: String, from: String) -> Self {
+        Self {
+            api_key,
+            from,
+            client: Client::new(),
+        }
+    }
+
+    /// Create from `RESEND_API_KEY` and `FROM_EMAIL`; None if email is not configured
+    pub fn from_env() -> Option<Self> {
+        let api_key = std::env::var("RESEND_API_KEY").ok()?;
+        let from = std::env::var("FROM_EMAIL").ok()?;
+        Some(Self::new(api_key, from))
+    }
+
+    /// Send an HTML email to a single recipient
+    pub async fn send(&self, to: &str, subject: &str, html: &str) -> Result<()> {
+        let request = SendEmailRequest {
+            from: &self.from,
+            to: &[to],
+            subject,
+            html,
+        };
+
+        let response = self
+            .client
+            .post(RESEND_API_URL)
+            .bearer_auth(&self.api_key)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send email: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Email API returned {}: {}", status, body));
+        }
+
+        Ok(())
+    }
+}
+
+/// Escape text for safe inclusion in HTML email bodies
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
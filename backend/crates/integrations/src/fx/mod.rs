@@ -0,0 +1,159 @@
+//! VND → USD exchange rate with in-memory caching.
+//!
+//! The rate is refreshed at most once a day. If a refresh fails, the last
+//! known rate keeps being used and the source isn't asked again for a few
+//! minutes, so an outage doesn't slow down every request that shows a price.
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Default rate source (free, no API key, USD base)
+const DEFAULT_RATE_URL: &str = "https://open.er-api.com/v6/latest/USD";
+
+/// How long a fetched rate is considered fresh
+const CACHE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long to wait before retrying after a failed refresh
+const FAILURE_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Longest a rate request may take before it counts as failed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+pub struct FxRates {
+    url: String,
+    client: Client,
+    cache: Arc<RwLock<RateCache>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct RateCache {
+    /// Last successfully fetched rate, with when it was fetched
+    last_good: Option<(f64, Instant)>,
+    /// When the most recent refresh failed, if it did
+    failed_at: Option<Instant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RatesResponse {
+    rates: HashMap<String, f64>,
+}
+
+impl FxRates {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to build HTTP client"),
+            cache: Arc::new(RwLock::new(RateCache::default())),
+        }
+    }
+
+    /// Create from `FX_RATE_API_URL`, falling back to the default source
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("FX_RATE_API_URL").unwrap_or_else(|_| DEFAULT_RATE_URL.to_string()))
+    }
+
+    /// Fetch the current VND per USD rate
+    async fn fetch_rate(&self) -> Result<f64> {
+        let response = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch exchange rate: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Exchange rate fetch error {}: {}", status, text));
+        }
+
+        let body: RatesResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse exchange rate response: {}", e))?;
+
+        body.rates
+            .get("VND")
+            .copied()
+            .filter(|r| *r > 0.0)
+            .ok_or_else(|| anyhow!("No VND rate in exchange rate response"))
+    }
+
+    /// Get VND per USD rate (cached for 24 hours)
+    ///
+    /// Returns the last known rate if a refresh fails, or None if no rate
+    /// has ever been fetched successfully. After a failure the source is left
+    /// alone for `FAILURE_BACKOFF`.
+    pub async fn vnd_per_usd(&self) -> Option<f64> {
+        // Check cache
+        let cached = *self.cache.read().await;
+        let last_good = cached.last_good.map(|(rate, _)| rate);
+        if cached.last_good.is_some_and(|(_, fetched_at)| fetched_at.elapsed() < CACHE_DURATION) {
+            return last_good;
+        }
+        if cached.failed_at.is_some_and(|failed_at| failed_at.elapsed() < FAILURE_BACKOFF) {
+            return last_good;
+        }
+
+        match self.fetch_rate().await {
+            Ok(rate) => {
+                let mut cache = self.cache.write().await;
+                *cache = RateCache {
+                    last_good: Some((rate, Instant::now())),
+                    failed_at: None,
+                };
+                Some(rate)
+            }
+            Err(e) => {
+                tracing::warn!("Exchange rate refresh failed, using last known rate: {}", e);
+                self.cache.write().await.failed_at = Some(Instant::now());
+                last_good
+            }
+        }
+    }
+
+    /// Convert a VND amount to USD using the current rate
+    pub async fn vnd_to_usd(&self, amount_vnd: i64) -> Option<f64> {
+        self.vnd_per_usd()
+            .await
+            .map(|rate| ((amount_vnd as f64 / rate) * 100.0).round() / 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_last_rate_and_backs_off() {
+        // Nothing listens on the discard port, so every fetch fails
+        let fx = FxRates::new("http://127.0.0.1:9/latest".to_string());
+        assert_eq!(fx.vnd_per_usd().await, None);
+
+        // Instant may not reach that far back right after boot
+        let Some(stale) = Instant::now().checked_sub(CACHE_DURATION + Duration::from_secs(1)) else {
+            return;
+        };
+        *fx.cache.write().await = RateCache {
+            last_good: Some((25_000.0, stale)),
+            failed_at: None,
+        };
+
+        assert_eq!(fx.vnd_per_usd().await, Some(25_000.0));
+        let failed_at = fx.cache.read().await.failed_at.expect("failure recorded");
+
+        // Within the backoff the failure isn't retried
+        assert_eq!(fx.vnd_per_usd().await, Some(25_000.0));
+        assert_eq!(fx.cache.read().await.failed_at, Some(failed_at));
+        assert_eq!(fx.vnd_to_usd(250_000).await, Some(10.0));
+    }
+}
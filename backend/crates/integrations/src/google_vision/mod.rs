@@ -1 +1,116 @@
-// Google Cloud Vision OCR integration will be added here
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const ANNOTATE_URL: &str = "https://vision.googleapis.com/v1/images:annotate";
+
+/// Google Cloud Vision client (text detection only)
+#[derive(Clone)]
+pub struct GoogleVision {
+    api_key: String,
+    client: Client,
+}
+
+#[derive(Serialize)]
+struct AnnotateRequest {
+    requests: Vec<ImageRequest>,
+}
+
+#[derive(Serialize)]
+struct ImageRequest {
+    image: Image,
+    features: Vec<Feature>,
+}
+
+#[derive(Serialize)]
+struct Image {
+    content: String,
+}
+
+#[derive(Serialize)]
+struct Feature {
+    #[serde(rename = "type")]
+    feature_type: &'static str,
+}
+
+#[derive(Deserialize)]
+struct AnnotateResponse {
+    #[serde(default)]
+    responses: Vec<ImageResponse>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageResponse {
+    full_text_annotation: Option<TextAnnotation>,
+    error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct TextAnnotation {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+impl GoogleVision {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            client: Client::new(),
+        }
+    }
+
+    /// Run text detection on an image and return the full detected text
+    pub async fn detect_text(&self, image_bytes: &[u8]) -> Result<String> {
+        let request = AnnotateRequest {
+            requests: vec![ImageRequest {
+                image: Image {
+                    content: STANDARD.encode(image_bytes),
+                },
+                features: vec![Feature {
+                    feature_type: "DOCUMENT_TEXT_DETECTION",
+                }],
+            }],
+        };
+
+        let response = self
+            .client
+            .post(ANNOTATE_URL)
+            .query(&[("key", &self.api_key)])
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to call Google Vision: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Google Vision error {}: {}", status, text));
+        }
+
+        let body: AnnotateResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse Google Vision response: {}", e))?;
+
+        let image_response = body
+            .responses
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Empty Google Vision response"))?;
+
+        if let Some(error) = image_response.error {
+            return Err(anyhow!("Google Vision error: {}", error.message));
+        }
+
+        Ok(image_response
+            .full_text_annotation
+            .map(|a| a.text)
+            .unwrap_or_default())
+    }
+}
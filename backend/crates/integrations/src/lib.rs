@@ -1,4 +1,6 @@
 pub mod supabase;
 pub mod stripe;
 pub mod google_vision;
+pub mod ocr;
+pub mod fx;
 pub mod email;
@@ -0,0 +1,191 @@
+//! Payment screenshot OCR verification.
+//!
+//! Reads an uploaded bank transfer screenshot, extracts the transferred
+//! amount and reference, and auto-confirms the booking when both match.
+//! Each user gets a limited number of OCR calls per month; once the cap is
+//! reached (or OCR is unavailable) the booking falls back to manual review.
+
+use anyhow::Result;
+use loafy_db::{
+    models::Booking,
+    queries::{bookings, config, ocr_usage},
+    PgPool,
+};
+use loafy_types::enums::VerificationStatus;
+
+use crate::google_vision::GoogleVision;
+
+/// Amount and reference details found in a screenshot
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractedPayment {
+    /// All plausible VND amounts found in the text
+    pub amounts: Vec<i64>,
+    /// Whether the booking code appears in the text
+    pub reference_found: bool,
+}
+
+impl ExtractedPayment {
+    /// True when both the expected amount and reference were found
+    pub fn matches(&self, expected_amount_vnd: i64) -> bool {
+        self.reference_found && self.amounts.contains(&expected_amount_vnd)
+    }
+}
+
+/// Extract amounts and look for the booking code in OCR text.
+///
+/// Banks format amounts as `100,000`, `100.000 VND` or `+100 000đ`, and often
+/// strip dashes from transfer memos, so the booking code is compared with all
+/// non-alphanumeric characters removed.
+pub fn extract_payment_details(text: &str, booking_code: &str) -> ExtractedPayment {
+    let normalized_text: String = text
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    let normalized_code: String = booking_code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+
+    let reference_found = !normalized_code.is_empty() && normalized_text.contains(&normalized_code);
+
+    let mut amounts = Vec::new();
+    for line in text.lines() {
+        let mut digits = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+            } else if matches!(c, ',' | '.' | ' ')
+                && !digits.is_empty()
+                && chars.peek().is_some_and(|n| n.is_ascii_digit())
+            {
+                // Thousands separator between digits
+                continue;
+            } else if !digits.is_empty() {
+                push_amount(&mut amounts, &digits);
+                digits.clear();
+            }
+        }
+        if !digits.is_empty() {
+            push_amount(&mut amounts, &digits);
+        }
+    }
+
+    ExtractedPayment {
+        amounts,
+        reference_found,
+    }
+}
+
+/// Keep only values that look like VND transfer amounts
+fn push_amount(amounts: &mut Vec<i64>, digits: &str) {
+    if let Ok(value) = digits.parse::<i64>() {
+        if value >= 1_000 && !amounts.contains(&value) {
+            amounts.push(value);
+        }
+    }
+}
+
+/// Verify an uploaded payment screenshot for a pending QR-transfer booking.
+///
+/// Consumes one OCR call from the user's monthly allowance. When the allowance
+/// is used up, OCR is not configured, or the screenshot doesn't match, the
+/// booking is set to `pending_review` for manual admin verification.
+pub async fn verify_payment_screenshot(
+    pool: &PgPool,
+    vision: Option<&GoogleVision>,
+    booking: &Booking,
+    image_bytes: &[u8],
+) -> Result<Booking> {
+    let expected_amount = (booking.price_paid_vnd + booking.guest_price_paid_vnd) as i64;
+
+    let status = match vision {
+        None => {
+            tracing::warn!("OCR not configured, sending booking {} to manual review", booking.booking_code);
+            VerificationStatus::PendingReview
+        }
+        Some(vision) => {
+            let limit = config::get_ocr_monthly_limit(pool).await.unwrap_or(5);
+            if !ocr_usage::try_consume(pool, booking.user_id, limit).await? {
+                tracing::info!(
+                    "User {} reached monthly OCR limit ({}), booking {} needs manual review",
+                    booking.user_id,
+                    limit,
+                    booking.booking_code
+                );
+                VerificationStatus::PendingReview
+            } else {
+                match vision.detect_text(image_bytes).await {
+                    Ok(text) => {
+                        let extracted = extract_payment_details(&text, &booking.booking_code);
+                        if extracted.matches(expected_amount) {
+                            VerificationStatus::AutoConfirmed
+                        } else {
+                            tracing::info!(
+                                "OCR mismatch for booking {} (reference found: {}, amounts: {:?}, expected: {})",
+                                booking.booking_code,
+                                extracted.reference_found,
+                                extracted.amounts,
+                                expected_amount
+                            );
+                            VerificationStatus::PendingReview
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("OCR failed for booking {}: {}", booking.booking_code, e);
+                        VerificationStatus::PendingReview
+                    }
+                }
+            }
+        }
+    };
+
+    let updated = match status {
+        VerificationStatus::AutoConfirmed => match bookings::auto_confirm_payment(pool, booking.id).await? {
+            Some(confirmed) => {
+                tracing::info!("✓ Auto-confirmed booking {} via OCR", booking.booking_code);
+                confirmed
+            }
+            None => {
+                tracing::warn!(
+                    "Booking {} is no longer awaiting payment, skipping OCR confirmation",
+                    booking.booking_code
+                );
+                return Ok(booking.clone());
+            }
+        },
+        _ => bookings::update_verification_status(pool, booking.id, "pending_review", None).await?,
+    };
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_matches_amount_and_reference() {
+        let text = "Chuyen tien thanh cong\nSo tien: 100,000 VND\nNoi dung: LB ABC12";
+        let extracted = extract_payment_details(text, "LB-ABC12");
+        assert!(extracted.reference_found);
+        assert!(extracted.amounts.contains(&100_000));
+        assert!(extracted.matches(100_000));
+    }
+
+    #[test]
+    fn test_extract_handles_dot_and_space_separators() {
+        let extracted = extract_payment_details("+190.000đ\n1 250 000 VND", "LB-XYZ99");
+        assert!(extracted.amounts.contains(&190_000));
+        assert!(extracted.amounts.contains(&1_250_000));
+        assert!(!extracted.reference_found);
+    }
+
+    #[test]
+    fn test_no_match_on_wrong_amount() {
+        let extracted = extract_payment_details("90,000 VND LBABC12", "LB-ABC12");
+        assert!(!extracted.matches(100_000));
+    }
+}
@@ -1,10 +1,13 @@
 pub mod payments;
+pub mod refunds;
+mod retry;
 pub mod subscriptions;
 pub mod webhooks;
 
 pub use payments::StripePayments;
+pub use refunds::{refund_cancelled_booking, RefundOutcome};
 pub use subscriptions::StripeSubscriptions;
-pub use webhooks::handle_stripe_webhook;
+pub use webhooks::{handle_stripe_webhook, reconcile_subscription, InvoiceOutcome, WebhookRejected};
 
 // Re-export commonly used Stripe types for convenience
-pub use stripe::{PaymentIntent, PaymentIntentId};
+pub use stripe::{PaymentIntent, PaymentIntentId, PaymentIntentStatus};
@@ -5,6 +5,8 @@ use stripe::{
     PaymentIntentId, Refund,
 };
 
+use super::retry::with_retry;
+
 /// Fixed exchange rate: 1 USD = 25,000 VND
 const VND_TO_USD_RATE: f64 = 25_000.0;
 
@@ -72,9 +74,10 @@ impl StripePayments {
 
     /// Get payment intent by ID
     pub async fn get_payment_intent(&self, intent_id: &PaymentIntentId) -> Result<PaymentIntent> {
-        let payment_intent = PaymentIntent::retrieve(&self.client, intent_id, &[])
-            .await
-            .map_err(|e| anyhow!("Failed to retrieve PaymentIntent: {}", e))?;
+        let payment_intent =
+            with_retry("PaymentIntent retrieve", || PaymentIntent::retrieve(&self.client, intent_id, &[]))
+                .await
+                .map_err(|e| anyhow!("Failed to retrieve PaymentIntent: {}", e))?;
 
         Ok(payment_intent)
     }
@@ -95,8 +98,10 @@ impl StripePayments {
 
     /// Refund a completed payment
     pub async fn refund_payment(&self, payment_intent_id: &PaymentIntentId) -> Result<Refund> {
-        let mut params = CreateRefund::default();
-        params.payment_intent = Some(payment_intent_id.clone());
+        let params = CreateRefund {
+            payment_intent: Some(payment_intent_id.clone()),
+            ..Default::default()
+        };
 
         let refund = Refund::create(&self.client, params)
             .await
@@ -0,0 +1,153 @@
+use loafy_db::{models::Booking, queries::bookings, PgPool};
+use loafy_types::enums::PaymentStatus;
+
+use super::{PaymentIntentId, StripePayments};
+
+/// What happened to the card payment of a booking cancelled on the club's side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefundOutcome {
+    /// Not paid by card, so nothing to refund
+    NotNeeded,
+    Refunded,
+    /// Stripe isn't configured or rejected the refund; an admin must refund by hand
+    Failed(String),
+}
+
+impl RefundOutcome {
+    /// Payment status recording the outcome on the booking
+    pub fn payment_status(&self) -> Option<PaymentStatus> {
+        match self {
+            RefundOutcome::NotNeeded => None,
+            RefundOutcome::Refunded => Some(PaymentStatus::Refunded),
+            RefundOutcome::Failed(_) => Some(PaymentStatus::RefundFailed),
+        }
+    }
+}
+
+/// Whether a booking, as it was before cancellation, holds a card payment to refund
+pub fn needs_refund(booking: &Booking) -> bool {
+    booking.payment_method == "stripe"
+        && booking.payment_status == PaymentStatus::Confirmed.as_str()
+        && booking.stripe_payment_id.is_some()
+}
+
+/// Refund a cancelled booking's card payment and record the outcome on it
+///
+/// `booking` is the booking as it was before cancellation. A successful refund
+/// sets its payment status to `refunded`; a failed one to `refund_failed`, so
+/// admins can find it and refund by hand.
+pub async fn refund_cancelled_booking(
+    pool: &PgPool,
+    stripe: Option<&StripePayments>,
+    booking: &Booking,
+) -> RefundOutcome {
+    let outcome = refund(stripe, booking).await;
+
+    match &outcome {
+        RefundOutcome::NotNeeded => return outcome,
+        RefundOutcome::Refunded => tracing::info!(
+            "Refunded booking {} (PaymentIntent: {})",
+            booking.booking_code,
+            booking.stripe_payment_id.as_deref().unwrap_or_default()
+        ),
+        RefundOutcome::Failed(reason) => tracing::error!(
+            "Booking {} needs a manual refund: {}",
+            booking.booking_code,
+            reason
+        ),
+    }
+
+    if let Some(status) = outcome.payment_status() {
+        if let Err(e) = bookings::set_payment_status(pool, booking.id, status.as_str()).await {
+            tracing::error!(
+                "Failed to record refund status '{}' on booking {}: {}",
+                status.as_str(),
+                booking.booking_code,
+                e
+            );
+        }
+    }
+
+    outcome
+}
+
+async fn refund(stripe: Option<&StripePayments>, booking: &Booking) -> RefundOutcome {
+    if !needs_refund(booking) {
+        return RefundOutcome::NotNeeded;
+    }
+    let Some(stripe) = stripe else {
+        return RefundOutcome::Failed("Stripe not configured".to_string());
+    };
+    let intent_id: PaymentIntentId = match booking.stripe_payment_id.as_deref().unwrap_or_default().parse() {
+        Ok(id) => id,
+        Err(_) => return RefundOutcome::Failed("Invalid payment intent ID".to_string()),
+    };
+
+    match stripe.refund_payment(&intent_id).await {
+        Ok(_) => RefundOutcome::Refunded,
+        Err(e) => RefundOutcome::Failed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn booking(payment_method: &str, payment_status: &str, stripe_payment_id: Option<&str>) -> Booking {
+        Booking {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            session_id: Uuid::nil(),
+            booking_code: "LOAFY-TEST".to_string(),
+            guest_count: 0,
+            tickets_used: 0,
+            discount_applied: "none".to_string(),
+            price_paid_vnd: 100_000,
+            price_paid_usd: None,
+            guest_price_paid_vnd: 0,
+            guest_price_paid_usd: None,
+            payment_method: payment_method.to_string(),
+            payment_status: payment_status.to_string(),
+            verification_status: None,
+            payment_screenshot_url: None,
+            stripe_payment_id: stripe_payment_id.map(str::to_string),
+            payment_deadline: None,
+            cancelled_at: None,
+            cancellation_reason: None,
+            discount_code_id: None,
+            discount_code: None,
+            rescheduled_from: None,
+            verified_by: None,
+            verified_at: None,
+            received_amount_vnd: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_needs_refund_only_for_confirmed_card_payments() {
+        assert!(needs_refund(&booking("stripe", "confirmed", Some("pi_123"))));
+        assert!(!needs_refund(&booking("stripe", "pending", Some("pi_123"))));
+        assert!(!needs_refund(&booking("stripe", "confirmed", None)));
+        assert!(!needs_refund(&booking("qr", "confirmed", None)));
+    }
+
+    #[tokio::test]
+    async fn test_refund_without_stripe_fails() {
+        let outcome = refund(None, &booking("stripe", "confirmed", Some("pi_123"))).await;
+        assert_eq!(outcome, RefundOutcome::Failed("Stripe not configured".to_string()));
+        assert_eq!(outcome.payment_status(), Some(PaymentStatus::RefundFailed));
+
+        let outcome = refund(None, &booking("qr", "confirmed", None)).await;
+        assert_eq!(outcome, RefundOutcome::NotNeeded);
+        assert_eq!(outcome.payment_status(), None);
+    }
+
+    #[test]
+    fn test_refunded_outcome_status() {
+        assert_eq!(RefundOutcome::Refunded.payment_status(), Some(PaymentStatus::Refunded));
+    }
+}
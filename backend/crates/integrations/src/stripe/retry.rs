@@ -0,0 +1,90 @@
+//! Retries for transient Stripe failures (rate limits, 5xx, network errors).
+//!
+//! Only wrap idempotent calls (lookups, retrieves, setting a flag). Creates
+//! (payment intents, checkout sessions, customers, refunds) are sent without an
+//! idempotency key, so retrying them could duplicate a charge or object.
+
+use std::future::Future;
+use std::time::Duration;
+use stripe::StripeError;
+
+/// Maximum attempts per call, including the first
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubles on each further attempt
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Delay before the first retry after a 429. async-stripe does not expose the
+/// `Retry-After` header, so rate-limited calls back off from a longer floor.
+const RATE_LIMIT_DELAY: Duration = Duration::from_secs(2);
+
+/// Run `call`, retrying transient failures with exponential backoff
+pub(crate) async fn with_retry<T, F, Fut>(operation: &str, mut call: F) -> Result<T, StripeError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, StripeError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(e) => match retry_delay(&e, attempt) {
+                Some(delay) if attempt < MAX_ATTEMPTS => {
+                    tracing::warn!(
+                        "Stripe {} failed (attempt {}/{}): {}. Retrying in {}ms",
+                        operation,
+                        attempt,
+                        MAX_ATTEMPTS,
+                        e,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => return Err(e),
+            },
+        }
+    }
+}
+
+/// Backoff before retrying after `attempt` failed, or None if the error is permanent
+fn retry_delay(error: &StripeError, attempt: u32) -> Option<Duration> {
+    let base = match error {
+        StripeError::Stripe(e) if e.http_status == 429 => RATE_LIMIT_DELAY,
+        StripeError::Stripe(e) if e.http_status >= 500 => BASE_DELAY,
+        StripeError::ClientError(_) | StripeError::Timeout => BASE_DELAY,
+        _ => return None,
+    };
+
+    Some(base * 2u32.pow(attempt.saturating_sub(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stripe::RequestError;
+
+    fn request_error(http_status: u16) -> StripeError {
+        StripeError::Stripe(RequestError { http_status, ..Default::default() })
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially() {
+        assert_eq!(retry_delay(&request_error(503), 1), Some(BASE_DELAY));
+        assert_eq!(retry_delay(&request_error(503), 2), Some(BASE_DELAY * 2));
+        assert_eq!(retry_delay(&StripeError::Timeout, 1), Some(BASE_DELAY));
+    }
+
+    #[test]
+    fn test_retry_delay_uses_rate_limit_floor() {
+        assert_eq!(retry_delay(&request_error(429), 1), Some(RATE_LIMIT_DELAY));
+        assert_eq!(retry_delay(&request_error(429), 2), Some(RATE_LIMIT_DELAY * 2));
+    }
+
+    #[test]
+    fn test_retry_delay_skips_client_errors() {
+        assert_eq!(retry_delay(&request_error(400), 1), None);
+        assert_eq!(retry_delay(&request_error(404), 1), None);
+        assert_eq!(retry_delay(&StripeError::UnsupportedVersion, 1), None);
+    }
+}
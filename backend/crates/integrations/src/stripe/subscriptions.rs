@@ -2,10 +2,12 @@ use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 use stripe::{
     CancelSubscription, CheckoutSession, CheckoutSessionMode, Client, CreateCheckoutSession,
-    CreateCheckoutSessionLineItems, CreateCustomer, Customer, CustomerId, ListCustomers,
-    Subscription, SubscriptionId, UpdateSubscription,
+    CreateCheckoutSessionLineItems, CreateCustomer, Customer, CustomerId, Expandable, Invoice,
+    ListCustomers, Subscription, SubscriptionId, UpdateSubscription,
 };
 
+use super::retry::with_retry;
+
 #[derive(Clone)]
 pub struct StripeSubscriptions {
     client: Client,
@@ -31,7 +33,7 @@ impl StripeSubscriptions {
         list_params.email = Some(email);
         list_params.limit = Some(1);
 
-        let customers = Customer::list(&self.client, &list_params)
+        let customers = with_retry("customer lookup", || Customer::list(&self.client, &list_params))
             .await
             .map_err(|e| anyhow!("Failed to list customers: {}", e))?;
 
@@ -64,16 +66,23 @@ impl StripeSubscriptions {
 
     /// Create a Checkout Session for subscription purchase
     /// Returns the checkout URL to redirect the user to
+    ///
+    /// With `gift_recipient_email`, `user_id` pays but the subscription is
+    /// granted to the recipient when the first invoice is paid.
     pub async fn create_checkout_session(
         &self,
         customer_id: &str,
         user_id: &str,
+        gift_recipient_email: Option<&str>,
         success_url: &str,
         cancel_url: &str,
     ) -> Result<CheckoutSession> {
         let mut metadata = HashMap::new();
         metadata.insert("user_id".to_string(), user_id.to_string());
         metadata.insert("subscription_type".to_string(), "loafy_club".to_string());
+        if let Some(email) = gift_recipient_email {
+            metadata.insert("gift_recipient_email".to_string(), email.to_string());
+        }
 
         let customer_id = customer_id
             .parse::<CustomerId>()
@@ -96,6 +105,10 @@ impl StripeSubscriptions {
             metadata: Some({
                 let mut meta = HashMap::new();
                 meta.insert("user_id".to_string(), user_id.to_string());
+                // Read back from the invoice's subscription details on invoice.paid
+                if let Some(email) = gift_recipient_email {
+                    meta.insert("gift_recipient_email".to_string(), email.to_string());
+                }
                 meta
             }),
             ..Default::default()
@@ -124,9 +137,11 @@ impl StripeSubscriptions {
         let mut update = UpdateSubscription::new();
         update.cancel_at_period_end = Some(true);
 
-        let subscription = Subscription::update(&self.client, &subscription_id, update)
-            .await
-            .map_err(|e| anyhow!("Failed to cancel subscription: {}", e))?;
+        let subscription = with_retry("subscription update", || {
+            Subscription::update(&self.client, &subscription_id, update.clone())
+        })
+        .await
+        .map_err(|e| anyhow!("Failed to cancel subscription: {}", e))?;
 
         tracing::info!(
             "Set subscription {} to cancel at period end",
@@ -145,9 +160,11 @@ impl StripeSubscriptions {
         let mut update = UpdateSubscription::new();
         update.cancel_at_period_end = Some(false);
 
-        let subscription = Subscription::update(&self.client, &subscription_id, update)
-            .await
-            .map_err(|e| anyhow!("Failed to resume subscription: {}", e))?;
+        let subscription = with_retry("subscription update", || {
+            Subscription::update(&self.client, &subscription_id, update.clone())
+        })
+        .await
+        .map_err(|e| anyhow!("Failed to resume subscription: {}", e))?;
 
         tracing::info!("Resumed subscription {}", subscription_id);
 
@@ -160,13 +177,29 @@ impl StripeSubscriptions {
             .parse::<SubscriptionId>()
             .map_err(|e| anyhow!("Invalid subscription ID: {}", e))?;
 
-        let subscription = Subscription::retrieve(&self.client, &subscription_id, &[])
-            .await
-            .map_err(|e| anyhow!("Failed to retrieve subscription: {}", e))?;
+        let subscription =
+            with_retry("subscription retrieve", || Subscription::retrieve(&self.client, &subscription_id, &[]))
+                .await
+                .map_err(|e| anyhow!("Failed to retrieve subscription: {}", e))?;
 
         Ok(subscription)
     }
 
+    /// Latest invoice of a subscription, including its line items
+    pub async fn get_latest_invoice(&self, subscription: &Subscription) -> Result<Option<Invoice>> {
+        let invoice_id = match &subscription.latest_invoice {
+            Some(Expandable::Id(id)) => id.clone(),
+            Some(Expandable::Object(invoice)) => invoice.id.clone(),
+            None => return Ok(None),
+        };
+
+        let invoice = with_retry("invoice retrieve", || Invoice::retrieve(&self.client, &invoice_id, &[]))
+            .await
+            .map_err(|e| anyhow!("Failed to retrieve invoice: {}", e))?;
+
+        Ok(Some(invoice))
+    }
+
     /// Immediately cancel a subscription (for admin use or special cases)
     #[allow(dead_code)]
     pub async fn cancel_immediately(&self, subscription_id: &str) -> Result<Subscription> {
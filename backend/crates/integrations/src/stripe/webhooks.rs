@@ -1,11 +1,26 @@
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, TimeZone, Utc};
-use loafy_db::{queries::{bookings, subscriptions, ticket_transactions}, PgPool};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use loafy_core::booking::refund_booking_by_payment_id;
+use loafy_core::metrics;
+use loafy_db::{
+    models::transaction_types,
+    queries::{bookings, config, stripe_events, subscription_gifts, subscriptions, ticket_transactions, users},
+    PgPool,
+};
 use stripe::{Event, EventObject, EventType, Webhook};
 use uuid::Uuid;
 
-/// Number of tickets granted per subscription purchase/renewal
-const SUBSCRIPTION_TICKETS: i32 = 10;
+use super::StripeSubscriptions;
+
+/// Minimum time a booking stays held once its payment needs 3D Secure,
+/// so the unpaid-release job doesn't cancel it mid-authentication
+const AUTHENTICATION_HOLD_MINUTES: i64 = 15;
+
+/// Webhook rejected before processing (bad signature or replayed event).
+/// Callers should answer with 400 rather than 500.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct WebhookRejected(pub String);
 
 /// Handle Stripe webhook event with signature verification
 pub async fn handle_stripe_webhook(
@@ -16,37 +31,88 @@ pub async fn handle_stripe_webhook(
 ) -> Result<()> {
     // Verify webhook signature and construct event
     let event = Webhook::construct_event(payload, signature, webhook_secret)
-        .map_err(|e| anyhow!("Webhook signature verification failed: {}", e))?;
+        .map_err(|e| WebhookRejected(format!("Webhook signature verification failed: {}", e)))?;
+
+    // Reject old events to limit the replay window. Stripe retries a failed
+    // delivery with the original `created`, so retries arriving after the
+    // tolerance are refused too; raise `stripe_webhook_max_event_age_seconds`
+    // if deliveries need longer to recover.
+    let max_age_secs = config::get_stripe_webhook_max_event_age_secs(pool)
+        .await
+        .unwrap_or(600);
+    let event_created_at = Utc
+        .timestamp_opt(event.created, 0)
+        .single()
+        .ok_or_else(|| WebhookRejected("Invalid event timestamp".to_string()))?;
+    let age_secs = (Utc::now() - event_created_at).num_seconds();
+    if age_secs > max_age_secs {
+        tracing::warn!(
+            "Rejected stale Stripe webhook {} ({:?}): created {}s ago, tolerance {}s",
+            event.id, event.type_, age_secs, max_age_secs
+        );
+        return Err(WebhookRejected(format!(
+            "Event is too old ({}s, tolerance {}s)",
+            age_secs, max_age_secs
+        ))
+        .into());
+    }
+
+    // Drop duplicate deliveries of the same event
+    let event_id = event.id.to_string();
+    let is_new = stripe_events::try_record(pool, &event_id, &event.type_.to_string(), event_created_at).await?;
+    if !is_new {
+        tracing::warn!("Ignoring replayed Stripe webhook {} ({:?})", event.id, event.type_);
+        return Ok(());
+    }
 
     tracing::info!("Received Stripe webhook: {:?} ({})", event.type_, event.id);
 
+    if let Err(e) = process_event(&event, pool).await {
+        // Let Stripe's retry be processed instead of being treated as a duplicate
+        if let Err(remove_err) = stripe_events::remove(pool, &event_id).await {
+            tracing::error!("Failed to forget Stripe event {}: {}", event_id, remove_err);
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Route a verified event to the appropriate handler
+async fn process_event(event: &Event, pool: &PgPool) -> Result<()> {
     // Route to appropriate handler based on event type
     match event.type_ {
         // Payment Intent events (for bookings)
         EventType::PaymentIntentSucceeded => {
-            handle_payment_succeeded(&event, pool).await?;
+            handle_payment_succeeded(event, pool).await?;
         }
         EventType::PaymentIntentPaymentFailed => {
-            handle_payment_failed(&event).await?;
+            handle_payment_failed(event).await?;
         }
         EventType::PaymentIntentCanceled => {
-            handle_payment_canceled(&event).await?;
+            handle_payment_canceled(event).await?;
+        }
+        EventType::PaymentIntentRequiresAction => {
+            handle_payment_requires_action(event, pool).await?;
+        }
+        EventType::ChargeRefunded => {
+            handle_charge_refunded(event, pool).await?;
         }
         // Subscription events
         EventType::CheckoutSessionCompleted => {
-            handle_checkout_completed(&event, pool).await?;
+            handle_checkout_completed(event, pool).await?;
         }
         EventType::InvoicePaid => {
-            handle_invoice_paid(&event, pool).await?;
+            handle_invoice_paid(event, pool).await?;
         }
         EventType::InvoicePaymentFailed => {
-            handle_invoice_payment_failed(&event, pool).await?;
+            handle_invoice_payment_failed(event, pool).await?;
         }
         EventType::CustomerSubscriptionUpdated => {
-            handle_subscription_updated(&event, pool).await?;
+            handle_subscription_updated(event, pool).await?;
         }
         EventType::CustomerSubscriptionDeleted => {
-            handle_subscription_deleted(&event, pool).await?;
+            handle_subscription_deleted(event, pool).await?;
         }
         _ => {
             tracing::debug!("Unhandled webhook event type: {:?}", event.type_);
@@ -82,6 +148,7 @@ async fn handle_payment_succeeded(event: &Event, pool: &PgPool) -> Result<()> {
     bookings::update_payment_status(pool, booking_uuid, "confirmed", Some(payment_intent_id))
         .await
         .map_err(|e| anyhow!("Failed to update booking payment status: {}", e))?;
+    metrics::payment_confirmed("stripe");
 
     tracing::info!(
         "Payment succeeded for booking {} (PaymentIntent: {})",
@@ -130,6 +197,83 @@ async fn handle_payment_canceled(event: &Event) -> Result<()> {
     Ok(())
 }
 
+/// Handle payment_intent.requires_action - the card needs 3D Secure
+///
+/// The frontend completes the challenge with Stripe.js; here we only make
+/// sure the booking outlives the authentication window.
+async fn handle_payment_requires_action(event: &Event, pool: &PgPool) -> Result<()> {
+    let payment_intent = match &event.data.object {
+        EventObject::PaymentIntent(pi) => pi,
+        _ => return Err(anyhow!("Expected PaymentIntent in event data")),
+    };
+
+    let booking_uuid = extract_booking_id(payment_intent)?;
+    let payment_intent_id = payment_intent.id.as_str();
+    let hold_until = Utc::now() + Duration::minutes(AUTHENTICATION_HOLD_MINUTES);
+
+    let held = bookings::hold_for_authentication(pool, booking_uuid, payment_intent_id, hold_until)
+        .await
+        .map_err(|e| anyhow!("Failed to hold booking for authentication: {}", e))?;
+
+    match held {
+        Some(booking) => tracing::info!(
+            "Payment for booking {} requires authentication (PaymentIntent: {}); held until {:?}",
+            booking_uuid,
+            payment_intent_id,
+            booking.payment_deadline
+        ),
+        None => tracing::warn!(
+            "Payment for booking {} requires authentication but the booking is no longer pending (PaymentIntent: {})",
+            booking_uuid,
+            payment_intent_id
+        ),
+    }
+
+    Ok(())
+}
+
+/// Handle charge.refunded - sync refunds issued directly in Stripe
+async fn handle_charge_refunded(event: &Event, pool: &PgPool) -> Result<()> {
+    let charge = match &event.data.object {
+        EventObject::Charge(charge) => charge,
+        _ => return Err(anyhow!("Expected Charge in event data")),
+    };
+
+    let Some(payment_intent_id) = charge.payment_intent.as_ref().map(|pi| pi.id().to_string()) else {
+        tracing::debug!("Refunded charge {} has no PaymentIntent, skipping", charge.id);
+        return Ok(());
+    };
+
+    // Partial refunds (e.g. a guest's share) leave the booking in place
+    if !charge.refunded {
+        tracing::info!(
+            "Partial refund on charge {} (PaymentIntent: {}): {} of {} refunded, booking unchanged",
+            charge.id,
+            payment_intent_id,
+            charge.amount_refunded,
+            charge.amount
+        );
+        return Ok(());
+    }
+
+    match refund_booking_by_payment_id(pool, &payment_intent_id)
+        .await
+        .map_err(|e| anyhow!("Failed to record refund: {}", e))?
+    {
+        Some(booking) => tracing::info!(
+            "Booking {} refunded in Stripe (PaymentIntent: {}); slots released",
+            booking.booking_code,
+            payment_intent_id
+        ),
+        None => tracing::debug!(
+            "No active booking for refunded PaymentIntent {}, skipping",
+            payment_intent_id
+        ),
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Subscription Event Handlers
 // ============================================================================
@@ -167,18 +311,18 @@ async fn handle_checkout_completed(event: &Event, pool: &PgPool) -> Result<()> {
     let subscription_id = session
         .subscription
         .as_ref()
-        .and_then(|s| match s {
-            stripe::Expandable::Id(id) => Some(id.as_str().to_string()),
-            stripe::Expandable::Object(sub) => Some(sub.id.as_str().to_string()),
+        .map(|s| match s {
+            stripe::Expandable::Id(id) => id.as_str().to_string(),
+            stripe::Expandable::Object(sub) => sub.id.as_str().to_string(),
         })
         .ok_or_else(|| anyhow!("No subscription ID in checkout session"))?;
 
     let customer_id = session
         .customer
         .as_ref()
-        .and_then(|c| match c {
-            stripe::Expandable::Id(id) => Some(id.as_str().to_string()),
-            stripe::Expandable::Object(cust) => Some(cust.id.as_str().to_string()),
+        .map(|c| match c {
+            stripe::Expandable::Id(id) => id.as_str().to_string(),
+            stripe::Expandable::Object(cust) => cust.id.as_str().to_string(),
         })
         .ok_or_else(|| anyhow!("No customer ID in checkout session"))?;
 
@@ -212,13 +356,54 @@ async fn handle_invoice_paid(event: &Event, pool: &PgPool) -> Result<()> {
         _ => return Err(anyhow!("Expected Invoice in event data")),
     };
 
+    apply_paid_invoice(pool, invoice, None).await?;
+    Ok(())
+}
+
+/// What applying a paid invoice changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvoiceOutcome {
+    /// Not a subscription invoice
+    Skipped,
+    /// The invoice's period was already granted
+    AlreadyApplied,
+    /// A new subscription was created with its first tickets
+    Created,
+    /// An existing subscription moved to the invoice's period
+    Renewed,
+    /// A gift subscription was granted or held for its recipient
+    Gifted,
+}
+
+impl InvoiceOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skipped => "skipped",
+            Self::AlreadyApplied => "already_applied",
+            Self::Created => "created",
+            Self::Renewed => "renewed",
+            Self::Gifted => "gifted",
+        }
+    }
+}
+
+/// Grant the tickets for a paid subscription invoice
+///
+/// Idempotent: an invoice whose period end matches the stored subscription
+/// is treated as already applied. `fallback_user_id` is used for a new
+/// subscription whose invoice metadata has no user.
+async fn apply_paid_invoice(
+    pool: &PgPool,
+    invoice: &stripe::Invoice,
+    fallback_user_id: Option<Uuid>,
+) -> Result<InvoiceOutcome> {
     // Only process subscription invoices
     let subscription_id = match &invoice.subscription {
         Some(stripe::Expandable::Id(id)) => id.as_str().to_string(),
         Some(stripe::Expandable::Object(sub)) => sub.id.as_str().to_string(),
         None => {
             tracing::debug!("Invoice has no subscription, skipping");
-            return Ok(());
+            return Ok(InvoiceOutcome::Skipped);
         }
     };
 
@@ -231,11 +416,18 @@ async fn handle_invoice_paid(event: &Event, pool: &PgPool) -> Result<()> {
         }
     };
 
+    let settings = config::load(pool).await?;
+    // Number of tickets granted per subscription purchase/renewal
+    let subscription_tickets = settings.subscription_ticket_count;
+
+    // Check if we have an existing subscription
+    let existing_sub = subscriptions::find_by_stripe_subscription_id(pool, &subscription_id).await?;
+
+    let first_line = invoice.lines.as_ref().and_then(|lines| lines.data.first());
+    let billing_interval = first_line.and_then(line_billing_interval);
+
     // Get period dates from invoice lines
-    let (period_start, period_end) = invoice
-        .lines
-        .as_ref()
-        .and_then(|lines| lines.data.first())
+    let invoice_period = first_line
         .and_then(|line| line.period.as_ref())
         .and_then(|period| {
             match (period.start, period.end) {
@@ -245,77 +437,145 @@ async fn handle_invoice_paid(event: &Event, pool: &PgPool) -> Result<()> {
                 )),
                 _ => None,
             }
-        })
-        .unwrap_or_else(|| {
-            // Default to 3 months if period not available
-            let now = Utc::now();
-            (now, now + chrono::Duration::days(90))
         });
 
-    // Check if we have an existing subscription
-    let existing_sub = subscriptions::find_by_stripe_subscription_id(pool, &subscription_id).await?;
+    let (period_start, period_end) = match invoice_period {
+        Some(period) => period,
+        None => {
+            // Derive the period from the plan's interval: this invoice's, else
+            // the one stored on the subscription, else the configured default
+            let start = existing_sub
+                .as_ref()
+                .and_then(|sub| sub.current_period_end)
+                .unwrap_or_else(Utc::now);
+            let interval = billing_interval.clone().or_else(|| {
+                let sub = existing_sub.as_ref()?;
+                Some((sub.billing_interval.clone()?, sub.billing_interval_count?))
+            });
+            let end = interval
+                .and_then(|(interval, count)| subscriptions::billing_period_end(start, &interval, count))
+                .or_else(|| subscriptions::billing_period_end(start, "month", settings.default_subscription_period_months.max(1)))
+                .unwrap_or(start);
+
+            tracing::warn!(
+                "Invoice {} for subscription {} has no period; assuming {} to {}",
+                invoice.id,
+                subscription_id,
+                start,
+                end
+            );
+            (start, end)
+        }
+    };
 
     if let Some(sub) = existing_sub {
-        // Check if this is a duplicate event (same period_end means we already processed this invoice)
-        if let Some(existing_period_end) = sub.current_period_end {
-            // If period_end matches within a few seconds, this is a duplicate event
-            let diff = (period_end - existing_period_end).num_seconds().abs();
-            if diff < 60 {
-                tracing::info!(
-                    "Duplicate invoice.paid event for subscription {}, period_end matches existing (diff: {}s). Skipping.",
-                    subscription_id,
-                    diff
-                );
-                return Ok(());
-            }
-        }
+        // Duplicate deliveries and reconcile runs of an applied invoice are
+        // skipped under the subscription's row lock
+        let rollover_cap = settings.rollover_cap();
+        let Some(renewal) = subscriptions::renew_subscription(
+            pool,
+            sub.id,
+            invoice.id.as_str(),
+            subscription_tickets,
+            rollover_cap,
+            period_end,
+        )
+        .await?
+        else {
+            tracing::info!(
+                "Invoice {} for subscription {} was already applied. Skipping.",
+                invoice.id,
+                subscription_id
+            );
+            return Ok(InvoiceOutcome::AlreadyApplied);
+        };
 
-        // This is a true renewal - period_end has changed
         tracing::info!(
             "Processing subscription renewal for user {}, subscription: {}",
             sub.user_id,
             subscription_id
         );
+        let new_balance = renewal.subscription.tickets_remaining;
 
-        let updated_sub = subscriptions::renew_subscription(
-            pool,
-            sub.id,
-            SUBSCRIPTION_TICKETS,
-            period_end,
-        )
-        .await?;
-
-        // Record ticket transaction
+        // Record the full grant, then whatever the cap took back, so the
+        // history shows why the balance didn't grow by the full amount. Both
+        // record the balance after the capped renewal.
         ticket_transactions::create_with_pool(
             pool,
             sub.user_id,
             Some(sub.id),
             None,
-            "subscription_grant",
-            SUBSCRIPTION_TICKETS,
-            updated_sub.tickets_remaining,
+            transaction_types::SUBSCRIPTION_GRANT,
+            subscription_tickets,
+            new_balance,
             Some("Subscription renewal"),
             None,
         )
         .await?;
+        metrics::tickets_granted("renewal", subscription_tickets);
+
+        if renewal.tickets_forfeited > 0 {
+            ticket_transactions::create_with_pool(
+                pool,
+                sub.user_id,
+                Some(sub.id),
+                None,
+                transaction_types::EXPIRED,
+                -renewal.tickets_forfeited,
+                new_balance,
+                Some(&format!(
+                    "Forfeited at renewal: balance capped at {} tickets",
+                    rollover_cap.unwrap_or_default()
+                )),
+                None,
+            )
+            .await?;
+        }
 
         tracing::info!(
-            "Renewed subscription for user {}: +{} tickets, new balance: {}",
+            "Renewed subscription for user {}: +{} tickets ({} forfeited over cap), new balance: {}",
             sub.user_id,
-            SUBSCRIPTION_TICKETS,
-            updated_sub.tickets_remaining
+            subscription_tickets - renewal.tickets_forfeited,
+            renewal.tickets_forfeited,
+            new_balance
         );
+        store_billing_interval(pool, &subscription_id, billing_interval.as_ref()).await?;
+        Ok(InvoiceOutcome::Renewed)
     } else {
         // This is a new subscription - need to get user_id from customer metadata
         // or from the subscription metadata
-
-        // Try to get user_id from invoice subscription_details metadata
-        let user_id = invoice
+        let subscription_metadata = invoice
             .subscription_details
             .as_ref()
-            .and_then(|details| details.metadata.as_ref())
+            .and_then(|details| details.metadata.as_ref());
+
+        // Gifts go to the recipient rather than the paying customer
+        if let Some(recipient_email) = subscription_metadata.and_then(|meta| meta.get("gift_recipient_email")) {
+            let purchaser_id = extract_user_id_from_metadata(&subscription_metadata.cloned())?;
+            let granted = grant_gift_subscription(
+                pool,
+                recipient_email,
+                purchaser_id,
+                &subscription_id,
+                &customer_id,
+                subscription_tickets,
+                period_start,
+                period_end,
+            )
+            .await?;
+            if !granted {
+                return Ok(InvoiceOutcome::AlreadyApplied);
+            }
+            store_billing_interval(pool, &subscription_id, billing_interval.as_ref()).await?;
+            subscriptions::set_last_invoice_id(pool, &subscription_id, invoice.id.as_str()).await?;
+            return Ok(InvoiceOutcome::Gifted);
+        }
+
+        // Try to get user_id from invoice subscription_details metadata
+        let user_id = subscription_metadata
             .and_then(|meta| meta.get("user_id"))
-            .and_then(|id| Uuid::parse_str(id).ok());
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .or(fallback_user_id);
 
         let user_id = match user_id {
             Some(id) => id,
@@ -338,17 +598,24 @@ async fn handle_invoice_paid(event: &Event, pool: &PgPool) -> Result<()> {
             }
         };
 
-        // Create new subscription
+        // Create the subscription, reactivating a returning subscriber's lapsed one
         let new_sub = subscriptions::create(
             pool,
             user_id,
             &subscription_id,
             &customer_id,
-            SUBSCRIPTION_TICKETS,
+            subscription_tickets,
             period_start,
             period_end,
         )
-        .await?;
+        .await?
+        .ok_or_else(|| {
+            anyhow!(
+                "Subscription {} can't be created: user {} already has an active subscription",
+                subscription_id,
+                user_id
+            )
+        })?;
 
         // Record ticket transaction
         ticket_transactions::create_with_pool(
@@ -356,25 +623,185 @@ async fn handle_invoice_paid(event: &Event, pool: &PgPool) -> Result<()> {
             user_id,
             Some(new_sub.id),
             None,
-            "subscription_grant",
-            SUBSCRIPTION_TICKETS,
-            SUBSCRIPTION_TICKETS,
+            transaction_types::SUBSCRIPTION_GRANT,
+            subscription_tickets,
+            subscription_tickets,
             Some("Initial subscription purchase"),
             None,
         )
         .await?;
+        metrics::tickets_granted("subscription", subscription_tickets);
 
         tracing::info!(
             "Created subscription for user {}: {} tickets, period ends {}",
             user_id,
-            SUBSCRIPTION_TICKETS,
+            subscription_tickets,
             period_end
         );
+        store_billing_interval(pool, &subscription_id, billing_interval.as_ref()).await?;
+        subscriptions::set_last_invoice_id(pool, &subscription_id, invoice.id.as_str()).await?;
+        Ok(InvoiceOutcome::Created)
+    }
+}
+
+/// Re-apply a subscription's latest paid invoice after a failed webhook
+///
+/// Fetches the subscription and its latest invoice from Stripe and runs them
+/// through the same path as `invoice.paid`, so periods that were already
+/// granted are left alone. A new subscription's user comes from the invoice
+/// metadata, else `user_id`, else the subscription's own metadata.
+pub async fn reconcile_subscription(
+    pool: &PgPool,
+    stripe: &StripeSubscriptions,
+    stripe_subscription_id: &str,
+    user_id: Option<Uuid>,
+) -> Result<(String, InvoiceOutcome)> {
+    let subscription = stripe.get_subscription(stripe_subscription_id).await?;
+    let invoice = stripe
+        .get_latest_invoice(&subscription)
+        .await?
+        .ok_or_else(|| anyhow!("Subscription {} has no invoice", stripe_subscription_id))?;
+
+    if invoice.status != Some(stripe::InvoiceStatus::Paid) {
+        return Err(anyhow!(
+            "Latest invoice {} of subscription {} is not paid",
+            invoice.id,
+            stripe_subscription_id
+        ));
+    }
+
+    let user_id = user_id.or_else(|| {
+        subscription
+            .metadata
+            .get("user_id")
+            .and_then(|id| Uuid::parse_str(id).ok())
+    });
+
+    let outcome = apply_paid_invoice(pool, &invoice, user_id).await?;
+
+    tracing::info!(
+        "Reconciled subscription {} from invoice {}: {}",
+        stripe_subscription_id,
+        invoice.id,
+        outcome.as_str()
+    );
+
+    Ok((invoice.id.to_string(), outcome))
+}
+
+/// Billing interval of an invoice line's plan as (interval, count)
+fn line_billing_interval(line: &stripe::InvoiceLineItem) -> Option<(String, i32)> {
+    if let Some(recurring) = line.price.as_ref().and_then(|price| price.recurring.as_ref()) {
+        return Some((recurring.interval.as_str().to_string(), recurring.interval_count as i32));
     }
+    let plan = line.plan.as_ref()?;
+    Some((plan.interval?.as_str().to_string(), plan.interval_count? as i32))
+}
 
+/// Remember a subscription's billing interval so later periods can be derived from it
+async fn store_billing_interval(
+    pool: &PgPool,
+    stripe_subscription_id: &str,
+    interval: Option<&(String, i32)>,
+) -> Result<()> {
+    if let Some((interval, count)) = interval {
+        subscriptions::set_billing_interval(pool, stripe_subscription_id, interval, *count).await?;
+    }
     Ok(())
 }
 
+/// Grant a gift subscription's first invoice to its recipient
+///
+/// Recipients with an account get the subscription and tickets now; unknown
+/// emails have the grant held until they sign up. Returns false when the
+/// invoice was already held, e.g. on a repeated reconcile.
+#[allow(clippy::too_many_arguments)]
+async fn grant_gift_subscription(
+    pool: &PgPool,
+    recipient_email: &str,
+    purchaser_id: Uuid,
+    subscription_id: &str,
+    customer_id: &str,
+    tickets: i32,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+) -> Result<bool> {
+    let recipient = users::find_by_email(pool, &recipient_email.trim().to_lowercase())
+        .await?
+        .filter(|user| user.deleted_at.is_none());
+
+    let Some(recipient) = recipient else {
+        let held = subscription_gifts::hold(
+            pool,
+            recipient_email,
+            purchaser_id,
+            subscription_id,
+            customer_id,
+            tickets,
+            period_start,
+            period_end,
+        )
+        .await?;
+        if !held {
+            return Ok(false);
+        }
+
+        tracing::info!(
+            "Holding gift subscription {} from user {} until {} signs up",
+            subscription_id,
+            purchaser_id,
+            recipient_email
+        );
+        return Ok(true);
+    };
+
+    // A lapsed subscription is reactivated. Checkout refuses recipients with an
+    // active one, so failing here means they subscribed since; fail loudly so
+    // the payment can be refunded.
+    let new_sub = subscriptions::create(
+        pool,
+        recipient.id,
+        subscription_id,
+        customer_id,
+        tickets,
+        period_start,
+        period_end,
+    )
+    .await?
+    .ok_or_else(|| {
+        anyhow!(
+            "Gift subscription {} from user {} can't be granted: recipient {} already has an active subscription",
+            subscription_id,
+            purchaser_id,
+            recipient.id
+        )
+    })?;
+
+    ticket_transactions::create_with_pool(
+        pool,
+        recipient.id,
+        Some(new_sub.id),
+        None,
+        transaction_types::SUBSCRIPTION_GRANT,
+        tickets,
+        tickets,
+        Some("Gift subscription"),
+        None,
+    )
+    .await?;
+    metrics::tickets_granted("gift", tickets);
+
+    tracing::info!(
+        "Granted gift subscription {} from user {} to user {}: {} tickets",
+        subscription_id,
+        purchaser_id,
+        recipient.id,
+        tickets
+    );
+
+    Ok(true)
+}
+
 /// Handle invoice.payment_failed - mark subscription as past_due
 async fn handle_invoice_payment_failed(event: &Event, pool: &PgPool) -> Result<()> {
     let invoice = match &event.data.object {
@@ -427,9 +854,9 @@ async fn handle_subscription_updated(event: &Event, pool: &PgPool) -> Result<()>
         stripe::SubscriptionStatus::PastDue => "past_due",
         stripe::SubscriptionStatus::Canceled => "cancelled",
         stripe::SubscriptionStatus::Unpaid => "past_due",
-        stripe::SubscriptionStatus::Incomplete => "past_due",
+        stripe::SubscriptionStatus::Incomplete => "incomplete",
         stripe::SubscriptionStatus::IncompleteExpired => "expired",
-        stripe::SubscriptionStatus::Trialing => "active",
+        stripe::SubscriptionStatus::Trialing => "trialing",
         stripe::SubscriptionStatus::Paused => "cancelled",
     };
 
@@ -449,6 +876,16 @@ async fn handle_subscription_updated(event: &Event, pool: &PgPool) -> Result<()>
         )
         .await?;
 
+        // Plan changes (e.g. monthly to annual) change how renewals extend
+        let interval = subscription
+            .items
+            .data
+            .first()
+            .and_then(|item| item.price.as_ref())
+            .and_then(|price| price.recurring.as_ref())
+            .map(|recurring| (recurring.interval.as_str().to_string(), recurring.interval_count as i32));
+        store_billing_interval(pool, subscription_id, interval.as_ref()).await?;
+
         tracing::info!(
             "Updated subscription {} status to {}, cancel_at_period_end: {}",
             subscription_id,
@@ -1,11 +1,41 @@
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
+/// Default time JWKS are reused before re-fetching
+pub const DEFAULT_JWKS_CACHE_DURATION: Duration = Duration::from_secs(300);
+
+/// Minimum gap between forced refreshes, so tokens with unknown `kid`s
+/// can't make us hammer the JWKS endpoint
+const MIN_JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum user lookups per token, including the first
+const USER_LOOKUP_ATTEMPTS: u32 = 3;
+
+/// Delay before the first user lookup retry; doubles on each further attempt.
+/// Kept short since a user is waiting on the login.
+const USER_LOOKUP_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// How long a single user lookup may take before it counts as unreachable
+const USER_LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why the user behind a token couldn't be fetched
+#[derive(Debug, thiserror::Error)]
+pub enum UserLookupError {
+    /// Supabase rejected the token (expired, revoked or malformed); signing in again fixes it
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+    /// Supabase was unreachable or kept failing; the same token may work later
+    #[error("Supabase unavailable: {0}")]
+    Unavailable(String),
+}
+
 #[derive(Clone)]
 pub struct SupabaseAuth {
     url: String,
@@ -14,12 +44,15 @@ pub struct SupabaseAuth {
     service_key: String,  // Reserved for future admin operations
     client: Client,
     jwks_cache: Arc<RwLock<Option<JwksCache>>>,
+    jwks_cache_duration: Duration,
+    /// Held while fetching JWKS so concurrent refreshes share one request
+    jwks_refresh: Arc<Mutex<()>>,
 }
 
 #[derive(Clone)]
 struct JwksCache {
     keys: Vec<JwkKey>,
-    fetched_at: std::time::Instant,
+    fetched_at: Instant,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -68,6 +101,45 @@ pub struct JwtClaims {
     pub aud: Option<String>,
     pub iss: Option<String>,
     pub role: Option<String>,
+    /// Supabase auth session the token belongs to
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// How the user authenticated in this session
+    #[serde(default)]
+    pub amr: Option<Vec<AuthMethodReference>>,
+}
+
+/// One `amr` entry: an authentication method and when it was used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthMethodReference {
+    pub method: String,
+    /// Unix timestamp (seconds)
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+}
+
+/// User record from the admin API
+///
+/// Only the sign-in fields we show are read, and all of them are optional:
+/// Supabase omits fields that were never set (e.g. for invited users).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminUser {
+    pub id: Uuid,
+    #[serde(default)]
+    pub last_sign_in_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub identities: Option<Vec<UserIdentity>>,
+}
+
+/// A linked sign-in method (e.g. google or email)
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserIdentity {
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_sign_in_at: Option<DateTime<Utc>>,
 }
 
 impl SupabaseAuth {
@@ -78,9 +150,17 @@ impl SupabaseAuth {
             service_key,
             client: Client::new(),
             jwks_cache: Arc::new(RwLock::new(None)),
+            jwks_cache_duration: DEFAULT_JWKS_CACHE_DURATION,
+            jwks_refresh: Arc::new(Mutex::new(())),
         }
     }
 
+    /// Set how long fetched JWKS are reused
+    pub fn with_jwks_cache_duration(mut self, duration: Duration) -> Self {
+        self.jwks_cache_duration = duration;
+        self
+    }
+
     /// Fetch JWKS from Supabase
     async fn fetch_jwks(&self) -> Result<Vec<JwkKey>> {
         let url = format!("{}/auth/v1/.well-known/jwks.json", self.url);
@@ -106,32 +186,45 @@ impl SupabaseAuth {
         Ok(jwks.keys)
     }
 
-    /// Get JWKS with caching (cache for 5 minutes)
+    /// Get JWKS, reusing the cached keys for `jwks_cache_duration`
     async fn get_jwks(&self) -> Result<Vec<JwkKey>> {
-        const CACHE_DURATION: std::time::Duration = std::time::Duration::from_secs(300);
-
-        // Check cache
-        {
-            let cache = self.jwks_cache.read().await;
-            if let Some(ref cached) = *cache {
-                if cached.fetched_at.elapsed() < CACHE_DURATION {
-                    return Ok(cached.keys.clone());
-                }
-            }
+        if let Some(keys) = self.cached_jwks(self.jwks_cache_duration).await {
+            return Ok(keys);
         }
 
-        // Fetch fresh JWKS
-        let keys = self.fetch_jwks().await?;
+        self.refresh_jwks().await
+    }
 
-        // Update cache
-        {
-            let mut cache = self.jwks_cache.write().await;
-            *cache = Some(JwksCache {
-                keys: keys.clone(),
-                fetched_at: std::time::Instant::now(),
-            });
+    /// Cached keys if they were fetched less than `max_age` ago
+    async fn cached_jwks(&self, max_age: Duration) -> Option<Vec<JwkKey>> {
+        let cache = self.jwks_cache.read().await;
+        cache
+            .as_ref()
+            .filter(|cached| cached.fetched_at.elapsed() < max_age)
+            .map(|cached| cached.keys.clone())
+    }
+
+    /// Re-fetch JWKS now, e.g. after Supabase rotated its signing keys
+    ///
+    /// Concurrent callers wait for a single fetch, and keys fetched within the
+    /// last `MIN_JWKS_REFRESH_INTERVAL` (or cache duration, if shorter) are
+    /// reused instead of re-fetched.
+    pub async fn refresh_jwks(&self) -> Result<Vec<JwkKey>> {
+        let _guard = self.jwks_refresh.lock().await;
+
+        // Another caller may have refreshed while we waited for the lock
+        let min_interval = MIN_JWKS_REFRESH_INTERVAL.min(self.jwks_cache_duration);
+        if let Some(keys) = self.cached_jwks(min_interval).await {
+            return Ok(keys);
         }
 
+        let keys = self.fetch_jwks().await?;
+
+        *self.jwks_cache.write().await = Some(JwksCache {
+            keys: keys.clone(),
+            fetched_at: Instant::now(),
+        });
+
         Ok(keys)
     }
 
@@ -200,8 +293,12 @@ impl SupabaseAuth {
         let header = decode_header(token)
             .map_err(|e| anyhow!("Failed to decode JWT header: {}", e))?;
 
-        // Get JWKS
-        let keys = self.get_jwks().await?;
+        // Get JWKS; an unknown kid usually means the keys were rotated
+        let mut keys = self.get_jwks().await?;
+        if self.find_key_for_token(&keys, header.kid.as_deref()).is_none() {
+            tracing::info!("No JWKS key for kid {:?}, refreshing keys", header.kid);
+            keys = self.refresh_jwks().await?;
+        }
 
         // Find the right key
         let jwk = self
@@ -250,7 +347,32 @@ impl SupabaseAuth {
     }
 
     /// Get user details from Supabase using JWT token
-    pub async fn get_user_from_token(&self, token: &str) -> Result<SupabaseUser> {
+    ///
+    /// Network errors, timeouts, 429s and 5xx are retried with exponential
+    /// backoff; a rejected token fails straight away.
+    pub async fn get_user_from_token(&self, token: &str) -> Result<SupabaseUser, UserLookupError> {
+        let mut attempt = 1;
+        loop {
+            match self.fetch_user(token).await {
+                Err(UserLookupError::Unavailable(e)) if attempt < USER_LOOKUP_ATTEMPTS => {
+                    let delay = USER_LOOKUP_BASE_DELAY * 2u32.pow(attempt - 1);
+                    tracing::warn!(
+                        "Supabase user lookup failed (attempt {}/{}): {}. Retrying in {}ms",
+                        attempt,
+                        USER_LOOKUP_ATTEMPTS,
+                        e,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Single `/auth/v1/user` request, classifying failures by status
+    async fn fetch_user(&self, token: &str) -> Result<SupabaseUser, UserLookupError> {
         let url = format!("{}/auth/v1/user", self.url);
 
         let response = self
@@ -258,22 +380,26 @@ impl SupabaseAuth {
             .get(&url)
             .header("Authorization", format!("Bearer {}", token))
             .header("apikey", &self.anon_key)
+            .timeout(USER_LOOKUP_TIMEOUT)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to get user from Supabase: {}", e))?;
+            .map_err(|e| UserLookupError::Unavailable(format!("Failed to reach Supabase: {}", e)))?;
 
-        if !response.status().is_success() {
-            let status = response.status();
+        let status = response.status();
+        if !status.is_success() {
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("Supabase API error {}: {}", status, text));
+            let message = format!("Supabase API error {}: {}", status, text);
+            return Err(if is_transient_status(status) {
+                UserLookupError::Unavailable(message)
+            } else {
+                UserLookupError::InvalidToken(message)
+            });
         }
 
-        let user: SupabaseUser = response
+        response
             .json()
             .await
-            .map_err(|e| anyhow!("Failed to parse user response: {}", e))?;
-
-        Ok(user)
+            .map_err(|e| UserLookupError::Unavailable(format!("Failed to parse user response: {}", e)))
     }
 
     /// Sign out user (revoke refresh token)
@@ -298,6 +424,53 @@ impl SupabaseAuth {
         Ok(())
     }
 
+    /// Sign out every other session of the token's user, keeping this one
+    pub async fn sign_out_others(&self, token: &str) -> Result<()> {
+        let url = format!("{}/auth/v1/logout?scope=others", self.url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("apikey", &self.anon_key)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to sign out other sessions from Supabase: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Supabase sign out others error {}: {}", status, text));
+        }
+
+        Ok(())
+    }
+
+    /// Get a user's sign-in details from Supabase Auth (admin operation)
+    pub async fn get_user_by_id(&self, user_id: Uuid) -> Result<AdminUser> {
+        let url = format!("{}/auth/v1/admin/users/{}", self.url, user_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.service_key))
+            .header("apikey", &self.service_key)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch user from Supabase: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Supabase get user error {}: {}", status, text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse Supabase user: {}", e))
+    }
+
     /// Delete user from Supabase Auth (admin operation)
     /// This permanently removes the user's authentication record
     pub async fn delete_user(&self, user_id: Uuid) -> Result<()> {
@@ -321,3 +494,39 @@ impl SupabaseAuth {
         Ok(())
     }
 }
+
+/// Whether a failed user lookup is Supabase's problem rather than the token's
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_statuses_are_retried() {
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(StatusCode::BAD_GATEWAY));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[test]
+    fn test_admin_user_tolerates_missing_fields() {
+        let user: AdminUser = serde_json::from_str(
+            r#"{"id": "6f1c2d3e-4b5a-4c7d-8e9f-0a1b2c3d4e5f", "identities": [{"provider": "google"}]}"#,
+        )
+        .unwrap();
+        assert!(user.last_sign_in_at.is_none());
+        let identities = user.identities.unwrap();
+        assert_eq!(identities[0].provider.as_deref(), Some("google"));
+        assert!(identities[0].last_sign_in_at.is_none());
+    }
+
+    #[test]
+    fn test_rejected_tokens_are_not_retried() {
+        assert!(!is_transient_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_transient_status(StatusCode::FORBIDDEN));
+        assert!(!is_transient_status(StatusCode::BAD_REQUEST));
+    }
+}
@@ -1,5 +1,8 @@
 pub mod auth;
 pub mod storage;
 
-pub use auth::{SupabaseAuth, SupabaseUser, JwtClaims};
-pub use storage::SupabaseStorage;
+pub use auth::{
+    AdminUser, AuthMethodReference, JwtClaims, SupabaseAuth, SupabaseUser, UserIdentity, UserLookupError,
+    DEFAULT_JWKS_CACHE_DURATION,
+};
+pub use storage::{SupabaseStorage, PAYMENT_PROOF_BUCKET, SESSION_IMAGE_BUCKET};
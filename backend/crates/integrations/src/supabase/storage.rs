@@ -2,6 +2,12 @@ use anyhow::{anyhow, Result};
 use reqwest::{multipart, Client};
 use serde::{Deserialize, Serialize};
 
+/// Bucket holding payment proof screenshots for QR-transfer bookings
+pub const PAYMENT_PROOF_BUCKET: &str = "payment-proofs";
+
+/// Public bucket holding session cover images
+pub const SESSION_IMAGE_BUCKET: &str = "session-images";
+
 #[derive(Clone)]
 pub struct SupabaseStorage {
     url: String,
@@ -33,6 +39,14 @@ impl SupabaseStorage {
         format!("{}/storage/v1/object/public/{}/{}", self.url, bucket, path)
     }
 
+    /// Recover an object's path from a URL returned by `upload_file`
+    /// Returns None for URLs outside this project's `bucket`
+    pub fn path_from_public_url(&self, bucket: &str, url: &str) -> Option<String> {
+        url.strip_prefix(&self.public_url(bucket, ""))
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+    }
+
     /// Get authorization header value
     fn auth_header(&self) -> String {
         format!("Bearer {}", self.service_key)
@@ -52,7 +66,7 @@ impl SupabaseStorage {
 
         let response = self
             .client
-            .post(&self.object_url(bucket, path))
+            .post(self.object_url(bucket, path))
             .header("Authorization", self.auth_header())
             .multipart(form)
             .send()
@@ -72,7 +86,7 @@ impl SupabaseStorage {
     pub async fn delete_file(&self, bucket: &str, path: &str) -> Result<()> {
         let response = self
             .client
-            .delete(&self.object_url(bucket, path))
+            .delete(self.object_url(bucket, path))
             .header("Authorization", self.auth_header())
             .send()
             .await
@@ -113,7 +127,7 @@ impl SupabaseStorage {
 
         let response = self
             .client
-            .post(&self.sign_url(bucket, path))
+            .post(self.sign_url(bucket, path))
             .header("Authorization", self.auth_header())
             .json(&SignRequest { expires_in })
             .send()
@@ -0,0 +1,151 @@
+use chrono::{Duration, Utc};
+use loafy_core::booking::club_today;
+use loafy_db::{
+    queries::{admin, config, users},
+    PgPool,
+};
+use loafy_integrations::email::{escape_html, EmailClient};
+
+/// Length of the digest period in days
+const DIGEST_PERIOD_DAYS: i64 = 7;
+
+/// Number of sessions listed in the top sessions table
+const TOP_SESSIONS_LIMIT: i32 = 5;
+
+/// Send the weekly metrics digest to admins
+/// Runs on the `admin_digest_cron` schedule (default Monday 08:00)
+///
+/// Covers the last 7 days with week-over-week deltas. Recipients come from
+/// `admin_digest_recipients`, falling back to every user with the admin role.
+pub async fn send_admin_digest(pool: &PgPool) -> anyhow::Result<()> {
+    let Some(email) = EmailClient::from_env() else {
+        tracing::warn!("Email not configured; skipping admin digest");
+        return Ok(());
+    };
+
+    let mut recipients = config::get_admin_digest_recipients(pool).await?;
+    if recipients.is_empty() {
+        recipients = users::list_admin_emails(pool).await?;
+    }
+    if recipients.is_empty() {
+        tracing::warn!("No admin digest recipients found");
+        return Ok(());
+    }
+
+    let current_start = Utc::now() - Duration::days(DIGEST_PERIOD_DAYS);
+    let previous_start = current_start - Duration::days(DIGEST_PERIOD_DAYS);
+
+    let today = club_today(config::load(pool).await?.session_utc_offset);
+    let stats = admin::get_admin_stats(pool, Some(current_start), today).await?;
+    let previous = admin::get_previous_period_stats(pool, current_start, previous_start).await?;
+    let profit = admin::get_profit_stats(pool, Some(current_start), today).await?;
+    // Profit stats only take a start date, so the previous week is the
+    // two-week total minus the current week
+    let two_week_profit = admin::get_profit_stats(pool, Some(previous_start), today).await?;
+    let previous_net_profit = two_week_profit.net_profit_vnd - profit.net_profit_vnd;
+    let top_sessions =
+        admin::get_sessions_profit(pool, Some(current_start), None, Some(TOP_SESSIONS_LIMIT)).await?;
+
+    let metric_rows = [
+        metric_row("New users", stats.new_users, previous.new_users, ""),
+        metric_row("Bookings", stats.total_bookings, previous.total_bookings, ""),
+        metric_row("Revenue", stats.total_revenue_vnd, previous.total_revenue_vnd, " ₫"),
+        metric_row("Net profit", profit.net_profit_vnd, previous_net_profit, " ₫"),
+        metric_row("Upcoming sessions", stats.upcoming_sessions, previous.upcoming_sessions, ""),
+    ]
+    .concat();
+
+    let session_rows = if top_sessions.is_empty() {
+        "<tr><td colspan=\"3\">No sessions this week</td></tr>".to_string()
+    } else {
+        top_sessions
+            .iter()
+            .map(|s| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td align=\"right\">{} ₫</td></tr>",
+                    escape_html(&s.title),
+                    s.date.format("%Y-%m-%d"),
+                    format_number(s.profit_vnd),
+                )
+            })
+            .collect::<String>()
+    };
+
+    let period = format!(
+        "{} – {}",
+        current_start.format("%Y-%m-%d"),
+        Utc::now().format("%Y-%m-%d")
+    );
+    let subject = format!("Loafy Club weekly digest ({})", period);
+    let html = format!(
+        "<h2>Weekly digest</h2>\
+         <p>{}</p>\
+         <table cellpadding=\"4\">\
+         <tr><th align=\"left\">Metric</th><th align=\"right\">This week</th><th align=\"right\">vs last week</th></tr>\
+         {}\
+         </table>\
+         <p>Pending bookings: {} · Profit margin: {:.1}%</p>\
+         <h3>Top sessions by profit</h3>\
+         <table cellpadding=\"4\">\
+         <tr><th align=\"left\">Session</th><th align=\"left\">Date</th><th align=\"right\">Profit</th></tr>\
+         {}\
+         </table>\
+         <p>— Loafy Club</p>",
+        period,
+        metric_rows,
+        stats.pending_bookings,
+        profit.profit_margin_percent,
+        session_rows,
+    );
+
+    let mut sent = 0;
+    for recipient in &recipients {
+        match email.send(recipient, &subject, &html).await {
+            Ok(()) => sent += 1,
+            Err(e) => tracing::error!("Failed to send admin digest to {}: {}", recipient, e),
+        }
+    }
+
+    tracing::info!("✓ Sent admin digest to {}/{} recipients", sent, recipients.len());
+
+    Ok(())
+}
+
+/// Render a metric table row with its week-over-week delta
+fn metric_row(label: &str, current: i64, previous: i64, unit: &str) -> String {
+    let delta = current - previous;
+    let percent = if previous != 0 {
+        format!(" ({:+.0}%)", delta as f64 / previous.abs() as f64 * 100.0)
+    } else {
+        String::new()
+    };
+    let sign = if delta > 0 { "+" } else { "" };
+
+    format!(
+        "<tr><td>{}</td><td align=\"right\">{}{}</td><td align=\"right\">{}{}{}{}</td></tr>",
+        label,
+        format_number(current),
+        unit,
+        sign,
+        format_number(delta),
+        unit,
+        percent,
+    )
+}
+
+/// Format an integer with thousands separators
+fn format_number(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if value < 0 {
+        format!("-{}", out)
+    } else {
+        out
+    }
+}
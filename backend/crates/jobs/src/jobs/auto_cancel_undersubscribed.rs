@@ -0,0 +1,122 @@
+use chrono::Utc;
+use loafy_core::session::cancel_session;
+use loafy_db::{
+    models::{Booking, NotificationKind, Session},
+    queries::{config, notification_preferences, sessions, users},
+    PgPool,
+};
+use loafy_integrations::{
+    email::{escape_html, EmailClient},
+    stripe::{refund_cancelled_booking, StripePayments},
+};
+
+/// Cancel sessions that haven't reached their minimum player count
+/// Runs every 15 minutes
+///
+/// For each session past its min-players cutoff that is still short:
+/// 1. Cancels the session and all its bookings (tickets are restored)
+/// 2. Refunds confirmed Stripe payments, marking failures `refund_failed`
+/// 3. Emails every affected participant
+pub async fn auto_cancel_undersubscribed(pool: &PgPool) -> anyhow::Result<()> {
+    let default_deadline_hours = config::get_min_players_deadline_hours(pool)
+        .await
+        .unwrap_or(24);
+    // Session date + time are club-local wall-clock times
+    let utc_offset = config::load(pool).await?.session_utc_offset;
+    let now = Utc::now().with_timezone(&utc_offset).naive_local();
+
+    let undersubscribed = sessions::find_undersubscribed_at_cutoff(pool, now, default_deadline_hours).await?;
+
+    if undersubscribed.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!(
+        "Found {} under-subscribed sessions to cancel",
+        undersubscribed.len()
+    );
+
+    let stripe = std::env::var("STRIPE_SECRET_KEY").ok().map(StripePayments::new);
+    let email = EmailClient::from_env();
+    if email.is_none() {
+        tracing::warn!("Email not configured; participants of auto-cancelled sessions won't be notified");
+    }
+
+    for session in undersubscribed {
+        let reason = format!(
+            "Not enough players (minimum {})",
+            session.min_players.unwrap_or_default()
+        );
+
+        let cancelled = match cancel_session(pool, session.id, &reason).await {
+            Ok(cancelled) => cancelled,
+            Err(e) => {
+                tracing::error!("Failed to auto-cancel session {}: {}", session.id, e);
+                continue;
+            }
+        };
+
+        tracing::info!(
+            "✓ Auto-cancelled session {} ({}) - {} bookings cancelled",
+            session.id,
+            session.title,
+            cancelled.bookings.len()
+        );
+
+        for booking in &cancelled.bookings {
+            refund_cancelled_booking(pool, stripe.as_ref(), booking).await;
+
+            if let Some(ref email) = email {
+                notify_participant(pool, email, &cancelled.session, booking, &reason).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Email a participant that their session was cancelled
+async fn notify_participant(
+    pool: &PgPool,
+    email: &EmailClient,
+    session: &Session,
+    booking: &Booking,
+    reason: &str,
+) {
+    let user = match users::find_by_id(pool, booking.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to load user for booking {}: {}", booking.booking_code, e);
+            return;
+        }
+    };
+
+    if !notification_preferences::should_email(pool, user.id, NotificationKind::SessionCancelled).await {
+        return;
+    }
+
+    let subject = format!("Session cancelled: {}", session.title);
+    let html = format!(
+        "<p>Hi {},</p>\
+         <p>Unfortunately <strong>{}</strong> on {} at {} has been cancelled: {}.</p>\
+         <p>Your booking {} has been cancelled. Any ticket used has been returned \
+         to your balance and card payments are refunded automatically.</p>\
+         <p>— Loafy Club</p>",
+        escape_html(user.name.as_deref().unwrap_or("there")),
+        escape_html(&session.title),
+        session.date.format("%Y-%m-%d"),
+        session.time.format("%H:%M"),
+        escape_html(reason),
+        escape_html(&booking.booking_code),
+    );
+
+    if let Err(e) = email.send(&user.email, &subject, &html).await {
+        tracing::error!(
+            "Failed to notify {} about cancelled session {}: {}",
+            user.email,
+            session.id,
+            e
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use chrono::{Datelike, Local};
+use chrono::Datelike;
+use loafy_core::booking::club_today;
 use loafy_db::{
     models::{bonus_types, transaction_types},
     queries::{config, subscriptions, ticket_transactions, users},
@@ -14,12 +15,13 @@ use loafy_db::{
 /// - Has an active subscription
 /// - Has not already received birthday bonus this year
 pub async fn allocate_birthday_tickets(pool: &PgPool) -> anyhow::Result<()> {
-    let today = Local::now().naive_local().date();
+    let settings = config::load(pool).await?;
+    let today = club_today(settings.session_utc_offset);
     let current_year = today.year();
 
     // Get configuration values
     let min_account_age_days = config::get_birthday_account_age_days(pool).await.unwrap_or(30);
-    let bonus_tickets = config::get_birthday_bonus_tickets(pool).await.unwrap_or(1);
+    let bonus_tickets = settings.birthday_ticket_count;
 
     // Find eligible users
     let eligible_users =
@@ -44,7 +46,7 @@ pub async fn allocate_birthday_tickets(pool: &PgPool) -> anyhow::Result<()> {
         );
 
         // Get user's subscription
-        let subscription = match subscriptions::find_by_user_id(pool, user.id).await? {
+        let subscription = match subscriptions::find_active_or_latest_by_user_id(pool, user.id).await? {
             Some(sub) if sub.is_active() => sub,
             _ => {
                 tracing::warn!(
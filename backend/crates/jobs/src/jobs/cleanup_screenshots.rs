@@ -0,0 +1,80 @@
+use chrono::{Duration, Utc};
+use loafy_db::{
+    queries::{bookings, config},
+    PgPool,
+};
+use loafy_integrations::supabase::{SupabaseStorage, PAYMENT_PROOF_BUCKET};
+
+/// Delete old payment proof screenshots from Supabase Storage
+/// Runs daily at 03:00
+///
+/// Only confirmed or cancelled bookings older than the retention window
+/// (default 90 days) are cleaned up. The booking record is kept; only the
+/// screenshot reference is cleared.
+pub async fn cleanup_payment_screenshots(pool: &PgPool) -> anyhow::Result<()> {
+    let retention_days = config::get_payment_screenshot_retention_days(pool)
+        .await
+        .unwrap_or(90);
+    let cutoff = Utc::now() - Duration::days(retention_days as i64);
+
+    let expired = bookings::find_expired_payment_screenshots(pool, cutoff).await?;
+
+    if expired.is_empty() {
+        tracing::debug!("No payment screenshots to clean up");
+        return Ok(());
+    }
+
+    let supabase_url = std::env::var("SUPABASE_URL")
+        .map_err(|_| anyhow::anyhow!("SUPABASE_URL must be set"))?;
+    let supabase_service_key = std::env::var("SUPABASE_SERVICE_KEY")
+        .map_err(|_| anyhow::anyhow!("SUPABASE_SERVICE_KEY must be set"))?;
+    let storage = SupabaseStorage::new(supabase_url, supabase_service_key);
+
+    tracing::info!(
+        "Found {} payment screenshots older than {} days",
+        expired.len(),
+        retention_days
+    );
+
+    let mut deleted = 0;
+    let mut failed = 0;
+
+    for booking in expired {
+        let Some(path) = booking.payment_screenshot_url else {
+            continue;
+        };
+
+        if let Err(e) = storage.delete_file(PAYMENT_PROOF_BUCKET, &path).await {
+            tracing::error!(
+                "Failed to delete screenshot for booking {}: {}",
+                booking.booking_code,
+                e
+            );
+            failed += 1;
+            continue;
+        }
+
+        match bookings::clear_payment_screenshot(pool, booking.id).await {
+            Ok(()) => {
+                tracing::info!("Deleted payment screenshot for booking {}", booking.booking_code);
+                deleted += 1;
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Deleted screenshot but failed to clear reference for booking {}: {}",
+                    booking.booking_code,
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    tracing::info!(
+        "✓ Payment screenshot cleanup: {} deleted, {} failed",
+        deleted,
+        failed
+    );
+
+    Ok(())
+}
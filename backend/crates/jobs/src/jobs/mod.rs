@@ -1,5 +1,15 @@
 pub mod release_unpaid;
 pub mod birthday_tickets;
+pub mod reset_ocr_counters;
+pub mod cleanup_screenshots;
+pub mod auto_cancel_undersubscribed;
+pub mod admin_digest;
+pub mod process_waitlist;
 
 pub use release_unpaid::release_unpaid_bookings;
 pub use birthday_tickets::allocate_birthday_tickets;
+pub use reset_ocr_counters::reset_ocr_counters;
+pub use cleanup_screenshots::cleanup_payment_screenshots;
+pub use auto_cancel_undersubscribed::auto_cancel_undersubscribed;
+pub use admin_digest::send_admin_digest;
+pub use process_waitlist::process_waitlist;
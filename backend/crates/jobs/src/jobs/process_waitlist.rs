@@ -0,0 +1,99 @@
+use loafy_core::waitlist::{promote_waitlisted, Promotion};
+use loafy_db::{
+    models::NotificationKind,
+    queries::{config, notification_preferences, users},
+    PgPool,
+};
+use loafy_integrations::email::{escape_html, EmailClient};
+
+/// Promote waitlisted users into freed-up slots
+/// Runs every 1 minute (offset from release_unpaid_bookings)
+///
+/// Each promoted user gets a pending booking and an email with their payment
+/// deadline. Unpaid holds are released by release_unpaid_bookings, after which
+/// the next run offers the slot to the next person in line. Nobody is
+/// promoted while maintenance mode pauses new bookings.
+pub async fn process_waitlist(pool: &PgPool) -> anyhow::Result<()> {
+    if config::load(pool).await?.maintenance_mode {
+        tracing::debug!("Maintenance mode is on; not promoting waitlisted users");
+        return Ok(());
+    }
+
+    let promotions = promote_waitlisted(pool).await?;
+
+    if promotions.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!("Promoted {} waitlisted users", promotions.len());
+
+    let email = EmailClient::from_env();
+    if email.is_none() {
+        tracing::warn!("Email not configured; promoted waitlist users won't be notified");
+    }
+
+    for promotion in &promotions {
+        tracing::info!(
+            "✓ Promoted user {} to booking {} for session {}",
+            promotion.entry.user_id,
+            promotion.booking.booking_code,
+            promotion.session.id
+        );
+
+        if let Some(ref email) = email {
+            notify_promoted(pool, email, promotion).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Email a user that a waitlist spot opened up for them
+async fn notify_promoted(pool: &PgPool, email: &EmailClient, promotion: &Promotion) {
+    let Promotion { session, booking, .. } = promotion;
+
+    let user = match users::find_by_id(pool, booking.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to load user for booking {}: {}", booking.booking_code, e);
+            return;
+        }
+    };
+
+    if !notification_preferences::should_email(pool, user.id, NotificationKind::WaitlistPromoted).await {
+        return;
+    }
+
+    let next_step = match booking.payment_deadline {
+        Some(deadline) => format!(
+            "Please pay by <strong>{} UTC</strong> to keep your spot. After that it goes to the next person on the waitlist.",
+            deadline.format("%Y-%m-%d %H:%M")
+        ),
+        None => "Your spot is confirmed — no payment needed.".to_string(),
+    };
+
+    let subject = format!("A spot opened up: {}", session.title);
+    let html = format!(
+        "<p>Hi {},</p>\
+         <p>Good news! A spot opened up in <strong>{}</strong> on {} at {} \
+         and we've reserved it for you (booking {}).</p>\
+         <p>{}</p>\
+         <p>— Loafy Club</p>",
+        escape_html(user.name.as_deref().unwrap_or("there")),
+        escape_html(&session.title),
+        session.date.format("%Y-%m-%d"),
+        session.time.format("%H:%M"),
+        escape_html(&booking.booking_code),
+        next_step,
+    );
+
+    if let Err(e) = email.send(&user.email, &subject, &html).await {
+        tracing::error!(
+            "Failed to notify {} about waitlist promotion for session {}: {}",
+            user.email,
+            session.id,
+            e
+        );
+    }
+}
@@ -1,23 +1,23 @@
-use chrono::{DateTime, Utc};
-use loafy_db::{
-    models::transaction_types,
-    queries::{bookings, sessions, subscriptions, ticket_transactions},
-    PgPool,
-};
+use chrono::{DateTime, Duration, Utc};
+use loafy_core::booking::release_unpaid_booking;
+use loafy_db::{queries::bookings, PgPool};
+
+/// Seconds past the payment deadline before a booking is released, so a
+/// payment confirmed by a slightly late webhook isn't cancelled under it
+const DEFAULT_RELEASE_GRACE_SECONDS: i64 = 60;
 
 /// Release unpaid bookings past their payment deadline
 /// Runs every 1 minute
 ///
 /// This job:
-/// 1. Finds bookings past their payment deadline
-/// 2. Restores any tickets used for the booking
-/// 3. Cancels the booking
-/// 4. Returns slots to the session
+/// 1. Finds bookings whose deadline passed more than `RELEASE_GRACE_SECONDS` ago
+/// 2. Re-checks each is still pending under lock, skipping any paid meanwhile
+/// 3. Restores any tickets used, cancels the booking and returns its slots
 pub async fn release_unpaid_bookings(pool: &PgPool) -> anyhow::Result<()> {
-    let now: DateTime<Utc> = Utc::now();
+    let cutoff: DateTime<Utc> = Utc::now() - release_grace();
 
-    // Find bookings past deadline
-    let expired_bookings = bookings::find_unpaid_expired_bookings(pool, now).await?;
+    // Find bookings past deadline (plus grace)
+    let expired_bookings = bookings::find_unpaid_expired_bookings(pool, cutoff).await?;
 
     if expired_bookings.is_empty() {
         return Ok(());
@@ -36,73 +36,23 @@ pub async fn release_unpaid_bookings(pool: &PgPool) -> anyhow::Result<()> {
             booking.payment_deadline
         );
 
-        // Restore ticket if one was used for this booking
-        if booking.tickets_used > 0 {
-            if let Ok(Some(subscription)) =
-                subscriptions::find_by_user_id(pool, booking.user_id).await
-            {
-                match subscriptions::restore_ticket(pool, subscription.id).await {
-                    Ok(new_balance) => {
-                        // Log the ticket restoration transaction
-                        let _ = ticket_transactions::create_with_pool(
-                            pool,
-                            booking.user_id,
-                            Some(subscription.id),
-                            Some(booking.id),
-                            transaction_types::RESTORED,
-                            1,
-                            new_balance,
-                            Some("Restored from expired unpaid booking"),
-                            None,
-                        )
-                        .await;
-
-                        tracing::info!(
-                            "Restored ticket for booking {} - new balance: {}",
-                            booking.booking_code,
-                            new_balance
-                        );
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "Failed to restore ticket for booking {}: {}",
-                            booking.booking_code,
-                            e
-                        );
-                    }
-                }
-            }
-        }
-
-        // Cancel booking
-        match bookings::cancel_booking(pool, booking.id).await {
-            Ok(_) => {
-                // Return slots to session
-                let slots_to_return = 1 + booking.guest_count;
-
-                if let Err(e) = sessions::increment_available_slots(
-                    pool,
-                    booking.session_id,
-                    slots_to_return,
-                )
-                .await
-                {
-                    tracing::error!(
-                        "Failed to return slots for booking {}: {}",
-                        booking.booking_code,
-                        e
-                    );
-                }
-
+        match release_unpaid_booking(pool, booking.id, cutoff).await {
+            Ok(Some(_)) => {
                 tracing::info!(
                     "✓ Released booking {} - returned {} slots",
                     booking.booking_code,
-                    slots_to_return
+                    1 + booking.guest_count
+                );
+            }
+            Ok(None) => {
+                tracing::info!(
+                    "Skipped booking {}: no longer pending",
+                    booking.booking_code
                 );
             }
             Err(e) => {
                 tracing::error!(
-                    "Failed to cancel booking {}: {}",
+                    "Failed to release booking {}: {}",
                     booking.booking_code,
                     e
                 );
@@ -112,3 +62,16 @@ pub async fn release_unpaid_bookings(pool: &PgPool) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Grace period from `RELEASE_GRACE_SECONDS`, falling back to the default
+fn release_grace() -> Duration {
+    let seconds = match std::env::var("RELEASE_GRACE_SECONDS") {
+        Ok(value) => value.trim().parse::<i64>().ok().filter(|s| *s >= 0).unwrap_or_else(|| {
+            tracing::warn!("Ignoring invalid RELEASE_GRACE_SECONDS={:?}, using default", value);
+            DEFAULT_RELEASE_GRACE_SECONDS
+        }),
+        Err(_) => DEFAULT_RELEASE_GRACE_SECONDS,
+    };
+
+    Duration::seconds(seconds)
+}
@@ -0,0 +1,11 @@
+use loafy_db::{queries::ocr_usage, PgPool};
+
+/// Reset per-user OCR counters for the new month
+/// Runs on the 1st of every month at 00:00
+pub async fn reset_ocr_counters(pool: &PgPool) -> anyhow::Result<()> {
+    let reset = ocr_usage::reset_all(pool).await?;
+
+    tracing::info!("✓ Reset OCR counters for {} users", reset);
+
+    Ok(())
+}
@@ -1,5 +1,9 @@
 mod jobs;
 
+use loafy_core::shutdown::{close_pool, shutdown_signal, shutdown_timeout};
+use loafy_db::queries::config;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tokio_cron_scheduler::{JobScheduler, Job};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -27,13 +31,20 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("✓ Database connection established");
 
     // Initialize job scheduler
-    let scheduler = JobScheduler::new().await?;
+    let mut scheduler = JobScheduler::new().await?;
+
+    // Each run holds a read lock; shutdown takes the write lock to wait for
+    // running jobs to finish
+    let running_jobs = Arc::new(RwLock::new(()));
 
     // Job 1: Release unpaid bookings (every 1 minute)
     let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
     let release_job = Job::new_async("0 * * * * *", move |_uuid, _l| {
         let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
         Box::pin(async move {
+            let _running = running_jobs.read().await;
             tracing::debug!("Running release_unpaid_bookings job");
             if let Err(e) = jobs::release_unpaid_bookings(&pool).await {
                 tracing::error!("release_unpaid_bookings job failed: {}", e);
@@ -47,9 +58,12 @@ async fn main() -> anyhow::Result<()> {
 
     // Job 2: Birthday ticket allocation (daily at 00:01)
     let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
     let birthday_job = Job::new_async("0 1 0 * * *", move |_uuid, _l| {
         let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
         Box::pin(async move {
+            let _running = running_jobs.read().await;
             tracing::info!("Running allocate_birthday_tickets job");
             if let Err(e) = jobs::allocate_birthday_tickets(&pool).await {
                 tracing::error!("allocate_birthday_tickets job failed: {}", e);
@@ -61,12 +75,112 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("✓ Registered job: allocate_birthday_tickets (daily at 00:01)");
 
+    // Job 3: Monthly OCR counter reset (1st of month at 00:00)
+    let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
+    let ocr_reset_job = Job::new_async("0 0 0 1 * *", move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
+        Box::pin(async move {
+            let _running = running_jobs.read().await;
+            tracing::info!("Running reset_ocr_counters job");
+            if let Err(e) = jobs::reset_ocr_counters(&pool).await {
+                tracing::error!("reset_ocr_counters job failed: {}", e);
+            }
+        })
+    })?;
+
+    scheduler.add(ocr_reset_job).await?;
+
+    tracing::info!("✓ Registered job: reset_ocr_counters (1st of month at 00:00)");
+
+    // Job 4: Payment screenshot cleanup (daily at 03:00)
+    let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
+    let screenshot_job = Job::new_async("0 0 3 * * *", move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
+        Box::pin(async move {
+            let _running = running_jobs.read().await;
+            tracing::info!("Running cleanup_payment_screenshots job");
+            if let Err(e) = jobs::cleanup_payment_screenshots(&pool).await {
+                tracing::error!("cleanup_payment_screenshots job failed: {}", e);
+            }
+        })
+    })?;
+
+    scheduler.add(screenshot_job).await?;
+
+    tracing::info!("✓ Registered job: cleanup_payment_screenshots (daily at 03:00)");
+
+    // Job 5: Auto-cancel under-subscribed sessions (every 15 minutes)
+    let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
+    let min_players_job = Job::new_async("0 */15 * * * *", move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
+        Box::pin(async move {
+            let _running = running_jobs.read().await;
+            tracing::info!("Running auto_cancel_undersubscribed job");
+            if let Err(e) = jobs::auto_cancel_undersubscribed(&pool).await {
+                tracing::error!("auto_cancel_undersubscribed job failed: {}", e);
+            }
+        })
+    })?;
+
+    scheduler.add(min_players_job).await?;
+
+    tracing::info!("✓ Registered job: auto_cancel_undersubscribed (every 15 minutes)");
+
+    // Job 6: Weekly admin digest email (configurable, default Monday 08:00)
+    let mut digest_cron = config::get_admin_digest_cron(&pool)
+        .await
+        .unwrap_or_else(|_| config::DEFAULT_ADMIN_DIGEST_CRON.to_string());
+    // A bad schedule mustn't keep the other jobs from starting
+    if let Err(e) = config::validate_cron(&digest_cron) {
+        tracing::warn!("{}; using the default admin digest schedule", e);
+        digest_cron = config::DEFAULT_ADMIN_DIGEST_CRON.to_string();
+    }
+    let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
+    let digest_job = Job::new_async(digest_cron.as_str(), move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
+        Box::pin(async move {
+            let _running = running_jobs.read().await;
+            tracing::info!("Running send_admin_digest job");
+            if let Err(e) = jobs::send_admin_digest(&pool).await {
+                tracing::error!("send_admin_digest job failed: {}", e);
+            }
+        })
+    })?;
+
+    scheduler.add(digest_job).await?;
+
+    tracing::info!("✓ Registered job: send_admin_digest ({})", digest_cron);
+
+    // Job 7: Promote waitlisted users (every 1 minute, offset from Job 1)
+    let pool_clone = pool.clone();
+    let running_clone = running_jobs.clone();
+    let waitlist_job = Job::new_async("30 * * * * *", move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let running_jobs = running_clone.clone();
+        Box::pin(async move {
+            let _running = running_jobs.read().await;
+            tracing::debug!("Running process_waitlist job");
+            if let Err(e) = jobs::process_waitlist(&pool).await {
+                tracing::error!("process_waitlist job failed: {}", e);
+            }
+        })
+    })?;
+
+    scheduler.add(waitlist_job).await?;
+
+    tracing::info!("✓ Registered job: process_waitlist (every 1 minute)");
+
     // TODO: Phase 2 jobs
-    // - Process waitlist (every 15 minutes)
     // - Stripe subscription sync (every hour)
-    // - Screenshot cleanup (daily at 03:00)
     // - Rate limit cleanup (daily at 04:00)
-    // - Monthly OCR counter reset (1st of month)
     // - Daily recap emails (hourly, user-configured time)
 
     // Run birthday job immediately if --run-birthday flag is present
@@ -87,8 +201,22 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("✓ Job scheduler started");
     tracing::info!("📡 Background jobs running");
 
-    // Keep the process running
-    loop {
-        tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+    // Run until SIGTERM/SIGINT, then stop scheduling and let running jobs finish
+    shutdown_signal().await;
+    tracing::info!("Shutdown signal received, stopping job scheduler...");
+
+    scheduler.shutdown().await?;
+
+    let drain_timeout = shutdown_timeout();
+    if tokio::time::timeout(drain_timeout, running_jobs.write()).await.is_err() {
+        tracing::warn!(
+            "Jobs still running after {}s, shutting down anyway",
+            drain_timeout.as_secs()
+        );
     }
+
+    close_pool(&pool).await;
+    tracing::info!("✓ Background jobs stopped");
+
+    Ok(())
 }
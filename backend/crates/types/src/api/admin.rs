@@ -3,8 +3,10 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use utoipa::ToSchema;
 use uuid::Uuid;
+use validator::Validate;
 
 use super::sessions::ParticipantInfo;
+use crate::enums::OrganizerSelfBooking;
 
 // =============================================================================
 // Pagination Types
@@ -36,6 +38,29 @@ macro_rules! paginated_response {
     };
 }
 
+/// Request to merge a duplicate account into a primary one
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct MergeUsersRequest {
+    /// Account that is kept
+    pub primary_id: Uuid,
+    /// Account whose data moves to the primary; soft-deleted afterwards
+    pub duplicate_id: Uuid,
+}
+
+/// Result of merging two accounts
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct MergeUsersResponse {
+    pub user: AdminUserResponse,
+    pub bookings_moved: i64,
+    pub ticket_transactions_moved: i64,
+    pub bonus_tickets_moved: i64,
+    pub waitlist_entries_moved: i64,
+    pub tickets_moved: i32,
+    pub subscription_moved: bool,
+}
+
 /// User restriction info for admin view
 #[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
@@ -60,6 +85,9 @@ pub struct AdminUserResponse {
     pub auth_provider: String,
     pub created_at: DateTime<Utc>,
     pub restriction: AdminUserRestriction,
+    /// Number of internal staff notes (set on list views)
+    #[ts(optional)]
+    pub notes_count: Option<i64>,
 }
 
 paginated_response!(
@@ -138,11 +166,108 @@ paginated_response!(
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
 pub struct ProfitStatsResponse {
     pub total_revenue_vnd: i64,
+    /// Variable plus fixed expenses
     pub total_expenses_vnd: i64,
+    /// Per-session expenses
+    pub variable_expenses_vnd: i64,
+    /// Prorated share of recurring fixed expenses
+    pub fixed_expenses_vnd: i64,
     pub net_profit_vnd: i64,
     pub profit_margin_percent: f64,
 }
 
+/// Request to create or replace a recurring fixed expense
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct FixedExpenseRequest {
+    /// Same categories as session expenses: court_rental, equipment, instructor or custom
+    pub category: String,
+    /// Required for the custom category
+    #[serde(default)]
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+    /// Amount per recurrence period
+    #[validate(range(min = 1))]
+    pub amount_vnd: i32,
+    /// `weekly` or `monthly`
+    pub recurrence: String,
+    pub starts_on: NaiveDate,
+    /// Last day the expense applies (inclusive); omit while ongoing
+    #[serde(default)]
+    pub ends_on: Option<NaiveDate>,
+}
+
+/// A recurring fixed expense
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct FixedExpenseResponse {
+    pub id: Uuid,
+    pub category: String,
+    pub description: Option<String>,
+    pub amount_vnd: i32,
+    pub recurrence: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create or replace a discount code
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct DiscountCodeRequest {
+    /// Letters, digits, `-` and `_`; stored uppercase
+    #[validate(length(min = 1, max = 50))]
+    pub code: String,
+    /// Percentage off; set exactly one of this and `amount_off_vnd`
+    #[serde(default)]
+    #[validate(range(min = 1, max = 100))]
+    pub percent_off: Option<i32>,
+    /// Fixed amount off each discounted slot
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub amount_off_vnd: Option<i32>,
+    /// Also discount paying guest slots
+    #[serde(default)]
+    pub applies_to_guests: bool,
+    /// Total uses across all users; omit for unlimited
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub max_uses: Option<i32>,
+    /// Uses per user; omit for unlimited
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub max_uses_per_user: Option<i32>,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    /// Exclusive
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Defaults to true
+    #[serde(default)]
+    pub active: Option<bool>,
+}
+
+/// A discount code and how often it has been used
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct DiscountCodeResponse {
+    pub id: Uuid,
+    pub code: String,
+    pub percent_off: Option<i32>,
+    pub amount_off_vnd: Option<i32>,
+    pub applies_to_guests: bool,
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub active: bool,
+    /// Non-cancelled bookings using the code
+    pub times_used: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 /// Per-session profit summary
 #[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
@@ -165,13 +290,97 @@ pub struct ExpenseCategoryResponse {
     pub percentage: f64,
 }
 
+/// A session expense with its per-court multiplier applied
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AdminSessionExpense {
+    pub id: Uuid,
+    pub category: String,
+    pub description: Option<String>,
+    pub cost_type: String,
+    /// Amount as entered (per court for `per_court` expenses)
+    pub amount_vnd: i32,
+    /// Amount the session actually pays (`amount_vnd * courts` for `per_court`)
+    pub computed_amount_vnd: i64,
+}
+
+/// Expense drill-down for one session
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AdminSessionExpensesResponse {
+    pub session_id: Uuid,
+    /// Multiplier applied to `per_court` expenses
+    pub courts: i32,
+    pub expenses: Vec<AdminSessionExpense>,
+    pub total_expenses_vnd: i64,
+}
+
+/// Historical performance of one recurring weekday + start hour slot
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SlotFillResponse {
+    /// ISO weekday: 1 = Monday .. 7 = Sunday
+    pub weekday: i32,
+    /// Session start hour (0-23)
+    pub hour: i32,
+    pub session_count: i64,
+    /// Average booked share of capacity, over sessions that went ahead
+    pub avg_fill_percent: f64,
+    /// Average confirmed revenue, over sessions that went ahead
+    pub avg_revenue_vnd: i64,
+    /// Share of the slot's sessions that were cancelled
+    pub cancellation_rate_percent: f64,
+}
+
+/// Fill analytics per recurring slot
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct FillAnalyticsResponse {
+    /// Number of past weeks covered
+    pub weeks: i32,
+    pub slots: Vec<SlotFillResponse>,
+}
+
+/// Rejected booking attempts by reason
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BookingFailuresResponse {
+    /// Not enough slots left
+    pub full: i64,
+    /// User already had an active booking for the session
+    pub already_booked: i64,
+    /// User hit their weekly booking limit
+    pub limit_reached: i64,
+}
+
+/// Booking conversion funnel for a period
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BookingFunnelResponse {
+    /// Bookings created plus rejected attempts
+    pub attempts: i64,
+    pub failed: BookingFailuresResponse,
+    pub created: i64,
+    /// Created bookings that were ever confirmed, including later refunds and cancellations
+    pub confirmed: i64,
+    /// Created bookings released because they weren't paid in time
+    pub expired_unpaid: i64,
+    /// Created bookings the user cancelled
+    pub cancelled_by_user: i64,
+    /// Confirmed bookings as a percentage of attempts
+    pub conversion_rate_percent: f64,
+}
+
 /// Daily profit data point for charts
 #[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
 pub struct DailyProfitDataPoint {
     pub date: String,
     pub revenue: i64,
+    /// Variable plus fixed expenses
     pub expenses: i64,
+    /// Prorated fixed expenses included in `expenses`
+    pub fixed_expenses: i64,
     pub profit: i64,
 }
 
@@ -182,6 +391,24 @@ pub struct SuspendUserRequest {
     pub reason: String,
     #[serde(default)]
     pub until: Option<DateTime<Utc>>,
+    /// Also cancel the user's upcoming bookings and email them the list
+    #[serde(default)]
+    pub cancel_bookings: bool,
+}
+
+/// What suspending a user would affect, for the admin to review first
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SuspensionImpactResponse {
+    pub user_id: Uuid,
+    pub upcoming_confirmed_bookings: i64,
+    pub pending_bookings: i64,
+    /// Upcoming, non-cancelled sessions the user organizes
+    pub organized_sessions: i64,
+    pub has_active_subscription: bool,
+    /// Status of the user's active or most recent subscription
+    pub subscription_status: Option<String>,
+    pub tickets_remaining: Option<i32>,
 }
 
 /// User restriction info for admin view
@@ -204,6 +431,25 @@ pub struct SuspendedUserError {
     pub until: Option<DateTime<Utc>>,
 }
 
+/// Request for a token to view the app as a user, for support
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ImpersonationTokenRequest {
+    /// Why support needs to see the user's view; kept in the audit log
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+/// Short-lived, read-only token acting as a user
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ImpersonationTokenResponse {
+    /// Bearer token; only GET requests are accepted with it
+    pub token: String,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
 // =============================================================================
 // Admin User Edit Types
 // =============================================================================
@@ -252,3 +498,184 @@ pub struct UpdateBookingRequest {
     #[serde(default)]
     pub admin_notes: Option<String>,
 }
+
+// =============================================================================
+// Admin Notes
+// =============================================================================
+
+/// Internal staff note about a user (admin only)
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AdminNoteResponse {
+    pub id: Uuid,
+    pub note: String,
+    pub author_id: Uuid,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to comp a user into a session at no charge (admin only)
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CompBookingRequest {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    #[serde(default)]
+    #[validate(range(min = 0))]
+    pub guest_count: i32,
+    /// Why the booking is free; kept in the user's staff notes
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+/// Optional details recorded when approving a payment proof
+#[derive(Debug, Clone, Default, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct VerifyPaymentRequest {
+    /// Amount that actually arrived; a mismatch with the booking price is
+    /// reported as a discrepancy but doesn't block confirmation
+    #[serde(default)]
+    #[validate(range(min = 0))]
+    pub received_amount_vnd: Option<i32>,
+}
+
+/// A verified booking whose received amount differs from its price
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct PaymentDiscrepancyResponse {
+    pub booking_id: Uuid,
+    pub booking_code: String,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub user_name: Option<String>,
+    pub session_id: Uuid,
+    pub session_title: String,
+    pub session_date: NaiveDate,
+    /// price_paid_vnd + guest_price_paid_vnd
+    pub expected_vnd: i32,
+    pub received_vnd: i32,
+    /// received - expected; negative is an underpayment
+    pub difference_vnd: i64,
+    pub verified_by: Option<Uuid>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+/// Payment discrepancies for a period
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct PaymentDiscrepanciesResponse {
+    pub period: String,
+    pub discrepancies: Vec<PaymentDiscrepancyResponse>,
+    /// Sum of the differences; negative means the club is owed money overall
+    pub total_difference_vnd: i64,
+}
+
+/// Request to add a staff note to a user
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CreateAdminNoteRequest {
+    #[validate(length(min = 1, max = 2000))]
+    pub note: String,
+}
+
+// =============================================================================
+// Config
+// =============================================================================
+
+/// Current pricing and ticket settings (admin only)
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ConfigResponse {
+    pub out_of_ticket_discount_percent: i32,
+    pub subscription_ticket_count: i32,
+    pub default_payment_hold_minutes: i32,
+    pub birthday_ticket_count: i32,
+    pub guest_tickets_enabled: bool,
+    pub platform_fee_percent: i32,
+    /// Maximum balance a renewal tops up to (0 = no cap)
+    pub ticket_rollover_cap: i32,
+    /// Subscription period assumed when the billing interval is unknown
+    pub default_subscription_period_months: i32,
+    pub organizer_self_booking: OrganizerSelfBooking,
+}
+
+/// Request to turn maintenance mode on or off
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+    /// Shown to users while enabled; omit for the default message
+    #[serde(default)]
+    #[validate(length(max = 500))]
+    pub message: Option<String>,
+}
+
+/// Current maintenance mode state
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct MaintenanceResponse {
+    pub enabled: bool,
+    pub message: Option<String>,
+}
+
+/// Permission that can be granted to roles
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct PermissionResponse {
+    pub name: String,
+    pub description: String,
+}
+
+/// Role with the permissions it grants
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct RolePermissionsResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// Permission names, matching `PermissionResponse::name`
+    pub permissions: Vec<String>,
+}
+
+/// Role → permission matrix
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct PermissionMatrixResponse {
+    pub permissions: Vec<PermissionResponse>,
+    pub roles: Vec<RolePermissionsResponse>,
+}
+
+/// Request to update pricing and ticket settings; omitted fields are unchanged
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct UpdateConfigRequest {
+    #[serde(default)]
+    #[validate(range(min = 0, max = 100))]
+    pub out_of_ticket_discount_percent: Option<i32>,
+    #[serde(default)]
+    #[validate(range(min = 0, max = 100))]
+    pub subscription_ticket_count: Option<i32>,
+    #[serde(default)]
+    #[validate(range(min = 5, max = 1440))]
+    pub default_payment_hold_minutes: Option<i32>,
+    #[serde(default)]
+    #[validate(range(min = 0, max = 20))]
+    pub birthday_ticket_count: Option<i32>,
+    #[serde(default)]
+    pub guest_tickets_enabled: Option<bool>,
+    #[serde(default)]
+    #[validate(range(min = 0, max = 100))]
+    pub platform_fee_percent: Option<i32>,
+    #[serde(default)]
+    #[validate(range(min = 0, max = 1000))]
+    pub ticket_rollover_cap: Option<i32>,
+    #[serde(default)]
+    #[validate(range(min = 1, max = 36))]
+    pub default_subscription_period_months: Option<i32>,
+    #[serde(default)]
+    pub organizer_self_booking: Option<OrganizerSelfBooking>,
+    /// Admin digest schedule: 6-field cron (sec min hour day month weekday), UTC.
+    /// Takes effect when the jobs service restarts.
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub admin_digest_cron: Option<String>,
+}
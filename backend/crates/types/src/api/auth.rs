@@ -1,4 +1,4 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use utoipa::ToSchema;
@@ -16,6 +16,8 @@ pub struct AuthUser {
     pub avatar_url: Option<String>,
     pub role: UserRole,
     pub birthday: Option<NaiveDate>,
+    /// Appear by name in session rosters
+    pub show_in_roster: bool,
 }
 
 #[derive(Debug, Deserialize, TS, ToSchema)]
@@ -25,6 +27,8 @@ pub struct UpdateProfileRequest {
     pub phone: Option<String>,
     pub avatar_url: Option<String>,
     pub birthday: Option<NaiveDate>,
+    /// Opt in or out of appearing by name in session rosters
+    pub show_in_roster: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -32,3 +36,40 @@ pub struct LoginRequest {
     pub provider: String,
     pub token: String,
 }
+
+/// The auth session making the request
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CurrentAuthSessionResponse {
+    /// Supabase session id; null for tokens issued without one
+    pub session_id: Option<String>,
+    /// How the user authenticated (e.g. "oauth", "password", "otp")
+    pub methods: Vec<String>,
+    pub signed_in_at: Option<DateTime<Utc>>,
+    /// When the current access token expires
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A sign-in method linked to the account
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SignInMethodResponse {
+    /// e.g. "google" or "email"
+    pub provider: String,
+    pub linked_at: Option<DateTime<Utc>>,
+    pub last_sign_in_at: Option<DateTime<Utc>>,
+}
+
+/// Where the current user is signed in
+///
+/// Supabase doesn't expose other devices' sessions individually, so they are
+/// summarized by sign-in method; `revoke-others` signs them all out.
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AuthSessionsResponse {
+    pub current: CurrentAuthSessionResponse,
+    pub sign_in_methods: Vec<SignInMethodResponse>,
+    pub last_sign_in_at: Option<DateTime<Utc>>,
+    /// False if Supabase was unreachable or returned incomplete sign-in details
+    pub complete: bool,
+}
@@ -18,7 +18,7 @@ pub struct BookingResponse {
     pub guest_count: i32,
     /// Number of tickets used for this booking (0 or 1)
     pub tickets_used: i32,
-    /// Type of discount applied: "ticket", "out_of_ticket", or "none"
+    /// Type of discount applied: "ticket", "out_of_ticket", "comp", "organizer", or "none"
     pub discount_applied: DiscountType,
     /// Base price per person from the session
     pub session_price_vnd: i32,
@@ -48,6 +48,83 @@ pub struct CreateBookingRequest {
     #[validate(range(min = 0, max = 10))]
     pub guest_count: i32,
     pub payment_method: PaymentMethod,
+    /// Spend tickets on guest slots too (up to the ticket balance)
+    #[serde(default)]
+    pub use_tickets_for_guests: Option<bool>,
+    /// Promo code to apply (case-insensitive)
+    #[serde(default)]
+    #[validate(length(min = 1, max = 50))]
+    pub discount_code: Option<String>,
+}
+
+/// A booking that could not be cancelled in a bulk cancel
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SkippedCancellation {
+    pub booking_code: String,
+    pub reason: String,
+}
+
+/// Result of cancelling all pending bookings
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BulkCancelResponse {
+    /// Booking codes that were cancelled
+    pub cancelled: Vec<String>,
+    /// Bookings left in place (e.g. cancellation deadline passed)
+    pub skipped: Vec<SkippedCancellation>,
+}
+
+/// What cancelling a booking now would do, shown before the user confirms
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CancelPreviewResponse {
+    /// False if already cancelled or the session has started
+    pub can_cancel: bool,
+    /// False once the deadline has passed; cancelling then gives nothing back
+    pub refund_eligible: bool,
+    /// Amount refunded to the card (0 for unpaid or non-Stripe bookings)
+    pub refund_amount_vnd: i32,
+    /// Whether subscription tickets used for the booking are given back
+    pub ticket_restored: bool,
+    /// Last moment the booking can be cancelled with a refund
+    pub deadline: DateTime<Utc>,
+}
+
+/// Cancelled booking and what the user got back
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CancelBookingResponse {
+    pub booking: BookingResponse,
+    /// Whether the cancellation was made before the refund deadline
+    pub refund_eligible: bool,
+    /// Amount refunded to the card (0 for unpaid, non-Stripe or late cancellations)
+    pub refund_amount_vnd: i32,
+    /// Whether subscription tickets used for the booking were given back
+    pub ticket_restored: bool,
+}
+
+/// Request to move a booking to another session
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RescheduleBookingRequest {
+    pub target_session_id: Uuid,
+}
+
+/// Bank transfer details for paying a pending QR-transfer booking
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct PaymentInstructionsResponse {
+    pub booking_id: Uuid,
+    /// Amount to transfer (price_paid_vnd + guest_price_paid_vnd)
+    pub amount_vnd: i32,
+    /// Booking code to use as the transfer memo/reference
+    pub transfer_reference: String,
+    pub bank_name: String,
+    pub account_number: String,
+    pub account_holder: String,
+    /// Session-specific QR code image, if the organizer uploaded one
+    pub qr_code_url: Option<String>,
+    pub payment_deadline: Option<DateTime<Utc>>,
 }
 
 /// Paginated response for user bookings
@@ -55,5 +132,72 @@ pub struct CreateBookingRequest {
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
 pub struct UserBookingsResponse {
     pub data: Vec<BookingResponse>,
-    pub page_info: PageInfo,
+    /// Offset page details; null when paging with `after`
+    pub page_info: Option<PageInfo>,
+    /// Pass as `after` to fetch the next page; None on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Current user's booking usage in the rolling weekly window
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BookingQuotaResponse {
+    /// Active bookings made within the window
+    pub used: i64,
+    /// Maximum bookings allowed in the window (null = unlimited)
+    pub limit: Option<i32>,
+    /// Length of the window in days
+    pub window_days: i64,
+    /// Start of the current window
+    pub window_start: DateTime<Utc>,
+}
+
+/// Newly generated calendar feed token; shown only once
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CalendarFeedTokenResponse {
+    pub token: String,
+    /// Feed path including the token, relative to the API base URL
+    pub feed_path: String,
+}
+
+/// Newly created booking invite; the token is shown only once
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BookingInviteResponse {
+    pub token: String,
+    /// Redemption path including the token, relative to the API base URL
+    pub redeem_path: String,
+    /// Session start; the invite can't be redeemed after this
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Booking and session a redeemed invite joined as a named guest
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct InviteRedemptionResponse {
+    pub booking_id: Uuid,
+    pub session_id: Uuid,
+    pub session_title: String,
+    pub session_date: NaiveDate,
+    pub session_time: NaiveTime,
+    pub session_location: String,
+    /// Name of the booker who shared the invite
+    pub host_name: Option<String>,
+}
+
+/// Current user's lifetime activity stats
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct UserStatsResponse {
+    /// Confirmed, non-cancelled bookings
+    pub confirmed_bookings: i64,
+    /// Confirmed bookings the user checked in for
+    pub sessions_attended: i64,
+    /// Total paid across confirmed bookings, including guests
+    pub total_spent_vnd: i64,
+    /// Subscription tickets spent on bookings (net of restorations)
+    pub tickets_used: i64,
+    /// Consecutive weeks with an attended session
+    pub current_streak_weeks: i32,
 }
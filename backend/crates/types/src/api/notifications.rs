@@ -16,3 +16,33 @@ pub struct NotificationResponse {
     pub read: bool,
     pub created_at: NaiveDateTime,
 }
+
+/// Which channels a notification type is delivered on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ChannelPreferences {
+    pub email: bool,
+    pub sms: bool,
+    pub push: bool,
+}
+
+/// Current user's notification preferences
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct NotificationPreferencesResponse {
+    pub booking_confirmation: ChannelPreferences,
+    /// Upcoming session reminders
+    pub reminder: ChannelPreferences,
+    pub session_cancelled: ChannelPreferences,
+    /// A waitlist spot opened up and was reserved
+    pub waitlist_promoted: ChannelPreferences,
+}
+
+/// Replace the current user's notification preferences
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub booking_confirmation: ChannelPreferences,
+    pub reminder: ChannelPreferences,
+    pub session_cancelled: ChannelPreferences,
+    pub waitlist_promoted: ChannelPreferences,
+}
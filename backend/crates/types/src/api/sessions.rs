@@ -5,14 +5,19 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::enums::{PaymentMethod, PaymentStatus, RosterVisibility};
+
 /// Basic participant info for session previews
 #[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
 pub struct ParticipantInfo {
+    /// Nil for anonymized participants
     pub id: Uuid,
     pub name: Option<String>,
     pub avatar_url: Option<String>,
     pub guest_count: i32,
+    /// The player opted out of appearing in rosters; name and avatar are withheld
+    pub anonymous: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
@@ -27,13 +32,33 @@ pub struct SessionResponse {
     #[ts(optional)]
     pub end_time: Option<NaiveTime>,
     pub location: String,
+    pub location_id: Option<Uuid>,
     pub courts: i32,
     pub max_players_per_court: i32,
     pub total_slots: i32,
     pub available_slots: i32,
+    /// Booked players on each court (courts fill in order)
+    pub court_fill: Vec<i32>,
     pub price_vnd: i32,
     pub price_usd: Option<String>,
+    /// Maximum guests a single booking may bring
+    pub max_guests_per_booking: i32,
+    /// Minimum confirmed players for the session to run (None = always runs)
+    pub min_players: Option<i32>,
+    /// Hours before start at which the minimum is checked
+    pub min_players_deadline_hours: Option<i32>,
+    /// Bookings are rejected before this time (None = open immediately)
+    pub booking_opens_at: Option<DateTime<Utc>>,
     pub cancelled: bool,
+    /// Public URL of the cover image
+    pub image_url: Option<String>,
+    /// Who can see the participant list
+    pub roster_visibility: RosterVisibility,
+    /// Players may queue once the session is full
+    pub waitlist_enabled: bool,
+    /// Players currently waiting for a slot
+    #[ts(optional)]
+    pub waitlist_count: Option<i32>,
     #[ts(optional)]
     pub expenses: Option<Vec<ExpenseResponse>>,
     #[ts(optional)]
@@ -44,6 +69,9 @@ pub struct SessionResponse {
     /// Total count of confirmed participants
     #[ts(optional)]
     pub confirmed_count: Option<i32>,
+    /// Payment status of the caller's booking for this session
+    /// (null when signed out or not booked)
+    pub my_booking_status: Option<String>,
 }
 
 /// Expense input for creating/updating session expenses
@@ -71,24 +99,218 @@ pub struct ExpenseResponse {
     pub amount_vnd: i32,
 }
 
+/// A session's expenses with the recomputed total (per-court costs multiplied out)
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SessionExpensesResponse {
+    pub session_id: Uuid,
+    pub expenses: Vec<ExpenseResponse>,
+    pub total_expenses_vnd: i64,
+}
+
+/// Create or replace an organizer's expense template
+#[derive(Debug, Clone, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ExpenseTemplateRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1, max = 20))]
+    pub expenses: Vec<ExpenseInput>,
+}
+
+/// A saved set of expenses that can be applied to new sessions
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ExpenseTemplateResponse {
+    pub id: Uuid,
+    pub organizer_id: Uuid,
+    pub name: String,
+    pub expenses: Vec<ExpenseInput>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Largest total capacity (courts × players per court) a session may have
+pub const MAX_SESSION_SLOTS: i32 = 200;
+
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateSessionRequest {
     #[validate(length(min = 1, max = 255))]
     pub title: String,
     pub description: Option<String>,
-    #[validate(length(min = 1))]
+    /// Location name; a new canonical location is created if no match exists.
+    /// May be omitted when `location_id` is given.
+    #[serde(default)]
+    #[validate(length(max = 255))]
     pub location: String,
+    /// Existing canonical location (takes precedence over `location`)
+    pub location_id: Option<Uuid>,
     /// ISO 8601 datetime string (e.g., "2025-12-29T10:00")
     pub start_time: String,
     /// ISO 8601 datetime string (e.g., "2025-12-29T12:00")
     pub end_time: String,
-    /// Total maximum slots for this session
-    #[validate(range(min = 1, max = 100))]
-    pub max_slots: i32,
+    /// Number of courts (defaults to 1)
+    #[validate(range(min = 1, max = 20))]
+    pub courts: Option<i32>,
+    /// Player capacity of each court; total slots = courts * max_players_per_court
+    #[validate(range(min = 1, max = 50))]
+    pub max_players_per_court: Option<i32>,
+    /// Total maximum slots, split evenly across `courts`.
+    /// Only used when `max_players_per_court` is not given.
+    #[validate(range(min = 1, max = 200))]
+    pub max_slots: Option<i32>,
+    #[validate(range(min = 0))]
     pub price_vnd: Option<i32>,
+    /// Maximum guests per booking (defaults to the global config value)
+    #[validate(range(min = 0, max = 10))]
+    pub max_guests_per_booking: Option<i32>,
+    /// Auto-cancel the session if fewer confirmed players by the deadline
+    #[validate(range(min = 1, max = 100))]
+    pub min_players: Option<i32>,
+    /// Hours before start to check min_players (defaults to the global config value)
+    #[validate(range(min = 1, max = 168))]
+    pub min_players_deadline_hours: Option<i32>,
+    /// When bookings open (RFC 3339); admins and the organizer may book earlier
+    pub booking_opens_at: Option<DateTime<Utc>>,
     pub early_access_ends_at: Option<String>,
+    /// Who can see the participant list (defaults to public; unchanged on update when omitted)
+    pub roster_visibility: Option<RosterVisibility>,
+    /// Whether players may queue once the session is full (defaults to on; unchanged on update when omitted)
+    pub waitlist_enabled: Option<bool>,
     /// Optional expenses for this session
     pub expenses: Option<Vec<ExpenseInput>>,
+    /// Expense template whose items are added to `expenses` (create only)
+    pub expense_template_id: Option<Uuid>,
+}
+
+/// Overrides for a duplicated session; everything else is copied from the source
+#[derive(Debug, Default, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct DuplicateSessionRequest {
+    /// Date of the copy (defaults to one week after the source)
+    pub date: Option<NaiveDate>,
+    /// Start time of the copy (defaults to the source's start time)
+    pub time: Option<NaiveTime>,
+}
+
+/// Maximum number of sessions accepted by one import
+pub const MAX_SESSION_IMPORT_ROWS: usize = 100;
+
+/// An import row that was rejected, by its position in the uploaded array
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SessionImportError {
+    /// Zero-based index of the row
+    pub row: usize,
+    pub message: String,
+}
+
+/// Result of importing a batch of sessions
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SessionImportResponse {
+    pub created: usize,
+    pub failed: usize,
+    /// IDs of the created sessions, in row order
+    pub session_ids: Vec<Uuid>,
+    pub errors: Vec<SessionImportError>,
+}
+
+/// An upcoming session the organizer can still edit, with what its bookings allow
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct EditableSessionResponse {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    #[schema(inline)]
+    pub session: SessionResponse,
+    /// Slots held by active bookings, including guests; capacity can't go below this
+    pub booked_slots: i32,
+    /// Whether a court can be removed without dropping below `booked_slots`
+    pub can_reduce_courts: bool,
+    /// No one has booked yet, so date, time and price changes affect nobody
+    pub safe_to_edit: bool,
+}
+
+/// One session's contribution to an organizer payout
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SessionPayout {
+    pub session_id: Uuid,
+    pub title: String,
+    pub date: NaiveDate,
+    /// Confirmed booking payments
+    pub gross_revenue_vnd: i64,
+    pub expenses_vnd: i64,
+    /// gross_revenue_vnd - expenses_vnd
+    pub net_vnd: i64,
+    /// Platform share of net_vnd (0 when the session made no profit)
+    pub platform_fee_vnd: i64,
+    /// net_vnd - platform_fee_vnd
+    pub payout_vnd: i64,
+}
+
+/// Payout report for an organizer's sessions in a period
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct OrganizerPayoutsResponse {
+    pub organizer_id: Uuid,
+    pub period: String,
+    pub platform_fee_percent: i32,
+    pub sessions: Vec<SessionPayout>,
+    pub total_gross_revenue_vnd: i64,
+    pub total_expenses_vnd: i64,
+    pub total_net_vnd: i64,
+    pub total_platform_fee_vnd: i64,
+    pub total_payout_vnd: i64,
+}
+
+/// Result of checking a booking code at the door
+///
+/// Unknown or cancelled codes give `valid: false` with the other fields empty.
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct VerifyBookingCodeResponse {
+    pub valid: bool,
+    pub user_name: Option<String>,
+    pub guest_count: Option<i32>,
+    pub payment_status: Option<PaymentStatus>,
+    pub already_checked_in: bool,
+}
+
+/// Request to check a player in by booking code
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CheckInRequest {
+    #[validate(length(min = 1, max = 32))]
+    pub code: String,
+}
+
+/// A booking that was just checked in
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct CheckInResponse {
+    pub booking_code: String,
+    pub user_name: Option<String>,
+    pub guest_count: i32,
+    pub payment_status: PaymentStatus,
+    pub checked_in_at: DateTime<Utc>,
+}
+
+/// Canonical location with the number of sessions held there
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct LocationResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub session_count: i64,
+}
+
+/// Request to create or rename a location
+#[derive(Debug, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct LocationRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
 }
 
 /// Response for session participants list
@@ -99,3 +321,92 @@ pub struct SessionParticipantsResponse {
     pub participants: Vec<ParticipantInfo>,
     pub total_count: i32,
 }
+
+/// Confirmed participant count for a session card
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SessionParticipantCountResponse {
+    pub session_id: Uuid,
+    pub confirmed_count: i32,
+}
+
+/// Request to join a full session's waitlist
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct JoinWaitlistRequest {
+    #[validate(range(min = 0, max = 10))]
+    #[serde(default)]
+    pub guest_count: i32,
+    /// Payment method for the booking created on promotion
+    pub payment_method: PaymentMethod,
+}
+
+/// Caller's place in a session's waitlist
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct WaitlistPositionResponse {
+    pub session_id: Uuid,
+    /// 1-based position in the queue
+    pub position: i64,
+    /// Number of people currently waiting
+    pub waitlist_length: i64,
+    pub guest_count: i32,
+    pub joined_at: DateTime<Utc>,
+    /// Worst-case hours until an offer once a slot frees up
+    pub estimated_wait_hours: i64,
+}
+
+/// Message to send to a session's confirmed participants
+#[derive(Debug, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AnnounceSessionRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub subject: String,
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+}
+
+/// Result of sending a session announcement
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct SessionAnnouncementResponse {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub subject: String,
+    /// Confirmed participants the announcement was addressed to
+    pub recipient_count: i32,
+    /// Emails that were accepted by the email provider
+    pub sent_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_request(max_slots: i32, price_vnd: i32) -> CreateSessionRequest {
+        serde_json::from_value(serde_json::json!({
+            "title": "Evening session",
+            "location": "Court A",
+            "start_time": "2026-05-01T18:00",
+            "end_time": "2026-05-01T20:00",
+            "max_slots": max_slots,
+            "price_vnd": price_vnd,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_max_slots_bounds() {
+        assert!(session_request(1, 0).validate().is_ok());
+        assert!(session_request(MAX_SESSION_SLOTS, 0).validate().is_ok());
+        assert!(session_request(0, 0).validate().is_err());
+        assert!(session_request(-1, 0).validate().is_err());
+        assert!(session_request(MAX_SESSION_SLOTS + 1, 0).validate().is_err());
+    }
+
+    #[test]
+    fn test_price_must_not_be_negative() {
+        assert!(session_request(10, 0).validate().is_ok());
+        assert!(session_request(10, -1).validate().is_err());
+    }
+}
@@ -5,7 +5,7 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::enums::SubscriptionStatus;
+use crate::enums::{BonusTicketType, SubscriptionStatus};
 use super::admin::PageInfo;
 
 #[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
@@ -28,6 +28,10 @@ pub struct TicketBalanceResponse {
     pub tickets_remaining: i32,
     pub has_active_subscription: bool,
     pub current_period_end: Option<NaiveDateTime>,
+    /// Highest balance a renewal tops up to (None = no cap)
+    pub rollover_cap: Option<i32>,
+    /// Renewal tickets that would be lost if the subscription renewed now
+    pub tickets_forfeited_at_renewal: i32,
 }
 
 /// Single ticket transaction in the history
@@ -48,6 +52,49 @@ pub struct TicketTransactionResponse {
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
 pub struct TicketTransactionsResponse {
     pub data: Vec<TicketTransactionResponse>,
+    /// Offset page details; null when paging with `after`
+    pub page_info: Option<PageInfo>,
+    /// Pass as `after` to fetch the next page; None on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Bonus ticket granted to the user (birthday, referral, manual)
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BonusTicketResponse {
+    pub id: Uuid,
+    pub bonus_type: BonusTicketType,
+    pub tickets: i32,
+    pub note: Option<String>,
+    pub granted_at: NaiveDateTime,
+}
+
+/// Paginated list of bonus tickets
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct BonusTicketsResponse {
+    pub data: Vec<BonusTicketResponse>,
+    pub page_info: PageInfo,
+}
+
+/// Ticket transaction in the admin ledger, with the owning user
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AdminTicketTransactionResponse {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    #[schema(inline)]
+    pub transaction: TicketTransactionResponse,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub admin_id: Option<Uuid>,
+}
+
+/// Paginated admin ticket ledger
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AdminTicketTransactionsResponse {
+    pub data: Vec<AdminTicketTransactionResponse>,
     pub page_info: PageInfo,
 }
 
@@ -59,6 +106,27 @@ pub struct AdminGrantTicketsRequest {
     pub reason: Option<String>,
 }
 
+/// Admin request to re-apply a subscription's latest paid invoice
+#[derive(Debug, Default, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ReconcileSubscriptionRequest {
+    /// Owner to use when neither the invoice nor the subscription names one
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
+}
+
+/// Result of reconciling a subscription with Stripe
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct ReconcileSubscriptionResponse {
+    pub stripe_subscription_id: String,
+    pub invoice_id: String,
+    /// skipped, already_applied, created, renewed or gifted
+    pub outcome: String,
+    /// The subscription as stored after reconciling (None for held gifts)
+    pub subscription: Option<SubscriptionResponse>,
+}
+
 /// Admin view of user's tickets
 #[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
@@ -70,6 +138,42 @@ pub struct AdminUserTicketsResponse {
     pub recent_transactions: Vec<TicketTransactionResponse>,
 }
 
+/// Lifetime ticket economics for a user, for resolving balance disputes
+#[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct AdminTicketSummaryResponse {
+    pub user_id: Uuid,
+    /// Tickets granted by subscription purchases and renewals
+    pub subscription_granted: i64,
+    /// Referral, birthday and manual bonus tickets
+    pub bonus_granted: i64,
+    pub total_granted: i64,
+    pub used: i64,
+    /// Tickets given back for cancelled bookings
+    pub restored: i64,
+    /// Forfeited at renewal or on subscription end
+    pub expired: i64,
+    /// Removed by an admin
+    pub revoked: i64,
+    /// Net tickets carried over from merged duplicate accounts
+    pub merged: i64,
+    /// Balance implied by summing every ledger entry
+    pub ledger_balance: i64,
+    /// Balance currently stored on the subscription
+    pub current_balance: i32,
+    /// Whether the stored balance matches the ledger
+    pub reconciles: bool,
+}
+
+/// Request to buy a subscription for someone else
+#[derive(Debug, Serialize, Deserialize, Validate, TS, ToSchema)]
+#[ts(export, export_to = "../../../../frontend/src/lib/types/")]
+pub struct GiftSubscriptionRequest {
+    /// Recipient's email; they don't need an account yet
+    #[validate(email)]
+    pub recipient_email: String,
+}
+
 /// Response for subscription checkout session creation
 #[derive(Debug, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../../frontend/src/lib/types/")]
@@ -77,16 +77,31 @@ pub enum PaymentStatus {
     Pending,
     Confirmed,
     Refunded,
+    /// Cancelled by the club but the card refund failed; needs a manual refund
+    RefundFailed,
     Cancelled,
 }
 
 impl_enum_from_str!(PaymentStatus, Pending,
     "confirmed" => Confirmed,
     "refunded" => Refunded,
+    "refund_failed" => RefundFailed,
     "cancelled" => Cancelled,
     "pending" => Pending,
 );
 
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Confirmed => "confirmed",
+            Self::Refunded => "refunded",
+            Self::RefundFailed => "refund_failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema, Default)]
 #[ts(export, export_to = "../../../frontend/src/lib/types/")]
 #[serde(rename_all = "snake_case")]
@@ -107,12 +122,28 @@ impl_enum_from_str!(VerificationStatus, Pending,
     "pending" => Pending,
 );
 
+impl VerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::AutoConfirmed => "auto_confirmed",
+            Self::PendingReview => "pending_review",
+            Self::Confirmed => "confirmed",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema, Default)]
 #[ts(export, export_to = "../../../frontend/src/lib/types/")]
 #[serde(rename_all = "snake_case")]
 pub enum SubscriptionStatus {
     #[default]
     Active,
+    /// In a Stripe trial period; gets tickets like an active subscription
+    Trialing,
+    /// First payment not completed yet
+    Incomplete,
     Expired,
     Cancelled,
     PastDue,
@@ -122,6 +153,8 @@ impl_enum_from_str!(SubscriptionStatus, Active,
     "expired" => Expired,
     "cancelled" => Cancelled,
     "past_due" => PastDue,
+    "trialing" => Trialing,
+    "incomplete" => Incomplete,
     "active" => Active,
 );
 
@@ -131,6 +164,10 @@ impl_enum_from_str!(SubscriptionStatus, Active,
 pub enum DiscountType {
     Ticket,
     OutOfTicket,
+    /// Free booking granted by an admin
+    Comp,
+    /// Organizer's own slot in their session, free under the `comp` self-booking policy
+    Organizer,
     #[default]
     None,
 }
@@ -138,9 +175,41 @@ pub enum DiscountType {
 impl_enum_from_str!(DiscountType, None,
     "ticket" => Ticket,
     "out_of_ticket" => OutOfTicket,
+    "comp" => Comp,
+    "organizer" => Organizer,
     "none" => None,
 );
 
+/// What happens when an organizer books their own session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema, Default)]
+#[ts(export, export_to = "../../../frontend/src/lib/types/")]
+#[serde(rename_all = "snake_case")]
+pub enum OrganizerSelfBooking {
+    /// They book and pay like everyone else
+    #[default]
+    Charge,
+    /// Their own slot is free; guests still pay
+    Comp,
+    /// They can't book their own session
+    Reject,
+}
+
+impl_enum_from_str!(OrganizerSelfBooking, Charge,
+    "charge" => Charge,
+    "comp" => Comp,
+    "reject" => Reject,
+);
+
+impl OrganizerSelfBooking {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Charge => "charge",
+            Self::Comp => "comp",
+            Self::Reject => "reject",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export, export_to = "../../../frontend/src/lib/types/")]
 #[serde(rename_all = "snake_case")]
@@ -155,3 +224,33 @@ impl_enum_from_str!(BonusTicketType, Referral,
     "manual" => Manual,
     "referral" => Referral,
 );
+
+/// Who can see the participant list of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema, Default)]
+#[ts(export, export_to = "../../../frontend/src/lib/types/")]
+#[serde(rename_all = "snake_case")]
+pub enum RosterVisibility {
+    /// Anyone browsing sessions
+    #[default]
+    Public,
+    /// Only players with a confirmed booking
+    Participants,
+    /// Nobody but the organizer and admins
+    Hidden,
+}
+
+impl_enum_from_str!(RosterVisibility, Public,
+    "public" => Public,
+    "participants" => Participants,
+    "hidden" => Hidden,
+);
+
+impl RosterVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Participants => "participants",
+            Self::Hidden => "hidden",
+        }
+    }
+}
@@ -1,14 +1,70 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 pub type Result<T> = std::result::Result<T, AppError>;
 
+/// Machine-readable error code returned alongside every API error
+///
+/// Clients should branch on this rather than on the (English) message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[ts(export, export_to = "../../../frontend/src/lib/types/")]
+pub enum ErrorCode {
+    // Generic codes, one per error kind
+    NotFound,
+    Unauthorized,
+    Forbidden,
+    BadRequest,
+    Conflict,
+    InternalError,
+    ValidationError,
+    PaymentError,
+    ExternalServiceError,
+    MaintenanceMode,
+
+    // Booking domain codes
+    SessionNotFound,
+    BookingNotFound,
+    SessionFull,
+    SessionCancelled,
+    SessionInPast,
+    AlreadyBooked,
+    TooManyGuests,
+    InvalidGuestCount,
+    BookingLimitReached,
+    BookingAlreadyCancelled,
+    CancellationDeadlinePassed,
+    CapacityBelowBookings,
+    BookingNotOpen,
+    AnnouncementLimitReached,
+    OwnSessionBookingNotAllowed,
+    InviteNotFound,
+    InviteExpired,
+    NoGuestSlotsAvailable,
+    DiscountCodeInvalid,
+    DiscountCodeExpired,
+    DiscountCodeExhausted,
+    DiscountCodeLimitReached,
+    DiscountCodeNotApplicable,
+}
+
+/// JSON body of an API error response
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export, export_to = "../../../frontend/src/lib/types/")]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
-    #[error("Not found: {0}")]
-    NotFound(String),
+    #[error("Not found: {1}")]
+    NotFound(ErrorCode, String),
 
     #[error("Unauthorized")]
     Unauthorized,
@@ -16,11 +72,11 @@ pub enum AppError {
     #[error("Forbidden")]
     Forbidden,
 
-    #[error("Bad request: {0}")]
-    BadRequest(String),
+    #[error("Bad request: {1}")]
+    BadRequest(ErrorCode, String),
 
-    #[error("Conflict: {0}")]
-    Conflict(String),
+    #[error("Conflict: {1}")]
+    Conflict(ErrorCode, String),
 
     #[error("Internal server error: {0}")]
     Internal(String),
@@ -39,13 +95,25 @@ impl AppError {
     pub fn status_code(&self) -> u16 {
         match self {
             Self::Database(_) | Self::Internal(_) => 500,
-            Self::NotFound(_) => 404,
+            Self::NotFound(..) => 404,
             Self::Unauthorized => 401,
             Self::Forbidden => 403,
-            Self::BadRequest(_) | Self::Validation(_) => 400,
-            Self::Conflict(_) => 409,
+            Self::BadRequest(..) | Self::Validation(_) => 400,
+            Self::Conflict(..) => 409,
             Self::Payment(_) => 402,
             Self::ExternalService(_) => 502,
         }
     }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Database(_) | Self::Internal(_) => ErrorCode::InternalError,
+            Self::NotFound(code, _) | Self::BadRequest(code, _) | Self::Conflict(code, _) => *code,
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::Forbidden => ErrorCode::Forbidden,
+            Self::Validation(_) => ErrorCode::ValidationError,
+            Self::Payment(_) => ErrorCode::PaymentError,
+            Self::ExternalService(_) => ErrorCode::ExternalServiceError,
+        }
+    }
 }
@@ -1,10 +1,12 @@
 pub mod api;
 pub mod enums;
 pub mod errors;
+pub mod pagination;
 pub mod period;
 pub mod validation;
 
-pub use period::{parse_period, PeriodFilter};
+pub use pagination::{trim_keyset_page, Cursor, Pagination};
+pub use period::{parse_period, PeriodFilter, UnsupportedPeriod, SUPPORTED_PERIODS};
 pub use validation::{validate_payment_method, validate_payment_status, validate_role};
 
-pub use errors::{AppError, Result};
+pub use errors::{AppError, ErrorCode, ErrorResponse, Result};
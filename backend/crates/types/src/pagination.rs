@@ -0,0 +1,137 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::api::admin::PageInfo;
+
+/// Normalized `page`/`per_page` query parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+    /// 1-based page number
+    pub page: i32,
+    pub per_page: i32,
+    /// Rows to skip; i64 so very large page numbers can't overflow
+    pub offset: i64,
+}
+
+impl Pagination {
+    /// Clamp `page` to at least 1 and `per_page` to `1..=max_per_page`
+    pub fn from_query(page: impl Into<i64>, per_page: impl Into<i64>, max_per_page: i32) -> Self {
+        let page = page.into().clamp(1, i32::MAX as i64) as i32;
+        let per_page = per_page.into().clamp(1, max_per_page.max(1) as i64) as i32;
+        let offset = (page as i64 - 1) * per_page as i64;
+
+        Self { page, per_page, offset }
+    }
+
+    /// Page info for a result set of `total` rows
+    pub fn page_info(&self, total: i64) -> PageInfo {
+        PageInfo {
+            page: self.page,
+            per_page: self.per_page,
+            total,
+            total_pages: ((total as f64) / (self.per_page as f64)).ceil() as i32,
+        }
+    }
+}
+
+/// Keyset position in a feed ordered by `created_at DESC, id DESC`
+///
+/// Sent to clients as an opaque string; the next page starts strictly after
+/// this row, so deep pages cost the same as the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    /// URL-safe base64 of the row's sort key
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.created_at.timestamp_micros(), self.id))
+    }
+
+    /// Decode a cursor produced by `encode`; None if it was tampered with or garbled
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        let (micros, id) = raw.split_once('|')?;
+
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// Trim a keyset fetch of `limit + 1` rows down to `limit`
+///
+/// Returns the cursor for the next page when the extra row came back.
+pub fn trim_keyset_page<T>(rows: &mut Vec<T>, limit: i32, key: impl Fn(&T) -> Cursor) -> Option<String> {
+    let limit = limit.max(0) as usize;
+    if rows.len() <= limit {
+        return None;
+    }
+    rows.truncate(limit);
+    rows.last().map(|row| key(row).encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_query_clamps_page_and_per_page() {
+        let p = Pagination::from_query(0, 500, 50);
+        assert_eq!((p.page, p.per_page, p.offset), (1, 50, 0));
+
+        let p = Pagination::from_query(3, 0, 50);
+        assert_eq!((p.page, p.per_page, p.offset), (3, 1, 2));
+    }
+
+    #[test]
+    fn test_from_query_offset_does_not_overflow() {
+        let p = Pagination::from_query(i64::MAX, 100i64, 100);
+        assert_eq!(p.page, i32::MAX);
+        assert_eq!(p.offset, (i32::MAX as i64 - 1) * 100);
+    }
+
+    #[test]
+    fn test_page_info_rounds_total_pages_up() {
+        let info = Pagination::from_query(2, 10, 50).page_info(21);
+        assert_eq!((info.page, info.per_page, info.total, info.total_pages), (2, 10, 21, 3));
+    }
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let cursor = Cursor {
+            created_at: DateTime::from_timestamp_micros(1_767_225_600_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn test_cursor_rejects_garbage() {
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("123|nope")), None);
+        assert_eq!(Cursor::decode(""), None);
+    }
+
+    #[test]
+    fn test_trim_keyset_page() {
+        let key = |n: &i64| Cursor {
+            created_at: DateTime::from_timestamp_micros(*n).unwrap(),
+            id: Uuid::nil(),
+        };
+
+        let mut rows = vec![3, 2, 1];
+        let next = trim_keyset_page(&mut rows, 2, key);
+        assert_eq!(rows, vec![3, 2]);
+        assert_eq!(next.and_then(|c| Cursor::decode(&c)), Some(key(&2)));
+
+        let mut rows = vec![3, 2];
+        assert_eq!(trim_keyset_page(&mut rows, 2, key), None);
+        assert_eq!(rows, vec![3, 2]);
+    }
+}
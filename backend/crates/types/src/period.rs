@@ -1,4 +1,13 @@
 use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+
+/// Period values accepted by `parse_period`
+pub const SUPPORTED_PERIODS: &[&str] = &["7d", "30d", "90d", "365d", "all"];
+
+/// A period string that isn't one of `SUPPORTED_PERIODS`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Unsupported period '{0}'. Must be one of: 7d, 30d, 90d, 365d, all")]
+pub struct UnsupportedPeriod(pub String);
 
 /// Represents a parsed period filter with optional since date and duration
 #[derive(Debug, Clone)]
@@ -14,6 +23,14 @@ impl PeriodFilter {
     pub fn since_or_default(&self) -> DateTime<Utc> {
         self.since.unwrap_or_else(|| Utc::now() - Duration::days(30))
     }
+
+    /// The equally long window immediately before this period, as `(start, end)`
+    ///
+    /// `end` is the current period's start (exclusive). None for "all time".
+    pub fn previous_window(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let (since, days) = (self.since?, self.days?);
+        Some((since - Duration::days(days), since))
+    }
 }
 
 /// Parse a period string into a PeriodFilter
@@ -24,32 +41,45 @@ impl PeriodFilter {
 /// - "90d" - Last 90 days
 /// - "365d" - Last 365 days
 /// - "all" - All time (no date filter)
-/// - Any other value defaults to 30 days
-pub fn parse_period(period: &str) -> PeriodFilter {
-    match period {
-        "7d" => PeriodFilter {
-            since: Some(Utc::now() - Duration::days(7)),
-            days: Some(7),
-        },
-        "30d" => PeriodFilter {
-            since: Some(Utc::now() - Duration::days(30)),
-            days: Some(30),
-        },
-        "90d" => PeriodFilter {
-            since: Some(Utc::now() - Duration::days(90)),
-            days: Some(90),
-        },
-        "365d" => PeriodFilter {
-            since: Some(Utc::now() - Duration::days(365)),
-            days: Some(365),
-        },
-        "all" => PeriodFilter {
-            since: None,
-            days: None,
-        },
-        _ => PeriodFilter {
-            since: Some(Utc::now() - Duration::days(30)),
-            days: Some(30),
-        },
+///
+/// Anything else is rejected rather than silently mapped to a default window.
+pub fn parse_period(period: &str) -> Result<PeriodFilter, UnsupportedPeriod> {
+    let days = match period {
+        "7d" => 7,
+        "30d" => 30,
+        "90d" => 90,
+        "365d" => 365,
+        "all" => {
+            return Ok(PeriodFilter {
+                since: None,
+                days: None,
+            })
+        }
+        _ => return Err(UnsupportedPeriod(period.to_string())),
+    };
+
+    Ok(PeriodFilter {
+        since: Some(Utc::now() - Duration::days(days)),
+        days: Some(days),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_periods_parse() {
+        for period in SUPPORTED_PERIODS {
+            assert!(parse_period(period).is_ok(), "{} should parse", period);
+        }
+        assert_eq!(parse_period("90d").unwrap().days, Some(90));
+        assert!(parse_period("all").unwrap().since.is_none());
+    }
+
+    #[test]
+    fn test_unknown_period_is_rejected() {
+        assert_eq!(parse_period("30days").unwrap_err(), UnsupportedPeriod("30days".to_string()));
+        assert!(parse_period("").is_err());
     }
 }
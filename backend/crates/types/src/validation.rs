@@ -7,7 +7,7 @@
 pub const VALID_ROLES: &[&str] = &["user", "organizer", "admin"];
 
 /// Valid payment status values
-pub const VALID_PAYMENT_STATUSES: &[&str] = &["pending", "confirmed", "failed", "refunded"];
+pub const VALID_PAYMENT_STATUSES: &[&str] = &["pending", "confirmed", "failed", "refunded", "refund_failed"];
 
 /// Valid payment method values
 pub const VALID_PAYMENT_METHODS: &[&str] = &["qr", "stripe", "cash", "free"];
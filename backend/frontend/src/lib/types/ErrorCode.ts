@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Machine-readable error code returned alongside every API error
+ *
+ * Clients should branch on this rather than on the (English) message text.
+ */
+export type ErrorCode = "NOT_FOUND" | "UNAUTHORIZED" | "FORBIDDEN" | "BAD_REQUEST" | "CONFLICT" | "INTERNAL_ERROR" | "VALIDATION_ERROR" | "PAYMENT_ERROR" | "EXTERNAL_SERVICE_ERROR" | "MAINTENANCE_MODE" | "SESSION_NOT_FOUND" | "BOOKING_NOT_FOUND" | "SESSION_FULL" | "SESSION_CANCELLED" | "SESSION_IN_PAST" | "ALREADY_BOOKED" | "TOO_MANY_GUESTS" | "INVALID_GUEST_COUNT" | "BOOKING_LIMIT_REACHED" | "BOOKING_ALREADY_CANCELLED" | "CANCELLATION_DEADLINE_PASSED" | "CAPACITY_BELOW_BOOKINGS" | "BOOKING_NOT_OPEN" | "ANNOUNCEMENT_LIMIT_REACHED" | "OWN_SESSION_BOOKING_NOT_ALLOWED" | "INVITE_NOT_FOUND" | "INVITE_EXPIRED" | "NO_GUEST_SLOTS_AVAILABLE" | "DISCOUNT_CODE_INVALID" | "DISCOUNT_CODE_EXPIRED" | "DISCOUNT_CODE_EXHAUSTED" | "DISCOUNT_CODE_LIMIT_REACHED" | "DISCOUNT_CODE_NOT_APPLICABLE";
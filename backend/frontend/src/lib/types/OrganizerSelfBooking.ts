@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What happens when an organizer books their own session
+ */
+export type OrganizerSelfBooking = "charge" | "comp" | "reject";
@@ -0,0 +1,10 @@
+-- Add per-session guest limit
+-- NULL means the global `max_guests_per_booking` config value applies
+
+ALTER TABLE sessions ADD COLUMN max_guests_per_booking INT;
+
+COMMENT ON COLUMN sessions.max_guests_per_booking IS 'Maximum guests a single booking may bring (NULL = global default)';
+
+INSERT INTO config (key, value, description) VALUES
+    ('max_guests_per_booking', '3', 'Default maximum guests per booking')
+ON CONFLICT (key) DO NOTHING;
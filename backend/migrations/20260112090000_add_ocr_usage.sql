@@ -0,0 +1,13 @@
+-- Per-user OCR usage tracking for payment screenshot verification
+-- Counters are reset on the 1st of each month by the reset_ocr_counters job
+
+CREATE TABLE ocr_usage (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    call_count INT NOT NULL DEFAULT 0,
+    period_start DATE NOT NULL DEFAULT date_trunc('month', NOW())::date,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+INSERT INTO config (key, value, description) VALUES
+    ('ocr_monthly_limit_per_user', '5', 'Maximum OCR payment verifications per user per month')
+ON CONFLICT (key) DO NOTHING;
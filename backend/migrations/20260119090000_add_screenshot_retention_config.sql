@@ -0,0 +1,6 @@
+-- Retention window for payment proof screenshots
+-- Screenshots of settled bookings older than this are deleted by the cleanup job
+
+INSERT INTO config (key, value, description) VALUES
+    ('payment_screenshot_retention_days', '90', 'Days to keep payment screenshots of confirmed/cancelled bookings')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,5 @@
+-- Track the original session when a booking is moved to another session
+
+ALTER TABLE bookings ADD COLUMN rescheduled_from UUID REFERENCES sessions(id);
+
+COMMENT ON COLUMN bookings.rescheduled_from IS 'Session the booking was moved from (most recent reschedule)';
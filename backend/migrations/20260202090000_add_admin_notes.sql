@@ -0,0 +1,11 @@
+-- Internal staff notes about users (append-only, admin-only)
+
+CREATE TABLE admin_notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE NOT NULL,
+    author_id UUID REFERENCES users(id) NOT NULL,
+    note TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_admin_notes_user ON admin_notes(user_id, created_at DESC);
@@ -0,0 +1,7 @@
+-- Per-user cap on active bookings made within a rolling 7-day window
+-- Subscribers get their own (usually higher) cap; 0 disables the limit
+
+INSERT INTO config (key, value, description) VALUES
+    ('max_bookings_per_week', '5', 'Max active bookings a drop-in user can make in a rolling 7-day window (0 = unlimited)'),
+    ('max_bookings_per_week_subscriber', '10', 'Max active bookings a subscriber can make in a rolling 7-day window (0 = unlimited)')
+ON CONFLICT (key) DO NOTHING;
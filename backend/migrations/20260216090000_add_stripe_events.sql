@@ -0,0 +1,12 @@
+-- Processed Stripe webhook events, used to drop duplicate/replayed deliveries
+
+CREATE TABLE stripe_events (
+    id TEXT PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    event_created_at TIMESTAMPTZ NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO config (key, value, description) VALUES
+    ('stripe_webhook_max_event_age_seconds', '600', 'Reject Stripe webhook events created longer ago than this (replay protection)')
+ON CONFLICT (key) DO NOTHING;
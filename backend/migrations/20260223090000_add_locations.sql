@@ -0,0 +1,30 @@
+-- Canonical session locations
+-- sessions.location (free text) is kept for backward compatibility while
+-- sessions are linked to a location row via location_id
+
+CREATE TABLE locations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Names are unique case-insensitively ("Court A" == "court a")
+CREATE UNIQUE INDEX idx_locations_name_lower ON locations(LOWER(name));
+
+ALTER TABLE sessions ADD COLUMN location_id UUID REFERENCES locations(id) ON DELETE SET NULL;
+CREATE INDEX idx_sessions_location_id ON sessions(location_id);
+
+-- Backfill: one location per distinct (case-insensitive) name, using the
+-- spelling of the earliest session
+INSERT INTO locations (name)
+SELECT DISTINCT ON (LOWER(TRIM(location))) TRIM(location)
+FROM sessions
+WHERE TRIM(location) <> ''
+ORDER BY LOWER(TRIM(location)), created_at ASC
+ON CONFLICT DO NOTHING;
+
+UPDATE sessions s
+SET location_id = l.id
+FROM locations l
+WHERE LOWER(TRIM(s.location)) = LOWER(l.name);
@@ -0,0 +1,5 @@
+-- Subscription status now mirrors Stripe more closely:
+-- 'trialing' (ticket-eligible trial) and 'incomplete' (first payment pending)
+-- are stored as-is instead of being collapsed into 'active' / 'past_due'
+
+COMMENT ON COLUMN subscriptions.status IS 'active, trialing, incomplete, past_due, expired, cancelled';
@@ -0,0 +1,11 @@
+-- Minimum confirmed players for a session to run
+-- Sessions still below min_players at (start - min_players_deadline_hours)
+-- are auto-cancelled by the auto_cancel_undersubscribed job
+
+ALTER TABLE sessions ADD COLUMN min_players INT;
+ALTER TABLE sessions ADD COLUMN min_players_deadline_hours INT;
+ALTER TABLE sessions ADD COLUMN cancellation_reason TEXT;
+
+INSERT INTO config (key, value, description) VALUES
+    ('min_players_deadline_hours', '24', 'Default hours before start at which under-subscribed sessions are auto-cancelled')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,8 @@
+-- Weekly admin digest email settings
+-- admin_digest_recipients: comma-separated emails; empty sends to every admin
+-- admin_digest_cron: 6-field cron (sec min hour day month weekday), read at jobs startup
+
+INSERT INTO config (key, value, description) VALUES
+    ('admin_digest_recipients', '', 'Comma-separated admin digest recipients (empty = all admins)'),
+    ('admin_digest_cron', '0 0 8 * * Mon', 'Schedule for the weekly admin digest email (UTC, 6-field cron)')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Sessions now honour their court layout: total_slots = courts * max_players_per_court
+-- Existing sessions were all created as a single court holding every slot;
+-- backfill so their capacity is unchanged under the new rule
+
+UPDATE sessions SET courts = 1 WHERE courts IS NULL OR courts < 1;
+
+UPDATE sessions
+SET max_players_per_court = total_slots
+WHERE courts = 1
+  AND (max_players_per_court IS NULL OR max_players_per_court <> total_slots);
+
+ALTER TABLE sessions ALTER COLUMN courts SET NOT NULL;
+ALTER TABLE sessions ADD CONSTRAINT sessions_courts_positive CHECK (courts >= 1);
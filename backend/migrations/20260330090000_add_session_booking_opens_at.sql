@@ -0,0 +1,4 @@
+-- Scheduled booking window: bookings are rejected before booking_opens_at
+-- (admins and the session organizer may still book early)
+
+ALTER TABLE sessions ADD COLUMN booking_opens_at TIMESTAMPTZ;
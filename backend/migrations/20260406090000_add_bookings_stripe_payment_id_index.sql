@@ -0,0 +1,5 @@
+-- Reverse lookup of bookings by Stripe PaymentIntent (charge.refunded webhook)
+
+CREATE INDEX idx_bookings_stripe_payment_id
+    ON bookings(stripe_payment_id)
+    WHERE stripe_payment_id IS NOT NULL;
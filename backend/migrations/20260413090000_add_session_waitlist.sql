@@ -0,0 +1,18 @@
+-- Session waitlist
+-- Users queue for full sessions; when slots free up the process_waitlist job
+-- creates a pending booking for the next person, who then has
+-- waitlist_exclusive_hours to pay before the slot passes to the next in line
+
+CREATE TABLE waitlist_entries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    guest_count INT NOT NULL DEFAULT 0,
+    payment_method VARCHAR(20) NOT NULL,
+    booking_id UUID REFERENCES bookings(id) ON DELETE SET NULL,
+    promoted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_waitlist_entries_session_user ON waitlist_entries(session_id, user_id);
+CREATE INDEX idx_waitlist_entries_queue ON waitlist_entries(session_id, created_at) WHERE promoted_at IS NULL;
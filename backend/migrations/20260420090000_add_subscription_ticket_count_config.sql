@@ -0,0 +1,5 @@
+-- Tickets granted per subscription purchase/renewal (previously hardcoded to 10)
+
+INSERT INTO config (key, value, description) VALUES
+    ('subscription_ticket_count', '10', 'Tickets granted per subscription purchase or renewal')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,6 @@
+-- Allow subscribers to spend tickets on guest slots (opt-in per booking)
+-- Set to 'false' for clubs where tickets are personal
+
+INSERT INTO config (key, value, description) VALUES
+    ('guest_tickets_enabled', 'true', 'Allow subscribers to use tickets for their guests (true/false)')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Messages sent by organizers to a session's confirmed participants
+
+CREATE TABLE session_announcements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID REFERENCES sessions(id) ON DELETE CASCADE NOT NULL,
+    author_id UUID REFERENCES users(id) NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    recipient_count INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_session_announcements_session ON session_announcements(session_id, created_at DESC);
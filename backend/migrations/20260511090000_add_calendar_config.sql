@@ -0,0 +1,8 @@
+-- Calendar export settings
+-- session_utc_offset: offset of the club's local time that session date/time are stored in
+-- default_session_duration_minutes: used for sessions without an end_time
+
+INSERT INTO config (key, value, description) VALUES
+    ('session_utc_offset', '+07:00', 'UTC offset of session dates and times (e.g. +07:00)'),
+    ('default_session_duration_minutes', '90', 'Assumed length of sessions without an end time')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,19 @@
+-- Per-user opt-in/out for each notification type and channel
+-- Users without a row get the defaults below (email on, SMS and push off)
+
+CREATE TABLE notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    booking_confirmation_email BOOLEAN NOT NULL DEFAULT TRUE,
+    booking_confirmation_sms BOOLEAN NOT NULL DEFAULT FALSE,
+    booking_confirmation_push BOOLEAN NOT NULL DEFAULT FALSE,
+    reminder_email BOOLEAN NOT NULL DEFAULT TRUE,
+    reminder_sms BOOLEAN NOT NULL DEFAULT FALSE,
+    reminder_push BOOLEAN NOT NULL DEFAULT FALSE,
+    session_cancelled_email BOOLEAN NOT NULL DEFAULT TRUE,
+    session_cancelled_sms BOOLEAN NOT NULL DEFAULT FALSE,
+    session_cancelled_push BOOLEAN NOT NULL DEFAULT FALSE,
+    waitlist_promoted_email BOOLEAN NOT NULL DEFAULT TRUE,
+    waitlist_promoted_sms BOOLEAN NOT NULL DEFAULT FALSE,
+    waitlist_promoted_push BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- Share of each session's net revenue kept by the platform in organizer payouts
+
+INSERT INTO config (key, value, description) VALUES
+    ('platform_fee_percent', '0', 'Percentage of session net revenue withheld from organizer payouts')
+ON CONFLICT (key) DO NOTHING;
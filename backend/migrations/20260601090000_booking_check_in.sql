@@ -0,0 +1,3 @@
+-- When a player was admitted at the door by the organizer
+
+ALTER TABLE bookings ADD COLUMN checked_in_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- Per-user token for the subscribable bookings calendar feed
+-- Only a SHA-256 hash is stored; regenerating replaces it, invalidating the old URL
+
+ALTER TABLE users ADD COLUMN calendar_feed_token_hash TEXT;
+
+CREATE UNIQUE INDEX idx_users_calendar_feed_token_hash ON users(calendar_feed_token_hash)
+    WHERE calendar_feed_token_hash IS NOT NULL;
@@ -0,0 +1,20 @@
+-- Subscriptions bought for someone who has no account yet
+-- The grant is held here until the recipient signs up with this email;
+-- gifts to existing users are granted straight away and never stored
+
+CREATE TABLE subscription_gifts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recipient_email TEXT NOT NULL,
+    purchaser_id UUID NOT NULL REFERENCES users(id),
+    stripe_subscription_id TEXT NOT NULL UNIQUE,
+    stripe_customer_id TEXT NOT NULL,
+    tickets INTEGER NOT NULL,
+    current_period_start TIMESTAMPTZ NOT NULL,
+    current_period_end TIMESTAMPTZ NOT NULL,
+    claimed_by UUID REFERENCES users(id),
+    claimed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_subscription_gifts_unclaimed ON subscription_gifts(recipient_email)
+    WHERE claimed_at IS NULL;
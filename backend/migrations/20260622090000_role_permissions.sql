@@ -0,0 +1,37 @@
+-- Fine-grained permissions granted to roles
+-- Handlers check a permission rather than a role name, so a role's abilities
+-- can change here without code changes
+
+CREATE TABLE permissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT UNIQUE NOT NULL,
+    description TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE role_permissions (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE INDEX idx_role_permissions_permission_id ON role_permissions(permission_id);
+
+INSERT INTO permissions (name, description) VALUES
+    ('sessions.create', 'Create sessions and manage their own'),
+    ('sessions.delete', 'Delete any session'),
+    ('sessions.import', 'Bulk import sessions'),
+    ('analytics.view', 'View session fill analytics'),
+    ('bookings.manage', 'View and edit any booking'),
+    ('tickets.manage', 'Grant and revoke user tickets'),
+    ('config.manage', 'Change app config and maintenance mode'),
+    ('roles.manage', 'View permissions and change user roles');
+
+-- Seed the existing roles with what they could already do
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r
+JOIN permissions p ON
+    r.name = 'admin'
+    OR (r.name = 'organizer' AND p.name IN ('sessions.create', 'analytics.view'));
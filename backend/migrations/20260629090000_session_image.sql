@@ -0,0 +1,3 @@
+-- Optional cover photo for sessions, stored in the public session-images bucket
+
+ALTER TABLE sessions ADD COLUMN image_url TEXT;
@@ -0,0 +1,6 @@
+-- Upper bound on a subscriber's ticket balance after renewal; renewal tickets
+-- beyond it are forfeited. 0 disables the cap.
+
+INSERT INTO config (key, value, description) VALUES
+    ('ticket_rollover_cap', '20', 'Maximum ticket balance a subscription renewal can top up to (0 = no cap)')
+ON CONFLICT (key) DO NOTHING;
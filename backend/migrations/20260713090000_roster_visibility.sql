@@ -0,0 +1,9 @@
+-- Participant visibility controls
+-- Users can opt out of appearing by name in session rosters; organizers choose
+-- who gets to see a session's roster at all.
+ALTER TABLE users
+    ADD COLUMN show_in_roster BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE sessions
+    ADD COLUMN roster_visibility VARCHAR(20) NOT NULL DEFAULT 'public'
+        CHECK (roster_visibility IN ('public', 'participants', 'hidden'));
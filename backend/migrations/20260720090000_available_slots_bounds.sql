@@ -0,0 +1,11 @@
+-- Keep available_slots within [0, total_slots] at the database level so a slot
+-- accounting bug fails loudly instead of overselling a session.
+
+-- Repair rows that already drifted out of range before adding the constraint
+UPDATE sessions
+SET available_slots = GREATEST(0, LEAST(available_slots, total_slots))
+WHERE available_slots < 0 OR available_slots > total_slots;
+
+ALTER TABLE sessions
+    ADD CONSTRAINT sessions_available_slots_bounds
+        CHECK (available_slots >= 0 AND available_slots <= total_slots);
@@ -0,0 +1,13 @@
+-- Billing interval of each subscription's Stripe plan, so periods are extended
+-- by the plan's real length when Stripe doesn't report the period dates.
+-- NULL (e.g. subscriptions created before this migration) falls back to the
+-- configured default period.
+ALTER TABLE subscriptions
+    ADD COLUMN billing_interval VARCHAR(10)
+        CHECK (billing_interval IN ('day', 'week', 'month', 'year')),
+    ADD COLUMN billing_interval_count INT
+        CHECK (billing_interval_count > 0);
+
+INSERT INTO config (key, value, description) VALUES
+    ('default_subscription_period_months', '3', 'Subscription period length assumed when the billing interval is unknown')
+ON CONFLICT (key) DO NOTHING;
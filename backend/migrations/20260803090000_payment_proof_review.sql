@@ -0,0 +1,6 @@
+-- Record who manually reviewed a payment proof and when
+-- Reviews only transition bookings out of 'pending_review', so the reviewer
+-- columns also show which admin won when two review the same booking.
+ALTER TABLE bookings
+    ADD COLUMN verified_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN verified_at TIMESTAMPTZ;
@@ -0,0 +1,8 @@
+-- Organizers can turn off the waitlist for a session
+-- Existing sessions keep the waitlist they've always had.
+ALTER TABLE sessions
+    ADD COLUMN waitlist_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Serves the "full but joinable waitlist" browse filter
+CREATE INDEX idx_sessions_waitlist_open ON sessions (date)
+    WHERE available_slots = 0 AND waitlist_enabled AND cancelled = false;
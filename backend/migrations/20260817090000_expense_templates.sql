@@ -0,0 +1,33 @@
+-- Reusable expense templates
+-- Organizers save the expenses they enter for every session (court rental,
+-- instructor, ...) and expand them into session_expenses at create time.
+
+CREATE TABLE expense_templates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    organizer_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_expense_templates_organizer_name ON expense_templates(organizer_id, name);
+
+CREATE TRIGGER update_expense_templates_updated_at
+    BEFORE UPDATE ON expense_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Same shape and constraints as session_expenses
+CREATE TABLE expense_template_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    template_id UUID NOT NULL REFERENCES expense_templates(id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    category VARCHAR(50) NOT NULL
+        CHECK (category IN ('court_rental', 'equipment', 'instructor', 'custom')),
+    description VARCHAR(255),
+    cost_type VARCHAR(20) NOT NULL DEFAULT 'total'
+        CHECK (cost_type IN ('per_court', 'total')),
+    amount_vnd INT NOT NULL,
+    CHECK (category <> 'custom' OR description IS NOT NULL)
+);
+
+CREATE INDEX idx_expense_template_items_template ON expense_template_items(template_id, position);
@@ -0,0 +1,8 @@
+-- Amount actually received for a manually verified transfer
+-- NULL means the reviewer didn't record one. A value that differs from
+-- price_paid_vnd + guest_price_paid_vnd is a payment discrepancy.
+ALTER TABLE bookings ADD COLUMN received_amount_vnd INT CHECK (received_amount_vnd >= 0);
+
+CREATE INDEX idx_bookings_payment_discrepancy ON bookings(verified_at)
+    WHERE received_amount_vnd IS NOT NULL
+      AND received_amount_vnd <> price_paid_vnd + guest_price_paid_vnd;
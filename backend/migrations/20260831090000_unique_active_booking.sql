@@ -0,0 +1,66 @@
+-- At most one active booking per user and session
+-- Booking creation checks for an existing booking under the session lock;
+-- this is the backstop for anything that slips past that check.
+
+-- The booking race already left some users with several active bookings for
+-- one session. Keep each user's oldest and cancel the rest, returning their
+-- slots and tickets like a normal cancellation, so the index can be created.
+CREATE TEMPORARY TABLE duplicate_active_bookings AS
+SELECT id, user_id, session_id, guest_count, tickets_used
+FROM (
+    SELECT b.*,
+           ROW_NUMBER() OVER (PARTITION BY user_id, session_id ORDER BY created_at, id) AS position
+    FROM bookings b
+    WHERE cancelled_at IS NULL
+) ranked
+WHERE position > 1;
+
+UPDATE sessions s
+SET available_slots = LEAST(s.total_slots, s.available_slots + returned.slots),
+    updated_at = NOW()
+FROM (
+    SELECT session_id, SUM(1 + guest_count) AS slots
+    FROM duplicate_active_bookings
+    GROUP BY session_id
+) returned
+WHERE s.id = returned.session_id;
+
+INSERT INTO ticket_transactions (user_id, subscription_id, booking_id, transaction_type, amount, balance_after, notes)
+SELECT d.user_id,
+       sub.id,
+       d.id,
+       'restored',
+       d.tickets_used,
+       sub.tickets_remaining + SUM(d.tickets_used) OVER (PARTITION BY d.user_id ORDER BY d.id),
+       'Restored from duplicate booking'
+FROM duplicate_active_bookings d
+JOIN subscriptions sub ON sub.user_id = d.user_id
+WHERE d.tickets_used > 0;
+
+UPDATE subscriptions sub
+SET tickets_remaining = sub.tickets_remaining + restored.tickets,
+    updated_at = NOW()
+FROM (
+    SELECT user_id, SUM(tickets_used) AS tickets
+    FROM duplicate_active_bookings
+    WHERE tickets_used > 0
+    GROUP BY user_id
+) restored
+WHERE sub.user_id = restored.user_id;
+
+-- Paid card duplicates can't be refunded from SQL; flag them for an admin
+UPDATE bookings b
+SET cancelled_at = NOW(),
+    payment_status = CASE
+        WHEN b.payment_method = 'stripe' AND b.payment_status = 'confirmed' THEN 'refund_failed'
+        ELSE 'cancelled'
+    END,
+    updated_at = NOW()
+FROM duplicate_active_bookings d
+WHERE b.id = d.id;
+
+DROP TABLE duplicate_active_bookings;
+
+CREATE UNIQUE INDEX idx_bookings_one_active_per_session
+    ON bookings(user_id, session_id)
+    WHERE cancelled_at IS NULL;
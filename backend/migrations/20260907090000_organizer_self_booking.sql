@@ -0,0 +1,6 @@
+-- What happens when an organizer books their own session:
+-- 'charge' (pays like everyone), 'comp' (own slot free) or 'reject'
+
+INSERT INTO config (key, value, description) VALUES
+    ('organizer_self_booking', 'charge', 'Organizer booking their own session: charge, comp or reject')
+ON CONFLICT (key) DO NOTHING;
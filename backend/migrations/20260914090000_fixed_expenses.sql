@@ -0,0 +1,25 @@
+-- Fixed recurring costs not tied to a session (coach retainer, storage rental, ...)
+-- Profit reports prorate amount_vnd per day over the recurrence period, from
+-- starts_on through ends_on (open-ended when NULL).
+
+CREATE TABLE fixed_expenses (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    category VARCHAR(50) NOT NULL
+        CHECK (category IN ('court_rental', 'equipment', 'instructor', 'custom')),
+    description VARCHAR(255),
+    amount_vnd INT NOT NULL CHECK (amount_vnd > 0),
+    recurrence VARCHAR(10) NOT NULL CHECK (recurrence IN ('weekly', 'monthly')),
+    starts_on DATE NOT NULL,
+    ends_on DATE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (category <> 'custom' OR description IS NOT NULL),
+    CHECK (ends_on IS NULL OR ends_on >= starts_on)
+);
+
+CREATE INDEX idx_fixed_expenses_starts_on ON fixed_expenses(starts_on);
+
+CREATE TRIGGER update_fixed_expenses_updated_at
+    BEFORE UPDATE ON fixed_expenses
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,15 @@
+-- Why a booking was cancelled, for the booking funnel
+-- NULL for bookings cancelled before this was tracked.
+ALTER TABLE bookings ADD COLUMN cancellation_reason VARCHAR(30)
+    CHECK (cancellation_reason IN ('user', 'admin', 'payment_expired', 'refunded', 'session_cancelled', 'account_deleted'));
+
+-- Booking attempts rejected with a conflict (session full, already booked, weekly limit)
+CREATE TABLE booking_attempt_failures (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    reason VARCHAR(30) NOT NULL CHECK (reason IN ('full', 'already_booked', 'limit_reached')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_attempt_failures_created_at ON booking_attempt_failures(created_at);
@@ -0,0 +1,17 @@
+-- Shareable invites for a booking's guest slots
+-- Each invite is single use and lets a signed-in friend join the booking as a
+-- named guest. Only the token's hash is stored.
+CREATE TABLE booking_invites (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    booking_id UUID NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- Session start; unredeemed invites stop working then
+    expires_at TIMESTAMPTZ NOT NULL,
+    redeemed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    redeemed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_invites_booking_id ON booking_invites(booking_id);
+CREATE INDEX idx_booking_invites_redeemed_by ON booking_invites(redeemed_by) WHERE redeemed_by IS NOT NULL;
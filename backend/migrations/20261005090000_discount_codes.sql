@@ -0,0 +1,35 @@
+-- Promo codes applied at booking time (e.g. FIRST50)
+-- Exactly one of percent_off / amount_off_vnd is set. Uses are counted from
+-- non-cancelled bookings carrying the code, so a cancelled or expired-unpaid
+-- booking gives its use back.
+CREATE TABLE discount_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- Stored uppercase; matched case-insensitively
+    code VARCHAR(50) NOT NULL UNIQUE CHECK (code = UPPER(code) AND code <> ''),
+    percent_off INT CHECK (percent_off BETWEEN 1 AND 100),
+    -- Off each discounted slot
+    amount_off_vnd INT CHECK (amount_off_vnd > 0),
+    -- Also discount paying guest slots, not just the booker's own
+    applies_to_guests BOOLEAN NOT NULL DEFAULT false,
+    -- NULL = unlimited
+    max_uses INT CHECK (max_uses > 0),
+    max_uses_per_user INT CHECK (max_uses_per_user > 0),
+    valid_from TIMESTAMPTZ,
+    valid_until TIMESTAMPTZ,
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((percent_off IS NULL) <> (amount_off_vnd IS NULL)),
+    CHECK (valid_from IS NULL OR valid_until IS NULL OR valid_until > valid_from)
+);
+
+CREATE TRIGGER update_discount_codes_updated_at
+    BEFORE UPDATE ON discount_codes
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Code applied to a booking; the text is kept if the code is later deleted
+ALTER TABLE bookings ADD COLUMN discount_code_id UUID REFERENCES discount_codes(id) ON DELETE SET NULL;
+ALTER TABLE bookings ADD COLUMN discount_code VARCHAR(50);
+
+CREATE INDEX idx_bookings_discount_code_id ON bookings(discount_code_id) WHERE discount_code_id IS NOT NULL;
@@ -0,0 +1,17 @@
+-- Audit log of support impersonation tokens
+-- Each row backs one short-lived, read-only token an admin minted to see the
+-- app as a user. The row id is the token's `jti`; every request made with the
+-- token bumps request_count and last_used_at.
+CREATE TABLE impersonation_grants (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    admin_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    request_count INTEGER NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_impersonation_grants_user_id ON impersonation_grants(user_id);
+CREATE INDEX idx_impersonation_grants_admin_id ON impersonation_grants(admin_id);
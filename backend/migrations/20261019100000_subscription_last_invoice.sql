@@ -0,0 +1,3 @@
+-- Last Stripe invoice applied to each subscription, so a retried webhook or a
+-- reconcile run never grants the same invoice's tickets twice
+ALTER TABLE subscriptions ADD COLUMN last_invoice_id TEXT;
@@ -0,0 +1,25 @@
+-- When a booking was first confirmed, so the booking funnel keeps counting
+-- bookings that were later refunded or cancelled after payment
+ALTER TABLE bookings ADD COLUMN confirmed_at TIMESTAMPTZ;
+
+-- Best guess for existing bookings: confirmed ones, refunds, and
+-- cancellations that went through a refund
+UPDATE bookings
+SET confirmed_at = COALESCE(verified_at, created_at)
+WHERE payment_status IN ('confirmed', 'refunded', 'refund_failed')
+   OR cancellation_reason = 'refunded';
+
+-- Set on whichever path confirms the booking (payment, ticket, comp, admin)
+CREATE OR REPLACE FUNCTION set_booking_confirmed_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.payment_status = 'confirmed' AND NEW.confirmed_at IS NULL THEN
+        NEW.confirmed_at = NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_bookings_confirmed_at
+    BEFORE INSERT OR UPDATE ON bookings
+    FOR EACH ROW EXECUTE FUNCTION set_booking_confirmed_at();
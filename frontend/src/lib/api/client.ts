@@ -1,5 +1,6 @@
 import axios, { type AxiosInstance, type InternalAxiosRequestConfig } from 'axios';
 import { supabase } from '$lib/auth/supabase';
+import type { ExpenseTemplateRequest } from '$lib/types/ExpenseTemplateRequest';
 
 const API_BASE_URL = import.meta.env.VITE_API_BASE_URL || 'http://localhost:3000';
 
@@ -86,7 +87,16 @@ export const api = {
 	users: {
 		updateProfile: (data: { name?: string; phone?: string; avatar_url?: string; birthday?: string }) =>
 			apiClient.put('/api/users/me', data),
-		deleteAccount: () => apiClient.delete('/api/users/me')
+		deleteAccount: () => apiClient.delete('/api/users/me'),
+		getNotificationPreferences: () => apiClient.get('/api/users/me/notifications'),
+		updateNotificationPreferences: (data: Record<
+			'booking_confirmation' | 'reminder' | 'session_cancelled' | 'waitlist_promoted',
+			{ email: boolean; sms: boolean; push: boolean }
+		>) => apiClient.put('/api/users/me/notifications', data),
+		createCalendarToken: () => apiClient.post('/api/users/me/calendar-token'),
+		revokeCalendarToken: () => apiClient.delete('/api/users/me/calendar-token'),
+		listAuthSessions: () => apiClient.get('/api/users/me/sessions'),
+		revokeOtherAuthSessions: () => apiClient.post('/api/users/me/sessions/revoke-others')
 	},
 
 	// Sessions
@@ -98,20 +108,29 @@ export const api = {
 			location?: string;
 			organizer_id?: string;
 			available_only?: boolean;
+			waitlist_available?: boolean;
 			page?: number;
 			per_page?: number;
 		}) => apiClient.get('/api/sessions', { params }),
-		locations: () => apiClient.get<string[]>('/api/sessions/locations'),
+		locations: () =>
+			apiClient.get<{ id: string; name: string; session_count: number }[]>('/api/sessions/locations'),
+		byLocation: (params: { location: string; date: string }) =>
+			apiClient.get('/api/sessions/by-location', { params }),
 		get: (id: string) => apiClient.get(`/api/sessions/${id}`),
 		getParticipants: (id: string) => apiClient.get(`/api/sessions/${id}/participants`),
+		getParticipantCount: (id: string) =>
+			apiClient.get(`/api/sessions/${id}/participant-count`),
 		create: (data: {
 			title: string;
 			description?: string;
 			location: string;
 			start_time: string;
 			end_time: string;
-			max_slots: number;
+			courts?: number;
+			max_players_per_court?: number;
+			max_slots?: number;
 			price_vnd: number;
+			booking_opens_at?: string;
 			early_access_ends_at?: string;
 			expenses?: Array<{
 				category: 'court_rental' | 'equipment' | 'instructor' | 'custom';
@@ -119,51 +138,92 @@ export const api = {
 				cost_type: 'per_court' | 'total';
 				amount_vnd: number;
 			}>;
+			expense_template_id?: string;
 		}) => apiClient.post('/api/sessions', data),
+		duplicate: (id: string, data?: { date?: string; time?: string }) =>
+			apiClient.post(`/api/sessions/${id}/duplicate`, data ?? {}),
 		update: (id: string, data: Partial<{
 			title: string;
 			description?: string;
 			location: string;
 			start_time: string;
 			end_time: string;
-			max_slots: number;
+			courts?: number;
+			max_players_per_court?: number;
+			max_slots?: number;
 			price_vnd: number;
+			booking_opens_at?: string;
 			early_access_ends_at?: string;
 			status: string;
 		}>) => apiClient.put(`/api/sessions/${id}`, data),
-		delete: (id: string) => apiClient.delete(`/api/sessions/${id}`)
+		delete: (id: string) => apiClient.delete(`/api/sessions/${id}`),
+		uploadImage: (id: string, file: File) => {
+			const form = new FormData();
+			form.append('file', file);
+			return apiClient.post(`/api/sessions/${id}/image`, form);
+		},
+		deleteImage: (id: string) => apiClient.delete(`/api/sessions/${id}/image`),
+		announce: (id: string, data: { subject: string; body: string }) =>
+			apiClient.post(`/api/sessions/${id}/announce`, data),
+		verifyCode: (id: string, code: string) =>
+			apiClient.get(`/api/sessions/${id}/verify-code`, { params: { code } }),
+		checkIn: (id: string, code: string) => apiClient.post(`/api/sessions/${id}/check-in`, { code })
 	},
 
 	// Bookings
 	bookings: {
-		list: (params?: { page?: number; per_page?: number }) =>
+		list: (params?: { page?: number; per_page?: number; after?: string }) =>
 			apiClient.get('/api/bookings', { params }),
 		get: (id: string) => apiClient.get(`/api/bookings/${id}`),
+		getByCode: (code: string) => apiClient.get(`/api/bookings/by-code/${encodeURIComponent(code)}`),
 		create: (data: {
 			session_id: string;
 			guest_count: number;
 			payment_method: 'stripe' | 'qr';
+			use_tickets_for_guests?: boolean;
+			discount_code?: string;
 		}) => apiClient.post('/api/bookings', data),
-		cancel: (id: string) => apiClient.delete(`/api/bookings/${id}`)
+		cancelPreview: (id: string) => apiClient.get(`/api/bookings/${id}/cancel-preview`),
+		cancel: (id: string) => apiClient.delete(`/api/bookings/${id}`),
+		createInvite: (id: string) => apiClient.post(`/api/bookings/${id}/invite`),
+		redeemInvite: (token: string) => apiClient.post(`/api/invites/${encodeURIComponent(token)}`)
 	},
 
 	// Payments
 	payments: {
 		createIntent: (booking_id: string) =>
-			apiClient.post('/api/payments/stripe/intent', { booking_id })
+			apiClient.post('/api/payments/stripe/intent', { booking_id }),
+		getIntentStatus: (booking_id: string) =>
+			apiClient.get(`/api/payments/stripe/intent/${booking_id}`)
 	},
 
 	// Subscriptions/Tickets
 	subscriptions: {
 		getTicketBalance: () => apiClient.get('/api/subscriptions/tickets'),
-		getTicketHistory: (params?: { page?: number; per_page?: number }) =>
+		getTicketHistory: (params?: { page?: number; per_page?: number; after?: string }) =>
 			apiClient.get('/api/subscriptions/tickets/history', { params }),
 		getCurrent: () => apiClient.get('/api/subscriptions/current'),
 		purchase: () => apiClient.post('/api/subscriptions/purchase'),
+		gift: (recipient_email: string) =>
+			apiClient.post('/api/subscriptions/gift', { recipient_email }),
 		cancel: () => apiClient.post('/api/subscriptions/cancel'),
 		resume: () => apiClient.post('/api/subscriptions/resume')
 	},
 
+	// Organizer
+	organizer: {
+		getPayouts: (params?: { period?: string; organizer_id?: string }) =>
+			apiClient.get('/api/organizer/payouts', { params }),
+		listEditableSessions: () => apiClient.get('/api/organizer/sessions/editable'),
+		listExpenseTemplates: () => apiClient.get('/api/organizer/expense-templates'),
+		getExpenseTemplate: (id: string) => apiClient.get(`/api/organizer/expense-templates/${id}`),
+		createExpenseTemplate: (data: ExpenseTemplateRequest) =>
+			apiClient.post('/api/organizer/expense-templates', data),
+		updateExpenseTemplate: (id: string, data: ExpenseTemplateRequest) =>
+			apiClient.put(`/api/organizer/expense-templates/${id}`, data),
+		deleteExpenseTemplate: (id: string) => apiClient.delete(`/api/organizer/expense-templates/${id}`)
+	},
+
 	// Admin
 	admin: {
 		getStats: (period: string = '30d') => apiClient.get(`/api/admin/stats?period=${period}`),
@@ -176,14 +236,21 @@ export const api = {
 			sort_by?: string;
 			sort_order?: string;
 		}) => apiClient.get('/api/admin/users', { params }),
+		listOrphanedRoleUsers: () => apiClient.get('/api/admin/users/orphaned-roles'),
 		updateUserRole: (userId: string, role: string) =>
 			apiClient.put(`/api/admin/users/${userId}/role`, { role }),
-		suspendUser: (userId: string, data: { reason: string; until?: string }) =>
+		suspendUser: (userId: string, data: { reason: string; until?: string; cancel_bookings?: boolean }) =>
 			apiClient.post(`/api/admin/users/${userId}/suspend`, data),
+		getSuspensionImpact: (userId: string) =>
+			apiClient.get(`/api/admin/users/${userId}/suspension-impact`),
 		unsuspendUser: (userId: string) => apiClient.post(`/api/admin/users/${userId}/unsuspend`),
+		createImpersonationToken: (userId: string, reason: string) =>
+			apiClient.post(`/api/admin/users/${userId}/impersonation-token`, { reason }),
 		updateUser: (userId: string, data: { name?: string; phone?: string; role?: string }) =>
 			apiClient.put(`/api/admin/users/${userId}`, data),
 		deleteUser: (userId: string) => apiClient.delete(`/api/admin/users/${userId}`),
+		mergeUsers: (primaryId: string, duplicateId: string) =>
+			apiClient.post('/api/admin/users/merge', { primary_id: primaryId, duplicate_id: duplicateId }),
 		listBookings: (params?: {
 			page?: number;
 			per_page?: number;
@@ -194,6 +261,12 @@ export const api = {
 			sort_order?: string;
 		}) => apiClient.get('/api/admin/bookings', { params }),
 		getBooking: (id: string) => apiClient.get(`/api/admin/bookings/${id}`),
+		compBooking: (data: {
+			user_id: string;
+			session_id: string;
+			guest_count?: number;
+			reason: string;
+		}) => apiClient.post('/api/admin/bookings/comp', data),
 		updateBooking: (id: string, data: {
 			guest_count?: number | null;
 			price_paid_vnd?: number | null;
@@ -202,6 +275,11 @@ export const api = {
 			payment_status?: string | null;
 			admin_notes?: string | null;
 		}) => apiClient.put(`/api/admin/bookings/${id}`, data),
+		verifyPaymentProof: (id: string, data?: { received_amount_vnd?: number }) =>
+			apiClient.post(`/api/admin/bookings/${id}/verify`, data ?? {}),
+		rejectPaymentProof: (id: string) => apiClient.post(`/api/admin/bookings/${id}/reject`),
+		getPaymentDiscrepancies: (period: string = '30d') =>
+			apiClient.get(`/api/admin/payments/discrepancies?period=${period}`),
 		listSessions: (params?: {
 			page?: number;
 			per_page?: number;
@@ -211,22 +289,49 @@ export const api = {
 			sort_by?: string;
 			sort_order?: string;
 		}) => apiClient.get('/api/admin/sessions', { params }),
+		importSessions: (sessions: Record<string, unknown>[]) =>
+			apiClient.post('/api/admin/sessions/import', sessions),
 		listRoles: () => apiClient.get('/api/admin/roles'),
+		getPermissionMatrix: () => apiClient.get('/api/admin/roles/permissions'),
+		getBookingFunnel: (period: string = '30d') =>
+			apiClient.get(`/api/admin/stats/booking-funnel?period=${period}`),
 		// Profit endpoints
 		getProfitStats: (period: string = '30d') =>
 			apiClient.get(`/api/admin/stats/profit?period=${period}`),
 		getSessionsProfit: (period: string = '30d', limit: number = 20) =>
 			apiClient.get(`/api/admin/sessions/profit?period=${period}&limit=${limit}`),
+		getSessionProfitReport: (sessionId: string) =>
+			apiClient.get(`/api/admin/sessions/${sessionId}/profit-report.pdf`, { responseType: 'blob' }),
 		getExpensesByCategory: (period: string = '30d') =>
 			apiClient.get(`/api/admin/expenses/by-category?period=${period}`),
+		getSessionExpenses: (sessionId: string) => apiClient.get(`/api/admin/sessions/${sessionId}/expenses`),
+		getFillAnalytics: (params?: { location?: string; time_of_day?: string; weeks?: number }) =>
+			apiClient.get('/api/admin/sessions/fill-analytics', { params }),
+		setMaintenance: (data: { enabled: boolean; message?: string }) =>
+			apiClient.post('/api/admin/maintenance', data),
 		getDailyProfitData: (period: string = '30d') =>
 			apiClient.get(`/api/admin/profit/daily?period=${period}`),
+		listFixedExpenses: () => apiClient.get('/api/admin/fixed-expenses'),
+		createFixedExpense: (data: Record<string, unknown>) => apiClient.post('/api/admin/fixed-expenses', data),
+		updateFixedExpense: (id: string, data: Record<string, unknown>) =>
+			apiClient.put(`/api/admin/fixed-expenses/${id}`, data),
+		deleteFixedExpense: (id: string) => apiClient.delete(`/api/admin/fixed-expenses/${id}`),
+		// Discount codes
+		listDiscountCodes: () => apiClient.get('/api/admin/discount-codes'),
+		createDiscountCode: (data: Record<string, unknown>) => apiClient.post('/api/admin/discount-codes', data),
+		updateDiscountCode: (id: string, data: Record<string, unknown>) =>
+			apiClient.put(`/api/admin/discount-codes/${id}`, data),
+		deleteDiscountCode: (id: string) => apiClient.delete(`/api/admin/discount-codes/${id}`),
 		// Ticket management
 		getUserTickets: (userId: string) =>
 			apiClient.get(`/api/admin/users/${userId}/tickets`),
+		getUserTicketSummary: (userId: string) =>
+			apiClient.get(`/api/admin/users/${userId}/ticket-summary`),
 		grantTickets: (userId: string, data: { amount: number; reason?: string }) =>
 			apiClient.post(`/api/admin/users/${userId}/tickets/grant`, data),
 		revokeTickets: (userId: string, data: { amount: number; reason?: string }) =>
-			apiClient.post(`/api/admin/users/${userId}/tickets/revoke`, data)
+			apiClient.post(`/api/admin/users/${userId}/tickets/revoke`, data),
+		reconcileSubscription: (stripeSubscriptionId: string, data?: { user_id?: string }) =>
+			apiClient.post(`/api/admin/subscriptions/${stripeSubscriptionId}/reconcile`, data ?? {})
 	}
 };
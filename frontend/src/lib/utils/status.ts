@@ -15,7 +15,7 @@ export type UserRole = 'admin' | 'organizer' | 'user';
 /**
  * Payment status type for type safety.
  */
-export type PaymentStatus = 'pending' | 'confirmed' | 'failed' | 'refunded';
+export type PaymentStatus = 'pending' | 'confirmed' | 'failed' | 'refunded' | 'refund_failed';
 
 /**
  * Session status type for type safety.
@@ -47,7 +47,7 @@ export function getRoleBadgeVariant(role: string): BadgeVariant {
 /**
  * Gets the badge variant for a payment status.
  *
- * @param status - The payment status (pending, confirmed, failed, refunded)
+ * @param status - The payment status (pending, confirmed, failed, refunded, refund_failed)
  * @returns The appropriate badge variant
  *
  * @example
@@ -65,6 +65,7 @@ export function getPaymentBadgeVariant(status: string): BadgeVariant {
 			return 'pending';
 		case 'failed':
 		case 'refunded':
+		case 'refund_failed':
 			return 'failed';
 		default:
 			return 'muted';